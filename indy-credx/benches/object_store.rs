@@ -0,0 +1,104 @@
+//! Micro-benchmark of the FFI object store, measuring handle lookups from a
+//! varying number of threads along with the cost of creating and freeing objects.
+//!
+//! Run with `cargo bench --bench object_store`.
+
+// link the exported FFI functions
+extern crate indy_credx;
+
+use std::os::raw::c_char;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+const OBJECTS: usize = 1000;
+const LOADS_PER_THREAD: usize = 200_000;
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+struct ObjectHandle(usize);
+
+#[repr(C)]
+struct FfiList {
+    count: usize,
+    data: *const ObjectHandle,
+}
+
+extern "C" {
+    fn credx_create_master_secret(master_secret_p: *mut ObjectHandle) -> usize;
+    fn credx_object_get_tag(handle: ObjectHandle, result_p: *mut *const c_char) -> usize;
+    fn credx_object_free(handle: ObjectHandle);
+    fn credx_object_free_list(handles: FfiList);
+}
+
+fn create_objects(count: usize) -> Vec<ObjectHandle> {
+    (0..count)
+        .map(|_| {
+            let mut handle = ObjectHandle(0);
+            assert_eq!(unsafe { credx_create_master_secret(&mut handle) }, 0);
+            handle
+        })
+        .collect()
+}
+
+fn load(handle: ObjectHandle) {
+    let mut tag = ptr::null();
+    assert_eq!(unsafe { credx_object_get_tag(handle, &mut tag) }, 0);
+    assert!(tag.is_null());
+}
+
+fn report(name: &str, ops: usize, elapsed: Duration) {
+    println!(
+        "{:<32} {:>10} ops {:>12?} {:>10.0} ns/op",
+        name,
+        ops,
+        elapsed,
+        elapsed.as_nanos() as f64 / ops as f64
+    );
+}
+
+fn bench_concurrent_loads(handles: &[ObjectHandle], threads: usize) {
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for thread in 0..threads {
+            scope.spawn(move || {
+                for idx in 0..LOADS_PER_THREAD {
+                    load(handles[(idx * threads + thread) % handles.len()]);
+                }
+            });
+        }
+    });
+    report(
+        &format!("load ({} threads)", threads),
+        threads * LOADS_PER_THREAD,
+        start.elapsed(),
+    );
+}
+
+fn bench_create_free() {
+    let start = Instant::now();
+    for handle in create_objects(OBJECTS) {
+        unsafe { credx_object_free(handle) };
+    }
+    report("create and free", OBJECTS, start.elapsed());
+
+    let handles = create_objects(OBJECTS);
+    let start = Instant::now();
+    unsafe {
+        credx_object_free_list(FfiList {
+            count: handles.len(),
+            data: handles.as_ptr(),
+        })
+    };
+    report("free list", OBJECTS, start.elapsed());
+}
+
+fn main() {
+    let handles = create_objects(OBJECTS);
+    for threads in [1, 2, 4, 8] {
+        bench_concurrent_loads(&handles, threads);
+    }
+    bench_create_free();
+    for handle in handles {
+        unsafe { credx_object_free(handle) };
+    }
+}
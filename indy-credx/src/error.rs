@@ -18,6 +18,8 @@ pub enum ErrorKind {
     InvalidUserRevocId,
     ProofRejected,
     RevocationRegistryFull,
+    LimitExceeded,
+    NonceMismatch,
 }
 
 impl ErrorKind {
@@ -31,6 +33,8 @@ impl ErrorKind {
             Self::InvalidUserRevocId => "Invalid revocation accumulator index",
             Self::ProofRejected => "Proof rejected",
             Self::RevocationRegistryFull => "Revocation registry full",
+            Self::LimitExceeded => "Limit exceeded",
+            Self::NonceMismatch => "Nonce mismatch",
         }
     }
 }
@@ -47,6 +51,8 @@ pub struct Error {
     kind: ErrorKind,
     pub cause: Option<Box<dyn StdError + Send + Sync + 'static>>,
     pub message: Option<String>,
+    /// Structured details about the error, reported alongside the message
+    pub extra: Option<serde_json::Value>,
     // backtrace (when supported)
 }
 
@@ -56,6 +62,7 @@ impl Error {
             kind,
             cause: None,
             message: Some(msg.into()),
+            extra: None,
         }
     }
 
@@ -64,6 +71,7 @@ impl Error {
             kind,
             cause: None,
             message: msg.map(Into::into),
+            extra: None,
         }
     }
 
@@ -75,6 +83,11 @@ impl Error {
         self.cause = Some(err.into());
         self
     }
+
+    pub fn with_extra(mut self, extra: serde_json::Value) -> Self {
+        self.extra = Some(extra);
+        self
+    }
 }
 
 impl fmt::Display for Error {
@@ -112,6 +125,7 @@ impl From<ErrorKind> for Error {
             kind,
             cause: None,
             message: None,
+            extra: None,
         }
     }
 }
@@ -5,8 +5,12 @@ use indy_utils::Qualifiable;
 
 use super::error::{catch_error, ErrorCode};
 use super::object::{IndyObjectId, ObjectHandle};
+use super::util::FfiStrList;
 use crate::services::{
-    issuer::create_credential_definition,
+    issuer::{
+        create_credential_definition, create_credential_definition_with_attributes,
+        verify_key_correctness_proof,
+    },
     types::{
         CredentialDefinition, CredentialDefinitionConfig, CredentialDefinitionId,
         CredentialDefinitionPrivate, CredentialKeyCorrectnessProof as KeyCorrectnessProof,
@@ -24,6 +28,34 @@ pub extern "C" fn credx_create_credential_definition(
     cred_def_p: *mut ObjectHandle,
     cred_def_pvt_p: *mut ObjectHandle,
     key_proof_p: *mut ObjectHandle,
+) -> ErrorCode {
+    credx_create_credential_definition_with_attributes(
+        origin_did,
+        schema,
+        tag,
+        signature_type,
+        support_revocation,
+        FfiStrList::empty(),
+        cred_def_p,
+        cred_def_pvt_p,
+        key_proof_p,
+    )
+}
+
+/// Create a credential definition signing only the listed schema attributes.
+/// All schema attributes are signed when `attr_names` is empty. The attributes are
+/// signed as a set, so the order in which they are listed is not significant.
+#[no_mangle]
+pub extern "C" fn credx_create_credential_definition_with_attributes(
+    origin_did: FfiStr,
+    schema: ObjectHandle,
+    tag: FfiStr,
+    signature_type: FfiStr,
+    support_revocation: i8,
+    attr_names: FfiStrList,
+    cred_def_p: *mut ObjectHandle,
+    cred_def_pvt_p: *mut ObjectHandle,
+    key_proof_p: *mut ObjectHandle,
 ) -> ErrorCode {
     catch_error(|| {
         check_useful_c_ptr!(cred_def_p);
@@ -42,15 +74,28 @@ pub extern "C" fn credx_create_credential_definition(
                 .ok_or_else(|| err_msg!("Missing signature type"))?;
             SignatureType::from_str(stype).map_err(err_map!(Input))?
         };
-        let (cred_def, cred_def_pvt, key_proof) = create_credential_definition(
-            &origin_did,
-            schema.load()?.cast_ref()?,
-            tag,
-            signature_type,
-            CredentialDefinitionConfig {
-                support_revocation: support_revocation != 0,
-            },
-        )?;
+        let config = CredentialDefinitionConfig {
+            support_revocation: support_revocation != 0,
+        };
+        let schema = schema.load()?;
+        let (cred_def, cred_def_pvt, key_proof) = if attr_names.is_empty() {
+            create_credential_definition(
+                &origin_did,
+                schema.cast_ref()?,
+                tag,
+                signature_type,
+                config,
+            )?
+        } else {
+            create_credential_definition_with_attributes(
+                &origin_did,
+                schema.cast_ref()?,
+                tag,
+                signature_type,
+                config,
+                &attr_names.to_string_vec()?,
+            )?
+        };
         let cred_def = ObjectHandle::create(cred_def)?;
         let cred_def_pvt = ObjectHandle::create(cred_def_pvt)?;
         let key_proof = ObjectHandle::create(key_proof)?;
@@ -64,7 +109,12 @@ pub extern "C" fn credx_create_credential_definition(
 }
 
 impl_indy_object!(CredentialDefinition, "CredentialDefinition");
-impl_indy_object_from_json!(CredentialDefinition, credx_credential_definition_from_json);
+impl_indy_object_from_json!(
+    CredentialDefinition,
+    credx_credential_definition_from_json,
+    versioned indy_data_types::anoncreds::cred_def::CredentialDefinitionV1
+);
+impl_indy_object_from_cbor!(CredentialDefinition, credx_credential_definition_from_cbor);
 
 impl IndyObjectId for CredentialDefinition {
     type Id = CredentialDefinitionId;
@@ -105,6 +155,23 @@ impl_indy_object_from_json!(
     CredentialDefinitionPrivate,
     credx_credential_definition_private_from_json
 );
+impl_indy_object_from_cbor!(
+    CredentialDefinitionPrivate,
+    credx_credential_definition_private_from_cbor
+);
 
 impl_indy_object!(KeyCorrectnessProof, "KeyCorrectnessProof");
 impl_indy_object_from_json!(KeyCorrectnessProof, credx_key_correctness_proof_from_json);
+impl_indy_object_from_cbor!(KeyCorrectnessProof, credx_key_correctness_proof_from_cbor);
+
+/// Verify the key correctness proof for a credential definition, returning a
+/// `ProofRejected` error when the proof is not valid
+#[no_mangle]
+pub extern "C" fn credx_verify_key_correctness_proof(
+    cred_def: ObjectHandle,
+    key_proof: ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        verify_key_correctness_proof(cred_def.load()?.cast_ref()?, key_proof.load()?.cast_ref()?)
+    })
+}
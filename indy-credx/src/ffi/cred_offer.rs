@@ -1,11 +1,18 @@
-use ffi_support::FfiStr;
+use ffi_support::{ByteBuffer, FfiStr};
+use indy_data_types::anoncreds::nonce::Nonce;
 use indy_utils::Qualifiable;
 
 use super::error::{catch_error, ErrorCode};
-use super::object::ObjectHandle;
+use super::object::{
+    check_parsed_object, parse_json_object, serialize_json, versioned_object_from_json,
+    IndyObjectList, ObjectHandle,
+};
+use super::util::{FfiList, FfiStrList};
 use crate::services::{
-    issuer::create_credential_offer,
-    types::{CredentialOffer, SchemaId},
+    issuer::{
+        create_credential_offer, create_credential_offers, validate_credential_request_session,
+    },
+    types::{CredentialDefinition, CredentialOffer, SchemaId},
 };
 
 #[no_mangle]
@@ -13,6 +20,7 @@ pub extern "C" fn credx_create_credential_offer(
     schema_id: FfiStr,
     cred_def: ObjectHandle,
     key_proof: ObjectHandle,
+    nonce: FfiStr,
     cred_offer_p: *mut ObjectHandle,
 ) -> ErrorCode {
     catch_error(|| {
@@ -23,10 +31,16 @@ pub extern "C" fn credx_create_credential_offer(
                 .ok_or_else(|| err_msg!("Missing schema ID"))?;
             SchemaId::from_str(sid)?
         };
+        let nonce = nonce
+            .as_opt_str()
+            .map(Nonce::from_dec)
+            .transpose()
+            .map_err(err_map!("Invalid nonce"))?;
         let cred_offer = create_credential_offer(
             &schema_id,
             cred_def.load()?.cast_ref()?,
             key_proof.load()?.cast_ref()?,
+            nonce,
         )?;
         let cred_offer = ObjectHandle::create(cred_offer)?;
         unsafe { *cred_offer_p = cred_offer };
@@ -34,5 +48,107 @@ pub extern "C" fn credx_create_credential_offer(
     })
 }
 
+/// Create a credential offer from a credential definition and key correctness
+/// proof passed as JSON rather than object handles, returning the offer as JSON
+#[no_mangle]
+pub extern "C" fn credx_create_credential_offer_json(
+    schema_id: FfiStr,
+    cred_def_json: ByteBuffer,
+    key_proof_json: ByteBuffer,
+    nonce: FfiStr,
+    cred_offer_json_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(cred_offer_json_p);
+        let schema_id = {
+            let sid = schema_id
+                .as_opt_str()
+                .ok_or_else(|| err_msg!("Missing schema ID"))?;
+            SchemaId::from_str(sid)?
+        };
+        let nonce = nonce
+            .as_opt_str()
+            .map(Nonce::from_dec)
+            .transpose()
+            .map_err(err_map!("Invalid nonce"))?;
+        let cred_def = check_parsed_object(versioned_object_from_json::<
+            CredentialDefinition,
+            indy_data_types::anoncreds::cred_def::CredentialDefinitionV1,
+        >(cred_def_json.as_slice())?)?;
+        let key_proof = parse_json_object(key_proof_json.as_slice())?;
+        let cred_offer = create_credential_offer(&schema_id, &cred_def, &key_proof, nonce)?;
+        let cred_offer = serialize_json(&cred_offer)?;
+        unsafe { *cred_offer_json_p = ByteBuffer::from_vec(cred_offer) };
+        Ok(())
+    })
+}
+
+/// Create one credential offer per session ID. `cred_offers_p` must have room
+/// for as many handles as there are session IDs.
+#[no_mangle]
+pub extern "C" fn credx_create_credential_offers(
+    schema_id: FfiStr,
+    cred_def: ObjectHandle,
+    key_proof: ObjectHandle,
+    session_ids: FfiStrList,
+    cred_offers_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(cred_offers_p);
+        let schema_id = {
+            let sid = schema_id
+                .as_opt_str()
+                .ok_or_else(|| err_msg!("Missing schema ID"))?;
+            SchemaId::from_str(sid)?
+        };
+        let session_ids = session_ids.to_string_vec()?;
+        let session_ids = session_ids.iter().map(String::as_str).collect::<Vec<_>>();
+        let cred_offers = create_credential_offers(
+            &schema_id,
+            cred_def.load()?.cast_ref()?,
+            key_proof.load()?.cast_ref()?,
+            &session_ids,
+        )?;
+        let handles = cred_offers
+            .into_iter()
+            .map(ObjectHandle::create)
+            .collect::<crate::error::Result<Vec<_>>>()?;
+        let result = unsafe { std::slice::from_raw_parts_mut(cred_offers_p, handles.len()) };
+        result.copy_from_slice(&handles);
+        Ok(())
+    })
+}
+
+/// Find the offer bound to a session for an incoming credential request,
+/// returning its index within `cred_offers`
+#[no_mangle]
+pub extern "C" fn credx_validate_credential_request_session(
+    cred_offers: FfiList<ObjectHandle>,
+    cred_request: ObjectHandle,
+    session_id: FfiStr,
+    index_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(index_p);
+        let session_id = session_id
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing session ID"))?;
+        let cred_offers = IndyObjectList::load(cred_offers.as_slice())?;
+        let cred_offers = cred_offers.refs::<CredentialOffer>()?;
+        let cred_offer = validate_credential_request_session(
+            &cred_offers,
+            cred_request.load()?.cast_ref()?,
+            session_id,
+        )?;
+        let index = cred_offers
+            .iter()
+            .position(|offer| std::ptr::eq(*offer, cred_offer))
+            .ok_or_else(|| err_msg!(Unexpected, "Credential offer not found"))?;
+        unsafe { *index_p = index as i64 };
+        Ok(())
+    })
+}
+
 impl_indy_object!(CredentialOffer, "CredentialOffer");
 impl_indy_object_from_json!(CredentialOffer, credx_credential_offer_from_json);
+impl_indy_object_from_cbor!(CredentialOffer, credx_credential_offer_from_cbor);
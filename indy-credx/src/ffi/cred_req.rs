@@ -49,9 +49,14 @@ pub extern "C" fn credx_create_credential_request(
 
 impl_indy_object!(CredentialRequest, "CredentialRequest");
 impl_indy_object_from_json!(CredentialRequest, credx_credential_request_from_json);
+impl_indy_object_from_cbor!(CredentialRequest, credx_credential_request_from_cbor);
 
 impl_indy_object!(CredentialRequestMetadata, "CredentialRequestMetadata");
 impl_indy_object_from_json!(
     CredentialRequestMetadata,
     credx_credential_request_metadata_from_json
 );
+impl_indy_object_from_cbor!(
+    CredentialRequestMetadata,
+    credx_credential_request_metadata_from_cbor
+);
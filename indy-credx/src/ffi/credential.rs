@@ -3,18 +3,21 @@ use std::convert::TryInto;
 use std::os::raw::c_char;
 use std::ptr;
 
-use ffi_support::{rust_string_to_c, FfiStr};
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
 
 use super::error::{catch_error, ErrorCode};
 use super::object::{IndyObject, ObjectHandle};
 use super::util::{FfiList, FfiStrList};
 use crate::error::Result;
 use crate::services::{
-    issuer::create_credential,
+    issuer::{create_credential, create_credential_with_allocation, registry_remaining_capacity},
     prover::process_credential,
     tails::TailsFileReader,
-    types::{Credential, CredentialRevocationConfig, MakeCredentialValues},
-    utils::encode_credential_attribute,
+    types::{
+        Credential, CredentialRevocationConfig, MakeCredentialValues, RevocationAllocationConfig,
+        RevocationIndexAllocation, RevocationRegistryDefinition, RevocationRegistryUsage,
+    },
+    utils::{encode_binary_attribute, encode_credential_attribute},
 };
 
 #[derive(Debug)]
@@ -63,45 +66,49 @@ pub extern "C" fn credx_create_credential(
     cred_p: *mut ObjectHandle,
     rev_reg_p: *mut ObjectHandle,
     rev_delta_p: *mut ObjectHandle,
+) -> ErrorCode {
+    credx_create_credential_with_media_types(
+        cred_def,
+        cred_def_private,
+        cred_offer,
+        cred_request,
+        attr_names,
+        attr_raw_values,
+        attr_enc_values,
+        FfiStrList::empty(),
+        revocation,
+        cred_p,
+        rev_reg_p,
+        rev_delta_p,
+    )
+}
+
+/// Create a credential, declaring the MIME types of binary attributes. Each
+/// entry of `attr_media_types` corresponds to the attribute name at the same
+/// position, and may be null for attributes without a media type.
+#[no_mangle]
+pub extern "C" fn credx_create_credential_with_media_types(
+    cred_def: ObjectHandle,
+    cred_def_private: ObjectHandle,
+    cred_offer: ObjectHandle,
+    cred_request: ObjectHandle,
+    attr_names: FfiStrList,
+    attr_raw_values: FfiStrList,
+    attr_enc_values: FfiStrList,
+    attr_media_types: FfiStrList,
+    revocation: *const FfiCredRevInfo,
+    cred_p: *mut ObjectHandle,
+    rev_reg_p: *mut ObjectHandle,
+    rev_delta_p: *mut ObjectHandle,
 ) -> ErrorCode {
     catch_error(|| {
         check_useful_c_ptr!(cred_p);
-        if attr_names.is_empty() {
-            return Err(err_msg!("Cannot create credential with no attribute"));
-        }
-        if attr_names.len() != attr_raw_values.len() {
-            return Err(err_msg!(
-                "Mismatch between length of attribute names and raw values"
-            ));
-        }
-        let enc_values = attr_enc_values.as_slice();
-        let mut cred_values = MakeCredentialValues::default();
-        let mut attr_idx = 0;
-        for (name, raw) in attr_names
-            .as_slice()
-            .into_iter()
-            .zip(attr_raw_values.as_slice())
-        {
-            let name = name
-                .as_opt_str()
-                .ok_or_else(|| err_msg!("Missing attribute name"))?
-                .to_string();
-            let raw = raw
-                .as_opt_str()
-                .ok_or_else(|| err_msg!("Missing attribute raw value"))?
-                .to_string();
-            let encoded = if attr_idx < enc_values.len() {
-                enc_values[attr_idx].as_opt_str().map(str::to_string)
-            } else {
-                None
-            };
-            if let Some(encoded) = encoded {
-                cred_values.add_encoded(name, raw, encoded);
-            } else {
-                cred_values.add_raw(name, raw)?;
-            }
-            attr_idx += 1;
-        }
+        let cred_values = make_credential_values(
+            attr_names,
+            attr_raw_values,
+            attr_enc_values,
+            attr_media_types,
+        )?;
         let revocation_config = if !revocation.is_null() {
             let revocation = unsafe { &*revocation };
             let tails_path = revocation
@@ -160,6 +167,212 @@ pub extern "C" fn credx_create_credential(
     })
 }
 
+/// The revocation registry used to issue a credential with
+/// `credx_create_credential_with_allocation`. The `allocation` strategy is one of
+/// `sequential` (the default), `random`, or `specified`, in which case `reg_idx`
+/// gives the index to use.
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiCredRevAllocInfo<'a> {
+    reg_def: ObjectHandle,
+    reg_def_private: ObjectHandle,
+    registry: ObjectHandle,
+    allocation: FfiStr<'a>,
+    reg_idx: i64,
+    tails_path: FfiStr<'a>,
+}
+
+impl FfiCredRevAllocInfo<'_> {
+    fn allocation(&self) -> Result<RevocationIndexAllocation> {
+        match self.allocation.as_opt_str() {
+            None | Some("sequential") => Ok(RevocationIndexAllocation::Sequential),
+            Some("random") => Ok(RevocationIndexAllocation::Random),
+            Some("specified") => Ok(RevocationIndexAllocation::Specified(
+                self.reg_idx
+                    .try_into()
+                    .map_err(|_| err_msg!("Invalid revocation index"))?,
+            )),
+            Some(other) => Err(err_msg!("Unknown revocation index allocation: {}", other)),
+        }
+    }
+}
+
+/// Create a revocable credential, allocating its revocation index from a
+/// `RevocationRegistryUsage` object. The updated usage object and the allocated
+/// index are returned along with the credential.
+#[no_mangle]
+pub extern "C" fn credx_create_credential_with_allocation(
+    cred_def: ObjectHandle,
+    cred_def_private: ObjectHandle,
+    cred_offer: ObjectHandle,
+    cred_request: ObjectHandle,
+    attr_names: FfiStrList,
+    attr_raw_values: FfiStrList,
+    attr_enc_values: FfiStrList,
+    attr_media_types: FfiStrList,
+    revocation: *const FfiCredRevAllocInfo,
+    usage: ObjectHandle,
+    cred_p: *mut ObjectHandle,
+    rev_reg_p: *mut ObjectHandle,
+    rev_delta_p: *mut ObjectHandle,
+    usage_p: *mut ObjectHandle,
+    reg_idx_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(cred_p);
+        check_useful_c_ptr!(rev_reg_p);
+        check_useful_c_ptr!(rev_delta_p);
+        check_useful_c_ptr!(usage_p);
+        check_useful_c_ptr!(reg_idx_p);
+        if revocation.is_null() {
+            return Err(err_msg!("Missing revocation configuration"));
+        }
+        let revocation = unsafe { &*revocation };
+        let cred_values = make_credential_values(
+            attr_names,
+            attr_raw_values,
+            attr_enc_values,
+            attr_media_types,
+        )?;
+        let tails_path = revocation
+            .tails_path
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing tails file path"))?;
+        let reg_def = revocation.reg_def.load()?;
+        let reg_def_private = revocation.reg_def_private.load()?;
+        let registry = revocation.registry.load()?;
+        let mut usage = usage.load()?.cast_ref::<RevocationRegistryUsage>()?.clone();
+        let (cred, reg_idx, rev_reg, rev_delta) = create_credential_with_allocation(
+            cred_def.load()?.cast_ref()?,
+            cred_def_private.load()?.cast_ref()?,
+            cred_offer.load()?.cast_ref()?,
+            cred_request.load()?.cast_ref()?,
+            cred_values.into(),
+            RevocationAllocationConfig {
+                reg_def: reg_def.cast_ref()?,
+                reg_def_private: reg_def_private.cast_ref()?,
+                registry: registry.cast_ref()?,
+                allocation: revocation.allocation()?,
+                tails_reader: TailsFileReader::new(tails_path),
+            },
+            &mut usage,
+        )?;
+        let cred = ObjectHandle::create(cred)?;
+        let rev_reg = ObjectHandle::create(rev_reg)?;
+        let rev_delta = rev_delta
+            .map(ObjectHandle::create)
+            .transpose()?
+            .unwrap_or_default();
+        let usage = ObjectHandle::create(usage)?;
+        unsafe {
+            *cred_p = cred;
+            *rev_reg_p = rev_reg;
+            *rev_delta_p = rev_delta;
+            *usage_p = usage;
+            *reg_idx_p = reg_idx as i64;
+        };
+        Ok(())
+    })
+}
+
+/// Start tracking the revocation indices allocated from a revocation registry
+#[no_mangle]
+pub extern "C" fn credx_create_revocation_registry_usage(
+    rev_reg_def: ObjectHandle,
+    usage_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(usage_p);
+        let usage = RevocationRegistryUsage::new(
+            rev_reg_def
+                .load()?
+                .cast_ref::<RevocationRegistryDefinition>()?,
+        );
+        let usage = ObjectHandle::create(usage)?;
+        unsafe { *usage_p = usage };
+        Ok(())
+    })
+}
+
+/// Get the number of credentials which may still be issued against a
+/// revocation registry
+#[no_mangle]
+pub extern "C" fn credx_revocation_registry_remaining_capacity(
+    rev_reg_def: ObjectHandle,
+    usage: ObjectHandle,
+    result_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let remaining = registry_remaining_capacity(
+            rev_reg_def
+                .load()?
+                .cast_ref::<RevocationRegistryDefinition>()?,
+            usage.load()?.cast_ref::<RevocationRegistryUsage>()?,
+        )?;
+        unsafe { *result_p = remaining as i64 };
+        Ok(())
+    })
+}
+
+impl_indy_object!(RevocationRegistryUsage, "RevocationRegistryUsage");
+impl_indy_object_from_json!(
+    RevocationRegistryUsage,
+    credx_revocation_registry_usage_from_json
+);
+
+fn make_credential_values(
+    attr_names: FfiStrList,
+    attr_raw_values: FfiStrList,
+    attr_enc_values: FfiStrList,
+    attr_media_types: FfiStrList,
+) -> Result<MakeCredentialValues> {
+    if attr_names.is_empty() {
+        return Err(err_msg!("Cannot create credential with no attribute"));
+    }
+    if attr_names.len() != attr_raw_values.len() {
+        return Err(err_msg!(
+            "Mismatch between length of attribute names and raw values"
+        ));
+    }
+    let enc_values = attr_enc_values.as_slice();
+    let media_types = attr_media_types.as_slice();
+    let mut cred_values = MakeCredentialValues::default();
+    let mut attr_idx = 0;
+    for (name, raw) in attr_names
+        .as_slice()
+        .into_iter()
+        .zip(attr_raw_values.as_slice())
+    {
+        let name = name
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing attribute name"))?
+            .to_string();
+        let raw = raw
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing attribute raw value"))?
+            .to_string();
+        let encoded = if attr_idx < enc_values.len() {
+            enc_values[attr_idx].as_opt_str().map(str::to_string)
+        } else {
+            None
+        };
+        let media_type = media_types
+            .get(attr_idx)
+            .and_then(|m| m.as_opt_str().map(str::to_string));
+        if let Some(encoded) = encoded {
+            cred_values.add_encoded(name.as_str(), raw, encoded);
+        } else {
+            cred_values.add_raw(name.as_str(), raw)?;
+        }
+        if let Some(media_type) = media_type {
+            cred_values.set_media_type(&name, media_type)?;
+        }
+        attr_idx += 1;
+    }
+    Ok(cred_values)
+}
+
 #[no_mangle]
 pub extern "C" fn credx_encode_credential_attributes(
     attr_raw_values: FfiStrList,
@@ -183,6 +396,26 @@ pub extern "C" fn credx_encode_credential_attributes(
     })
 }
 
+/// Hash binary attribute content, such as an image or document, returning the
+/// raw value and the encoded value to be used for the attribute
+#[no_mangle]
+pub extern "C" fn credx_encode_binary_attribute(
+    content: ByteBuffer,
+    raw_p: *mut *const c_char,
+    encoded_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(raw_p);
+        check_useful_c_ptr!(encoded_p);
+        let (raw, encoded) = encode_binary_attribute(content.as_slice())?;
+        unsafe {
+            *raw_p = rust_string_to_c(raw);
+            *encoded_p = rust_string_to_c(encoded);
+        };
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn credx_process_credential(
     cred: ObjectHandle,
@@ -217,8 +450,13 @@ pub extern "C" fn credx_process_credential(
 }
 
 impl_indy_object!(Credential, "Credential");
-impl_indy_object_from_json!(Credential, credx_credential_from_json);
+impl_indy_object_from_json!(Credential, credx_credential_from_json, limited);
+impl_indy_object_from_cbor!(Credential, credx_credential_from_cbor);
 
+/// Read a field of a credential: one of `schema_id`, `cred_def_id`, `rev_reg_id`,
+/// `rev_reg_index`, `values.<name>` for the raw value of the attribute `name`, or
+/// `media_types.<name>` for its declared MIME type.
+/// A null pointer is written when the field is not present.
 #[no_mangle]
 pub extern "C" fn credx_credential_get_attribute(
     handle: ObjectHandle,
@@ -228,23 +466,31 @@ pub extern "C" fn credx_credential_get_attribute(
     catch_error(|| {
         check_useful_c_ptr!(result_p);
         let cred = handle.load()?;
-        let cred = cred.cast_ref::<Credential>()?;
-        let val = match name.as_opt_str().unwrap_or_default() {
-            "schema_id" => rust_string_to_c(cred.schema_id.to_string()),
-            "cred_def_id" => rust_string_to_c(cred.cred_def_id.to_string()),
-            "rev_reg_id" => cred
-                .rev_reg_id
-                .as_ref()
-                .map(|s| rust_string_to_c(s.to_string()))
-                .unwrap_or(ptr::null_mut()),
-            "rev_reg_index" => cred
-                .signature
-                .extract_index()
-                .map(|s| rust_string_to_c(s.to_string()))
-                .unwrap_or(ptr::null_mut()),
-            s => return Err(err_msg!("Unsupported attribute: {}", s)),
-        };
+        let val = credential_attribute(
+            cred.cast_ref::<Credential>()?,
+            name.as_opt_str().unwrap_or_default(),
+        )?
+        .map(rust_string_to_c)
+        .unwrap_or(ptr::null_mut());
         unsafe { *result_p = val };
         Ok(())
     })
 }
+
+pub(crate) fn credential_attribute(cred: &Credential, name: &str) -> Result<Option<String>> {
+    Ok(match name {
+        "schema_id" => Some(cred.schema_id.to_string()),
+        "cred_def_id" => Some(cred.cred_def_id.to_string()),
+        "rev_reg_id" => cred.rev_reg_id.as_ref().map(|s| s.to_string()),
+        "rev_reg_index" => cred.signature.extract_index().map(|s| s.to_string()),
+        s => {
+            if let Some(attr) = s.strip_prefix("values.") {
+                cred.values.0.get(attr).map(|v| v.raw.clone())
+            } else if let Some(attr) = s.strip_prefix("media_types.") {
+                cred.values.0.get(attr).and_then(|v| v.media_type.clone())
+            } else {
+                return Err(err_msg!("Unsupported attribute: {}", s));
+            }
+        }
+    })
+}
@@ -1,5 +1,6 @@
 use crate::error::{Error, ErrorKind, Result};
 
+use std::error::Error as StdError;
 use std::os::raw::c_char;
 use std::panic::{catch_unwind, UnwindSafe};
 use std::sync::RwLock;
@@ -22,6 +23,50 @@ pub enum ErrorCode {
     InvalidUserRevocId = 6,
     ProofRejected = 7,
     RevocationRegistryFull = 8,
+    LimitExceeded = 9,
+    NonceMismatch = 10,
+}
+
+impl ErrorCode {
+    /// All error codes, in order of their numeric values
+    pub const ALL: [ErrorCode; 11] = [
+        ErrorCode::Success,
+        ErrorCode::Input,
+        ErrorCode::IOError,
+        ErrorCode::InvalidState,
+        ErrorCode::Unexpected,
+        ErrorCode::CredentialRevoked,
+        ErrorCode::InvalidUserRevocId,
+        ErrorCode::ProofRejected,
+        ErrorCode::RevocationRegistryFull,
+        ErrorCode::LimitExceeded,
+        ErrorCode::NonceMismatch,
+    ];
+
+    pub fn category(self) -> Option<ErrorCategory> {
+        match self {
+            ErrorCode::Success => None,
+            ErrorCode::Input | ErrorCode::LimitExceeded => Some(ErrorCategory::Input),
+            ErrorCode::IOError => Some(ErrorCategory::IO),
+            ErrorCode::InvalidState | ErrorCode::Unexpected => Some(ErrorCategory::Unexpected),
+            ErrorCode::CredentialRevoked
+            | ErrorCode::InvalidUserRevocId
+            | ErrorCode::RevocationRegistryFull => Some(ErrorCategory::Revocation),
+            ErrorCode::ProofRejected | ErrorCode::NonceMismatch => Some(ErrorCategory::Proof),
+        }
+    }
+}
+
+/// The broad category of an error code, allowing callers to branch on the
+/// type of failure without matching each individual code
+#[derive(Debug, PartialEq, Copy, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+    Input,
+    IO,
+    Revocation,
+    Proof,
+    Unexpected,
 }
 
 impl From<ErrorKind> for ErrorCode {
@@ -35,6 +80,8 @@ impl From<ErrorKind> for ErrorCode {
             ErrorKind::InvalidUserRevocId => ErrorCode::InvalidUserRevocId,
             ErrorKind::ProofRejected => ErrorCode::ProofRejected,
             ErrorKind::RevocationRegistryFull => ErrorCode::RevocationRegistryFull,
+            ErrorKind::LimitExceeded => ErrorCode::LimitExceeded,
+            ErrorKind::NonceMismatch => ErrorCode::NonceMismatch,
         }
     }
 }
@@ -58,6 +105,27 @@ pub extern "C" fn credx_get_current_error(error_json_p: *mut *const c_char) -> E
     ErrorCode::Success
 }
 
+/// List the stable numeric error codes along with their names and categories
+#[no_mangle]
+pub extern "C" fn credx_get_error_codes(codes_json_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(codes_json_p);
+        let codes = ErrorCode::ALL
+            .iter()
+            .map(|code| {
+                serde_json::json!({
+                    "code": *code as usize,
+                    "kind": code,
+                    "category": code.category(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let codes = serde_json::to_string(&codes)?;
+        unsafe { *codes_json_p = rust_string_to_c(codes) };
+        Ok(())
+    })
+}
+
 pub fn catch_error<F>(f: F) -> ErrorCode
 where
     F: FnOnce() -> Result<()> + UnwindSafe,
@@ -79,8 +147,26 @@ where
 pub fn get_current_error_json() -> String {
     if let Some(err) = Option::take(&mut *LAST_ERROR.write().unwrap()) {
         let message = err.to_string();
-        let code = ErrorCode::from(err.kind()) as usize;
-        serde_json::json!({"code": code, "message": message}).to_string()
+        let code = ErrorCode::from(err.kind());
+        let mut causes = vec![];
+        let mut source = err.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        let mut json = serde_json::json!({
+            "code": code as usize,
+            "kind": code,
+            "category": code.category(),
+            "message": message,
+        });
+        if !causes.is_empty() {
+            json["causes"] = causes.into();
+        }
+        if let Some(extra) = err.extra {
+            json["extra"] = extra;
+        }
+        json.to_string()
     } else {
         r#"{"code":0,"message":null}"#.to_owned()
     }
@@ -1,6 +1,14 @@
+use ffi_support::{ByteBuffer, FfiStr};
+
 use super::error::{catch_error, ErrorCode};
-use super::object::ObjectHandle;
-use crate::services::{prover::create_master_secret, types::MasterSecret};
+use super::object::{serialize_json, ObjectHandle, ToJson};
+use crate::error::Result;
+use crate::services::{
+    prover::{create_master_secret, create_master_secret_proof},
+    types::{MasterSecret, MasterSecretProof},
+    verifier::verify_master_secret_proof,
+};
+use indy_data_types::anoncreds::nonce::Nonce;
 
 #[no_mangle]
 pub extern "C" fn credx_create_master_secret(master_secret_p: *mut ObjectHandle) -> ErrorCode {
@@ -12,5 +20,107 @@ pub extern "C" fn credx_create_master_secret(master_secret_p: *mut ObjectHandle)
     })
 }
 
-impl_indy_object!(MasterSecret, "MasterSecret");
+impl ToJson for MasterSecret {
+    fn to_json(&self) -> Result<Vec<u8>> {
+        // exporting the secret over FFI is always deliberate
+        serialize_json(&self.export())
+    }
+}
+
+impl_indy_object!(@impl MasterSecret, "MasterSecret");
 impl_indy_object_from_json!(MasterSecret, credx_master_secret_from_json);
+impl_indy_object_from_cbor!(MasterSecret, credx_master_secret_from_cbor);
+
+/// Export a master (link) secret encrypted under a password, for backup
+#[no_mangle]
+pub extern "C" fn credx_link_secret_export(
+    master_secret: ObjectHandle,
+    password: ByteBuffer,
+    result_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let protected = master_secret
+            .load()?
+            .cast_ref::<MasterSecret>()?
+            .to_protected_bytes(password.as_slice())
+            .map_err(err_map!(Unexpected))?;
+        unsafe { *result_p = ByteBuffer::from_vec(protected) };
+        Ok(())
+    })
+}
+
+/// Restore a master (link) secret exported with `credx_link_secret_export`
+#[no_mangle]
+pub extern "C" fn credx_link_secret_import(
+    protected: ByteBuffer,
+    password: ByteBuffer,
+    master_secret_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(master_secret_p);
+        let secret = MasterSecret::from_protected_bytes(protected.as_slice(), password.as_slice())
+            .map_err(err_map!(Input))?;
+        let secret = ObjectHandle::create(secret)?;
+        unsafe { *master_secret_p = secret };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_create_master_secret_proof(
+    cred: ObjectHandle,
+    master_secret: ObjectHandle,
+    schema: ObjectHandle,
+    cred_def: ObjectHandle,
+    nonce: FfiStr,
+    proof_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(proof_p);
+        let nonce = load_nonce(nonce)?;
+        let proof = create_master_secret_proof(
+            cred.load()?.cast_ref()?,
+            master_secret.load()?.cast_ref()?,
+            schema.load()?.cast_ref()?,
+            cred_def.load()?.cast_ref()?,
+            &nonce,
+        )?;
+        let proof = ObjectHandle::create(proof)?;
+        unsafe { *proof_p = proof };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_verify_master_secret_proof(
+    proof: ObjectHandle,
+    schema: ObjectHandle,
+    cred_def: ObjectHandle,
+    nonce: FfiStr,
+    result_p: *mut i8,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let nonce = load_nonce(nonce)?;
+        let verify = verify_master_secret_proof(
+            proof.load()?.cast_ref()?,
+            schema.load()?.cast_ref()?,
+            cred_def.load()?.cast_ref()?,
+            &nonce,
+        )?;
+        unsafe { *result_p = verify as i8 };
+        Ok(())
+    })
+}
+
+fn load_nonce(nonce: FfiStr) -> Result<Nonce> {
+    let nonce = nonce
+        .as_opt_str()
+        .ok_or_else(|| err_msg!("Missing nonce"))?;
+    Nonce::from_dec(nonce).map_err(err_map!(Input, "Invalid nonce"))
+}
+
+impl_indy_object!(MasterSecretProof, "MasterSecretProof");
+impl_indy_object_from_json!(MasterSecretProof, credx_master_secret_proof_from_json);
+impl_indy_object_from_cbor!(MasterSecretProof, credx_master_secret_proof_from_cbor);
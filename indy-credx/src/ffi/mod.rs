@@ -1,8 +1,17 @@
 use std::os::raw::c_char;
 
-use ffi_support::{rust_string_to_c, ByteBuffer};
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
 use zeroize::Zeroize;
 
+use std::sync::Arc;
+
+use crate::services::{
+    types::{CredxConfig, IssuerMetrics},
+    utils::{
+        clear_public_key_cache, get_config, get_issuer_metrics, set_config, set_issuer_metrics,
+    },
+};
+
 pub static LIB_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 ffi_support::define_string_destructor!(credx_string_free);
@@ -32,8 +41,11 @@ mod credential;
 mod master_secret;
 mod pres_req;
 mod presentation;
+mod resolver;
 mod revocation;
 mod schema;
+mod self_test;
+mod utf16;
 
 #[no_mangle]
 pub extern "C" fn credx_set_default_logger() -> ErrorCode {
@@ -44,6 +56,78 @@ pub extern "C" fn credx_set_default_logger() -> ErrorCode {
     })
 }
 
+#[no_mangle]
+pub extern "C" fn credx_set_config(config_json: FfiStr) -> ErrorCode {
+    catch_error(|| {
+        let config_json = config_json
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing configuration"))?;
+        set_config(serde_json::from_str::<CredxConfig>(config_json)?)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_get_config(config_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(config_p);
+        let config = serde_json::to_string(&get_config())?;
+        unsafe { *config_p = rust_string_to_c(config) };
+        Ok(())
+    })
+}
+
+/// Enable or disable the recording of issuer activity counters. Enabling
+/// metrics which are already being recorded retains the existing counters.
+#[no_mangle]
+pub extern "C" fn credx_set_issuer_metrics_enabled(enabled: i8) -> ErrorCode {
+    catch_error(|| {
+        if enabled == 0 {
+            set_issuer_metrics(None);
+        } else if get_issuer_metrics().is_none() {
+            set_issuer_metrics(Some(Arc::new(IssuerMetrics::new())));
+        }
+        Ok(())
+    })
+}
+
+/// Get a JSON object of the issuer activity counters keyed by credential
+/// definition ID, which is empty when metrics are not enabled
+#[no_mangle]
+pub extern "C" fn credx_get_issuer_metrics(metrics_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(metrics_p);
+        let snapshot = get_issuer_metrics()
+            .map(|metrics| metrics.snapshot())
+            .unwrap_or_default();
+        let metrics = serde_json::to_string(&snapshot)?;
+        unsafe { *metrics_p = rust_string_to_c(metrics) };
+        Ok(())
+    })
+}
+
+/// Set the directory operation transcripts are written to. Passing a null
+/// or empty path disables recording.
+#[cfg(feature = "transcript")]
+#[no_mangle]
+pub extern "C" fn credx_set_transcript_dir(path: FfiStr) -> ErrorCode {
+    catch_error(|| {
+        let dir = path
+            .as_opt_str()
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from);
+        crate::services::transcript::set_transcript_dir(dir);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_clear_public_key_cache() -> ErrorCode {
+    catch_error(|| {
+        clear_public_key_cache();
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn credx_version() -> *mut c_char {
     rust_string_to_c(LIB_VERSION.to_owned())
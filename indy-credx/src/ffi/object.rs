@@ -2,62 +2,200 @@ use std::any::TypeId;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
+use std::io::Write;
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_char;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use ffi_support::{rust_string_to_c, ByteBuffer};
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
 use once_cell::sync::Lazy;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
 use super::error::{catch_error, ErrorCode};
-use crate::error::Result;
+use super::util::FfiList;
+use crate::error::{Error, ErrorKind, Result};
+use crate::services::types::CheckLimits;
+use crate::services::utils::{check_limits, current_timestamp};
 
-pub(crate) static FFI_OBJECTS: Lazy<Mutex<BTreeMap<ObjectHandle, IndyObject>>> =
-    Lazy::new(|| Mutex::new(BTreeMap::new()));
+pub(crate) static FFI_OBJECTS: Lazy<ObjectStore> = Lazy::new(ObjectStore::new);
+
+/// The number of independently locked shards in the object store
+const OBJECT_STORE_SHARDS: usize = 16;
+
+/// A registered object along with the time it was created and any user data
+/// attached by the host application
+#[derive(Clone, Debug)]
+pub(crate) struct ObjectEntry {
+    obj: IndyObject,
+    created: u64,
+    tag: Option<String>,
+}
+
+/// The registry of live FFI objects.
+///
+/// Handles are distributed over a fixed set of shards, each behind its own
+/// read-write lock, so that concurrent loads do not block each other and
+/// creating or freeing an object only locks the shard containing its handle.
+pub(crate) struct ObjectStore {
+    shards: Vec<RwLock<BTreeMap<ObjectHandle, ObjectEntry>>>,
+}
+
+impl ObjectStore {
+    fn new() -> Self {
+        Self {
+            shards: (0..OBJECT_STORE_SHARDS)
+                .map(|_| RwLock::new(BTreeMap::new()))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn shard(&self, handle: &ObjectHandle) -> &RwLock<BTreeMap<ObjectHandle, ObjectEntry>> {
+        &self.shards[handle.0 % self.shards.len()]
+    }
+
+    fn read(
+        &self,
+        handle: &ObjectHandle,
+    ) -> Result<RwLockReadGuard<'_, BTreeMap<ObjectHandle, ObjectEntry>>> {
+        self.shard(handle)
+            .read()
+            .map_err(|_| err_msg!("Error locking object store"))
+    }
+
+    fn write(
+        &self,
+        handle: &ObjectHandle,
+    ) -> Result<RwLockWriteGuard<'_, BTreeMap<ObjectHandle, ObjectEntry>>> {
+        self.shard(handle)
+            .write()
+            .map_err(|_| err_msg!("Error locking object store"))
+    }
+
+    fn insert(&self, handle: ObjectHandle, entry: ObjectEntry) -> Result<()> {
+        self.write(&handle)?.insert(handle, entry);
+        Ok(())
+    }
+
+    fn get(&self, handle: &ObjectHandle) -> Result<IndyObject> {
+        self.read(handle)?
+            .get(handle)
+            .map(|entry| entry.obj.clone())
+            .ok_or_else(|| err_msg!("Invalid object handle"))
+    }
+
+    fn remove(&self, handle: &ObjectHandle) -> Result<IndyObject> {
+        self.write(handle)?
+            .remove(handle)
+            .map(|entry| entry.obj)
+            .ok_or_else(|| err_msg!("Invalid object handle"))
+    }
+
+    /// Remove a set of handles, locking each shard at most once. Returns the
+    /// number of objects removed.
+    fn remove_many(&self, handles: &[ObjectHandle]) -> Result<usize> {
+        let mut removed = 0;
+        for (idx, shard) in self.shards.iter().enumerate() {
+            let mut shard_handles = handles
+                .iter()
+                .filter(|handle| handle.0 % self.shards.len() == idx)
+                .peekable();
+            if shard_handles.peek().is_none() {
+                continue;
+            }
+            let mut shard = shard
+                .write()
+                .map_err(|_| err_msg!("Error locking object store"))?;
+            removed += shard_handles
+                .filter(|handle| shard.remove(handle).is_some())
+                .count();
+        }
+        Ok(removed)
+    }
+
+    /// Remove all objects, returning the number removed
+    fn clear(&self) -> Result<usize> {
+        let mut removed = 0;
+        for shard in self.shards.iter() {
+            let mut shard = shard
+                .write()
+                .map_err(|_| err_msg!("Error locking object store"))?;
+            removed += shard.len();
+            shard.clear();
+        }
+        Ok(removed)
+    }
+
+    fn len(&self) -> Result<usize> {
+        let mut count = 0;
+        for shard in self.shards.iter() {
+            count += shard
+                .read()
+                .map_err(|_| err_msg!("Error locking object store"))?
+                .len();
+        }
+        Ok(count)
+    }
+
+    /// Collect a snapshot of all entries, in order of creation
+    fn entries(&self) -> Result<Vec<(ObjectHandle, ObjectEntry)>> {
+        let mut entries = Vec::new();
+        for shard in self.shards.iter() {
+            let shard = shard
+                .read()
+                .map_err(|_| err_msg!("Error locking object store"))?;
+            entries.extend(shard.iter().map(|(h, e)| (*h, e.clone())));
+        }
+        entries.sort_by_key(|(handle, _)| *handle);
+        Ok(entries)
+    }
+}
 
 indy_utils::new_handle_type!(ObjectHandle, FFI_OBJECT_COUNTER);
 
 impl ObjectHandle {
     pub(crate) fn create<O: AnyIndyObject + 'static>(value: O) -> Result<Self> {
         let handle = Self::next();
-        FFI_OBJECTS
-            .lock()
-            .map_err(|_| err_msg!("Error locking object store"))?
-            .insert(handle, IndyObject::new(value));
+        FFI_OBJECTS.insert(
+            handle,
+            ObjectEntry {
+                obj: IndyObject::new(value),
+                created: current_timestamp(),
+                tag: None,
+            },
+        )?;
         Ok(handle)
     }
 
     pub(crate) fn load(&self) -> Result<IndyObject> {
-        FFI_OBJECTS
-            .lock()
-            .map_err(|_| err_msg!("Error locking object store"))?
-            .get(self)
-            .cloned()
-            .ok_or_else(|| err_msg!("Invalid object handle"))
+        FFI_OBJECTS.get(self)
     }
 
     pub(crate) fn opt_load(&self) -> Result<Option<IndyObject>> {
         if self.0 != 0 {
-            Some(
-                FFI_OBJECTS
-                    .lock()
-                    .map_err(|_| err_msg!("Error locking object store"))?
-                    .get(self)
-                    .cloned()
-                    .ok_or_else(|| err_msg!("Invalid object handle")),
-            )
-            .transpose()
+            Some(FFI_OBJECTS.get(self)).transpose()
         } else {
             Ok(None)
         }
     }
 
     pub(crate) fn remove(&self) -> Result<IndyObject> {
+        FFI_OBJECTS.remove(self)
+    }
+
+    fn set_tag(&self, tag: Option<String>) -> Result<()> {
         FFI_OBJECTS
-            .lock()
-            .map_err(|_| err_msg!("Error locking object store"))?
-            .remove(self)
+            .write(self)?
+            .get_mut(self)
+            .map(|entry| entry.tag = tag)
+            .ok_or_else(|| err_msg!("Invalid object handle"))
+    }
+
+    fn get_tag(&self) -> Result<Option<String>> {
+        FFI_OBJECTS
+            .read(self)?
+            .get(self)
+            .map(|entry| entry.tag.clone())
             .ok_or_else(|| err_msg!("Invalid object handle"))
     }
 }
@@ -111,6 +249,21 @@ impl Hash for IndyObject {
 
 pub(crate) trait ToJson {
     fn to_json(&self) -> Result<Vec<u8>>;
+
+    /// Serialize the object to a writer without buffering the complete output
+    fn write_json(&self, writer: &mut dyn Write) -> Result<()> {
+        let json = self.to_json()?;
+        writer
+            .write_all(&json)
+            .map_err(err_map!(IOError, "Error writing object"))
+    }
+
+    /// Serialize the object to CBOR, by default converting its JSON representation
+    #[cfg(feature = "cbor")]
+    fn to_cbor(&self) -> Result<Vec<u8>> {
+        let value: serde_json::Value = serde_json::from_slice(&self.to_json()?)?;
+        crate::services::cbor::to_cbor(&value)
+    }
 }
 
 impl ToJson for IndyObject {
@@ -118,20 +271,38 @@ impl ToJson for IndyObject {
     fn to_json(&self) -> Result<Vec<u8>> {
         self.0.to_json()
     }
-}
 
-impl<T> ToJson for T
-where
-    T: Serialize,
-{
-    fn to_json(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(self).map_err(err_map!("Error serializing object"))
+    #[inline]
+    fn write_json(&self, writer: &mut dyn Write) -> Result<()> {
+        self.0.write_json(writer)
     }
+
+    #[cfg(feature = "cbor")]
+    #[inline]
+    fn to_cbor(&self) -> Result<Vec<u8>> {
+        self.0.to_cbor()
+    }
+}
+
+pub(crate) fn serialize_json<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(err_map!("Error serializing object"))
+}
+
+pub(crate) fn write_json<T: Serialize>(value: &T, writer: &mut dyn Write) -> Result<()> {
+    serde_json::to_writer(writer, value).map_err(|err| {
+        if err.is_io() {
+            err_msg!(IOError, "Error writing object: {}", err)
+        } else {
+            err_msg!("Error serializing object: {}", err)
+        }
+    })
 }
 
 pub(crate) trait AnyIndyObject: Debug + ToJson + Send + Sync {
     fn type_name(&self) -> &'static str;
 
+    fn validate(&self) -> std::result::Result<(), indy_utils::ValidationError>;
+
     #[doc(hidden)]
     fn type_id(&self) -> TypeId
     where
@@ -143,16 +314,169 @@ pub(crate) trait AnyIndyObject: Debug + ToJson + Send + Sync {
 
 macro_rules! impl_indy_object {
     ($ident:path, $name:expr) => {
+        impl $crate::ffi::object::ToJson for $ident {
+            fn to_json(&self) -> $crate::error::Result<Vec<u8>> {
+                $crate::ffi::object::serialize_json(self)
+            }
+
+            fn write_json(
+                &self,
+                writer: &mut dyn std::io::Write,
+            ) -> $crate::error::Result<()> {
+                $crate::ffi::object::write_json(self, writer)
+            }
+
+            #[cfg(feature = "cbor")]
+            fn to_cbor(&self) -> $crate::error::Result<Vec<u8>> {
+                $crate::services::cbor::to_cbor(self)
+            }
+        }
+
+        impl_indy_object!(@impl $ident, $name);
+    };
+    (@impl $ident:path, $name:expr) => {
         impl $crate::ffi::object::AnyIndyObject for $ident {
             fn type_name(&self) -> &'static str {
                 $name
             }
+
+            fn validate(&self) -> std::result::Result<(), indy_utils::ValidationError> {
+                indy_utils::Validatable::validate(self)
+            }
         }
     };
 }
 
+/// Deserialize an object, reporting the path to the offending field on failure
+pub(crate) fn object_from_json<T: DeserializeOwned>(json: &[u8]) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_slice(json);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(json_path_error)
+}
+
+/// Deserialize an object, rejecting it when it exceeds the configured size and
+/// complexity limits
+pub(crate) fn limited_object_from_json<T: DeserializeOwned + CheckLimits>(
+    json: &[u8],
+) -> Result<T> {
+    let obj = object_from_json::<T>(json)?;
+    check_limits(&obj)?;
+    Ok(obj)
+}
+
+/// Deserialize an object tagged with a `ver` property. The tagged content is
+/// buffered by serde, so on failure the untagged version is parsed again in
+/// order to locate the offending field.
+pub(crate) fn versioned_object_from_json<T: DeserializeOwned, V: DeserializeOwned>(
+    json: &[u8],
+) -> Result<T> {
+    object_from_json::<T>(json).map_err(|err| {
+        let mut value = match serde_json::from_slice::<serde_json::Value>(json) {
+            Ok(serde_json::Value::Object(value)) => value,
+            _ => return err,
+        };
+        value.remove("ver");
+        match serde_path_to_error::deserialize::<_, V>(serde_json::Value::Object(value)) {
+            Err(inner_err) if inner_err.path().iter().next().is_some() => {
+                json_path_error(inner_err)
+            }
+            _ => err,
+        }
+    })
+}
+
+/// Deserialize an object tagged with a `ver` property, rejecting it when it
+/// exceeds the configured size and complexity limits
+pub(crate) fn limited_versioned_object_from_json<
+    T: DeserializeOwned + CheckLimits,
+    V: DeserializeOwned,
+>(
+    json: &[u8],
+) -> Result<T> {
+    let obj = versioned_object_from_json::<T, V>(json)?;
+    check_limits(&obj)?;
+    Ok(obj)
+}
+
+fn json_path_error(err: serde_path_to_error::Error<serde_json::Error>) -> Error {
+    let path = err.path().to_string();
+    let inner = err.into_inner();
+    let mut message = inner.to_string();
+    if inner.line() > 0 {
+        // drop the position suffix, it is reported separately
+        if let Some(pos) = message.rfind(" at line ") {
+            message.truncate(pos);
+        }
+    }
+    let expected = message
+        .rfind(", expected ")
+        .map(|pos| message[pos + 11..].to_string());
+    let extra = serde_json::json!({
+        "path": path,
+        "expected": expected,
+        "line": inner.line(),
+        "column": inner.column(),
+    });
+    if inner.is_syntax() || inner.is_eof() {
+        err_msg!("Invalid JSON: {}", message).with_extra(extra)
+    } else {
+        err_msg!("Invalid JSON at `{}`: {}", path, message).with_extra(extra)
+    }
+}
+
+/// Register a deserialized object, validating it first when strict parsing is enabled
+pub(crate) fn create_parsed_object<T: AnyIndyObject + indy_utils::Validatable + 'static>(
+    obj: T,
+) -> Result<ObjectHandle> {
+    if crate::services::utils::get_config().strict_parsing {
+        indy_utils::Validatable::validate(&obj)?;
+    }
+    ObjectHandle::create(obj)
+}
+
+/// Parse an object passed as JSON, applying the same checks as object creation
+pub(crate) fn parse_json_object<T: DeserializeOwned + indy_utils::Validatable>(
+    json: &[u8],
+) -> Result<T> {
+    check_parsed_object(object_from_json(json)?)
+}
+
+pub(crate) fn check_parsed_object<T: indy_utils::Validatable>(obj: T) -> Result<T> {
+    if crate::services::utils::get_config().strict_parsing {
+        obj.validate()?;
+    }
+    Ok(obj)
+}
+
 macro_rules! impl_indy_object_from_json {
     ($ident:path, $method:ident) => {
+        impl_indy_object_from_json!(
+            @impl $ident,
+            $method,
+            $crate::ffi::object::object_from_json::<$ident>
+        );
+    };
+    ($ident:path, $method:ident, limited) => {
+        impl_indy_object_from_json!(
+            @impl $ident,
+            $method,
+            $crate::ffi::object::limited_object_from_json::<$ident>
+        );
+    };
+    ($ident:path, $method:ident, versioned $v1:path) => {
+        impl_indy_object_from_json!(
+            @impl $ident,
+            $method,
+            $crate::ffi::object::versioned_object_from_json::<$ident, $v1>
+        );
+    };
+    ($ident:path, $method:ident, limited versioned $v1:path) => {
+        impl_indy_object_from_json!(
+            @impl $ident,
+            $method,
+            $crate::ffi::object::limited_versioned_object_from_json::<$ident, $v1>
+        );
+    };
+    (@impl $ident:path, $method:ident, $parse:expr) => {
         #[no_mangle]
         pub extern "C" fn $method(
             json: ffi_support::ByteBuffer,
@@ -160,8 +484,27 @@ macro_rules! impl_indy_object_from_json {
         ) -> $crate::ffi::error::ErrorCode {
             $crate::ffi::error::catch_error(|| {
                 check_useful_c_ptr!(result_p);
-                let obj = serde_json::from_slice::<$ident>(json.as_slice())?;
-                let handle = $crate::ffi::object::ObjectHandle::create(obj)?;
+                let obj: $ident = $parse(json.as_slice())?;
+                let handle = $crate::ffi::object::create_parsed_object(obj)?;
+                unsafe { *result_p = handle };
+                Ok(())
+            })
+        }
+    };
+}
+
+macro_rules! impl_indy_object_from_cbor {
+    ($ident:path, $method:ident) => {
+        #[cfg(feature = "cbor")]
+        #[no_mangle]
+        pub extern "C" fn $method(
+            cbor: ffi_support::ByteBuffer,
+            result_p: *mut $crate::ffi::object::ObjectHandle,
+        ) -> $crate::ffi::error::ErrorCode {
+            $crate::ffi::error::catch_error(|| {
+                check_useful_c_ptr!(result_p);
+                let obj: $ident = $crate::services::cbor::from_cbor(cbor.as_slice())?;
+                let handle = $crate::ffi::object::create_parsed_object(obj)?;
                 unsafe { *result_p = handle };
                 Ok(())
             })
@@ -183,6 +526,151 @@ pub extern "C" fn credx_object_get_json(
     })
 }
 
+/// Serialize an object to CBOR
+#[cfg(feature = "cbor")]
+#[no_mangle]
+pub extern "C" fn credx_object_get_cbor(
+    handle: ObjectHandle,
+    result_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let obj = handle.load()?;
+        let cbor = obj.to_cbor()?;
+        unsafe { *result_p = ByteBuffer::from_vec(cbor) };
+        Ok(())
+    })
+}
+
+/// Receive a chunk of serialized object JSON. Returning an error code other than
+/// `Success` aborts the export.
+pub type JsonChunkCallback = extern "C" fn(context: i64, data: *const u8, len: i64) -> ErrorCode;
+
+struct ChunkWriter {
+    callback: JsonChunkCallback,
+    context: i64,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl ChunkWriter {
+    fn emit(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let code = (self.callback)(self.context, self.buffer.as_ptr(), self.buffer.len() as i64);
+        self.buffer.clear();
+        if code == ErrorCode::Success {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!(
+                "export aborted by callback: error code {}",
+                code as usize
+            )))
+        }
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == self.chunk_size {
+            self.emit()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.emit()
+    }
+}
+
+/// Serialize an object to JSON, passing the output to `callback` in chunks of at
+/// most `chunk_size` bytes as it is produced instead of buffering the whole document
+#[no_mangle]
+pub extern "C" fn credx_object_get_json_chunked(
+    handle: ObjectHandle,
+    chunk_size: i64,
+    callback: Option<JsonChunkCallback>,
+    context: i64,
+) -> ErrorCode {
+    catch_error(|| {
+        let callback = callback.ok_or_else(|| err_msg!("Missing chunk callback"))?;
+        if chunk_size <= 0 {
+            return Err(err_msg!("Invalid chunk size"));
+        }
+        let chunk_size = chunk_size as usize;
+        let obj = handle.load()?;
+        let mut writer = ChunkWriter {
+            callback,
+            context,
+            buffer: Vec::with_capacity(chunk_size.min(JSON_CHUNK_PREALLOC)),
+            chunk_size,
+        };
+        obj.write_json(&mut writer)?;
+        writer
+            .flush()
+            .map_err(err_map!(IOError, "Error writing object"))
+    })
+}
+
+/// Serialize an object to JSON directly into a caller-provided buffer. The number
+/// of bytes written is stored in `written_p`. When the buffer is too small an
+/// error is returned and `written_p` receives the required length instead.
+#[no_mangle]
+pub extern "C" fn credx_object_write_json(
+    handle: ObjectHandle,
+    buffer: *mut u8,
+    buffer_len: i64,
+    written_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(written_p);
+        if buffer_len < 0 || (buffer.is_null() && buffer_len > 0) {
+            return Err(err_msg!("Invalid output buffer"));
+        }
+        let obj = handle.load()?;
+        let out: &mut [u8] = if buffer_len == 0 {
+            &mut []
+        } else {
+            unsafe { std::slice::from_raw_parts_mut(buffer, buffer_len as usize) }
+        };
+        let mut cursor = std::io::Cursor::new(out);
+        match obj.write_json(&mut cursor) {
+            Ok(()) => {
+                unsafe { *written_p = cursor.position() as i64 };
+                Ok(())
+            }
+            Err(err) if err.kind() == ErrorKind::IOError => {
+                let mut counter = CountingWriter(0);
+                obj.write_json(&mut counter)?;
+                unsafe { *written_p = counter.0 as i64 };
+                Err(err_msg!(
+                    "Output buffer too small: {} bytes required",
+                    counter.0
+                ))
+            }
+            Err(err) => Err(err),
+        }
+    })
+}
+
+const JSON_CHUNK_PREALLOC: usize = 64 * 1024;
+
+struct CountingWriter(usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn credx_object_get_type_name(
     handle: ObjectHandle,
@@ -197,11 +685,125 @@ pub extern "C" fn credx_object_get_type_name(
     })
 }
 
+/// Validate an object, producing a JSON report of the validation problems found
+#[no_mangle]
+pub extern "C" fn credx_object_validate(
+    handle: ObjectHandle,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let obj = handle.load()?;
+        let problems = match obj.0.validate() {
+            Ok(()) => vec![],
+            Err(err) => vec![serde_json::json!({ "message": err.to_string() })],
+        };
+        let report = serde_json::json!({
+            "type": obj.type_name(),
+            "valid": problems.is_empty(),
+            "problems": problems,
+        });
+        unsafe { *result_p = rust_string_to_c(report.to_string()) };
+        Ok(())
+    })
+}
+
+/// Attach a user data string to an object handle, replacing any existing value.
+/// Passing a null tag removes the user data. The tag is released with the handle.
+#[no_mangle]
+pub extern "C" fn credx_object_set_tag(handle: ObjectHandle, tag: FfiStr) -> ErrorCode {
+    catch_error(|| handle.set_tag(tag.into_opt_string()))
+}
+
+/// Get the user data string attached to an object handle, or null if none is set
+#[no_mangle]
+pub extern "C" fn credx_object_get_tag(
+    handle: ObjectHandle,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let tag = handle
+            .get_tag()?
+            .map(rust_string_to_c)
+            .unwrap_or(std::ptr::null_mut());
+        unsafe { *result_p = tag };
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn credx_object_free(handle: ObjectHandle) {
     handle.remove().ok();
 }
 
+/// Free a list of object handles in a single call. Invalid handles are ignored.
+#[no_mangle]
+pub extern "C" fn credx_object_free_list(handles: FfiList<ObjectHandle>) {
+    FFI_OBJECTS.remove_many(handles.as_slice()).ok();
+}
+
+/// Free all live objects. Handles are never reused, so any handles retained by
+/// the caller become invalid rather than referring to objects created later.
+#[no_mangle]
+pub extern "C" fn credx_clear_object_store() -> ErrorCode {
+    catch_error(|| {
+        FFI_OBJECTS.clear()?;
+        Ok(())
+    })
+}
+
+/// Get the number of live object handles, in order to detect handle leaks
+#[no_mangle]
+pub extern "C" fn credx_object_count(result_p: *mut i64) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let count = FFI_OBJECTS.len()?;
+        unsafe { *result_p = count as i64 };
+        Ok(())
+    })
+}
+
+/// List the live object handles as a JSON array, in order of creation
+#[no_mangle]
+pub extern "C" fn credx_object_list_handles(result_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let handles = FFI_OBJECTS
+            .entries()?
+            .into_iter()
+            .map(|(handle, _)| handle.0)
+            .collect::<Vec<_>>();
+        let handles = serde_json::to_string(&handles)
+            .map_err(err_map!(Unexpected, "Error serializing object handles"))?;
+        unsafe { *result_p = rust_string_to_c(handles) };
+        Ok(())
+    })
+}
+
+/// Describe the live object handles as a JSON array of entries with the handle,
+/// type name, creation timestamp and user data tag of each object
+#[no_mangle]
+pub extern "C" fn credx_object_dump(result_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let entries = FFI_OBJECTS
+            .entries()?
+            .into_iter()
+            .map(|(handle, entry)| {
+                serde_json::json!({
+                    "handle": handle.0,
+                    "type": entry.obj.type_name(),
+                    "created": entry.created,
+                    "tag": entry.tag,
+                })
+            })
+            .collect::<Vec<_>>();
+        unsafe { *result_p = rust_string_to_c(serde_json::Value::from(entries).to_string()) };
+        Ok(())
+    })
+}
+
 pub(crate) trait IndyObjectId: AnyIndyObject {
     type Id: Eq + Hash;
 
@@ -260,3 +862,267 @@ impl DerefMut for IndyObjectList {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::types::PresentationRequest;
+    use ffi_support::FfiStr;
+    use std::ffi::{CStr, CString};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    // tests using the global object store must not run concurrently
+    static GLOBAL_STORE: Mutex<()> = Mutex::new(());
+
+    fn lock_global_store() -> std::sync::MutexGuard<'static, ()> {
+        GLOBAL_STORE.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn create_test_object() -> ObjectHandle {
+        ObjectHandle::create(test_pres_req()).unwrap()
+    }
+
+    fn ffi_get_tag(handle: ObjectHandle) -> std::result::Result<Option<String>, ErrorCode> {
+        let mut tag_p = std::ptr::null();
+        match credx_object_get_tag(handle, &mut tag_p) {
+            ErrorCode::Success if tag_p.is_null() => Ok(None),
+            ErrorCode::Success => {
+                let tag = unsafe { CString::from_raw(tag_p as *mut c_char) };
+                Ok(Some(tag.into_string().unwrap()))
+            }
+            err => Err(err),
+        }
+    }
+
+    fn test_pres_req() -> PresentationRequest {
+        serde_json::from_value(serde_json::json!({
+            "nonce": "1234",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {},
+            "requested_predicates": {},
+        }))
+        .unwrap()
+    }
+
+    fn test_entry() -> ObjectEntry {
+        ObjectEntry {
+            obj: IndyObject::new(test_pres_req()),
+            created: 0,
+            tag: None,
+        }
+    }
+
+    #[test]
+    fn object_store_entries_ordered() {
+        let store = ObjectStore::new();
+        for idx in (1..=40).rev() {
+            store.insert(ObjectHandle(idx), test_entry()).unwrap();
+        }
+        assert_eq!(store.len().unwrap(), 40);
+        assert!(store.get(&ObjectHandle(17)).is_ok());
+        store.remove(&ObjectHandle(17)).unwrap();
+        assert!(store.get(&ObjectHandle(17)).is_err());
+        let handles = store
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|(handle, _)| handle.0)
+            .collect::<Vec<_>>();
+        assert_eq!(handles.len(), 39);
+        assert!(handles.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn object_store_remove_many_and_clear() {
+        let store = ObjectStore::new();
+        for idx in 1..=40 {
+            store.insert(ObjectHandle(idx), test_entry()).unwrap();
+        }
+        let handles = (1..=20)
+            .chain(100..=105)
+            .map(ObjectHandle)
+            .collect::<Vec<_>>();
+        assert_eq!(store.remove_many(&handles).unwrap(), 20);
+        assert_eq!(store.len().unwrap(), 20);
+        assert!(store.get(&ObjectHandle(20)).is_err());
+        assert!(store.get(&ObjectHandle(21)).is_ok());
+        assert_eq!(store.remove_many(&[]).unwrap(), 0);
+
+        assert_eq!(store.clear().unwrap(), 20);
+        assert_eq!(store.len().unwrap(), 0);
+        assert!(store.get(&ObjectHandle(21)).is_err());
+    }
+
+    #[test]
+    fn object_store_spans_shards() {
+        let store = ObjectStore::new();
+        let count = OBJECT_STORE_SHARDS * 3;
+        for idx in 1..=count {
+            store.insert(ObjectHandle(idx), test_entry()).unwrap();
+        }
+        assert_eq!(store.len().unwrap(), count);
+        for shard in store.shards.iter() {
+            assert_eq!(shard.read().unwrap().len(), 3);
+        }
+
+        // neighbouring handles map to different shards
+        for idx in 1..=count {
+            let handle = ObjectHandle(idx);
+            assert!(store.get(&handle).is_ok());
+            assert!(store.read(&handle).unwrap().contains_key(&handle));
+            assert!(!store
+                .read(&handle)
+                .unwrap()
+                .contains_key(&ObjectHandle(idx + 1)));
+        }
+
+        for idx in (1..=count).step_by(2) {
+            store.remove(&ObjectHandle(idx)).unwrap();
+            assert!(store.remove(&ObjectHandle(idx)).is_err());
+            assert!(store.get(&ObjectHandle(idx)).is_err());
+        }
+        assert_eq!(store.len().unwrap(), count / 2);
+        for idx in (2..=count).step_by(2) {
+            assert!(store.get(&ObjectHandle(idx)).is_ok());
+        }
+        assert_eq!(store.entries().unwrap().len(), count / 2);
+    }
+
+    #[test]
+    fn object_free_list() {
+        let _guard = lock_global_store();
+        let handles = (0..OBJECT_STORE_SHARDS + 4)
+            .map(|_| create_test_object())
+            .collect::<Vec<_>>();
+        let (freed, kept) = handles.split_at(OBJECT_STORE_SHARDS);
+
+        // unknown and already freed handles are ignored
+        let mut free = freed.to_vec();
+        free.push(ObjectHandle(0));
+        free.push(freed[0]);
+        credx_object_free_list(FfiList::from_slice(&free));
+        credx_object_free_list(FfiList::empty());
+
+        for handle in freed {
+            assert!(handle.load().is_err());
+        }
+        for handle in kept {
+            assert!(handle.load().is_ok());
+        }
+        credx_object_free_list(FfiList::from_slice(kept));
+        for handle in kept {
+            assert!(handle.load().is_err());
+        }
+    }
+
+    #[test]
+    fn clear_object_store() {
+        let _guard = lock_global_store();
+        let handles = (0..OBJECT_STORE_SHARDS * 2)
+            .map(|_| create_test_object())
+            .collect::<Vec<_>>();
+
+        let mut count = 0i64;
+        assert_eq!(credx_object_count(&mut count), ErrorCode::Success);
+        assert!(count >= handles.len() as i64);
+
+        assert_eq!(credx_clear_object_store(), ErrorCode::Success);
+        assert_eq!(credx_object_count(&mut count), ErrorCode::Success);
+        assert_eq!(count, 0);
+        for handle in handles.iter() {
+            assert!(handle.load().is_err());
+        }
+
+        // new handles are not reused after clearing
+        let handle = create_test_object();
+        assert!(handles.iter().all(|h| h.0 < handle.0));
+        credx_object_free(handle);
+    }
+
+    #[test]
+    fn object_tags() {
+        let _guard = lock_global_store();
+        let handle = create_test_object();
+        let other = create_test_object();
+        assert_eq!(ffi_get_tag(handle), Ok(None));
+
+        let tag = CString::new("wallet-1").unwrap();
+        let tag_str = || unsafe { FfiStr::from_cstr(CStr::from_ptr(tag.as_ptr())) };
+        assert_eq!(credx_object_set_tag(handle, tag_str()), ErrorCode::Success);
+        assert_eq!(ffi_get_tag(handle), Ok(Some("wallet-1".to_string())));
+        assert_eq!(ffi_get_tag(other), Ok(None));
+
+        handle.set_tag(Some("wallet-2".to_string())).unwrap();
+        assert_eq!(ffi_get_tag(handle), Ok(Some("wallet-2".to_string())));
+
+        // a null tag removes the user data
+        assert_eq!(
+            credx_object_set_tag(handle, unsafe { FfiStr::from_raw(std::ptr::null()) }),
+            ErrorCode::Success
+        );
+        assert_eq!(ffi_get_tag(handle), Ok(None));
+
+        // the tag is released with the handle
+        handle.set_tag(Some("wallet-3".to_string())).unwrap();
+        credx_object_free(handle);
+        assert!(ffi_get_tag(handle).is_err());
+        assert!(handle.set_tag(None).is_err());
+        assert_eq!(
+            credx_object_set_tag(ObjectHandle(0), tag_str()),
+            ErrorCode::Input
+        );
+        credx_object_free(other);
+    }
+
+    fn run_threads(threads: usize, load: impl Fn(usize) + Sync) -> Duration {
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            for thread in 0..threads {
+                let load = &load;
+                scope.spawn(move || load(thread));
+            }
+        });
+        start.elapsed()
+    }
+
+    /// Compare concurrent handle loads against a single mutex-protected map.
+    /// Run with `cargo test --release object_store_contention -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn object_store_contention() {
+        const OBJECTS: usize = 1000;
+        const THREADS: usize = 8;
+        const LOADS: usize = 200_000;
+
+        let sharded = ObjectStore::new();
+        let single = Mutex::new(BTreeMap::new());
+        for idx in 1..=OBJECTS {
+            sharded.insert(ObjectHandle(idx), test_entry()).unwrap();
+            single
+                .lock()
+                .unwrap()
+                .insert(ObjectHandle(idx), test_entry());
+        }
+
+        let single_time = run_threads(THREADS, |thread| {
+            for idx in 0..LOADS {
+                let handle = ObjectHandle((idx * THREADS + thread) % OBJECTS + 1);
+                let obj = single.lock().unwrap().get(&handle).map(|e| e.obj.clone());
+                assert!(obj.is_some());
+            }
+        });
+        let sharded_time = run_threads(THREADS, |thread| {
+            for idx in 0..LOADS {
+                let handle = ObjectHandle((idx * THREADS + thread) % OBJECTS + 1);
+                assert!(sharded.get(&handle).is_ok());
+            }
+        });
+
+        println!(
+            "{} threads x {} loads: single mutex {:?}, sharded {:?}",
+            THREADS, LOADS, single_time, sharded_time
+        );
+    }
+}
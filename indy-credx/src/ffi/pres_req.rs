@@ -1,12 +1,22 @@
 use std::os::raw::c_char;
 
-use ffi_support::rust_string_to_c;
+use ffi_support::{rust_string_to_c, FfiStr};
 
 use super::error::{catch_error, ErrorCode};
-use crate::services::{types::PresentationRequest, verifier::generate_nonce};
+use super::object::ObjectHandle;
+use crate::services::{
+    types::PresentationRequest,
+    verifier::{check_presentation_nonce, generate_nonce},
+};
+use indy_data_types::anoncreds::nonce::Nonce;
 
 impl_indy_object!(PresentationRequest, "PresentationRequest");
-impl_indy_object_from_json!(PresentationRequest, credx_presentation_request_from_json);
+impl_indy_object_from_json!(
+    PresentationRequest,
+    credx_presentation_request_from_json,
+    limited
+);
+impl_indy_object_from_cbor!(PresentationRequest, credx_presentation_request_from_cbor);
 
 #[no_mangle]
 pub extern "C" fn credx_generate_nonce(nonce_p: *mut *const c_char) -> ErrorCode {
@@ -17,3 +27,17 @@ pub extern "C" fn credx_generate_nonce(nonce_p: *mut *const c_char) -> ErrorCode
         Ok(())
     })
 }
+
+#[no_mangle]
+pub extern "C" fn credx_presentation_request_check_nonce(
+    pres_req: ObjectHandle,
+    nonce: FfiStr,
+) -> ErrorCode {
+    catch_error(|| {
+        let nonce = nonce
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing nonce"))?;
+        let nonce = Nonce::from_dec(nonce).map_err(err_map!(Input, "Invalid nonce"))?;
+        check_presentation_nonce(pres_req.load()?.cast_ref()?, &nonce)
+    })
+}
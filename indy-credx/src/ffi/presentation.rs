@@ -1,20 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
+use std::os::raw::c_char;
+use std::ptr;
 
-use ffi_support::FfiStr;
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
 
 use super::error::{catch_error, ErrorCode};
-use super::object::{IndyObject, IndyObjectId, IndyObjectList, ObjectHandle};
-use super::util::{FfiList, FfiStrList};
+use super::object::{
+    check_parsed_object, limited_object_from_json, parse_json_object, serialize_json,
+    versioned_object_from_json, IndyObject, IndyObjectId, IndyObjectList, ObjectHandle,
+};
+use super::util::{FfiIter, FfiIterNextCallback, FfiList, FfiStrList};
 use crate::error::Result;
 use crate::services::{
-    prover::create_presentation,
-    types::{PresentCredentials, Presentation, RevocationRegistryDefinition},
-    verifier::verify_presentation,
+    prover::{create_presentation, select_revocation_timestamp},
+    types::{
+        Credential, CredentialDefinition, CredentialRevocationState, PresentCredentials,
+        Presentation, PresentationRequest, RevocationRegistry, RevocationRegistryDefinition,
+        Schema,
+    },
+    verifier::{
+        required_artifacts, summarize_presentation, verify_binary_attribute, verify_presentation,
+        verify_presentation_with_overrides,
+    },
 };
 
 impl_indy_object!(Presentation, "Presentation");
-impl_indy_object_from_json!(Presentation, credx_presentation_from_json);
+impl_indy_object_from_json!(Presentation, credx_presentation_from_json, limited);
+impl_indy_object_from_cbor!(Presentation, credx_presentation_from_cbor);
 
 #[derive(Debug)]
 #[repr(C)]
@@ -90,27 +103,7 @@ pub extern "C" fn credx_create_presentation(
         let schemas = IndyObjectList::load(schemas.as_slice())?;
         let cred_defs = IndyObjectList::load(cred_defs.as_slice())?;
 
-        let self_attested = if !self_attest_names.is_empty() {
-            let mut self_attested = HashMap::new();
-            for (name, raw) in self_attest_names
-                .as_slice()
-                .into_iter()
-                .zip(self_attest_values.as_slice())
-            {
-                let name = name
-                    .as_opt_str()
-                    .ok_or_else(|| err_msg!("Missing attribute name"))?
-                    .to_string();
-                let raw = raw
-                    .as_opt_str()
-                    .ok_or_else(|| err_msg!("Missing attribute raw value"))?
-                    .to_string();
-                self_attested.insert(name, raw);
-            }
-            Some(self_attested)
-        } else {
-            None
-        };
+        let self_attested = load_self_attested(&self_attest_names, &self_attest_values)?;
 
         let mut present_creds = PresentCredentials::default();
 
@@ -161,7 +154,257 @@ pub extern "C" fn credx_create_presentation(
     })
 }
 
-#[derive(Debug)]
+#[repr(C)]
+pub struct FfiCredentialJsonEntry {
+    credential: ByteBuffer,
+    timestamp: i64,
+    rev_state: ByteBuffer,
+}
+
+impl FfiCredentialJsonEntry {
+    fn load(&self) -> Result<(Credential, Option<u64>, Option<CredentialRevocationState>)> {
+        let credential = parse_json_object(self.credential.as_slice())?;
+        let timestamp = if self.timestamp < 0 {
+            None
+        } else {
+            Some(self.timestamp as u64)
+        };
+        let rev_state = Some(self.rev_state.as_slice())
+            .filter(|json| !json.is_empty())
+            .map(parse_json_object)
+            .transpose()?;
+        Ok((credential, timestamp, rev_state))
+    }
+}
+
+/// Create a presentation from credentials, schemas and credential definitions
+/// passed as JSON rather than object handles, returning the presentation as JSON.
+/// An empty revocation state buffer or a negative timestamp indicates that no
+/// revocation state applies to a credential.
+#[no_mangle]
+pub extern "C" fn credx_create_presentation_from_json(
+    pres_req_json: ByteBuffer,
+    credentials: FfiList<FfiCredentialJsonEntry>,
+    credentials_prove: FfiList<FfiCredentialProve>,
+    self_attest_names: FfiStrList,
+    self_attest_values: FfiStrList,
+    master_secret: ObjectHandle,
+    schemas_json: FfiList<ByteBuffer>,
+    cred_defs_json: FfiList<ByteBuffer>,
+    presentation_json_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(presentation_json_p);
+        if self_attest_names.len() != self_attest_values.len() {
+            return Err(err_msg!(
+                "Inconsistent lengths for self-attested value parameters"
+            ));
+        }
+
+        let pres_req: PresentationRequest = parse_json_object(pres_req_json.as_slice())?;
+        let entries = credentials.try_collect(FfiCredentialJsonEntry::load)?;
+        let schemas = schemas_json.try_collect(|json| {
+            check_parsed_object(versioned_object_from_json::<
+                Schema,
+                indy_data_types::anoncreds::schema::SchemaV1,
+            >(json.as_slice())?)
+        })?;
+        let cred_defs = cred_defs_json.try_collect(|json| {
+            check_parsed_object(versioned_object_from_json::<
+                CredentialDefinition,
+                indy_data_types::anoncreds::cred_def::CredentialDefinitionV1,
+            >(json.as_slice())?)
+        })?;
+        let self_attested = load_self_attested(&self_attest_names, &self_attest_values)?;
+
+        let mut present_creds = PresentCredentials::default();
+        for (entry_idx, (credential, timestamp, rev_state)) in entries.iter().enumerate() {
+            let mut add_cred =
+                present_creds.add_credential(credential, *timestamp, rev_state.as_ref());
+            for prove in credentials_prove.as_slice() {
+                if prove.entry_idx < 0 {
+                    return Err(err_msg!("Invalid credential index"));
+                }
+                if prove.entry_idx as usize != entry_idx {
+                    continue;
+                }
+
+                let referent = prove
+                    .referent
+                    .as_opt_str()
+                    .ok_or_else(|| err_msg!("Missing referent for credential proof info"))?
+                    .to_string();
+
+                if prove.is_predicate == 0 {
+                    add_cred.add_requested_attribute(referent, prove.reveal != 0);
+                } else {
+                    add_cred.add_requested_predicate(referent);
+                }
+            }
+        }
+
+        let presentation = create_presentation(
+            &pres_req,
+            present_creds,
+            self_attested,
+            master_secret.load()?.cast_ref()?,
+            &schemas
+                .iter()
+                .map(|schema| (schema.get_id(), schema))
+                .collect(),
+            &cred_defs
+                .iter()
+                .map(|cred_def| (cred_def.get_id(), cred_def))
+                .collect(),
+        )?;
+        let presentation = serialize_json(&presentation)?;
+        unsafe { *presentation_json_p = ByteBuffer::from_vec(presentation) };
+        Ok(())
+    })
+}
+
+fn load_self_attested(
+    self_attest_names: &FfiStrList,
+    self_attest_values: &FfiStrList,
+) -> Result<Option<HashMap<String, String>>> {
+    if self_attest_names.is_empty() {
+        return Ok(None);
+    }
+    let mut self_attested = HashMap::new();
+    for (name, raw) in self_attest_names
+        .as_slice()
+        .into_iter()
+        .zip(self_attest_values.as_slice())
+    {
+        let name = name
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing attribute name"))?
+            .to_string();
+        let raw = raw
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing attribute raw value"))?
+            .to_string();
+        self_attested.insert(name, raw);
+    }
+    Ok(Some(self_attested))
+}
+
+#[derive(Deserialize)]
+struct PresentationSelection {
+    #[serde(default)]
+    requested_attributes: BTreeMap<String, SelectedAttribute>,
+    #[serde(default)]
+    requested_predicates: BTreeMap<String, SelectedCredential>,
+    #[serde(default)]
+    self_attested_attributes: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SelectedCredential {
+    credential: usize,
+    #[serde(default)]
+    timestamp: Option<u64>,
+    #[serde(default)]
+    rev_state: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SelectedAttribute {
+    #[serde(flatten)]
+    credential: SelectedCredential,
+    #[serde(default = "default_revealed")]
+    revealed: bool,
+}
+
+fn default_revealed() -> bool {
+    true
+}
+
+/// Create a presentation from a JSON selection document, mapping each requested
+/// attribute and predicate referent to a credential handle along with an optional
+/// timestamp and revocation state handle. Referents which select the same
+/// credential, timestamp and revocation state are proven together.
+#[no_mangle]
+pub extern "C" fn credx_create_presentation_from_selection(
+    pres_req: ObjectHandle,
+    selection_json: FfiStr,
+    master_secret: ObjectHandle,
+    schemas: FfiList<ObjectHandle>,
+    cred_defs: FfiList<ObjectHandle>,
+    presentation_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(presentation_p);
+        let selection_json = selection_json
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing presentation selection"))?;
+        let selection: PresentationSelection = serde_json::from_str(selection_json)
+            .map_err(err_map!("Invalid presentation selection"))?;
+
+        let mut groups: BTreeMap<SelectedCredential, Vec<(String, Option<bool>)>> = BTreeMap::new();
+        for (referent, attr) in selection.requested_attributes {
+            groups
+                .entry(attr.credential)
+                .or_default()
+                .push((referent, Some(attr.revealed)));
+        }
+        for (referent, selected) in selection.requested_predicates {
+            groups.entry(selected).or_default().push((referent, None));
+        }
+
+        let entries = groups
+            .into_iter()
+            .map(|(selected, referents)| {
+                let entry = CredentialEntry {
+                    credential: ObjectHandle(selected.credential).load()?,
+                    timestamp: selected.timestamp,
+                    rev_state: selected
+                        .rev_state
+                        .map(|handle| ObjectHandle(handle).load())
+                        .transpose()?,
+                };
+                Result::Ok((entry, referents))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let schemas = IndyObjectList::load(schemas.as_slice())?;
+        let cred_defs = IndyObjectList::load(cred_defs.as_slice())?;
+
+        let mut present_creds = PresentCredentials::default();
+        for (entry, referents) in entries.iter() {
+            let mut add_cred = present_creds.add_credential(
+                entry.credential.cast_ref()?,
+                entry.timestamp,
+                entry
+                    .rev_state
+                    .as_ref()
+                    .map(IndyObject::cast_ref)
+                    .transpose()?,
+            );
+            for (referent, revealed) in referents {
+                match revealed {
+                    Some(revealed) => add_cred.add_requested_attribute(referent, *revealed),
+                    None => add_cred.add_requested_predicate(referent),
+                }
+            }
+        }
+
+        let self_attested = Some(selection.self_attested_attributes).filter(|s| !s.is_empty());
+        let presentation = create_presentation(
+            pres_req.load()?.cast_ref()?,
+            present_creds,
+            self_attested,
+            master_secret.load()?.cast_ref()?,
+            &schemas.refs_map()?,
+            &cred_defs.refs_map()?,
+        )?;
+        let presentation = ObjectHandle::create(presentation)?;
+        unsafe { *presentation_p = presentation };
+        Ok(())
+    })
+}
+
+#[derive(Debug, Default)]
 #[repr(C)]
 pub struct FfiRevocationEntry {
     def_entry_idx: i64,
@@ -184,6 +427,32 @@ impl FfiRevocationEntry {
     }
 }
 
+#[derive(Debug)]
+#[repr(C)]
+pub struct FfiNonRevokedIntervalOverride {
+    rev_reg_def_idx: i64,
+    requested_from_ts: i64,
+    override_ts: i64,
+}
+
+impl FfiNonRevokedIntervalOverride {
+    fn load(&self) -> Result<(usize, u64, u64)> {
+        let rev_reg_def_idx = self
+            .rev_reg_def_idx
+            .try_into()
+            .map_err(|_| err_msg!("Invalid revocation registry definition index"))?;
+        let requested_from_ts = self
+            .requested_from_ts
+            .try_into()
+            .map_err(|_| err_msg!("Invalid requested timestamp for interval override"))?;
+        let override_ts = self
+            .override_ts
+            .try_into()
+            .map_err(|_| err_msg!("Invalid override timestamp for interval override"))?;
+        Ok((rev_reg_def_idx, requested_from_ts, override_ts))
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn credx_verify_presentation(
     presentation: ObjectHandle,
@@ -193,43 +462,367 @@ pub extern "C" fn credx_verify_presentation(
     rev_reg_defs: FfiList<ObjectHandle>,
     rev_reg_entries: FfiList<FfiRevocationEntry>,
     result_p: *mut i8,
+) -> ErrorCode {
+    credx_verify_presentation_with_result(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_reg_entries,
+        result_p,
+        ptr::null_mut(),
+    )
+}
+
+/// Verify a presentation, also writing the verification failures as a JSON array
+/// to `failures_p` when it is not null
+#[no_mangle]
+pub extern "C" fn credx_verify_presentation_with_result(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    schemas: FfiList<ObjectHandle>,
+    cred_defs: FfiList<ObjectHandle>,
+    rev_reg_defs: FfiList<ObjectHandle>,
+    rev_reg_entries: FfiList<FfiRevocationEntry>,
+    result_p: *mut i8,
+    failures_p: *mut *const c_char,
+) -> ErrorCode {
+    credx_verify_presentation_with_overrides(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_reg_entries,
+        FfiList::empty(),
+        result_p,
+        failures_p,
+    )
+}
+
+/// Verify a presentation, accepting revocation registry timestamps in place of
+/// the `from` or `to` timestamps of the requested non-revocation intervals
+#[no_mangle]
+pub extern "C" fn credx_verify_presentation_with_overrides(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    schemas: FfiList<ObjectHandle>,
+    cred_defs: FfiList<ObjectHandle>,
+    rev_reg_defs: FfiList<ObjectHandle>,
+    rev_reg_entries: FfiList<FfiRevocationEntry>,
+    nonrevoke_interval_override: FfiList<FfiNonRevokedIntervalOverride>,
+    result_p: *mut i8,
+    failures_p: *mut *const c_char,
 ) -> ErrorCode {
     catch_error(|| {
-        let schemas = IndyObjectList::load(schemas.as_slice())?;
-        let cred_defs = IndyObjectList::load(cred_defs.as_slice())?;
-        let rev_reg_defs = IndyObjectList::load(rev_reg_defs.as_slice())?;
-        let rev_reg_entries = {
-            let entries = rev_reg_entries.as_slice();
-            entries.into_iter().try_fold(
-                Vec::with_capacity(entries.len()),
-                |mut r, ffi_entry| {
-                    r.push(ffi_entry.load()?);
-                    Result::Ok(r)
-                },
-            )?
-        };
+        verify_presentation_entries(
+            presentation,
+            pres_req,
+            schemas,
+            cred_defs,
+            rev_reg_defs,
+            rev_reg_entries
+                .as_slice()
+                .iter()
+                .map(FfiRevocationEntry::load),
+            nonrevoke_interval_override,
+            result_p,
+            failures_p,
+        )
+    })
+}
+
+/// Verify a presentation, fetching the revocation registry entries one at a time
+/// from `rev_reg_entries_cb` rather than from a contiguous list. This allows long
+/// revocation histories to be provided without building a single large array.
+#[no_mangle]
+pub extern "C" fn credx_verify_presentation_streamed(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    schemas: FfiList<ObjectHandle>,
+    cred_defs: FfiList<ObjectHandle>,
+    rev_reg_defs: FfiList<ObjectHandle>,
+    rev_reg_entries_cb: Option<FfiIterNextCallback<FfiRevocationEntry>>,
+    rev_reg_entries_context: i64,
+    nonrevoke_interval_override: FfiList<FfiNonRevokedIntervalOverride>,
+    result_p: *mut i8,
+    failures_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        verify_presentation_entries(
+            presentation,
+            pres_req,
+            schemas,
+            cred_defs,
+            rev_reg_defs,
+            FfiIter::new(rev_reg_entries_cb, rev_reg_entries_context)
+                .map(|entry| entry.and_then(|entry| entry.load())),
+            nonrevoke_interval_override,
+            result_p,
+            failures_p,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_presentation_entries(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    schemas: FfiList<ObjectHandle>,
+    cred_defs: FfiList<ObjectHandle>,
+    rev_reg_defs: FfiList<ObjectHandle>,
+    rev_reg_entries: impl Iterator<Item = Result<(usize, IndyObject, u64)>>,
+    nonrevoke_interval_override: FfiList<FfiNonRevokedIntervalOverride>,
+    result_p: *mut i8,
+    failures_p: *mut *const c_char,
+) -> Result<()> {
+    check_useful_c_ptr!(result_p);
+    let schemas = IndyObjectList::load(schemas.as_slice())?;
+    let cred_defs = IndyObjectList::load(cred_defs.as_slice())?;
+    let rev_reg_defs = IndyObjectList::load(rev_reg_defs.as_slice())?;
+    let rev_reg_entries = rev_reg_entries.collect::<Result<Vec<_>>>()?;
+    let mut rev_regs = HashMap::new();
+    for (idx, entry, timestamp) in rev_reg_entries.iter() {
+        if *idx > rev_reg_defs.len() {
+            return Err(err_msg!("Invalid revocation registry entry index"));
+        }
+        let id = rev_reg_defs[*idx]
+            .cast_ref::<RevocationRegistryDefinition>()?
+            .get_id();
+        rev_regs
+            .entry(id)
+            .or_insert_with(HashMap::new)
+            .insert(*timestamp, entry.cast_ref()?);
+    }
+    let mut overrides = HashMap::new();
+    for ffi_override in nonrevoke_interval_override.as_slice() {
+        let (idx, requested_from_ts, override_ts) = ffi_override.load()?;
+        if idx >= rev_reg_defs.len() {
+            return Err(err_msg!("Invalid revocation registry definition index"));
+        }
+        let id = rev_reg_defs[idx]
+            .cast_ref::<RevocationRegistryDefinition>()?
+            .get_id();
+        overrides
+            .entry(id)
+            .or_insert_with(HashMap::new)
+            .insert(requested_from_ts, override_ts);
+    }
+    let verify = verify_presentation_with_overrides(
+        presentation.load()?.cast_ref()?,
+        pres_req.load()?.cast_ref()?,
+        &schemas.refs_map()?,
+        &cred_defs.refs_map()?,
+        Some(&rev_reg_defs.refs_map()?),
+        Some(&rev_regs),
+        if overrides.is_empty() {
+            None
+        } else {
+            Some(&overrides)
+        },
+    )?;
+    unsafe { *result_p = verify.is_valid() as i8 };
+    if !failures_p.is_null() {
+        let failures = serde_json::to_string(&verify.failures)?;
+        unsafe { *failures_p = rust_string_to_c(failures) };
+    }
+    Ok(())
+}
+
+#[repr(C)]
+pub struct FfiRevocationJsonEntry {
+    def_entry_idx: i64,
+    entry: ByteBuffer,
+    timestamp: i64,
+}
+
+impl FfiRevocationJsonEntry {
+    fn load(&self) -> Result<(usize, RevocationRegistry, u64)> {
+        let def_entry_idx = self
+            .def_entry_idx
+            .try_into()
+            .map_err(|_| err_msg!("Invalid revocation registry entry index"))?;
+        let entry = check_parsed_object(versioned_object_from_json::<
+            RevocationRegistry,
+            indy_data_types::anoncreds::rev_reg::RevocationRegistryV1,
+        >(self.entry.as_slice())?)?;
+        let timestamp = self
+            .timestamp
+            .try_into()
+            .map_err(|_| err_msg!("Invalid timestamp for revocation entry"))?;
+        Ok((def_entry_idx, entry, timestamp))
+    }
+}
+
+/// Verify a presentation, taking the presentation, request and ledger objects as
+/// JSON rather than object handles. The result is written as a JSON object with
+/// `verified` and `failures` properties.
+#[no_mangle]
+pub extern "C" fn credx_verify_presentation_json(
+    presentation_json: ByteBuffer,
+    pres_req_json: ByteBuffer,
+    schemas_json: FfiList<ByteBuffer>,
+    cred_defs_json: FfiList<ByteBuffer>,
+    rev_reg_defs_json: FfiList<ByteBuffer>,
+    rev_reg_entries: FfiList<FfiRevocationJsonEntry>,
+    result_json_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_json_p);
+        let presentation: Presentation =
+            check_parsed_object(limited_object_from_json(presentation_json.as_slice())?)?;
+        let pres_req: PresentationRequest =
+            check_parsed_object(limited_object_from_json(pres_req_json.as_slice())?)?;
+        let schemas = schemas_json.try_collect(|json| {
+            check_parsed_object(versioned_object_from_json::<
+                Schema,
+                indy_data_types::anoncreds::schema::SchemaV1,
+            >(json.as_slice())?)
+        })?;
+        let cred_defs = cred_defs_json.try_collect(|json| {
+            check_parsed_object(versioned_object_from_json::<
+                CredentialDefinition,
+                indy_data_types::anoncreds::cred_def::CredentialDefinitionV1,
+            >(json.as_slice())?)
+        })?;
+        let rev_reg_defs = rev_reg_defs_json.try_collect(|json| {
+            check_parsed_object(versioned_object_from_json::<
+                RevocationRegistryDefinition,
+                indy_data_types::anoncreds::rev_reg_def::RevocationRegistryDefinitionV1,
+            >(json.as_slice())?)
+        })?;
+        let rev_reg_entries = rev_reg_entries.try_collect(FfiRevocationJsonEntry::load)?;
         let mut rev_regs = HashMap::new();
         for (idx, entry, timestamp) in rev_reg_entries.iter() {
-            if *idx > rev_reg_defs.len() {
-                return Err(err_msg!("Invalid revocation registry entry index"));
-            }
-            let id = rev_reg_defs[*idx]
-                .cast_ref::<RevocationRegistryDefinition>()?
-                .get_id();
+            let rev_reg_def = rev_reg_defs
+                .get(*idx)
+                .ok_or_else(|| err_msg!("Invalid revocation registry entry index"))?;
             rev_regs
-                .entry(id)
+                .entry(rev_reg_def.get_id())
                 .or_insert_with(HashMap::new)
-                .insert(*timestamp, entry.cast_ref()?);
+                .insert(*timestamp, entry);
         }
+
         let verify = verify_presentation(
+            &presentation,
+            &pres_req,
+            &schemas
+                .iter()
+                .map(|schema| (schema.get_id(), schema))
+                .collect(),
+            &cred_defs
+                .iter()
+                .map(|cred_def| (cred_def.get_id(), cred_def))
+                .collect(),
+            Some(
+                &rev_reg_defs
+                    .iter()
+                    .map(|rev_reg_def| (rev_reg_def.get_id(), rev_reg_def))
+                    .collect(),
+            ),
+            Some(&rev_regs),
+        )?;
+        let result = serialize_json(&serde_json::json!({
+            "verified": verify.is_valid(),
+            "failures": verify.failures,
+        }))?;
+        unsafe { *result_json_p = ByteBuffer::from_vec(result) };
+        Ok(())
+    })
+}
+
+/// Select the revocation registry timestamp to present for a referent, writing
+/// -1 when no non-revocation interval applies.
+#[no_mangle]
+pub extern "C" fn credx_select_revocation_timestamp(
+    pres_req: ObjectHandle,
+    referent: FfiStr,
+    timestamps: FfiList<i64>,
+    timestamp_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(timestamp_p);
+        let referent = referent
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing referent"))?;
+        let timestamps = timestamps.try_collect(|ts| {
+            (*ts)
+                .try_into()
+                .map_err(|_| err_msg!("Invalid timestamp: {}", ts))
+        })?;
+        let timestamp = select_revocation_timestamp(
+            pres_req.load()?.cast_ref::<PresentationRequest>()?,
+            referent,
+            &timestamps,
+        )?;
+        unsafe {
+            *timestamp_p = timestamp.map(|ts| ts as i64).unwrap_or(-1);
+        };
+        Ok(())
+    })
+}
+
+/// Determine the ledger objects required to verify a presentation, returned as JSON
+#[no_mangle]
+pub extern "C" fn credx_presentation_required_artifacts(
+    pres_req: ObjectHandle,
+    presentation: ObjectHandle,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let artifacts = required_artifacts(
+            pres_req.load()?.cast_ref()?,
+            presentation.load()?.cast_ref()?,
+        )?;
+        let json = serde_json::to_string(&artifacts)?;
+        unsafe { *result_p = rust_string_to_c(json) };
+        Ok(())
+    })
+}
+
+/// Summarize the data disclosed by a presentation for each referent, returned as JSON
+#[no_mangle]
+pub extern "C" fn credx_presentation_summarize(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let summary = summarize_presentation(
             presentation.load()?.cast_ref()?,
             pres_req.load()?.cast_ref()?,
-            &schemas.refs_map()?,
-            &cred_defs.refs_map()?,
-            Some(&rev_reg_defs.refs_map()?),
-            Some(&rev_regs),
         )?;
-        unsafe { *result_p = verify as i8 };
+        let json = serde_json::to_string(&summary)?;
+        unsafe { *result_p = rust_string_to_c(json) };
+        Ok(())
+    })
+}
+
+/// Check supplied binary content against the hash revealed for a binary attribute,
+/// identified by its referent and, for a revealed attribute group, its name
+#[no_mangle]
+pub extern "C" fn credx_presentation_verify_binary_attribute(
+    presentation: ObjectHandle,
+    referent: FfiStr,
+    name: FfiStr,
+    content: ByteBuffer,
+    result_p: *mut i8,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let referent = referent
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing referent"))?;
+        let verified = verify_binary_attribute(
+            presentation.load()?.cast_ref()?,
+            referent,
+            name.as_opt_str(),
+            content.as_slice(),
+        )?;
+        unsafe { *result_p = verified as i8 };
         Ok(())
     })
 }
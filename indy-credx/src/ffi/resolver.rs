@@ -0,0 +1,212 @@
+use std::ffi::CString;
+use std::sync::RwLock;
+
+use ffi_support::FfiStr;
+use once_cell::sync::Lazy;
+
+use super::error::{catch_error, ErrorCode};
+use super::object::{IndyObject, ObjectHandle};
+use crate::error::Result;
+use crate::services::{
+    prover::process_credential_resolved,
+    types::{
+        Credential, CredentialDefinition, CredentialDefinitionId, LedgerResolver,
+        RevocationRegistryDefinition, RevocationRegistryId, RevocationStatusList, Schema, SchemaId,
+    },
+    verifier::verify_presentation_resolved,
+};
+
+/// Resolve an object by ID, writing a handle to the new object on success.
+/// The library takes ownership of the returned handle.
+pub type ResolveObjectCallback =
+    extern "C" fn(context: i64, id: FfiStr, object_p: *mut ObjectHandle) -> ErrorCode;
+
+/// Resolve a revocation status list by revocation registry ID as of a timestamp
+pub type ResolveStatusListCallback = extern "C" fn(
+    context: i64,
+    id: FfiStr,
+    timestamp: i64,
+    object_p: *mut ObjectHandle,
+) -> ErrorCode;
+
+#[derive(Clone, Copy)]
+struct FfiResolver {
+    resolve_schema: Option<ResolveObjectCallback>,
+    resolve_cred_def: Option<ResolveObjectCallback>,
+    resolve_rev_reg_def: Option<ResolveObjectCallback>,
+    resolve_rev_status_list: Option<ResolveStatusListCallback>,
+    context: i64,
+}
+
+static RESOLVER: Lazy<RwLock<Option<FfiResolver>>> = Lazy::new(|| RwLock::new(None));
+
+impl FfiResolver {
+    fn current() -> Result<Self> {
+        RESOLVER
+            .read()
+            .map_err(|_| err_msg!(Unexpected, "Error locking resolver"))?
+            .ok_or_else(|| err_msg!(InvalidState, "No resolver registered"))
+    }
+
+    fn call(
+        kind: &str,
+        id: &str,
+        callback: impl FnOnce(FfiStr, *mut ObjectHandle) -> ErrorCode,
+    ) -> Result<IndyObject> {
+        let c_id = CString::new(id).map_err(err_map!("Invalid {} ID", kind))?;
+        let mut handle = ObjectHandle::default();
+        let code = callback(FfiStr::from_cstr(&c_id), &mut handle);
+        if code != ErrorCode::Success {
+            return Err(err_msg!(
+                IOError,
+                "Error resolving {} {}: error code {}",
+                kind,
+                id,
+                code as usize
+            ));
+        }
+        handle.remove()
+    }
+
+    fn resolve_object(
+        &self,
+        kind: &str,
+        id: &str,
+        callback: Option<ResolveObjectCallback>,
+    ) -> Result<IndyObject> {
+        let callback = callback
+            .ok_or_else(|| err_msg!(InvalidState, "No resolver registered for {}", kind))?;
+        Self::call(kind, id, |id, object_p| {
+            callback(self.context, id, object_p)
+        })
+    }
+}
+
+impl LedgerResolver for FfiResolver {
+    fn resolve_schema(&self, schema_id: &SchemaId) -> Result<Schema> {
+        let obj = self.resolve_object("schema", &schema_id.0, self.resolve_schema)?;
+        Ok(obj.cast_ref::<Schema>()?.clone())
+    }
+
+    fn resolve_credential_definition(
+        &self,
+        cred_def_id: &CredentialDefinitionId,
+    ) -> Result<CredentialDefinition> {
+        let obj = self.resolve_object(
+            "credential definition",
+            &cred_def_id.0,
+            self.resolve_cred_def,
+        )?;
+        obj.cast_ref::<CredentialDefinition>()?
+            .try_clone()
+            .map_err(err_map!(Unexpected, "Error copying credential definition"))
+    }
+
+    fn resolve_revocation_registry_definition(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+    ) -> Result<RevocationRegistryDefinition> {
+        let obj = self.resolve_object(
+            "revocation registry definition",
+            &rev_reg_id.0,
+            self.resolve_rev_reg_def,
+        )?;
+        Ok(obj.cast_ref::<RevocationRegistryDefinition>()?.clone())
+    }
+
+    fn resolve_revocation_status_list(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        timestamp: u64,
+    ) -> Result<RevocationStatusList> {
+        let callback = self.resolve_rev_status_list.ok_or_else(|| {
+            err_msg!(
+                InvalidState,
+                "No resolver registered for revocation status list"
+            )
+        })?;
+        let obj = Self::call("revocation status list", &rev_reg_id.0, |id, object_p| {
+            callback(self.context, id, timestamp as i64, object_p)
+        })?;
+        Ok(obj.cast_ref::<RevocationStatusList>()?.clone())
+    }
+}
+
+/// Register host callbacks used to resolve ledger objects by ID. Any callback
+/// may be omitted, and passing no callbacks removes the registered resolver.
+#[no_mangle]
+pub extern "C" fn credx_register_resolver(
+    resolve_schema: Option<ResolveObjectCallback>,
+    resolve_cred_def: Option<ResolveObjectCallback>,
+    resolve_rev_reg_def: Option<ResolveObjectCallback>,
+    resolve_rev_status_list: Option<ResolveStatusListCallback>,
+    context: i64,
+) -> ErrorCode {
+    catch_error(|| {
+        let resolver = if resolve_schema.is_none()
+            && resolve_cred_def.is_none()
+            && resolve_rev_reg_def.is_none()
+            && resolve_rev_status_list.is_none()
+        {
+            None
+        } else {
+            Some(FfiResolver {
+                resolve_schema,
+                resolve_cred_def,
+                resolve_rev_reg_def,
+                resolve_rev_status_list,
+                context,
+            })
+        };
+        *RESOLVER
+            .write()
+            .map_err(|_| err_msg!(Unexpected, "Error locking resolver"))? = resolver;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_verify_presentation_resolved(
+    presentation: ObjectHandle,
+    pres_req: ObjectHandle,
+    result_p: *mut i8,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let resolver = FfiResolver::current()?;
+        let verify = verify_presentation_resolved(
+            presentation.load()?.cast_ref()?,
+            pres_req.load()?.cast_ref()?,
+            &resolver,
+        )?;
+        unsafe { *result_p = verify.is_valid() as i8 };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_process_credential_resolved(
+    cred: ObjectHandle,
+    cred_req_metadata: ObjectHandle,
+    master_secret: ObjectHandle,
+    cred_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(cred_p);
+        let resolver = FfiResolver::current()?;
+        let mut cred = cred
+            .load()?
+            .cast_ref::<Credential>()?
+            .try_clone()
+            .map_err(err_map!(Unexpected, "Error copying credential"))?;
+        process_credential_resolved(
+            &mut cred,
+            cred_req_metadata.load()?.cast_ref()?,
+            master_secret.load()?.cast_ref()?,
+            &resolver,
+        )?;
+        let cred = ObjectHandle::create(cred)?;
+        unsafe { *cred_p = cred };
+        Ok(())
+    })
+}
@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::os::raw::c_char;
 
-use ffi_support::{rust_string_to_c, FfiStr};
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
 use indy_utils::Qualifiable;
 
 use super::error::{catch_error, ErrorCode};
@@ -11,15 +11,17 @@ use super::util::FfiList;
 use crate::error::Result;
 use crate::services::{
     issuer::{
-        create_revocation_registry, merge_revocation_registry_deltas, revoke_credential,
-        update_revocation_registry,
+        create_or_update_revocation_witnesses, create_revocation_registry_with_progress,
+        create_status_list_credential, export_revocation_registry_deltas,
+        import_revocation_registry_deltas, merge_revocation_registry_deltas, revoke_credential,
+        revoke_credentials, update_revocation_registry,
     },
-    prover::create_or_update_revocation_state,
+    prover::{create_or_update_revocation_state, create_revocation_state_from_witness},
     tails::{TailsFileReader, TailsFileWriter},
     types::{
         CredentialRevocationState, DidValue, IssuanceType, RegistryType, RevocationRegistry,
         RevocationRegistryDefinition, RevocationRegistryDefinitionPrivate, RevocationRegistryDelta,
-        RevocationRegistryId,
+        RevocationRegistryId, RevocationStatusList, RevocationWitnessUpdate,
     },
 };
 
@@ -36,6 +38,44 @@ pub extern "C" fn credx_create_revocation_registry(
     reg_def_private_p: *mut ObjectHandle,
     reg_entry_p: *mut ObjectHandle,
     reg_init_delta_p: *mut ObjectHandle,
+) -> ErrorCode {
+    credx_create_revocation_registry_with_progress(
+        origin_did,
+        cred_def,
+        tag,
+        rev_reg_type,
+        issuance_type,
+        max_cred_num,
+        tails_dir_path,
+        None,
+        0,
+        reg_def_p,
+        reg_def_private_p,
+        reg_entry_p,
+        reg_init_delta_p,
+    )
+}
+
+/// Receive the number of tails generated so far and the total number of tails.
+/// Returning an error code other than `Success` cancels tails generation.
+pub type TailsProgressCallback =
+    extern "C" fn(context: i64, generated: i64, total: i64) -> ErrorCode;
+
+#[no_mangle]
+pub extern "C" fn credx_create_revocation_registry_with_progress(
+    origin_did: FfiStr,
+    cred_def: ObjectHandle,
+    tag: FfiStr,
+    rev_reg_type: FfiStr,
+    issuance_type: FfiStr,
+    max_cred_num: i64,
+    tails_dir_path: FfiStr,
+    progress_cb: Option<TailsProgressCallback>,
+    progress_context: i64,
+    reg_def_p: *mut ObjectHandle,
+    reg_def_private_p: *mut ObjectHandle,
+    reg_entry_p: *mut ObjectHandle,
+    reg_init_delta_p: *mut ObjectHandle,
 ) -> ErrorCode {
     catch_error(|| {
         check_useful_c_ptr!(reg_def_p);
@@ -60,17 +100,23 @@ pub extern "C" fn credx_create_revocation_registry(
             None => IssuanceType::default(),
         };
         let mut tails_writer = TailsFileWriter::new(tails_dir_path.into_opt_string());
-        let (reg_def, reg_def_private, reg_entry, reg_init_delta) = create_revocation_registry(
-            &origin_did,
-            cred_def.load()?.cast_ref()?,
-            tag,
-            rev_reg_type,
-            issuance_type,
-            max_cred_num
-                .try_into()
-                .map_err(|_| err_msg!("Invalid maximum credential count"))?,
-            &mut tails_writer,
-        )?;
+        let mut progress = |generated: u32, total: u32| match progress_cb {
+            Some(cb) => cb(progress_context, generated.into(), total.into()) == ErrorCode::Success,
+            None => true,
+        };
+        let (reg_def, reg_def_private, reg_entry, reg_init_delta) =
+            create_revocation_registry_with_progress(
+                &origin_did,
+                cred_def.load()?.cast_ref()?,
+                tag,
+                rev_reg_type,
+                issuance_type,
+                max_cred_num
+                    .try_into()
+                    .map_err(|_| err_msg!("Invalid maximum credential count"))?,
+                &mut tails_writer,
+                &mut progress,
+            )?;
         let reg_def = ObjectHandle::create(reg_def)?;
         let reg_def_private = ObjectHandle::create(reg_def_private)?;
         let reg_entry = ObjectHandle::create(reg_entry)?;
@@ -122,6 +168,40 @@ pub extern "C" fn credx_update_revocation_registry(
     })
 }
 
+#[no_mangle]
+pub extern "C" fn credx_revoke_credentials(
+    rev_reg_def: ObjectHandle,
+    rev_reg: ObjectHandle,
+    cred_rev_idxs: FfiList<i64>,
+    tails_path: FfiStr,
+    rev_reg_p: *mut ObjectHandle,
+    rev_reg_delta_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(rev_reg_p);
+        check_useful_c_ptr!(rev_reg_delta_p);
+        let cred_rev_idxs = registry_indices_to_set(cred_rev_idxs.as_slice().into_iter().cloned())?;
+        let tails_reader = TailsFileReader::new(
+            tails_path
+                .as_opt_str()
+                .ok_or_else(|| err_msg!("Missing tails file path"))?,
+        );
+        let (rev_reg, rev_reg_delta) = revoke_credentials(
+            rev_reg_def.load()?.cast_ref()?,
+            rev_reg.load()?.cast_ref()?,
+            cred_rev_idxs,
+            &tails_reader,
+        )?;
+        let rev_reg = ObjectHandle::create(rev_reg)?;
+        let rev_reg_delta = ObjectHandle::create(rev_reg_delta)?;
+        unsafe {
+            *rev_reg_p = rev_reg;
+            *rev_reg_delta_p = rev_reg_delta;
+        };
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn credx_revoke_credential(
     rev_reg_def: ObjectHandle,
@@ -157,6 +237,34 @@ pub extern "C" fn credx_revoke_credential(
     })
 }
 
+#[no_mangle]
+pub extern "C" fn credx_create_status_list_credential(
+    rev_reg_def: ObjectHandle,
+    revoked: FfiList<i64>,
+    issuer_id: FfiStr,
+    status_list_id: FfiStr,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let revoked = registry_indices_to_set(revoked.as_slice().iter().cloned())?;
+        let issuer_id = issuer_id
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing issuer ID"))?;
+        let status_list_id = status_list_id
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing status list ID"))?;
+        let credential = create_status_list_credential(
+            rev_reg_def.load()?.cast_ref()?,
+            &revoked,
+            issuer_id,
+            status_list_id,
+        )?;
+        unsafe { *result_p = rust_string_to_c(credential.to_string()) };
+        Ok(())
+    })
+}
+
 fn registry_indices_to_set(indices: impl Iterator<Item = i64>) -> Result<BTreeSet<u32>> {
     indices.into_iter().try_fold(BTreeSet::new(), |mut r, idx| {
         r.insert(
@@ -170,7 +278,12 @@ fn registry_indices_to_set(indices: impl Iterator<Item = i64>) -> Result<BTreeSe
 impl_indy_object!(RevocationRegistryDefinition, "RevocationRegistryDefinition");
 impl_indy_object_from_json!(
     RevocationRegistryDefinition,
-    credx_revocation_registry_definition_from_json
+    credx_revocation_registry_definition_from_json,
+    versioned indy_data_types::anoncreds::rev_reg_def::RevocationRegistryDefinitionV1
+);
+impl_indy_object_from_cbor!(
+    RevocationRegistryDefinition,
+    credx_revocation_registry_definition_from_cbor
 );
 
 impl IndyObjectId for RevocationRegistryDefinition {
@@ -225,9 +338,18 @@ impl_indy_object_from_json!(
     RevocationRegistryDefinitionPrivate,
     credx_revocation_registry_definition_private_from_json
 );
+impl_indy_object_from_cbor!(
+    RevocationRegistryDefinitionPrivate,
+    credx_revocation_registry_definition_private_from_cbor
+);
 
 impl_indy_object!(RevocationRegistry, "RevocationRegistry");
-impl_indy_object_from_json!(RevocationRegistry, credx_revocation_registry_from_json);
+impl_indy_object_from_json!(
+    RevocationRegistry,
+    credx_revocation_registry_from_json,
+    versioned indy_data_types::anoncreds::rev_reg::RevocationRegistryV1
+);
+impl_indy_object_from_cbor!(RevocationRegistry, credx_revocation_registry_from_cbor);
 
 #[no_mangle]
 pub extern "C" fn credx_merge_revocation_registry_deltas(
@@ -249,10 +371,129 @@ pub extern "C" fn credx_merge_revocation_registry_deltas(
     })
 }
 
+/// Produce the delta between an earlier and the current state of a revocation
+/// registry, given the indices issued and revoked in the meantime
+#[no_mangle]
+pub extern "C" fn credx_revocation_registry_diff(
+    rev_reg: ObjectHandle,
+    earlier_rev_reg: ObjectHandle,
+    issued: FfiList<i64>,
+    revoked: FfiList<i64>,
+    rev_reg_delta_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(rev_reg_delta_p);
+        let issued = registry_indices_to_set(issued.as_slice().iter().cloned())?;
+        let revoked = registry_indices_to_set(revoked.as_slice().iter().cloned())?;
+        let rev_reg_delta = rev_reg.load()?.cast_ref::<RevocationRegistry>()?.diff(
+            earlier_rev_reg.load()?.cast_ref()?,
+            &issued.into_iter().collect(),
+            &revoked.into_iter().collect(),
+        )?;
+        let rev_reg_delta = ObjectHandle::create(rev_reg_delta)?;
+        unsafe { *rev_reg_delta_p = rev_reg_delta };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_revocation_registry_apply_delta(
+    rev_reg: ObjectHandle,
+    rev_reg_delta: ObjectHandle,
+    rev_reg_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(rev_reg_p);
+        let updated = rev_reg
+            .load()?
+            .cast_ref::<RevocationRegistry>()?
+            .apply_delta(rev_reg_delta.load()?.cast_ref()?)?;
+        let updated = ObjectHandle::create(updated)?;
+        unsafe { *rev_reg_p = updated };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_export_revocation_registry_deltas(
+    rev_reg_id: FfiStr,
+    rev_reg_deltas: FfiList<ObjectHandle>,
+    timestamps: FfiList<i64>,
+    blob_p: *mut ByteBuffer,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(blob_p);
+        let rev_reg_id = RevocationRegistryId::from_str(
+            rev_reg_id
+                .as_opt_str()
+                .ok_or_else(|| err_msg!("Missing rev_reg_id"))?,
+        )?;
+        if rev_reg_deltas.len() != timestamps.len() {
+            return Err(err_msg!(
+                "Inconsistent lengths for revocation registry deltas and timestamps"
+            ));
+        }
+        let deltas = rev_reg_deltas.as_slice();
+        let deltas = timestamps
+            .as_slice()
+            .iter()
+            .zip(deltas)
+            .map(|(timestamp, delta)| {
+                let timestamp = (*timestamp)
+                    .try_into()
+                    .map_err(|_| err_msg!("Invalid timestamp for revocation registry delta"))?;
+                Ok((
+                    timestamp,
+                    delta.load()?.cast_ref::<RevocationRegistryDelta>()?.clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let blob = export_revocation_registry_deltas(&rev_reg_id, &deltas)?;
+        unsafe {
+            *blob_p = ByteBuffer::from_vec(blob);
+        };
+        Ok(())
+    })
+}
+
+/// Import a revocation registry delta batch, returning the deltas merged into a
+/// single delta along with the timestamp of the most recent one.
+#[no_mangle]
+pub extern "C" fn credx_import_revocation_registry_deltas(
+    blob: ByteBuffer,
+    rev_reg_delta_p: *mut ObjectHandle,
+    timestamp_p: *mut i64,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(rev_reg_delta_p);
+        check_useful_c_ptr!(timestamp_p);
+        let (_rev_reg_id, deltas) = import_revocation_registry_deltas(blob.as_slice())?;
+        let mut deltas = deltas.into_iter();
+        let (mut timestamp, mut merged) = deltas
+            .next()
+            .ok_or_else(|| err_msg!("No revocation registry deltas provided"))?;
+        for (next_timestamp, delta) in deltas {
+            merged = merge_revocation_registry_deltas(&merged, &delta)?;
+            timestamp = next_timestamp;
+        }
+        let merged = ObjectHandle::create(merged)?;
+        unsafe {
+            *rev_reg_delta_p = merged;
+            *timestamp_p = timestamp as i64;
+        };
+        Ok(())
+    })
+}
+
 impl_indy_object!(RevocationRegistryDelta, "RevocationRegistryDelta");
 impl_indy_object_from_json!(
     RevocationRegistryDelta,
-    credx_revocation_registry_delta_from_json
+    credx_revocation_registry_delta_from_json,
+    versioned indy_data_types::anoncreds::rev_reg::RevocationRegistryDeltaV1
+);
+impl_indy_object_from_cbor!(
+    RevocationRegistryDelta,
+    credx_revocation_registry_delta_from_cbor
 );
 
 #[no_mangle]
@@ -294,5 +535,82 @@ pub extern "C" fn credx_create_or_update_revocation_state(
     })
 }
 
+#[no_mangle]
+pub extern "C" fn credx_create_or_update_revocation_witness(
+    rev_reg_def: ObjectHandle,
+    rev_reg_delta: ObjectHandle,
+    rev_reg_index: i64,
+    timestamp: i64,
+    tails_path: FfiStr,
+    witness_update: ObjectHandle,
+    witness_update_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(witness_update_p);
+        let prev_update = witness_update.opt_load()?;
+        let tails_reader = TailsFileReader::new(
+            tails_path
+                .as_opt_str()
+                .ok_or_else(|| err_msg!("Missing tails file path"))?,
+        );
+        let prev_updates = prev_update
+            .as_ref()
+            .map(IndyObject::cast_ref)
+            .transpose()?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut updates = create_or_update_revocation_witnesses(
+            tails_reader,
+            rev_reg_def.load()?.cast_ref()?,
+            rev_reg_delta.load()?.cast_ref()?,
+            &[rev_reg_index
+                .try_into()
+                .map_err(|_| err_msg!("Invalid credential revocation index"))?],
+            timestamp
+                .try_into()
+                .map_err(|_| err_msg!("Invalid timestamp"))?,
+            &prev_updates,
+        )?;
+        let witness_update = ObjectHandle::create(updates.remove(0))?;
+        unsafe { *witness_update_p = witness_update };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_create_revocation_state_from_witness(
+    rev_reg_def: ObjectHandle,
+    rev_reg_delta: ObjectHandle,
+    witness_update: ObjectHandle,
+    rev_state_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(rev_state_p);
+        let rev_state = create_revocation_state_from_witness(
+            rev_reg_def.load()?.cast_ref()?,
+            rev_reg_delta.load()?.cast_ref()?,
+            witness_update.load()?.cast_ref()?,
+        )?;
+        let rev_state = ObjectHandle::create(rev_state)?;
+        unsafe { *rev_state_p = rev_state };
+        Ok(())
+    })
+}
+
+impl_indy_object!(RevocationStatusList, "RevocationStatusList");
+impl_indy_object_from_json!(RevocationStatusList, credx_revocation_status_list_from_json);
+impl_indy_object_from_cbor!(RevocationStatusList, credx_revocation_status_list_from_cbor);
+
+impl_indy_object!(RevocationWitnessUpdate, "RevocationWitnessUpdate");
+impl_indy_object_from_json!(
+    RevocationWitnessUpdate,
+    credx_revocation_witness_update_from_json
+);
+impl_indy_object_from_cbor!(
+    RevocationWitnessUpdate,
+    credx_revocation_witness_update_from_cbor
+);
+
 impl_indy_object!(CredentialRevocationState, "CredentialRevocationState");
 impl_indy_object_from_json!(CredentialRevocationState, credx_revocation_state_from_json);
+impl_indy_object_from_cbor!(CredentialRevocationState, credx_revocation_state_from_cbor);
@@ -71,7 +71,12 @@ pub extern "C" fn credx_schema_get_attribute(
 }
 
 impl_indy_object!(Schema, "Schema");
-impl_indy_object_from_json!(Schema, credx_schema_from_json);
+impl_indy_object_from_json!(
+    Schema,
+    credx_schema_from_json,
+    limited versioned indy_data_types::anoncreds::schema::SchemaV1
+);
+impl_indy_object_from_cbor!(Schema, credx_schema_from_cbor);
 
 impl IndyObjectId for Schema {
     type Id = SchemaId;
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::os::raw::c_char;
+use std::time::Instant;
+
+use ffi_support::rust_string_to_c;
+
+use super::error::{catch_error, ErrorCode};
+use crate::error::{Error, Result};
+use crate::services::{
+    issuer, prover,
+    types::{
+        CredentialDefinitionConfig, DidValue, MakeCredentialValues, PresentCredentials,
+        PresentationRequest, SignatureType,
+    },
+    verifier,
+};
+
+const SELF_TEST_DID: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+
+struct StageTimer {
+    started: Instant,
+    timings: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StageTimer {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            timings: serde_json::Map::new(),
+        }
+    }
+
+    fn run<T>(&mut self, stage: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result =
+            f().map_err(|err| Error::from_msg(err.kind(), format!("{} failed: {}", stage, err)));
+        self.timings.insert(
+            stage.to_owned(),
+            serde_json::json!(start.elapsed().as_secs_f64() * 1000.0),
+        );
+        result
+    }
+}
+
+fn run_self_test(timer: &mut StageTimer) -> Result<bool> {
+    let did = DidValue::new(SELF_TEST_DID, None);
+    let schema = timer.run("create_schema", || {
+        issuer::create_schema(&did, "self_test", "1.0", ["name", "age"][..].into(), None)
+    })?;
+    let (cred_def, cred_def_private, key_proof) =
+        timer.run("create_credential_definition", || {
+            issuer::create_credential_definition(
+                &did,
+                &schema,
+                "self_test",
+                SignatureType::CL,
+                CredentialDefinitionConfig {
+                    support_revocation: false,
+                },
+            )
+        })?;
+    let master_secret = timer.run("create_master_secret", prover::create_master_secret)?;
+    let credential = timer.run("issue_credential", || {
+        let offer = issuer::create_credential_offer(schema.id(), &cred_def, &key_proof, None)?;
+        let (request, metadata) = prover::create_credential_request(
+            &did,
+            &cred_def,
+            &master_secret,
+            "self_test",
+            &offer,
+        )?;
+        let mut values = MakeCredentialValues::default();
+        values.add_raw("name", "Alex")?;
+        values.add_raw("age", "28")?;
+        let (mut credential, _, _) = issuer::create_credential(
+            &cred_def,
+            &cred_def_private,
+            &offer,
+            &request,
+            values.into(),
+            None,
+        )?;
+        prover::process_credential(&mut credential, &metadata, &master_secret, &cred_def, None)?;
+        Ok(credential)
+    })?;
+    let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+        "nonce": verifier::generate_nonce()?,
+        "name": "self_test",
+        "version": "1.0",
+        "requested_attributes": {"attr1_referent": {"name": "name"}},
+        "requested_predicates": {
+            "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+        }
+    }))?;
+    let mut schemas = HashMap::new();
+    schemas.insert(schema.id().clone(), &schema);
+    let mut cred_defs = HashMap::new();
+    cred_defs.insert(cred_def.id().clone(), &cred_def);
+    let presentation = timer.run("create_presentation", || {
+        let mut present = PresentCredentials::default();
+        let mut cred = present.add_credential(&credential, None, None);
+        cred.add_requested_attribute("attr1_referent", true);
+        cred.add_requested_predicate("predicate1_referent");
+        prover::create_presentation(
+            &pres_req,
+            present,
+            None,
+            &master_secret,
+            &schemas,
+            &cred_defs,
+        )
+    })?;
+    timer.run("verify_presentation", || {
+        verifier::verify_presentation(&presentation, &pres_req, &schemas, &cred_defs, None, None)
+            .map(|result| result.is_valid())
+    })
+}
+
+/// Run an end-to-end issuance and verification of a minimal credential,
+/// producing a JSON report of the outcome and the time taken by each stage
+#[no_mangle]
+pub extern "C" fn credx_self_test(result_p: *mut *const c_char) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let mut timer = StageTimer::new();
+        let (success, error) = match run_self_test(&mut timer) {
+            Ok(true) => (true, None),
+            Ok(false) => (false, Some("Presentation verification failed".to_owned())),
+            Err(err) => (false, Some(err.to_string())),
+        };
+        let report = serde_json::json!({
+            "success": success,
+            "error": error,
+            "version": super::LIB_VERSION,
+            "timings_ms": timer.timings,
+            "total_ms": timer.started.elapsed().as_secs_f64() * 1000.0,
+        });
+        unsafe { *result_p = rust_string_to_c(report.to_string()) };
+        Ok(())
+    })
+}
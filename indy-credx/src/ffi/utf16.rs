@@ -0,0 +1,162 @@
+use std::ptr;
+use std::slice;
+
+use ffi_support::FfiStr;
+
+use super::credential::credential_attribute;
+use super::error::{catch_error, ErrorCode};
+use super::object::{
+    create_parsed_object, limited_object_from_json, limited_versioned_object_from_json,
+    object_from_json, versioned_object_from_json, ObjectHandle, ToJson,
+};
+use crate::error::Result;
+use crate::services::types::{
+    Credential, CredentialDefinition, CredentialDefinitionPrivate, CredentialKeyCorrectnessProof,
+    CredentialOffer, CredentialRequest, CredentialRequestMetadata, CredentialRevocationState,
+    MasterSecret, MasterSecretProof, Presentation, PresentationRequest, RevocationRegistry,
+    RevocationRegistryDefinition, RevocationRegistryDefinitionPrivate, RevocationRegistryDelta,
+    RevocationRegistryUsage, RevocationStatusList, RevocationWitnessUpdate, Schema,
+};
+use indy_data_types::anoncreds::{
+    cred_def::CredentialDefinitionV1,
+    rev_reg::{RevocationRegistryDeltaV1, RevocationRegistryV1},
+    rev_reg_def::RevocationRegistryDefinitionV1,
+    schema::SchemaV1,
+};
+
+/// Read a NUL-terminated UTF-16 string, rejecting invalid surrogate pairs
+fn utf16_to_string(value: *const u16) -> Result<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let units = unsafe {
+        let mut len = 0;
+        while *value.add(len) != 0 {
+            len += 1;
+        }
+        slice::from_raw_parts(value, len)
+    };
+    String::from_utf16(units)
+        .map(Some)
+        .map_err(err_map!("Invalid UTF-16 string"))
+}
+
+/// Allocate a NUL-terminated UTF-16 string, to be released with `credx_string_utf16_free`
+fn string_to_utf16(value: &str) -> Result<*mut u16> {
+    let mut units = value.encode_utf16().collect::<Vec<_>>();
+    if units.contains(&0) {
+        return Err(err_msg!("String contains a NUL character"));
+    }
+    units.push(0);
+    Ok(Box::into_raw(units.into_boxed_slice()) as *mut u16)
+}
+
+#[no_mangle]
+pub extern "C" fn credx_string_utf16_free(value: *mut u16) {
+    if value.is_null() {
+        return;
+    }
+    unsafe {
+        let mut len = 0;
+        while *value.add(len) != 0 {
+            len += 1;
+        }
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(value, len + 1)));
+    }
+}
+
+/// Parse an object of the named type from a UTF-16 JSON string
+#[no_mangle]
+pub extern "C" fn credx_object_from_json_utf16(
+    type_name: FfiStr,
+    json: *const u16,
+    result_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let type_name = type_name
+            .as_opt_str()
+            .ok_or_else(|| err_msg!("Missing object type name"))?;
+        let json = utf16_to_string(json)?.ok_or_else(|| err_msg!("Missing JSON"))?;
+        let json = json.as_bytes();
+        macro_rules! parse {
+            ($ident:path) => {
+                create_parsed_object(object_from_json::<$ident>(json)?)?
+            };
+            ($ident:path, limited) => {
+                create_parsed_object(limited_object_from_json::<$ident>(json)?)?
+            };
+            ($ident:path, versioned $v1:path) => {
+                create_parsed_object(versioned_object_from_json::<$ident, $v1>(json)?)?
+            };
+            ($ident:path, limited versioned $v1:path) => {
+                create_parsed_object(limited_versioned_object_from_json::<$ident, $v1>(json)?)?
+            };
+        }
+        let handle = match type_name {
+            "Credential" => parse!(Credential, limited),
+            "CredentialDefinition" => {
+                parse!(CredentialDefinition, versioned CredentialDefinitionV1)
+            }
+            "CredentialDefinitionPrivate" => parse!(CredentialDefinitionPrivate),
+            "CredentialOffer" => parse!(CredentialOffer),
+            "CredentialRequest" => parse!(CredentialRequest),
+            "CredentialRequestMetadata" => parse!(CredentialRequestMetadata),
+            "CredentialRevocationState" => parse!(CredentialRevocationState),
+            "KeyCorrectnessProof" => parse!(CredentialKeyCorrectnessProof),
+            "MasterSecret" => parse!(MasterSecret),
+            "MasterSecretProof" => parse!(MasterSecretProof),
+            "Presentation" => parse!(Presentation, limited),
+            "PresentationRequest" => parse!(PresentationRequest, limited),
+            "RevocationRegistry" => parse!(RevocationRegistry, versioned RevocationRegistryV1),
+            "RevocationRegistryDefinition" => parse!(
+                RevocationRegistryDefinition,
+                versioned RevocationRegistryDefinitionV1
+            ),
+            "RevocationRegistryDefinitionPrivate" => parse!(RevocationRegistryDefinitionPrivate),
+            "RevocationRegistryDelta" => {
+                parse!(RevocationRegistryDelta, versioned RevocationRegistryDeltaV1)
+            }
+            "RevocationRegistryUsage" => parse!(RevocationRegistryUsage),
+            "RevocationStatusList" => parse!(RevocationStatusList),
+            "RevocationWitnessUpdate" => parse!(RevocationWitnessUpdate),
+            "Schema" => parse!(Schema, limited versioned SchemaV1),
+            other => return Err(err_msg!("Unsupported object type: {}", other)),
+        };
+        unsafe { *result_p = handle };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_object_get_json_utf16(
+    handle: ObjectHandle,
+    result_p: *mut *mut u16,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let json = handle.load()?.to_json()?;
+        let json = String::from_utf8(json).map_err(err_map!(Unexpected, "Invalid JSON"))?;
+        unsafe { *result_p = string_to_utf16(&json)? };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn credx_credential_get_attribute_utf16(
+    handle: ObjectHandle,
+    name: *const u16,
+    result_p: *mut *mut u16,
+) -> ErrorCode {
+    catch_error(|| {
+        check_useful_c_ptr!(result_p);
+        let name = utf16_to_string(name)?.unwrap_or_default();
+        let cred = handle.load()?;
+        let val = match credential_attribute(cred.cast_ref::<Credential>()?, &name)? {
+            Some(val) => string_to_utf16(&val)?,
+            None => ptr::null_mut(),
+        };
+        unsafe { *result_p = val };
+        Ok(())
+    })
+}
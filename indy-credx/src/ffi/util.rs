@@ -3,6 +3,7 @@ use std::slice;
 
 use ffi_support::FfiStr;
 
+use super::error::ErrorCode;
 use crate::error::Result;
 
 #[derive(Debug)]
@@ -14,6 +15,24 @@ pub struct FfiList<'a, T> {
 }
 
 impl<'a, T> FfiList<'a, T> {
+    #[inline]
+    pub fn empty() -> Self {
+        Self {
+            count: 0,
+            data: std::ptr::null(),
+            _pd: PhantomData,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_slice(items: &'a [T]) -> Self {
+        Self {
+            count: items.len(),
+            data: items.as_ptr(),
+            _pd: PhantomData,
+        }
+    }
+
     #[inline]
     pub fn as_slice(&self) -> &[T] {
         if self.data.is_null() {
@@ -48,6 +67,51 @@ impl<'a, T> FfiList<'a, T> {
     }
 }
 
+/// Fetch the next item of an input sequence, writing it to `item_p` and setting
+/// `has_item_p` to 1, or setting `has_item_p` to 0 once the sequence is exhausted.
+/// The callback is only invoked on the calling thread, before the enclosing call
+/// returns. Returning an error code other than `Success` aborts the operation.
+pub type FfiIterNextCallback<T> =
+    extern "C" fn(context: i64, item_p: *mut T, has_item_p: *mut i8) -> ErrorCode;
+
+/// An input sequence provided by the caller one item at a time, avoiding the need
+/// to collect large inputs into a contiguous array
+pub struct FfiIter<T> {
+    callback: Option<FfiIterNextCallback<T>>,
+    context: i64,
+}
+
+impl<T> FfiIter<T> {
+    #[inline]
+    pub fn new(callback: Option<FfiIterNextCallback<T>>, context: i64) -> Self {
+        Self { callback, context }
+    }
+}
+
+impl<T: Default> Iterator for FfiIter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let callback = self.callback?;
+        let mut item = T::default();
+        let mut has_item = 0i8;
+        let code = callback(self.context, &mut item, &mut has_item);
+        if code != ErrorCode::Success {
+            self.callback.take();
+            return Some(Err(err_msg!(
+                "Input sequence aborted by callback: error code {}",
+                code as usize
+            )));
+        }
+        if has_item == 0 {
+            self.callback.take();
+            None
+        } else {
+            Some(Ok(item))
+        }
+    }
+}
+
 pub type FfiStrList<'a> = FfiList<'a, FfiStr<'a>>;
 
 impl<'a> FfiStrList<'a> {
@@ -21,3 +21,6 @@ pub use services::*;
 
 #[cfg(feature = "ffi")]
 mod ffi;
+
+#[cfg(feature = "wasm")]
+mod wasm;
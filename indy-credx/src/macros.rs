@@ -8,6 +8,41 @@ macro_rules! assert_kind {
     };
 }
 
+#[cfg(feature = "transcript")]
+macro_rules! with_transcript {
+    ($operation:expr, $body:expr) => {{
+        let recording = $crate::services::transcript::Recording::begin($operation);
+        let result = $body;
+        recording.finish(&result);
+        result
+    }};
+}
+
+#[cfg(not(feature = "transcript"))]
+macro_rules! with_transcript {
+    ($operation:expr, $body:expr) => {{
+        $body
+    }};
+}
+
+#[cfg(feature = "transcript")]
+macro_rules! transcript {
+    ($kind:ident, $name:expr, $value:expr) => {
+        $crate::services::transcript::record(
+            $crate::services::transcript::TranscriptEntryKind::$kind,
+            $name,
+            $value,
+        )
+    };
+}
+
+#[cfg(not(feature = "transcript"))]
+macro_rules! transcript {
+    ($kind:ident, $name:expr, $value:expr) => {
+        ()
+    };
+}
+
 #[cfg(debug_assertions)]
 macro_rules! secret {
     ($val:expr) => {{
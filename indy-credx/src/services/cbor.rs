@@ -0,0 +1,83 @@
+//! CBOR encoding of the anoncreds data types, as a compact alternative to JSON
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::Result;
+
+/// Encode an object as CBOR
+pub fn to_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    serde_cbor::to_vec(value).map_err(err_map!("Error encoding object as CBOR"))
+}
+
+/// Decode an object from CBOR
+pub fn from_cbor<T: DeserializeOwned>(cbor: &[u8]) -> Result<T> {
+    serde_cbor::from_slice(cbor).map_err(err_map!("Error decoding CBOR object"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::issuer;
+    use crate::services::types::{
+        CredentialDefinition, CredentialDefinitionConfig, CredentialDefinitionPrivate, DidValue,
+        PresentationRequest, Schema, SignatureType,
+    };
+
+    #[test]
+    fn cbor_round_trip_works() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {
+                "attr1_referent": {
+                    "name": "name",
+                    "restrictions": {"issuer_did": "NcYxiDXkpYi6ov5FcYDi1e"}
+                }
+            },
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+            },
+            "non_revoked": {"from": 10, "to": 20}
+        }))
+        .unwrap();
+        let cbor = to_cbor(&pres_req).unwrap();
+        assert!(cbor.len() < serde_json::to_vec(&pres_req).unwrap().len());
+        let decoded: PresentationRequest = from_cbor(&cbor).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&pres_req).unwrap()
+        );
+
+        let schema: Schema = serde_json::from_value(serde_json::json!({
+            "ver": "1.0",
+            "id": "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0",
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age"],
+            "seqNo": 14
+        }))
+        .unwrap();
+        let (cred_def, cred_def_private, _) = issuer::create_credential_definition(
+            &DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None),
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(false),
+        )
+        .unwrap();
+        let decoded: CredentialDefinition = from_cbor(&to_cbor(&cred_def).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&cred_def).unwrap()
+        );
+        let decoded: CredentialDefinitionPrivate =
+            from_cbor(&to_cbor(&cred_def_private).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded.value).unwrap(),
+            serde_json::to_value(&cred_def_private.value).unwrap()
+        );
+
+        from_cbor::<Schema>(b"invalid").unwrap_err();
+    }
+}
@@ -1,24 +1,299 @@
 use ursa::{
     bn::BigNumber,
     cl::{
-        issuer, verifier, CredentialSchema, CredentialValues as CryptoCredentialValues,
-        MasterSecret as CryptoMasterSecret, NonCredentialSchema, SubProofRequest,
+        issuer, verifier, CredentialPublicKey, CredentialSchema,
+        CredentialValues as CryptoCredentialValues, MasterSecret as CryptoMasterSecret,
+        NonCredentialSchema, SubProofRequest,
     },
 };
 
 use crate::error::Result;
+use crate::services::types::{
+    AttributeNormalization, CheckLimits, Clock, CredentialDefinitionId, CredxConfig, IssuerEvent,
+    IssuerMetrics, NonRevokedIntervalPolicy, SystemClock, ValidationConfig,
+};
 
 use indy_data_types::anoncreds::{
+    cred_def::CredentialDefinitionV1,
     credential::AttributeValues,
     nonce::Nonce,
-    pres_request::{AttributeInfo, NonRevocedInterval, PredicateInfo},
+    pres_request::{AttributeInfo, NonRevocedInterval, PredicateInfo, PredicateTypes},
 };
-use indy_utils::hash::SHA256;
+use indy_data_types::ConversionError;
+use indy_utils::{hash::SHA256, Validatable};
 
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use once_cell::sync::Lazy;
+
+static CONFIG: Lazy<RwLock<CredxConfig>> = Lazy::new(|| RwLock::new(CredxConfig::default()));
+
+static CLOCK: Lazy<RwLock<Arc<dyn Clock>>> = Lazy::new(|| RwLock::new(Arc::new(SystemClock)));
+
+static ISSUER_METRICS: Lazy<RwLock<Option<Arc<IssuerMetrics>>>> = Lazy::new(|| RwLock::new(None));
+
+static PUBLIC_KEY_CACHE: Lazy<Mutex<PublicKeyCache>> =
+    Lazy::new(|| Mutex::new(PublicKeyCache::default()));
+
+/// Read a value from the global configuration.
+///
+/// The global state is only ever replaced as a whole, so a poisoned lock still
+/// holds a consistent value and is recovered rather than reported.
+fn read_config<T>(f: impl FnOnce(&CredxConfig) -> T) -> T {
+    f(&CONFIG.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Replace the global configuration used by all services
+pub fn set_config(config: CredxConfig) -> Result<()> {
+    config.validate()?;
+    let cache_size = config.public_key_cache_size;
+    *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = config;
+    public_key_cache().trim(cache_size);
+    Ok(())
+}
+
+pub fn get_config() -> CredxConfig {
+    read_config(CredxConfig::clone)
+}
+
+pub fn get_validation_config() -> ValidationConfig {
+    read_config(|config| config.validation)
+}
+
+/// Check an object against the configured size and complexity limits
+pub fn check_limits(obj: &impl CheckLimits) -> Result<()> {
+    obj.check_limits(&get_validation_config())
+        .map_err(err_map!(LimitExceeded))
+}
+
+/// Lock the public key cache. The cache only holds values derived from the
+/// credential definitions, so a poisoned lock is recovered like the configuration.
+fn public_key_cache() -> std::sync::MutexGuard<'static, PublicKeyCache> {
+    PUBLIC_KEY_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn get_public_key_cache_size() -> usize {
+    read_config(|config| config.public_key_cache_size)
+}
+
+/// Remove all cached credential definition public keys
+pub fn clear_public_key_cache() {
+    public_key_cache().trim(0);
+}
+
+/// A least-recently-used cache of parsed credential definition public keys.
+///
+/// Each entry records a digest of the serialized key so that a different
+/// credential definition presented under the same ID is never served a stale key.
+#[derive(Default)]
+struct PublicKeyCache {
+    entries: HashMap<CredentialDefinitionId, (Vec<u8>, Arc<CredentialPublicKey>, u64)>,
+    counter: u64,
+}
+
+impl PublicKeyCache {
+    fn get(
+        &mut self,
+        id: &CredentialDefinitionId,
+        digest: &[u8],
+    ) -> Option<Arc<CredentialPublicKey>> {
+        self.counter += 1;
+        let counter = self.counter;
+        match self.entries.get_mut(id) {
+            Some((key_digest, key, used)) if key_digest.as_slice() == digest => {
+                *used = counter;
+                Some(key.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn insert(
+        &mut self,
+        id: CredentialDefinitionId,
+        digest: Vec<u8>,
+        key: Arc<CredentialPublicKey>,
+        size: usize,
+    ) {
+        self.counter += 1;
+        self.entries.insert(id, (digest, key, self.counter));
+        self.trim(size);
+    }
+
+    fn trim(&mut self, size: usize) {
+        while self.entries.len() > size {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, _, used))| *used)
+                .map(|(id, _)| id.clone());
+            match oldest {
+                Some(id) => self.entries.remove(&id),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Get the public key of a credential definition, using the public key cache
+/// when it is enabled to avoid parsing the key on every use
+pub(crate) fn credential_public_key(
+    cred_def: &CredentialDefinitionV1,
+) -> std::result::Result<Arc<CredentialPublicKey>, ConversionError> {
+    let size = get_public_key_cache_size();
+    if size == 0 {
+        return Ok(Arc::new(cred_def.get_public_key()?));
+    }
+    let digest = SHA256::digest(
+        serde_json::to_vec(&cred_def.value)
+            .map_err(|err| ConversionError::from_msg(err.to_string()))?,
+    );
+    if let Some(key) = public_key_cache().get(&cred_def.id, &digest) {
+        return Ok(key);
+    }
+    let key = Arc::new(cred_def.get_public_key()?);
+    public_key_cache().insert(cred_def.id.clone(), digest, key.clone(), size);
+    Ok(key)
+}
+
+/// Replace the clock used for all comparisons against the current time
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    *CLOCK.write().unwrap_or_else(PoisonError::into_inner) = clock;
+}
+
+/// Get the current time in seconds since the Unix epoch from the configured clock
+pub fn current_timestamp() -> u64 {
+    CLOCK.read().unwrap_or_else(PoisonError::into_inner).now()
+}
+
+pub fn get_clock_skew() -> u64 {
+    read_config(|config| config.clock_skew)
+}
+
+/// Check that a timestamp is not later than the current time, allowing for
+/// the configured clock skew
+pub fn is_timestamp_current(timestamp: u64) -> bool {
+    timestamp <= current_timestamp().saturating_add(get_clock_skew())
+}
+
+/// Install the metrics updated by issuer operations, or remove them with `None`
+pub fn set_issuer_metrics(metrics: Option<Arc<IssuerMetrics>>) {
+    *ISSUER_METRICS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = metrics;
+}
+
+pub fn get_issuer_metrics() -> Option<Arc<IssuerMetrics>> {
+    ISSUER_METRICS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+pub(crate) fn record_issuer_event(cred_def_id: &CredentialDefinitionId, event: IssuerEvent) {
+    if let Some(metrics) = get_issuer_metrics() {
+        metrics.record(cred_def_id, event);
+    }
+}
+
+pub fn get_attribute_normalization() -> AttributeNormalization {
+    read_config(|config| config.attribute_normalization)
+}
+
+pub fn get_max_schema_attributes() -> usize {
+    read_config(|config| config.validation.max_schema_attributes)
+}
+
+pub fn check_schema_attributes_count(count: usize) -> Result<()> {
+    if count == 0 {
+        return Err(err_msg!("Empty list of Schema attributes has been passed"));
+    }
+    let limit = get_max_schema_attributes();
+    if count > limit {
+        return Err(err_msg!(
+            LimitExceeded,
+            "The number of Schema attributes {} exceeds the configured limit of {}",
+            count,
+            limit
+        ));
+    }
+    Ok(())
+}
+
+/// Attribute names reserved for the hidden attributes of a credential
+pub const RESERVED_ATTRIBUTE_NAMES: &[&str] = &["master_secret"];
+
+/// Check that a schema attribute name is not empty or reserved
+pub fn check_attribute_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(err_msg!("Schema attribute names must not be empty"));
+    }
+    let normalized = attr_common_view(name);
+    if RESERVED_ATTRIBUTE_NAMES
+        .iter()
+        .any(|reserved| attr_common_view(reserved) == normalized)
+    {
+        return Err(err_msg!("Schema attribute name \"{}\" is reserved", name));
+    }
+    Ok(())
+}
+
+/// Check the attribute names of a schema: each name must be valid, the names must
+/// remain distinct once normalized, and their number must be within the configured limit
+pub fn check_schema_attribute_names<'a>(names: impl IntoIterator<Item = &'a String>) -> Result<()> {
+    let mut normalized = HashMap::new();
+    for name in names {
+        check_attribute_name(name)?;
+        if let Some(existing) = normalized.insert(attr_common_view(name), name) {
+            return Err(err_msg!(
+                "Attribute names \"{}\" and \"{}\" are equivalent after normalization",
+                existing,
+                name
+            ));
+        }
+    }
+    check_schema_attributes_count(normalized.len())
+}
+
+pub fn get_non_revoked_interval_policy() -> (NonRevokedIntervalPolicy, u64) {
+    read_config(|config| {
+        (
+            config.non_revoked_interval_policy,
+            config.non_revoked_tolerance,
+        )
+    })
+}
+
+pub fn is_timestamp_in_interval(timestamp: u64, interval: &NonRevocedInterval) -> bool {
+    let (policy, tolerance) = get_non_revoked_interval_policy();
+    policy.accepts(timestamp, &effective_interval(interval), tolerance)
+}
+
+/// Limit the end of a non-revocation interval to the current time, allowing for
+/// the configured clock skew, as no later revocation registry state can exist
+pub fn effective_interval(interval: &NonRevocedInterval) -> NonRevocedInterval {
+    let latest = current_timestamp().saturating_add(get_clock_skew());
+    NonRevocedInterval {
+        from: interval.from,
+        to: interval.to.map(|to| to.min(latest)),
+    }
+}
 
 pub fn attr_common_view(attr: &str) -> String {
-    attr.replace(" ", "").to_lowercase()
+    get_attribute_normalization().normalize(attr)
+}
+
+/// Look up a value by attribute name, applying the normalization policy
+pub fn get_attr_normalized<'a, V>(map: &'a HashMap<String, V>, attr: &str) -> Option<&'a V> {
+    map.get(attr).or_else(|| {
+        let attr = attr_common_view(attr);
+        map.iter()
+            .find(|(key, _)| attr_common_view(key) == attr)
+            .map(|(_, value)| value)
+    })
 }
 
 pub fn build_credential_schema(attrs: &HashSet<String>) -> Result<CredentialSchema> {
@@ -36,6 +311,27 @@ pub fn build_credential_schema(attrs: &HashSet<String>) -> Result<CredentialSche
     Ok(res)
 }
 
+/// Build the credential schema for the attributes signed by a credential
+/// definition, which may cover only a subset of the schema attributes
+pub fn build_cred_def_credential_schema(
+    schema_attrs: &HashSet<String>,
+    cred_def: &CredentialDefinitionV1,
+) -> Result<CredentialSchema> {
+    let key_attrs = cred_def
+        .value
+        .attribute_names()
+        .map_err(err_map!("Invalid credential definition public key"))?;
+    if key_attrs.len() == schema_attrs.len() {
+        return build_credential_schema(schema_attrs);
+    }
+    let attrs = schema_attrs
+        .iter()
+        .filter(|attr| key_attrs.contains(&attr_common_view(attr)))
+        .cloned()
+        .collect();
+    build_credential_schema(&attrs)
+}
+
 pub fn build_non_credential_schema() -> Result<NonCredentialSchema> {
     trace!("build_non_credential_schema");
 
@@ -75,17 +371,187 @@ pub fn encode_credential_attribute(raw_value: &str) -> Result<String> {
     if let Ok(val) = raw_value.parse::<i32>() {
         Ok(val.to_string())
     } else {
-        let digest = SHA256::digest(raw_value.as_bytes());
-        #[cfg(target_endian = "big")]
-        let digest = {
-            let mut d = digest;
-            d.reverse();
-            d
-        };
-        Ok(BigNumber::from_bytes(&digest)?.to_dec()?)
+        encode_digest(&SHA256::digest(raw_value.as_bytes()))
     }
 }
 
+/// Encode an attribute value, keeping only values in the canonical form of a
+/// 32-bit signed integer as-is. Unlike the default encoding, integers with
+/// leading zeros or an explicit sign, such as "007" or "+5", are hashed.
+pub fn encode_canonical_credential_attribute(raw_value: &str) -> Result<String> {
+    match raw_value.parse::<i32>() {
+        Ok(val) if val.to_string() == raw_value => Ok(raw_value.to_string()),
+        _ => encode_digest(&SHA256::digest(raw_value.as_bytes())),
+    }
+}
+
+/// Encode an attribute value by hashing it along with a salt. Unlike the
+/// default encoding, integer values are hashed as well.
+pub fn encode_credential_attribute_salted(raw_value: &str, salt: &str) -> Result<String> {
+    let digest = SHA256::digest([salt.as_bytes(), raw_value.as_bytes()].concat());
+    encode_digest(&digest)
+}
+
+/// The prefix of the raw value of a binary attribute
+pub const BINARY_ATTRIBUTE_PREFIX: &str = "sha256:";
+
+/// Encode binary attribute content, returning the raw value (the prefixed, hex-encoded
+/// SHA-256 hash of the content) and the encoded value (the hash as an integer)
+pub fn encode_binary_attribute(content: &[u8]) -> Result<(String, String)> {
+    let digest = SHA256::digest(content);
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    Ok((
+        format!("{}{}", BINARY_ATTRIBUTE_PREFIX, hex),
+        encode_digest(&digest)?,
+    ))
+}
+
+/// Generate a random salt for a salted attribute encoding
+pub fn generate_attribute_salt() -> String {
+    let salt: [u8; 16] = rand::random();
+    indy_utils::base58::encode(salt)
+}
+
+/// Compute the encoding of a binary attribute from the hash in its raw value,
+/// returning `None` for raw values which are not binary attribute hashes
+pub(crate) fn binary_attribute_encoding(raw_value: &str) -> Result<Option<String>> {
+    raw_value
+        .strip_prefix(BINARY_ATTRIBUTE_PREFIX)
+        .and_then(decode_hex)
+        .map(|digest| encode_digest(&digest))
+        .transpose()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let pairs = hex.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+fn encode_digest(digest: &[u8]) -> Result<String> {
+    #[cfg(target_endian = "big")]
+    let digest = &{
+        let mut d = digest.to_vec();
+        d.reverse();
+        d
+    };
+    Ok(BigNumber::from_bytes(digest)?.to_dec()?)
+}
+
+/// The maximum size in bits of an encoded decimal attribute value
+pub const MAX_DECIMAL_ATTRIBUTE_BITS: i32 = 256;
+
+/// Encode a fixed-point decimal or arbitrarily large integer attribute value as
+/// the integer `raw_value * 10^scale`.
+///
+/// Values may not have more fractional digits than the scale, so the encoding is
+/// never lossy. Predicates are only supported for encoded values within 32-bit
+/// range, and must use a predicate value encoded with the same scale, see
+/// `encode_decimal_predicate_value`.
+pub fn encode_decimal_attribute(raw_value: &str, scale: u8) -> Result<String> {
+    let value = scale_decimal(raw_value, scale)?;
+    if value.num_bits()? > MAX_DECIMAL_ATTRIBUTE_BITS {
+        return Err(err_msg!(
+            "Decimal attribute value exceeds {} bits: {}",
+            MAX_DECIMAL_ATTRIBUTE_BITS,
+            raw_value
+        ));
+    }
+    Ok(value.to_dec()?)
+}
+
+/// Encode a predicate value for comparison with a decimal attribute encoded
+/// with the same scale
+pub fn encode_decimal_predicate_value(value: &str, scale: u8) -> Result<i32> {
+    scale_decimal(value, scale)?
+        .to_dec()?
+        .parse::<i32>()
+        .map_err(|_| err_msg!("Decimal predicate value out of range: {}", value))
+}
+
+fn scale_decimal(value: &str, scale: u8) -> Result<BigNumber> {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((int_part, frac_part)) if !frac_part.is_empty() => (int_part, frac_part),
+        Some(_) => return Err(err_msg!("Invalid decimal value: {}", value)),
+        None => (unsigned, ""),
+    };
+    if int_part.is_empty()
+        || !int_part.bytes().all(|c| c.is_ascii_digit())
+        || !frac_part.bytes().all(|c| c.is_ascii_digit())
+    {
+        return Err(err_msg!("Invalid decimal value: {}", value));
+    }
+    if frac_part.len() > scale as usize {
+        return Err(err_msg!(
+            "Decimal value has more than {} fractional digits: {}",
+            scale,
+            value
+        ));
+    }
+    let digits = format!(
+        "{}{}{}{}",
+        sign,
+        int_part,
+        frac_part,
+        "0".repeat(scale as usize - frac_part.len())
+    );
+    Ok(BigNumber::from_dec(&digits)?)
+}
+
+/// Encode a calendar date as a `YYYYMMDD` integer, suitable for comparison in
+/// predicates, such as with a credential expiry date attribute
+pub fn encode_expiry_date(year: u32, month: u32, day: u32) -> Result<i32> {
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return Err(err_msg!("Invalid month: {}", month)),
+    };
+    if !(1..=9999).contains(&year) {
+        return Err(err_msg!("Invalid year: {}", year));
+    }
+    if !(1..=days_in_month).contains(&day) {
+        return Err(err_msg!("Invalid day: {}", day));
+    }
+    Ok((year * 10000 + month * 100 + day) as i32)
+}
+
+/// Encode the UTC date for a Unix timestamp as a `YYYYMMDD` integer
+pub fn encode_expiry_date_for_timestamp(timestamp: u64) -> Result<i32> {
+    // civil date from days since the epoch, per Howard Hinnant's algorithm
+    let days = (timestamp / 86400) as i64 + 719_468;
+    let era = days / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    encode_expiry_date(year as u32, month as u32, day as u32)
+}
+
+/// Encode the current UTC date as a `YYYYMMDD` integer
+pub fn current_expiry_date() -> Result<i32> {
+    encode_expiry_date_for_timestamp(current_timestamp())
+}
+
 pub fn build_sub_proof_request(
     attrs_for_credential: &[AttributeInfo],
     predicates_for_credential: &[PredicateInfo],
@@ -119,6 +585,7 @@ pub fn build_sub_proof_request(
     }
 
     for predicate in predicates_for_credential {
+        check_predicate_supported(predicate)?;
         let p_type = format!("{}", predicate.p_type);
 
         sub_proof_request_builder.add_predicate(
@@ -135,6 +602,35 @@ pub fn build_sub_proof_request(
     Ok(res)
 }
 
+/// Check that a predicate can be expressed as a range proof. Predicates are proven
+/// over signed 32-bit integers, so strict comparisons against the limits of that
+/// range are not supported.
+pub fn check_predicate_supported(predicate: &PredicateInfo) -> Result<()> {
+    match predicate.p_type {
+        PredicateTypes::GT if predicate.p_value == i32::MAX => Err(err_msg!(
+            "Predicate value for attribute {:?} is outside the range supported by predicates",
+            predicate.name
+        )),
+        PredicateTypes::LT if predicate.p_value == i32::MIN => Err(err_msg!(
+            "Predicate value for attribute {:?} is outside the range supported by predicates",
+            predicate.name
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Calculate the difference between an attribute value and a predicate value which
+/// is proven to be non-negative by the range proof
+pub fn predicate_delta(p_type: &PredicateTypes, p_value: i32, attr_value: i32) -> i64 {
+    let (p_value, attr_value) = (p_value as i64, attr_value as i64);
+    match p_type {
+        PredicateTypes::GE => attr_value - p_value,
+        PredicateTypes::GT => attr_value - p_value - 1,
+        PredicateTypes::LE => p_value - attr_value,
+        PredicateTypes::LT => p_value - attr_value - 1,
+    }
+}
+
 pub fn get_non_revoc_interval(
     global_interval: &Option<NonRevocedInterval>,
     local_interval: &Option<NonRevocedInterval>,
@@ -158,10 +654,79 @@ pub fn new_nonce() -> Result<Nonce> {
     Nonce::new().map_err(err_map!(Unexpected))
 }
 
+/// Serializes tests which replace the global configuration, restoring the
+/// previous configuration when dropped
+#[cfg(test)]
+pub(crate) struct ConfigGuard {
+    previous: CredxConfig,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl ConfigGuard {
+    pub(crate) fn set(config: CredxConfig) -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let lock = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let previous = get_config();
+        set_config(config).unwrap();
+        Self {
+            previous,
+            _lock: lock,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        set_config(self.previous.clone()).ok();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn public_key_cache_works() {
+        use crate::services::issuer::{create_credential_definition, create_schema};
+        use crate::services::types::{
+            CredentialDefinition, CredentialDefinitionConfig, SignatureType,
+        };
+        use indy_utils::did::DidValue;
+
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            vec!["name".to_owned()].into(),
+            None,
+        )
+        .unwrap();
+        let (cred_def, _, _) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "cache",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(false),
+        )
+        .unwrap();
+        let CredentialDefinition::CredentialDefinitionV1(cred_def) = cred_def;
+
+        let mut cache = PublicKeyCache::default();
+        let key = Arc::new(cred_def.get_public_key().unwrap());
+        cache.insert(cred_def.id.clone(), vec![1], key.clone(), 1);
+        assert!(Arc::ptr_eq(&cache.get(&cred_def.id, &[1]).unwrap(), &key));
+        assert!(cache.get(&cred_def.id, &[2]).is_none());
+        cache.insert(CredentialDefinitionId("other".to_string()), vec![1], key, 1);
+        assert!(cache.get(&cred_def.id, &[1]).is_none());
+        assert_eq!(cache.entries.len(), 1);
+
+        let key = credential_public_key(&cred_def).unwrap();
+        assert_eq!(*key, cred_def.get_public_key().unwrap());
+    }
+
     fn _interval() -> NonRevocedInterval {
         NonRevocedInterval {
             from: None,
@@ -186,4 +751,186 @@ mod tests {
         let res = get_non_revoc_interval(&None, &None);
         assert_eq!(None, res);
     }
+
+    #[test]
+    fn attribute_normalization_works() {
+        let attr = "First Name";
+        assert_eq!(AttributeNormalization::Default.normalize(attr), "firstname");
+        assert_eq!(
+            AttributeNormalization::Lowercase.normalize(attr),
+            "first name"
+        );
+        assert_eq!(
+            AttributeNormalization::StripSpaces.normalize(attr),
+            "FirstName"
+        );
+        assert_eq!(AttributeNormalization::Exact.normalize(attr), "First Name");
+        assert_eq!(
+            "strip_spaces".parse::<AttributeNormalization>().unwrap(),
+            AttributeNormalization::StripSpaces
+        );
+        assert!("other".parse::<AttributeNormalization>().is_err());
+    }
+
+    #[test]
+    fn config_deserialize_with_defaults() {
+        let config: CredxConfig =
+            serde_json::from_str(r#"{"strict_parsing": true, "non_revoked_tolerance": 60}"#)
+                .unwrap();
+        assert!(config.strict_parsing);
+        assert_eq!(config.non_revoked_tolerance, 60);
+        assert_eq!(
+            config.attribute_normalization,
+            AttributeNormalization::Default
+        );
+        assert_eq!(config.validation, ValidationConfig::default());
+
+        let invalid = CredxConfig {
+            validation: ValidationConfig {
+                max_schema_attributes: 0,
+                ..ValidationConfig::default()
+            },
+            ..CredxConfig::default()
+        };
+        set_config(invalid).unwrap_err();
+    }
+
+    #[test]
+    fn config_recovers_from_poisoned_lock() {
+        let _config = ConfigGuard::set(CredxConfig::default());
+        std::thread::spawn(|| {
+            let _lock = CONFIG.write().unwrap();
+            panic!("poison the configuration lock");
+        })
+        .join()
+        .unwrap_err();
+        assert!(CONFIG.is_poisoned());
+
+        set_config(CredxConfig {
+            strict_parsing: true,
+            ..CredxConfig::default()
+        })
+        .unwrap();
+        assert!(get_config().strict_parsing);
+    }
+
+    #[test]
+    fn validation_config_deserialize_with_defaults() {
+        let config: CredxConfig =
+            serde_json::from_str(r#"{"validation": {"max_predicates": 4}}"#).unwrap();
+        assert_eq!(config.validation.max_predicates, 4);
+        assert_eq!(
+            config.validation.max_restriction_depth,
+            ValidationConfig::default().max_restriction_depth
+        );
+
+        set_config(CredxConfig {
+            validation: ValidationConfig {
+                max_restriction_depth: 0,
+                ..ValidationConfig::default()
+            },
+            ..CredxConfig::default()
+        })
+        .unwrap_err();
+        assert_eq!(get_validation_config(), ValidationConfig::default());
+    }
+
+    #[test]
+    fn test_clock_controls_current_time() {
+        use crate::services::types::TestClock;
+
+        // a time far ahead, so that concurrent tests are unaffected
+        let clock = Arc::new(TestClock::new(4102444800));
+        set_clock(clock.clone());
+        assert_eq!(current_timestamp(), 4102444800);
+        assert_eq!(current_expiry_date().unwrap(), 21000101);
+        clock.advance(86400);
+        assert_eq!(current_expiry_date().unwrap(), 21000102);
+
+        let skew = get_clock_skew();
+        assert!(is_timestamp_current(4102531200 + skew));
+        assert!(!is_timestamp_current(4102531201 + skew));
+        let interval = effective_interval(&NonRevocedInterval {
+            from: Some(100),
+            to: Some(u64::MAX),
+        });
+        assert_eq!(interval.to, Some(4102531200 + skew));
+        set_clock(Arc::new(SystemClock));
+    }
+
+    #[test]
+    fn encode_expiry_date_works() {
+        assert_eq!(encode_expiry_date(2030, 12, 31).unwrap(), 20301231);
+        assert_eq!(encode_expiry_date(2024, 2, 29).unwrap(), 20240229);
+        assert!(encode_expiry_date(2023, 2, 29).is_err());
+        assert!(encode_expiry_date(2023, 13, 1).is_err());
+        assert!(encode_expiry_date(2023, 4, 31).is_err());
+        assert_eq!(encode_expiry_date_for_timestamp(0).unwrap(), 19700101);
+        assert_eq!(
+            encode_expiry_date_for_timestamp(1709251199).unwrap(),
+            20240229
+        );
+        assert_eq!(
+            encode_expiry_date_for_timestamp(1709251200).unwrap(),
+            20240301
+        );
+    }
+
+    #[test]
+    fn encode_binary_attribute_works() {
+        let (raw, encoded) = encode_binary_attribute(b"content").unwrap();
+        assert_eq!(
+            raw,
+            "sha256:ed7002b439e9ac845f22357d822bac1444730fbdb6016d3ec9432297b9ec9f73"
+        );
+        assert_eq!(encoded, encode_digest(&SHA256::digest(b"content")).unwrap());
+        assert_ne!(encode_binary_attribute(b"other").unwrap().1, encoded);
+    }
+
+    #[test]
+    fn binary_attribute_encoding_works() {
+        let (raw, encoded) = encode_binary_attribute(b"content").unwrap();
+        assert_eq!(binary_attribute_encoding(&raw).unwrap(), Some(encoded));
+        assert_eq!(binary_attribute_encoding("Alex").unwrap(), None);
+        assert_eq!(binary_attribute_encoding("sha256:xyz").unwrap(), None);
+    }
+
+    #[test]
+    fn encode_credential_attribute_salted_works() {
+        let salt = generate_attribute_salt();
+        assert_ne!(salt, generate_attribute_salt());
+        let encoded = encode_credential_attribute_salted("42", &salt).unwrap();
+        assert_ne!(encoded, "42");
+        assert_eq!(
+            encoded,
+            encode_credential_attribute_salted("42", &salt).unwrap()
+        );
+        assert_ne!(
+            encoded,
+            encode_credential_attribute_salted("42", &generate_attribute_salt()).unwrap()
+        );
+    }
+
+    #[test]
+    fn encode_decimal_attribute_works() {
+        assert_eq!(encode_decimal_attribute("12.5", 2).unwrap(), "1250");
+        assert_eq!(encode_decimal_attribute("-0.01", 2).unwrap(), "-1");
+        assert_eq!(encode_decimal_attribute("7", 0).unwrap(), "7");
+        assert_eq!(
+            encode_decimal_attribute("340282366920938463463374607431768211456", 0).unwrap(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            encode_decimal_attribute("1.000000000000000001", 18).unwrap(),
+            "1000000000000000001"
+        );
+        assert!(encode_decimal_attribute("1.234", 2).is_err());
+        assert!(encode_decimal_attribute("1.", 2).is_err());
+        assert!(encode_decimal_attribute(".5", 2).is_err());
+        assert!(encode_decimal_attribute("1e5", 2).is_err());
+        assert!(encode_decimal_attribute(&"9".repeat(80), 0).is_err());
+
+        assert_eq!(encode_decimal_predicate_value("12.5", 2).unwrap(), 1250);
+        assert!(encode_decimal_predicate_value("30000000", 2).is_err());
+    }
 }
@@ -1,12 +1,12 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::iter::FromIterator;
 
 use super::types::*;
 use crate::error::Result;
 use crate::services::helpers::*;
 use crate::ursa::cl::{
-    issuer::Issuer as CryptoIssuer, RevocationRegistryDelta as CryptoRevocationRegistryDelta,
-    Witness,
+    issuer::Issuer as CryptoIssuer, prover::Prover as CryptoProver,
+    RevocationRegistryDelta as CryptoRevocationRegistryDelta, Witness,
 };
 use indy_data_types::anoncreds::{
     cred_def::{CredentialDefinitionData, CredentialDefinitionV1},
@@ -20,7 +20,7 @@ use indy_data_types::anoncreds::{
 };
 use indy_utils::{Qualifiable, Validatable};
 
-use super::tails::{TailsFileReader, TailsReader, TailsWriter};
+use super::tails::{TailsProgress, TailsReader, TailsWriter};
 
 pub fn create_schema(
     origin_did: &DidValue,
@@ -33,6 +33,7 @@ pub fn create_schema(
         origin_did, schema_name, schema_version, attr_names);
 
     origin_did.validate()?;
+    check_schema_attribute_names(&attr_names.0)?;
     let schema_id = SchemaId::new(&origin_did, schema_name, schema_version);
     let schema = SchemaV1 {
         id: schema_id,
@@ -44,6 +45,136 @@ pub fn create_schema(
     Ok(Schema::SchemaV1(schema))
 }
 
+/// Collects the attributes of a new schema, rejecting invalid attribute names
+/// as they are added.
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    origin_did: DidValue,
+    name: String,
+    version: String,
+    attr_names: Vec<String>,
+    seq_no: Option<u32>,
+}
+
+impl SchemaBuilder {
+    pub fn new(origin_did: &DidValue, name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            origin_did: origin_did.clone(),
+            name: name.into(),
+            version: version.into(),
+            attr_names: Vec::new(),
+            seq_no: None,
+        }
+    }
+
+    /// Add an attribute, which must be valid and distinct from the existing
+    /// attributes once normalized
+    pub fn add_attribute(&mut self, name: impl Into<String>) -> Result<&mut Self> {
+        let name = name.into();
+        check_attribute_name(&name)?;
+        let normalized = attr_common_view(&name);
+        if let Some(existing) = self
+            .attr_names
+            .iter()
+            .find(|existing| attr_common_view(existing) == normalized)
+        {
+            return Err(err_msg!(
+                "Attribute names \"{}\" and \"{}\" are equivalent after normalization",
+                existing,
+                name
+            ));
+        }
+        self.attr_names.push(name);
+        Ok(self)
+    }
+
+    pub fn add_attributes<I, S>(&mut self, names: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for name in names {
+            self.add_attribute(name)?;
+        }
+        Ok(self)
+    }
+
+    pub fn seq_no(&mut self, seq_no: u32) -> &mut Self {
+        self.seq_no.replace(seq_no);
+        self
+    }
+
+    pub fn build(&self) -> Result<Schema> {
+        create_schema(
+            &self.origin_did,
+            &self.name,
+            &self.version,
+            AttributeNames::from(self.attr_names.clone()),
+            self.seq_no,
+        )
+    }
+}
+
+/// Derive a schema from a sample JSON record, along with the proposed type of each attribute.
+///
+/// The sample must be a flat JSON object. Attribute names which are equivalent once
+/// normalized (such as `First Name` and `firstname`) are rejected.
+pub fn infer_schema(
+    origin_did: &DidValue,
+    schema_name: &str,
+    schema_version: &str,
+    sample_json: &str,
+) -> Result<(Schema, HashMap<String, AttributeType>)> {
+    trace!(
+        "infer_schema >>> origin_did: {:?}, schema_name: {:?}, schema_version: {:?}, sample_json: {:?}",
+        origin_did,
+        schema_name,
+        schema_version,
+        secret!(sample_json)
+    );
+
+    let sample: serde_json::Map<String, serde_json::Value> = serde_json::from_str(sample_json)
+        .map_err(err_map!("Sample credential data must be a JSON object"))?;
+
+    let mut normalized = HashMap::with_capacity(sample.len());
+    let mut attr_types = HashMap::with_capacity(sample.len());
+    for (name, value) in sample {
+        if let Some(existing) = normalized.insert(attr_common_view(&name), name.clone()) {
+            return Err(err_msg!(
+                "Attribute names \"{}\" and \"{}\" are equivalent after normalization",
+                existing,
+                name
+            ));
+        }
+        let attr_type = match value {
+            serde_json::Value::Null | serde_json::Value::String(_) => AttributeType::String,
+            serde_json::Value::Bool(_) => AttributeType::Boolean,
+            serde_json::Value::Number(n) => {
+                if n.as_i64().map(|n| n as i32 as i64 == n).unwrap_or(false) {
+                    AttributeType::Integer
+                } else {
+                    AttributeType::Number
+                }
+            }
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                return Err(err_msg!("Attribute \"{}\" must have a scalar value", name));
+            }
+        };
+        attr_types.insert(name, attr_type);
+    }
+
+    let attr_names = AttributeNames::from(attr_types.keys().cloned().collect::<HashSet<_>>());
+    let schema = create_schema(origin_did, schema_name, schema_version, attr_names, None)?;
+
+    trace!(
+        "infer_schema <<< schema: {:?}, attr_types: {:?}",
+        schema,
+        attr_types
+    );
+
+    Ok((schema, attr_types))
+}
+
 pub fn make_credential_definition_id(
     origin_did: &DidValue,
     schema_id: &SchemaId,
@@ -81,16 +212,60 @@ pub fn create_credential_definition(
     CredentialDefinition,
     CredentialDefinitionPrivate,
     CredentialKeyCorrectnessProof,
+)> {
+    create_credential_definition_impl(origin_did, schema, tag, signature_type, config, None)
+}
+
+/// Create a credential definition which signs only the listed schema attributes.
+///
+/// The attributes are signed as a set, so the order in which they are listed is
+/// not significant and is not preserved. Listing an attribute which is not defined
+/// by the schema, or listing one more than once, is an error.
+pub fn create_credential_definition_with_attributes(
+    origin_did: &DidValue,
+    schema: &Schema,
+    tag: &str,
+    signature_type: SignatureType,
+    config: CredentialDefinitionConfig,
+    attr_names: &[String],
+) -> Result<(
+    CredentialDefinition,
+    CredentialDefinitionPrivate,
+    CredentialKeyCorrectnessProof,
+)> {
+    create_credential_definition_impl(
+        origin_did,
+        schema,
+        tag,
+        signature_type,
+        config,
+        Some(attr_names),
+    )
+}
+
+fn create_credential_definition_impl(
+    origin_did: &DidValue,
+    schema: &Schema,
+    tag: &str,
+    signature_type: SignatureType,
+    config: CredentialDefinitionConfig,
+    attr_names: Option<&[String]>,
+) -> Result<(
+    CredentialDefinition,
+    CredentialDefinitionPrivate,
+    CredentialKeyCorrectnessProof,
 )> {
     trace!(
-        "create_credential_definition >>> schema: {:?}, config: {:?}",
+        "create_credential_definition >>> schema: {:?}, config: {:?}, attr_names: {:?}",
         schema,
-        config
+        config,
+        attr_names
     );
 
     let schema = match schema {
         Schema::SchemaV1(s) => s,
     };
+    check_schema_attributes_count(schema.attr_names.0.len())?;
     let cred_def_id =
         make_credential_definition_id(origin_did, &schema.id, schema.seq_no, tag, signature_type)?;
 
@@ -103,7 +278,12 @@ pub fn create_credential_definition(
             .unwrap_or(schema.id.0.clone()),
     );
 
-    let credential_schema = build_credential_schema(&schema.attr_names.0)?;
+    let credential_schema = match attr_names {
+        Some(attr_names) => {
+            build_credential_schema(&select_schema_attributes(&schema.attr_names, attr_names)?)?
+        }
+        None => build_credential_schema(&schema.attr_names.0)?,
+    };
     let non_credential_schema = build_non_credential_schema()?;
 
     let (credential_public_key, credential_private_key, correctness_proof) =
@@ -118,10 +298,10 @@ pub fn create_credential_definition(
         schema_id,
         signature_type,
         tag: tag.to_owned(),
-        value: CredentialDefinitionData {
-            primary: credential_public_key.get_primary_key()?.try_clone()?,
-            revocation: credential_public_key.get_revocation_key()?.clone(),
-        },
+        value: CredentialDefinitionData::new(
+            credential_public_key.get_primary_key()?.try_clone()?,
+            credential_public_key.get_revocation_key()?.clone(),
+        ),
     });
 
     let cred_def_private = CredentialDefinitionPrivate {
@@ -140,6 +320,81 @@ pub fn create_credential_definition(
     Ok((cred_def, cred_def_private, cred_key_proof))
 }
 
+/// Check that the attributes selected for a credential definition are distinct
+/// attributes of the schema
+fn select_schema_attributes(
+    schema_attrs: &AttributeNames,
+    attributes: &[String],
+) -> Result<HashSet<String>> {
+    if attributes.is_empty() {
+        return Err(err_msg!(
+            "Empty list of credential definition attributes has been passed"
+        ));
+    }
+    let mut selected = HashSet::with_capacity(attributes.len());
+    for attr in attributes {
+        let normalized = attr_common_view(attr);
+        let schema_attr = schema_attrs
+            .0
+            .iter()
+            .find(|name| attr_common_view(name) == normalized)
+            .ok_or_else(|| err_msg!("Attribute \"{}\" is not defined by the schema", attr))?;
+        if !selected.insert(schema_attr.clone()) {
+            return Err(err_msg!(
+                "Attribute \"{}\" is listed more than once for the credential definition",
+                attr
+            ));
+        }
+    }
+    Ok(selected)
+}
+
+/// Verify the key correctness proof of a credential definition, such as when both
+/// are received out-of-band, before creating a credential request.
+///
+/// Fails with a `ProofRejected` error when the proof does not match the public key.
+pub fn verify_key_correctness_proof(
+    cred_def: &CredentialDefinition,
+    key_correctness_proof: &CredentialKeyCorrectnessProof,
+) -> Result<()> {
+    trace!(
+        "verify_key_correctness_proof >>> cred_def: {:?}, key_correctness_proof: {:?}",
+        cred_def,
+        key_correctness_proof
+    );
+
+    let CredentialDefinition::CredentialDefinitionV1(cred_def) = cred_def;
+    let credential_pub_key = credential_public_key(cred_def).map_err(err_map!(
+        Input,
+        "Invalid public key for credential definition {}",
+        cred_def.id
+    ))?;
+
+    // the proof is checked before blinding, so blind a throwaway master secret
+    let master_secret = CryptoProver::new_master_secret()?;
+    let mut credential_values_builder = CryptoIssuer::new_credential_values_builder()?;
+    credential_values_builder.add_value_hidden("master_secret", &master_secret.value()?)?;
+    let cred_values = credential_values_builder.finalize()?;
+    CryptoProver::blind_credential_secrets(
+        &credential_pub_key,
+        &key_correctness_proof.value,
+        &cred_values,
+        new_nonce()?.as_native(),
+    )
+    .map_err(|err| {
+        err_msg!(
+            ProofRejected,
+            "Invalid key correctness proof for credential definition {}: {}",
+            cred_def.id,
+            err
+        )
+    })?;
+
+    trace!("verify_key_correctness_proof <<<");
+
+    Ok(())
+}
+
 pub fn make_revocation_registry_id(
     origin_did: &DidValue,
     cred_def: &CredentialDefinition,
@@ -182,6 +437,41 @@ pub fn create_revocation_registry<TW>(
     RevocationRegistry,
     RevocationRegistryDelta,
 )>
+where
+    TW: TailsWriter,
+{
+    create_revocation_registry_with_progress(
+        origin_did,
+        cred_def,
+        tag,
+        rev_reg_type,
+        issuance_type,
+        max_cred_num,
+        tails_writer,
+        &mut |_, _| true,
+    )
+}
+
+/// Create a revocation registry, reporting the progress of tails generation.
+///
+/// `progress` receives the number of tails generated and the total number of
+/// tails, and may return `false` to cancel the operation.
+#[allow(clippy::too_many_arguments)]
+pub fn create_revocation_registry_with_progress<TW>(
+    origin_did: &DidValue,
+    cred_def: &CredentialDefinition,
+    tag: &str,
+    rev_reg_type: RegistryType,
+    issuance_type: IssuanceType,
+    max_cred_num: u32,
+    tails_writer: &mut TW,
+    progress: TailsProgress<'_>,
+) -> Result<(
+    RevocationRegistryDefinition,
+    RevocationRegistryDefinitionPrivate,
+    RevocationRegistry,
+    RevocationRegistryDelta,
+)>
 where
     TW: TailsWriter,
 {
@@ -193,21 +483,29 @@ where
     let cred_def = match cred_def {
         CredentialDefinition::CredentialDefinitionV1(c) => c,
     };
-    let credential_pub_key = cred_def.get_public_key().map_err(err_map!(
+    let credential_pub_key = credential_public_key(cred_def).map_err(err_map!(
         Unexpected,
         "Error fetching public key from credential definition"
     ))?;
 
     // NOTE: registry is created with issuance_by_default: false, then updated later
-    // this avoids generating the tails twice and is significantly faster
+    // this avoids generating the tails twice and is significantly faster, but is
+    // only possible when the written tails can be read back
+    let update_by_default =
+        issuance_type == IssuanceType::ISSUANCE_BY_DEFAULT && tails_writer.is_readable();
     let (revoc_key_pub, revoc_key_priv, revoc_registry, mut rev_tails_generator) =
-        CryptoIssuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, false)?;
+        CryptoIssuer::new_revocation_registry_def(
+            &credential_pub_key,
+            max_cred_num,
+            issuance_type == IssuanceType::ISSUANCE_BY_DEFAULT && !update_by_default,
+        )?;
 
     let rev_keys_pub = RevocationRegistryDefinitionValuePublicKeys {
         accum_key: revoc_key_pub,
     };
 
-    let (tails_location, tails_hash) = tails_writer.write(&mut rev_tails_generator)?;
+    let (tails_location, tails_hash) =
+        tails_writer.write_with_progress(&mut rev_tails_generator, progress)?;
 
     let revoc_reg_def_value = RevocationRegistryDefinitionValue {
         max_cred_num,
@@ -232,8 +530,10 @@ where
     });
 
     // now update registry to reflect issuance-by-default
-    let (revoc_reg, revoc_init_delta) = if issuance_type == IssuanceType::ISSUANCE_BY_DEFAULT {
-        let tails_reader = TailsFileReader::new(&tails_location);
+    let (revoc_reg, revoc_init_delta) = if update_by_default {
+        let tails_reader = tails_writer
+            .reader(&tails_location)
+            .ok_or_else(|| err_msg!(Unexpected, "Tails writer does not support reading"))?;
         let issued = BTreeSet::from_iter((1..=max_cred_num).into_iter());
         update_revocation_registry(
             &revoc_reg_def,
@@ -258,9 +558,161 @@ where
         revoc_reg
     );
 
+    record_issuer_event(&cred_def.id, IssuerEvent::RegistryCreated);
+
     Ok((revoc_reg_def, revoc_def_priv, revoc_reg, revoc_init_delta))
 }
 
+/// Create a batch of revocation registries sharing the same configuration.
+///
+/// Each registry is tagged with `tag` followed by its (1-based) position in the
+/// batch, e.g. `tag-1`, `tag-2`, and its tails file is written by `tails_writer`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_revocation_registries<TW>(
+    origin_did: &DidValue,
+    cred_def: &CredentialDefinition,
+    tag: &str,
+    rev_reg_type: RegistryType,
+    issuance_type: IssuanceType,
+    count: u32,
+    max_cred_num: u32,
+    tails_writer: &mut TW,
+) -> Result<
+    Vec<(
+        RevocationRegistryDefinition,
+        RevocationRegistryDefinitionPrivate,
+        RevocationRegistry,
+        RevocationRegistryDelta,
+    )>,
+>
+where
+    TW: TailsWriter,
+{
+    trace!("create_revocation_registries >>> origin_did: {:?}, cred_def: {:?}, tag: {:?}, count: {:?}, max_cred_num: {:?}, rev_reg_type: {:?}, issuance_type: {:?}",
+            origin_did, cred_def, tag, count, max_cred_num, rev_reg_type, issuance_type);
+
+    if count == 0 {
+        return Err(err_msg!(
+            "Revocation registry count must be greater than zero"
+        ));
+    }
+
+    let registries = (1..=count)
+        .map(|idx| {
+            create_revocation_registry(
+                origin_did,
+                cred_def,
+                &format!("{}-{}", tag, idx),
+                rev_reg_type,
+                issuance_type,
+                max_cred_num,
+                tails_writer,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    trace!(
+        "create_revocation_registries <<< rev_reg_ids: {:?}",
+        registries
+            .iter()
+            .map(|(def, ..)| match def {
+                RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => &v1.id,
+            })
+            .collect::<Vec<_>>()
+    );
+
+    Ok(registries)
+}
+
+/// Derive the tag of a successor revocation registry.
+///
+/// A numeric tag or a trailing `-N` suffix (as produced by
+/// `create_revocation_registries`) is incremented, otherwise the original
+/// registry is treated as the first and `-2` is appended.
+pub fn next_revocation_registry_tag(tag: &str) -> String {
+    if let Ok(idx) = tag.parse::<u32>() {
+        return (idx + 1).to_string();
+    }
+    if let Some((prefix, suffix)) = tag.rsplit_once('-') {
+        if let Ok(idx) = suffix.parse::<u32>() {
+            return format!("{}-{}", prefix, idx + 1);
+        }
+    }
+    format!("{}-2", tag)
+}
+
+/// Create a successor to an existing revocation registry, for use once its
+/// `max_cred_num` has been exhausted.
+///
+/// The new registry shares the credential definition, registry type, issuance
+/// type and capacity of the original, is tagged according to
+/// `next_revocation_registry_tag`, and has its tails file written by
+/// `tails_writer`. A record linking the two registries is returned alongside
+/// the new registry objects.
+pub fn rotate_revocation_registry<TW>(
+    rev_reg_def: &RevocationRegistryDefinition,
+    cred_def: &CredentialDefinition,
+    tails_writer: &mut TW,
+) -> Result<(
+    RevocationRegistryDefinition,
+    RevocationRegistryDefinitionPrivate,
+    RevocationRegistry,
+    RevocationRegistryDelta,
+    RevocationRegistryRotation,
+)>
+where
+    TW: TailsWriter,
+{
+    trace!(
+        "rotate_revocation_registry >>> rev_reg_def: {:?}, cred_def: {:?}",
+        rev_reg_def,
+        cred_def
+    );
+
+    let rev_reg_def = match rev_reg_def {
+        RevocationRegistryDefinition::RevocationRegistryDefinitionV1(r) => r,
+    };
+    let cred_def_id = match cred_def {
+        CredentialDefinition::CredentialDefinitionV1(c) => &c.id,
+    };
+    if &rev_reg_def.cred_def_id != cred_def_id {
+        return Err(err_msg!(
+            "Revocation registry definition does not belong to the credential definition: {}",
+            cred_def_id
+        ));
+    }
+    let origin_did = rev_reg_def.id.issuer_did().ok_or_else(|| {
+        err_msg!(
+            "Cannot determine the issuer DID of revocation registry: {}",
+            rev_reg_def.id
+        )
+    })?;
+    let tag = next_revocation_registry_tag(&rev_reg_def.tag);
+
+    let (next_def, next_def_priv, next_reg, next_delta) = create_revocation_registry(
+        &origin_did,
+        cred_def,
+        &tag,
+        rev_reg_def.revoc_def_type,
+        rev_reg_def.value.issuance_type,
+        rev_reg_def.value.max_cred_num,
+        tails_writer,
+    )?;
+
+    let rotation = RevocationRegistryRotation {
+        cred_def_id: cred_def_id.clone(),
+        previous_rev_reg_id: rev_reg_def.id.clone(),
+        rev_reg_id: match &next_def {
+            RevocationRegistryDefinition::RevocationRegistryDefinitionV1(r) => r.id.clone(),
+        },
+        tag,
+    };
+
+    trace!("rotate_revocation_registry <<< rotation: {:?}", rotation);
+
+    Ok((next_def, next_def_priv, next_reg, next_delta, rotation))
+}
+
 pub fn update_revocation_registry(
     rev_reg_def: &RevocationRegistryDefinition,
     rev_reg: &RevocationRegistry,
@@ -275,6 +727,23 @@ pub fn update_revocation_registry(
         RevocationRegistry::RevocationRegistryV1(v1) => v1.value.clone(),
     };
     let max_cred_num = rev_reg_def.value.max_cred_num;
+    for idx in issued.iter().chain(revoked.iter()) {
+        if *idx == 0 || *idx > max_cred_num {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Revocation index {} is outside the registry range 1..={}",
+                idx,
+                max_cred_num
+            ));
+        }
+    }
+    if let Some(idx) = issued.intersection(&revoked).next() {
+        return Err(err_msg!(
+            "Revocation index {} cannot be both issued and revoked",
+            idx
+        ));
+    }
+    let revoked_count = revoked.len() as u64;
     let delta = CryptoIssuer::update_revocation_registry(
         &mut rev_reg,
         max_cred_num,
@@ -282,6 +751,12 @@ pub fn update_revocation_registry(
         revoked,
         tails_reader,
     )?;
+    if revoked_count > 0 {
+        record_issuer_event(
+            &rev_reg_def.cred_def_id,
+            IssuerEvent::CredentialsRevoked(revoked_count),
+        );
+    }
     Ok((
         RevocationRegistry::RevocationRegistryV1(RevocationRegistryV1 { value: rev_reg }),
         RevocationRegistryDelta::RevocationRegistryDeltaV1(RevocationRegistryDeltaV1 {
@@ -290,14 +765,29 @@ pub fn update_revocation_registry(
     ))
 }
 
+/// Create a credential offer for a credential definition.
+///
+/// A nonce is generated unless one is supplied, for instance to bind the offer to a
+/// transport session. A supplied nonce must be a decimal value of at most 80 bits.
 pub fn create_credential_offer(
     schema_id: &SchemaId,
     cred_def: &CredentialDefinition,
     correctness_proof: &CredentialKeyCorrectnessProof,
+    nonce: Option<Nonce>,
 ) -> Result<CredentialOffer> {
-    trace!("create_credential_offer >>> cred_def: {:?}", cred_def);
+    trace!(
+        "create_credential_offer >>> cred_def: {:?}, nonce: {:?}",
+        cred_def,
+        nonce
+    );
 
-    let nonce = Nonce::new().map_err(err_map!(Unexpected, "Error creating nonce"))?;
+    let nonce = match nonce {
+        Some(nonce) => {
+            validate_offer_nonce(&nonce)?;
+            nonce
+        }
+        None => Nonce::new().map_err(err_map!(Unexpected, "Error creating nonce"))?,
+    };
 
     let cred_def = match cred_def {
         CredentialDefinition::CredentialDefinitionV1(c) => c,
@@ -312,18 +802,117 @@ pub fn create_credential_offer(
         key_correctness_proof: key_correctness_proof.value,
         nonce,
         method_name: None,
+        session_id: None,
     };
 
     trace!("create_credential_offer <<< result: {:?}", credential_offer);
     Ok(credential_offer)
 }
 
+/// Encode the attributes for which only a raw value was supplied
+fn encode_missing_values(
+    cred_values: &mut CredentialValues,
+    encoder: &dyn AttributeEncoder,
+) -> Result<()> {
+    for (name, values) in cred_values.0.iter_mut() {
+        if values.encoded.is_empty() {
+            values.encoded = encoder.encode(name, &values.raw)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_offer_nonce(nonce: &Nonce) -> Result<()> {
+    let value = nonce
+        .as_ref()
+        .parse::<u128>()
+        .map_err(|_| err_msg!("Invalid nonce: value exceeds 80 bits"))?;
+    if value >> 80 != 0 {
+        return Err(err_msg!("Invalid nonce: value exceeds 80 bits"));
+    }
+    Ok(())
+}
+
+/// Create a credential offer for each of a batch of holder sessions.
+///
+/// Every offer receives a fresh nonce and carries its session identifier, so that
+/// the matching credential request can be checked with
+/// `validate_credential_request_session`.
+pub fn create_credential_offers(
+    schema_id: &SchemaId,
+    cred_def: &CredentialDefinition,
+    correctness_proof: &CredentialKeyCorrectnessProof,
+    session_ids: &[&str],
+) -> Result<Vec<CredentialOffer>> {
+    trace!(
+        "create_credential_offers >>> cred_def: {:?}, session_ids: {:?}",
+        cred_def,
+        session_ids
+    );
+
+    let mut seen = HashSet::with_capacity(session_ids.len());
+    for session_id in session_ids {
+        if session_id.is_empty() {
+            return Err(err_msg!("Session ID must not be empty"));
+        }
+        if !seen.insert(*session_id) {
+            return Err(err_msg!("Duplicate session ID: {}", session_id));
+        }
+    }
+
+    let offers = session_ids
+        .iter()
+        .map(|session_id| {
+            let mut offer = create_credential_offer(schema_id, cred_def, correctness_proof, None)?;
+            offer.session_id = Some((*session_id).to_owned());
+            Ok(offer)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    trace!("create_credential_offers <<< count: {}", offers.len());
+    Ok(offers)
+}
+
+/// Find the offer bound to the holder session a credential request arrived on,
+/// and check that the request was made for the same credential definition.
+///
+/// The request is also bound to the nonce of the returned offer, which is verified
+/// when the offer is passed to `create_credential`.
+pub fn validate_credential_request_session<'o>(
+    cred_offers: &[&'o CredentialOffer],
+    cred_request: &CredentialRequest,
+    session_id: &str,
+) -> Result<&'o CredentialOffer> {
+    trace!(
+        "validate_credential_request_session >>> cred_request: {:?}, session_id: {:?}",
+        cred_request,
+        session_id
+    );
+
+    let cred_offer = *cred_offers
+        .iter()
+        .find(|offer| offer.session_id.as_deref() == Some(session_id))
+        .ok_or_else(|| err_msg!("No credential offer found for session: {}", session_id))?;
+    if cred_offer.cred_def_id != cred_request.cred_def_id {
+        return Err(err_msg!(
+            "Credential request does not match the offer for session: {}",
+            session_id
+        ));
+    }
+
+    trace!(
+        "validate_credential_request_session <<< cred_offer.nonce: {:?}",
+        cred_offer.nonce
+    );
+    Ok(cred_offer)
+}
+
 pub fn create_credential(
     cred_def: &CredentialDefinition,
     cred_def_private: &CredentialDefinitionPrivate,
     cred_offer: &CredentialOffer,
     cred_request: &CredentialRequest,
-    cred_values: CredentialValues,
+    mut cred_values: CredentialValues,
     revocation_config: Option<CredentialRevocationConfig>,
 ) -> Result<(
     Credential,
@@ -337,12 +926,13 @@ pub fn create_credential(
 
     let cred_public_key = match cred_def {
         CredentialDefinition::CredentialDefinitionV1(cd) => {
-            cd.get_public_key().map_err(err_map!(
+            credential_public_key(cd).map_err(err_map!(
                 Unexpected,
                 "Error fetching public key from credential definition"
             ))?
         }
     };
+    encode_missing_values(&mut cred_values, &StandardEncoder)?;
     let credential_values = build_credential_values(&cred_values.0, None)?;
 
     let (
@@ -359,6 +949,12 @@ pub fn create_credential(
                     (&v1.value, v1.id.clone())
                 }
             };
+            check_registry_index(
+                revocation.registry_idx,
+                rev_reg_def.max_cred_num,
+                revocation.registry_used,
+                &rev_reg_def.issuance_type,
+            )?;
             let mut rev_reg = match revocation.registry {
                 RevocationRegistry::RevocationRegistryV1(v1) => v1.value.clone(),
             };
@@ -450,45 +1046,279 @@ pub fn create_credential(
         rev_reg_delta
     );
 
+    record_issuer_event(cred_def.id(), IssuerEvent::CredentialIssued);
+
     Ok((credential, rev_reg, rev_reg_delta))
 }
 
-pub fn revoke_credential(
-    rev_reg_def: &RevocationRegistryDefinition,
-    rev_reg: &RevocationRegistry,
-    cred_rev_idx: u32,
-    tails_reader: &TailsReader,
-) -> Result<(RevocationRegistry, RevocationRegistryDelta)> {
-    trace!(
-        "revoke >>> rev_reg_def: {:?}, rev_reg: {:?}, cred_rev_idx: {:?}",
-        rev_reg_def,
-        rev_reg,
-        secret!(&cred_rev_idx)
-    );
+/// Check that a revocation index can be assigned to a new credential.
+///
+/// An index beyond the capacity of the registry, or a registry with every
+/// index already issued, is reported as `RevocationRegistryFull` so that the
+/// issuer can rotate to a new registry.
+fn check_registry_index(
+    registry_idx: u32,
+    max_cred_num: u32,
+    registry_used: &HashSet<u32>,
+    issuance_type: &IssuanceType,
+) -> Result<()> {
+    if registry_idx == 0 {
+        return Err(err_msg!(
+            InvalidUserRevocId,
+            "Revocation registry index must be greater than zero"
+        ));
+    }
+    if registry_idx > max_cred_num {
+        return Err(err_msg!(
+            RevocationRegistryFull,
+            "Revocation registry index {} exceeds the registry capacity of {}",
+            registry_idx,
+            max_cred_num
+        ));
+    }
+    if *issuance_type == IssuanceType::ISSUANCE_ON_DEMAND
+        && registry_used.len() as u32 >= max_cred_num
+        && !registry_used.contains(&registry_idx)
+    {
+        return Err(err_msg!(
+            RevocationRegistryFull,
+            "Revocation registry is full: all {} indices have been issued",
+            max_cred_num
+        ));
+    }
+    Ok(())
+}
 
+/// Get the number of credentials which may still be issued against a
+/// revocation registry, according to the tracked registry usage
+pub fn registry_remaining_capacity(
+    rev_reg_def: &RevocationRegistryDefinition,
+    usage: &RevocationRegistryUsage,
+) -> Result<u32> {
+    if rev_reg_def.id() != &usage.rev_reg_id {
+        return Err(err_msg!(
+            "Revocation registry usage does not match the registry definition: {}",
+            usage.rev_reg_id
+        ));
+    }
     let max_cred_num = match rev_reg_def {
         RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => v1.value.max_cred_num,
     };
-    let mut rev_reg = match rev_reg {
-        RevocationRegistry::RevocationRegistryV1(v1) => v1.value.clone(),
-    };
-    let rev_reg_delta =
-        CryptoIssuer::revoke_credential(&mut rev_reg, max_cred_num, cred_rev_idx, tails_reader)?;
-
-    let new_rev_reg =
-        RevocationRegistry::RevocationRegistryV1(RevocationRegistryV1 { value: rev_reg });
-    let delta = RevocationRegistryDelta::RevocationRegistryDeltaV1(RevocationRegistryDeltaV1 {
-        value: rev_reg_delta,
-    });
-    trace!("revoke <<< rev_reg_delta {:?}", delta);
-
-    Ok((new_rev_reg, delta))
+    Ok(max_cred_num.saturating_sub(usage.used.len() as u32))
 }
 
-#[allow(dead_code)]
-pub fn recover_credential(
-    rev_reg_def: &RevocationRegistryDefinition,
-    rev_reg: &RevocationRegistry,
+/// Create a revocable credential, allocating its revocation index from the
+/// registry usage. The index is only recorded as used once the credential has
+/// been created, and is returned along with the credential.
+pub fn create_credential_with_allocation(
+    cred_def: &CredentialDefinition,
+    cred_def_private: &CredentialDefinitionPrivate,
+    cred_offer: &CredentialOffer,
+    cred_request: &CredentialRequest,
+    cred_values: CredentialValues,
+    revocation: RevocationAllocationConfig,
+    usage: &mut RevocationRegistryUsage,
+) -> Result<(
+    Credential,
+    u32,
+    RevocationRegistry,
+    Option<RevocationRegistryDelta>,
+)> {
+    trace!(
+        "create_credential_with_allocation >>> revocation: {:?}, usage: {:?}",
+        revocation,
+        usage
+    );
+
+    if revocation.reg_def.id() != &usage.rev_reg_id {
+        return Err(err_msg!(
+            "Revocation registry usage does not match the registry definition: {}",
+            usage.rev_reg_id
+        ));
+    }
+    let registry_idx = usage.select(revocation.allocation)?;
+    let (credential, rev_reg, rev_reg_delta) = create_credential(
+        cred_def,
+        cred_def_private,
+        cred_offer,
+        cred_request,
+        cred_values,
+        Some(CredentialRevocationConfig {
+            reg_def: revocation.reg_def,
+            reg_def_private: revocation.reg_def_private,
+            registry: revocation.registry,
+            registry_idx,
+            registry_used: &usage.used,
+            tails_reader: revocation.tails_reader,
+        }),
+    )?;
+    let rev_reg =
+        rev_reg.ok_or_else(|| err_msg!(Unexpected, "Missing updated revocation registry"))?;
+    usage.mark_used(registry_idx);
+
+    trace!(
+        "create_credential_with_allocation <<< registry_idx: {:?}",
+        secret!(registry_idx)
+    );
+    Ok((credential, registry_idx, rev_reg, rev_reg_delta))
+}
+
+/// Issue a batch of non-revocable credentials for the same credential definition.
+///
+/// The credential public key is parsed once and shared between the signatures,
+/// which are computed concurrently when the `parallel` feature is enabled.
+pub fn create_credentials(
+    cred_def: &CredentialDefinition,
+    cred_def_private: &CredentialDefinitionPrivate,
+    requests: Vec<(&CredentialOffer, &CredentialRequest, CredentialValues)>,
+) -> Result<Vec<Credential>> {
+    trace!(
+        "create_credentials >>> cred_def: {:?}, cred_def_private: {:?}, requests: {:?}",
+        cred_def,
+        secret!(&cred_def_private),
+        requests.len()
+    );
+
+    let cred_public_key = match cred_def {
+        CredentialDefinition::CredentialDefinitionV1(cd) => {
+            credential_public_key(cd).map_err(err_map!(
+                Unexpected,
+                "Error fetching public key from credential definition"
+            ))?
+        }
+    };
+    for (cred_offer, _, _) in requests.iter() {
+        if &cred_offer.cred_def_id != cred_def.id() {
+            return Err(err_msg!(
+                "Credential offer for `{}` does not match the credential definition",
+                cred_offer.cred_def_id
+            ));
+        }
+    }
+
+    let sign = |(cred_offer, cred_request, mut cred_values): (
+        &CredentialOffer,
+        &CredentialRequest,
+        CredentialValues,
+    )|
+     -> Result<Credential> {
+        encode_missing_values(&mut cred_values, &StandardEncoder)?;
+        let credential_values = build_credential_values(&cred_values.0, None)?;
+        let (signature, signature_correctness_proof) = CryptoIssuer::sign_credential(
+            &cred_request.prover_did.0,
+            &cred_request.blinded_ms,
+            &cred_request.blinded_ms_correctness_proof,
+            cred_offer.nonce.as_native(),
+            cred_request.nonce.as_native(),
+            &credential_values,
+            &cred_public_key,
+            &cred_def_private.value,
+        )?;
+        Ok(Credential {
+            schema_id: cred_offer.schema_id.clone(),
+            cred_def_id: cred_offer.cred_def_id.clone(),
+            rev_reg_id: None,
+            values: cred_values,
+            signature,
+            signature_correctness_proof,
+            rev_reg: None,
+            witness: None,
+        })
+    };
+
+    #[cfg(feature = "parallel")]
+    let credentials = {
+        use rayon::prelude::*;
+        requests
+            .into_par_iter()
+            .map(sign)
+            .collect::<Result<Vec<_>>>()?
+    };
+    #[cfg(not(feature = "parallel"))]
+    let credentials = requests.into_iter().map(sign).collect::<Result<Vec<_>>>()?;
+
+    for _ in credentials.iter() {
+        record_issuer_event(cred_def.id(), IssuerEvent::CredentialIssued);
+    }
+
+    trace!(
+        "create_credentials <<< credentials: {:?}",
+        secret!(&credentials)
+    );
+
+    Ok(credentials)
+}
+
+pub fn revoke_credential(
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg: &RevocationRegistry,
+    cred_rev_idx: u32,
+    tails_reader: &TailsReader,
+) -> Result<(RevocationRegistry, RevocationRegistryDelta)> {
+    trace!(
+        "revoke >>> rev_reg_def: {:?}, rev_reg: {:?}, cred_rev_idx: {:?}",
+        rev_reg_def,
+        rev_reg,
+        secret!(&cred_rev_idx)
+    );
+
+    let (max_cred_num, cred_def_id) = match rev_reg_def {
+        RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+            (v1.value.max_cred_num, &v1.cred_def_id)
+        }
+    };
+    let mut rev_reg = match rev_reg {
+        RevocationRegistry::RevocationRegistryV1(v1) => v1.value.clone(),
+    };
+    let rev_reg_delta =
+        CryptoIssuer::revoke_credential(&mut rev_reg, max_cred_num, cred_rev_idx, tails_reader)?;
+    record_issuer_event(cred_def_id, IssuerEvent::CredentialsRevoked(1));
+
+    let new_rev_reg =
+        RevocationRegistry::RevocationRegistryV1(RevocationRegistryV1 { value: rev_reg });
+    let delta = RevocationRegistryDelta::RevocationRegistryDeltaV1(RevocationRegistryDeltaV1 {
+        value: rev_reg_delta,
+    });
+    trace!("revoke <<< rev_reg_delta {:?}", delta);
+
+    Ok((new_rev_reg, delta))
+}
+
+/// Revoke a set of credentials in a single update of the revocation registry,
+/// producing one delta covering all of the revoked indexes
+pub fn revoke_credentials(
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg: &RevocationRegistry,
+    cred_rev_idxs: BTreeSet<u32>,
+    tails_reader: &TailsReader,
+) -> Result<(RevocationRegistry, RevocationRegistryDelta)> {
+    trace!(
+        "revoke_credentials >>> rev_reg_def: {:?}, rev_reg: {:?}, cred_rev_idxs: {:?}",
+        rev_reg_def,
+        rev_reg,
+        secret!(&cred_rev_idxs)
+    );
+
+    if cred_rev_idxs.is_empty() {
+        return Err(err_msg!("No credential revocation indexes provided"));
+    }
+    let (rev_reg, delta) = update_revocation_registry(
+        rev_reg_def,
+        rev_reg,
+        BTreeSet::new(),
+        cred_rev_idxs,
+        tails_reader,
+    )?;
+
+    trace!("revoke_credentials <<< rev_reg_delta {:?}", delta);
+
+    Ok((rev_reg, delta))
+}
+
+#[allow(dead_code)]
+pub fn recover_credential(
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg: &RevocationRegistry,
     cred_rev_idx: u32,
     tails_reader: &TailsReader,
 ) -> Result<(RevocationRegistry, RevocationRegistryDelta)> {
@@ -518,6 +1348,90 @@ pub fn recover_credential(
     Ok((new_rev_reg, delta))
 }
 
+/// Compute witnesses for credentials in a revocation registry on behalf of holders.
+///
+/// For an entry with no previous update, `rev_reg_delta` must cover the registry from
+/// its creation. Previous updates are advanced using `rev_reg_delta` as the change
+/// since their timestamp. Holders consume the result with
+/// `create_revocation_state_from_witness`, and the resulting proofs are verified as usual.
+pub fn create_or_update_revocation_witnesses(
+    tails_reader: TailsReader,
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg_delta: &RevocationRegistryDelta,
+    rev_reg_idxs: &[u32],
+    timestamp: u64,
+    prev_updates: &[&RevocationWitnessUpdate],
+) -> Result<Vec<RevocationWitnessUpdate>> {
+    trace!(
+        "create_or_update_revocation_witnesses >>> tails_reader: {:?}, rev_reg_def: {:?}, \
+rev_reg_delta: {:?}, rev_reg_idxs: {:?}, timestamp: {:?}",
+        tails_reader,
+        rev_reg_def,
+        rev_reg_delta,
+        rev_reg_idxs,
+        timestamp
+    );
+
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+    let RevocationRegistryDelta::RevocationRegistryDeltaV1(rev_reg_delta) = rev_reg_delta;
+
+    let mut updates = Vec::with_capacity(rev_reg_idxs.len());
+    for rev_reg_idx in rev_reg_idxs.iter().copied() {
+        if rev_reg_idx == 0 || rev_reg_idx > rev_reg_def.value.max_cred_num {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Invalid credential revocation index: {}",
+                rev_reg_idx
+            ));
+        }
+        let prev = prev_updates
+            .iter()
+            .find(|prev| prev.rev_reg_idx == rev_reg_idx);
+        let witness = match prev {
+            None => Witness::new(
+                rev_reg_idx,
+                rev_reg_def.value.max_cred_num,
+                rev_reg_def.value.issuance_type.to_bool(),
+                &rev_reg_delta.value,
+                &tails_reader,
+            )?,
+            Some(prev) => {
+                if prev.rev_reg_id != rev_reg_def.id {
+                    return Err(err_msg!(
+                        "Witness update does not match the revocation registry"
+                    ));
+                }
+                if prev.timestamp > timestamp {
+                    return Err(err_msg!(
+                        "Witness update for index {} is newer than the requested timestamp",
+                        rev_reg_idx
+                    ));
+                }
+                let mut witness = prev.witness.clone();
+                witness.update(
+                    rev_reg_idx,
+                    rev_reg_def.value.max_cred_num,
+                    &rev_reg_delta.value,
+                    &tails_reader,
+                )?;
+                witness
+            }
+        };
+        updates.push(RevocationWitnessUpdate {
+            rev_reg_id: rev_reg_def.id.clone(),
+            rev_reg_idx,
+            timestamp,
+            witness,
+        });
+    }
+
+    trace!(
+        "create_or_update_revocation_witnesses <<< count: {}",
+        updates.len()
+    );
+    Ok(updates)
+}
+
 pub fn merge_revocation_registry_deltas(
     rev_reg_delta: &RevocationRegistryDelta,
     other_delta: &RevocationRegistryDelta,
@@ -534,9 +1448,483 @@ pub fn merge_revocation_registry_deltas(
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevocationRegistryDeltaBatch {
+    rev_reg_id: RevocationRegistryId,
+    deltas: Vec<TimestampedRevocationRegistryDelta>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimestampedRevocationRegistryDelta {
+    timestamp: u64,
+    delta: RevocationRegistryDelta,
+}
+
+/// Bundle an ordered series of timestamped deltas for a revocation registry
+/// into a single gzip-compressed blob.
+pub fn export_revocation_registry_deltas(
+    rev_reg_id: &RevocationRegistryId,
+    deltas: &[(u64, RevocationRegistryDelta)],
+) -> Result<Vec<u8>> {
+    trace!(
+        "export_revocation_registry_deltas >>> rev_reg_id: {:?}, deltas: {:?}",
+        rev_reg_id,
+        deltas
+    );
+
+    check_revocation_registry_delta_chain(deltas)?;
+    let batch = RevocationRegistryDeltaBatch {
+        rev_reg_id: rev_reg_id.clone(),
+        deltas: deltas
+            .iter()
+            .map(|(timestamp, delta)| TimestampedRevocationRegistryDelta {
+                timestamp: *timestamp,
+                delta: delta.clone(),
+            })
+            .collect(),
+    };
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    serde_json::to_writer(&mut encoder, &batch)?;
+    let blob = encoder.finish()?;
+
+    trace!(
+        "export_revocation_registry_deltas <<< blob length: {:?}",
+        blob.len()
+    );
+
+    Ok(blob)
+}
+
+/// Unpack a blob produced by `export_revocation_registry_deltas`, checking that
+/// the timestamps are increasing and each delta continues from the accumulator
+/// of the one before it.
+pub fn import_revocation_registry_deltas(
+    blob: &[u8],
+) -> Result<(RevocationRegistryId, Vec<(u64, RevocationRegistryDelta)>)> {
+    trace!(
+        "import_revocation_registry_deltas >>> blob length: {:?}",
+        blob.len()
+    );
+
+    let batch: RevocationRegistryDeltaBatch =
+        serde_json::from_reader(flate2::read::GzDecoder::new(blob))
+            .map_err(err_map!("Invalid revocation registry delta batch"))?;
+    let deltas = batch
+        .deltas
+        .into_iter()
+        .map(|entry| (entry.timestamp, entry.delta))
+        .collect::<Vec<_>>();
+    check_revocation_registry_delta_chain(&deltas)?;
+
+    trace!(
+        "import_revocation_registry_deltas <<< rev_reg_id: {:?}, deltas: {:?}",
+        batch.rev_reg_id,
+        deltas
+    );
+
+    Ok((batch.rev_reg_id, deltas))
+}
+
+const PUBLIC_BUNDLE_VERSION: &str = "1.0";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicBundleRef<'a> {
+    ver: &'static str,
+    schema: &'a Schema,
+    cred_def: &'a CredentialDefinition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rev_reg_def: Option<&'a RevocationRegistryDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicBundle {
+    ver: String,
+    schema: Schema,
+    cred_def: CredentialDefinition,
+    #[serde(default)]
+    rev_reg_def: Option<RevocationRegistryDefinition>,
+}
+
+/// Bundle the public objects for a credential definition into a single
+/// versioned JSON document, for distribution to holders and verifiers
+/// without a ledger.
+pub fn export_public_bundle(
+    schema: &Schema,
+    cred_def: &CredentialDefinition,
+    rev_reg_def: Option<&RevocationRegistryDefinition>,
+) -> Result<String> {
+    trace!(
+        "export_public_bundle >>> schema: {:?}, cred_def: {:?}, rev_reg_def: {:?}",
+        schema,
+        cred_def,
+        rev_reg_def
+    );
+
+    check_public_bundle(schema, cred_def, rev_reg_def)?;
+    let bundle = serde_json::to_string(&PublicBundleRef {
+        ver: PUBLIC_BUNDLE_VERSION,
+        schema,
+        cred_def,
+        rev_reg_def,
+    })?;
+
+    trace!("export_public_bundle <<< bundle: {:?}", bundle);
+
+    Ok(bundle)
+}
+
+/// Unpack a document produced by `export_public_bundle`, checking that the
+/// objects refer to one another.
+pub fn import_public_bundle(
+    bundle: &str,
+) -> Result<(
+    Schema,
+    CredentialDefinition,
+    Option<RevocationRegistryDefinition>,
+)> {
+    trace!("import_public_bundle >>> bundle: {:?}", bundle);
+
+    let bundle: PublicBundle =
+        serde_json::from_str(bundle).map_err(err_map!("Invalid public bundle"))?;
+    if bundle.ver != PUBLIC_BUNDLE_VERSION {
+        return Err(err_msg!(
+            "Unsupported public bundle version: {}",
+            bundle.ver
+        ));
+    }
+    bundle.schema.validate()?;
+    check_limits(&bundle.schema)?;
+    bundle.cred_def.validate()?;
+    if let Some(rev_reg_def) = &bundle.rev_reg_def {
+        rev_reg_def.validate()?;
+    }
+    check_public_bundle(
+        &bundle.schema,
+        &bundle.cred_def,
+        bundle.rev_reg_def.as_ref(),
+    )?;
+
+    trace!("import_public_bundle <<<");
+
+    Ok((bundle.schema, bundle.cred_def, bundle.rev_reg_def))
+}
+
+fn check_public_bundle(
+    schema: &Schema,
+    cred_def: &CredentialDefinition,
+    rev_reg_def: Option<&RevocationRegistryDefinition>,
+) -> Result<()> {
+    let Schema::SchemaV1(schema) = schema;
+    let CredentialDefinition::CredentialDefinitionV1(cred_def) = cred_def;
+    // credential definitions on an Indy ledger refer to the schema by sequence number
+    let schema_seq_no = schema.seq_no.map(|seq_no| seq_no.to_string());
+    if cred_def.schema_id != schema.id && Some(&cred_def.schema_id.0) != schema_seq_no.as_ref() {
+        return Err(err_msg!(
+            "Credential definition does not refer to the schema: {}",
+            schema.id
+        ));
+    }
+    if let Some(RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def)) =
+        rev_reg_def
+    {
+        if rev_reg_def.cred_def_id != cred_def.id {
+            return Err(err_msg!(
+                "Revocation registry definition does not refer to the credential definition: {}",
+                cred_def.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_revocation_registry_delta_chain(deltas: &[(u64, RevocationRegistryDelta)]) -> Result<()> {
+    if deltas.is_empty() {
+        return Err(err_msg!("No revocation registry deltas provided"));
+    }
+    let mut prev: Option<(u64, serde_json::Value)> = None;
+    for (timestamp, delta) in deltas {
+        let value = match delta {
+            RevocationRegistryDelta::RevocationRegistryDeltaV1(v1) => {
+                serde_json::to_value(&v1.value)?
+            }
+        };
+        if let Some((prev_timestamp, prev_value)) = &prev {
+            if timestamp <= prev_timestamp {
+                return Err(err_msg!(
+                    "Revocation registry delta timestamps must be increasing: {} follows {}",
+                    timestamp,
+                    prev_timestamp
+                ));
+            }
+            if value.get("prevAccum") != prev_value.get("accum") {
+                return Err(err_msg!(
+                    "Revocation registry delta at timestamp {} does not continue from the previous accumulator",
+                    timestamp
+                ));
+            }
+        }
+        prev.replace((*timestamp, value));
+    }
+    Ok(())
+}
+
+/// Minimum bitstring length (16KB) mandated by the W3C Bitstring Status List
+/// specification to preserve herd privacy.
+pub const STATUS_LIST_MIN_LENGTH: usize = 131_072;
+
+/// Render the revoked credential indices of a revocation registry as an
+/// (unsigned) W3C Bitstring Status List credential.
+///
+/// Each credential's registry index is used directly as its `statusListIndex`,
+/// so bit 0 of the list is never set. The returned payload must still be
+/// secured by the issuer before it is published.
+pub fn create_status_list_credential(
+    rev_reg_def: &RevocationRegistryDefinition,
+    revoked: &BTreeSet<u32>,
+    issuer_id: &str,
+    status_list_id: &str,
+) -> Result<serde_json::Value> {
+    trace!(
+        "create_status_list_credential >>> rev_reg_def: {:?}, revoked: {:?}, issuer_id: {:?}, status_list_id: {:?}",
+        rev_reg_def,
+        secret!(revoked),
+        issuer_id,
+        status_list_id
+    );
+
+    let max_cred_num = match rev_reg_def {
+        RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => v1.value.max_cred_num,
+    };
+
+    let bit_len = std::cmp::max(STATUS_LIST_MIN_LENGTH, max_cred_num as usize + 1);
+    let mut bits = vec![0u8; (bit_len + 7) / 8];
+    for idx in revoked {
+        if *idx == 0 || *idx > max_cred_num {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Revocation index {} is outside the registry range 1..={}",
+                idx,
+                max_cred_num
+            ));
+        }
+        let idx = *idx as usize;
+        // the spec orders bits most-significant first within each byte
+        bits[idx / 8] |= 0x80 >> (idx % 8);
+    }
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, &bits)?;
+    let compressed = encoder.finish()?;
+    // multibase prefix 'u' denotes base64url without padding
+    let encoded_list = format!(
+        "u{}",
+        base64::encode_config(compressed, base64::URL_SAFE_NO_PAD)
+    );
+
+    let credential = serde_json::json!({
+        "@context": ["https://www.w3.org/ns/credentials/v2"],
+        "id": status_list_id,
+        "type": ["VerifiableCredential", "BitstringStatusListCredential"],
+        "issuer": issuer_id,
+        "credentialSubject": {
+            "id": format!("{}#list", status_list_id),
+            "type": "BitstringStatusList",
+            "statusPurpose": "revocation",
+            "encodedList": encoded_list,
+        },
+    });
+
+    trace!(
+        "create_status_list_credential <<< credential: {:?}",
+        credential
+    );
+
+    Ok(credential)
+}
+
+/// Retire a credential definition, dropping its private key and producing a
+/// tombstone record of the teardown.
+///
+/// The private key primes are owned by the big number backend and are released
+/// when the key is dropped.
+pub fn destroy_credential_definition(
+    cred_def: &CredentialDefinition,
+    cred_def_private: CredentialDefinitionPrivate,
+) -> Result<IssuerTombstone> {
+    trace!(
+        "destroy_credential_definition >>> cred_def: {:?}, cred_def_private: {:?}",
+        cred_def,
+        secret!(&cred_def_private)
+    );
+
+    let cred_def_id = cred_def.id().clone();
+    drop(cred_def_private);
+    let tombstone = IssuerTombstone {
+        kind: TombstoneKind::CredentialDefinition,
+        id: cred_def_id.to_string(),
+        cred_def_id,
+        public_digest: public_digest(cred_def)?,
+        revoked: Vec::new(),
+        timestamp: current_timestamp(),
+    };
+
+    trace!(
+        "destroy_credential_definition <<< tombstone: {:?}",
+        tombstone
+    );
+
+    Ok(tombstone)
+}
+
+/// Retire a revocation registry, zeroizing its private key and producing a
+/// tombstone record of the teardown.
+///
+/// When a registry and set of outstanding indexes are provided, those credentials
+/// are revoked first and the updated registry and delta are returned for publishing.
+pub fn destroy_revocation_registry(
+    rev_reg_def: &RevocationRegistryDefinition,
+    mut rev_reg_def_private: RevocationRegistryDefinitionPrivate,
+    revoke: Option<(&RevocationRegistry, BTreeSet<u32>, &TailsReader)>,
+) -> Result<(
+    IssuerTombstone,
+    Option<(RevocationRegistry, RevocationRegistryDelta)>,
+)> {
+    trace!(
+        "destroy_revocation_registry >>> rev_reg_def: {:?}, rev_reg_def_private: {:?}, revoke: {:?}",
+        rev_reg_def,
+        secret!(&rev_reg_def_private),
+        revoke.as_ref().map(|(_, revoked, _)| revoked)
+    );
+
+    let (rev_reg_id, cred_def_id) = match rev_reg_def {
+        RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+            (v1.id.clone(), v1.cred_def_id.clone())
+        }
+    };
+    let (revoked, updated) = match revoke {
+        Some((rev_reg, revoked, tails_reader)) if !revoked.is_empty() => {
+            let updated = update_revocation_registry(
+                rev_reg_def,
+                rev_reg,
+                BTreeSet::new(),
+                revoked.clone(),
+                tails_reader,
+            )?;
+            (revoked.into_iter().collect(), Some(updated))
+        }
+        _ => (Vec::new(), None),
+    };
+    zeroize_inline(&mut rev_reg_def_private.value);
+    drop(rev_reg_def_private);
+
+    let tombstone = IssuerTombstone {
+        kind: TombstoneKind::RevocationRegistry,
+        id: rev_reg_id.to_string(),
+        cred_def_id,
+        public_digest: public_digest(rev_reg_def)?,
+        revoked,
+        timestamp: current_timestamp(),
+    };
+
+    trace!("destroy_revocation_registry <<< tombstone: {:?}", tombstone);
+
+    Ok((tombstone, updated))
+}
+
+fn public_digest<T: serde::Serialize>(value: &T) -> Result<String> {
+    let digest = indy_utils::hash::SHA256::digest(serde_json::to_vec(value)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Overwrite a value held entirely inline, with no heap allocations, by zeros
+fn zeroize_inline<T>(value: &mut T) {
+    assert!(!std::mem::needs_drop::<T>());
+    let bytes = value as *mut T as *mut u8;
+    for idx in 0..std::mem::size_of::<T>() {
+        // volatile writes are not elided even though the value is about to be dropped
+        unsafe { std::ptr::write_volatile(bytes.add(idx), 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tails::TailsFileReader;
+
+    #[test]
+    fn test_issuer_metrics_record_events() {
+        let metrics = IssuerMetrics::new();
+        let cred_def_id =
+            CredentialDefinitionId::from("NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag".to_string());
+        metrics.record(&cred_def_id, IssuerEvent::RegistryCreated);
+        metrics.record(&cred_def_id, IssuerEvent::CredentialIssued);
+        metrics.record(&cred_def_id, IssuerEvent::CredentialIssued);
+        metrics.record(&cred_def_id, IssuerEvent::CredentialsRevoked(2));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        let recorded = &snapshot[&cred_def_id];
+        assert_eq!(recorded.credentials_issued, 2);
+        assert_eq!(recorded.credentials_revoked, 2);
+        assert_eq!(recorded.registries_created, 1);
+        assert!(recorded.last_revoked.is_some());
+
+        metrics.reset();
+        assert_eq!(metrics.get(&cred_def_id), None);
+    }
+
+    #[derive(Debug)]
+    struct UppercaseEncoder;
+
+    impl AttributeEncoder for UppercaseEncoder {
+        fn encode(&self, name: &str, raw: &str) -> Result<String> {
+            if name == "code" {
+                StandardEncoder.encode(name, &raw.to_uppercase())
+            } else {
+                StandardEncoder.encode(name, raw)
+            }
+        }
+    }
+
+    #[test]
+    fn encode_missing_values_works() {
+        let mut values = MakeCredentialValues::default();
+        values.add_encoded("code", "abc", String::new());
+        values.add_encoded("age", "28", String::new());
+        values.add_encoded("name", "Alex", "1234".to_string());
+        let mut values = values.into();
+        encode_missing_values(&mut values, &UppercaseEncoder).unwrap();
+
+        assert_eq!(
+            values.0["code"].encoded,
+            encode_credential_attribute("ABC").unwrap()
+        );
+        assert_eq!(values.0["age"].encoded, "28");
+        assert_eq!(values.0["name"].encoded, "1234");
+    }
+
+    #[test]
+    fn add_raw_with_encoder_works() {
+        let mut values = MakeCredentialValues::default();
+        values
+            .add_raw_with_encoder("code", "abc", &UppercaseEncoder)
+            .unwrap();
+        values.add_raw("name", "abc").unwrap();
+        let values: CredentialValues = values.into();
+
+        assert_eq!(
+            values.0["code"].encoded,
+            encode_credential_attribute("ABC").unwrap()
+        );
+        assert_eq!(
+            values.0["name"].encoded,
+            encode_credential_attribute("abc").unwrap()
+        );
+    }
 
     #[test]
     fn test_encode_attribute() {
@@ -604,5 +1992,1086 @@ mod tests {
             encode_credential_attribute("\x02").unwrap(),
             "99398763056634537812744552006896172984671876672520535998211840060697129507206"
         );
+        // integers outside of the canonical form keep their value
+        assert_eq!(encode_credential_attribute("007").unwrap(), "7");
+        assert_eq!(encode_credential_attribute("+5").unwrap(), "5");
+        assert_eq!(encode_credential_attribute("-0").unwrap(), "0");
+        assert!(encode_credential_attribute(" 1")
+            .unwrap()
+            .parse::<i32>()
+            .is_err());
+
+        // unless the canonical encoding is used
+        assert_eq!(encode_canonical_credential_attribute("7").unwrap(), "7");
+        assert_eq!(
+            encode_canonical_credential_attribute("-2147483648").unwrap(),
+            "-2147483648"
+        );
+        assert_eq!(
+            encode_canonical_credential_attribute("007").unwrap(),
+            "44608119095630492481017134257834365612796282458274476478873559534504197876631"
+        );
+        assert_eq!(
+            encode_canonical_credential_attribute("+5").unwrap(),
+            "1750515543741438901228798751119195788978562198301161085510979368940106517001"
+        );
+        assert_eq!(
+            encode_canonical_credential_attribute("-0").unwrap(),
+            "107413606770738771354060003529704906623296854012143859948039936364918895758090"
+        );
+        assert_eq!(
+            encode_canonical_credential_attribute(" 1").unwrap(),
+            "101845468265237731434727701032909192901075578134913494203521508446598787009991"
+        );
+    }
+
+    fn create_test_cred_def() -> (DidValue, CredentialDefinition) {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, _, _) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(true),
+        )
+        .unwrap();
+        (origin_did, cred_def)
+    }
+
+    #[test]
+    fn test_create_revocation_registry_stream_writer() {
+        use crate::tails::TailsStreamWriter;
+        use crate::ursa::hash::{sha2::Sha256, Digest};
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let mut tails_writer = TailsStreamWriter::new(Vec::new(), "https://tails.example.com/1");
+        let (rev_reg_def, _, _, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            10,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails = tails_writer.into_inner();
+
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+        assert_eq!(
+            rev_reg_def.value.tails_location,
+            "https://tails.example.com/1"
+        );
+        assert_eq!(&tails[..2], &[0u8, 2u8]);
+        assert_eq!(
+            rev_reg_def.value.tails_hash,
+            indy_utils::base58::encode(Sha256::digest(&tails))
+        );
+    }
+
+    #[test]
+    fn test_create_revocation_registry_buffer_writer() {
+        use crate::tails::{TailsBufferReader, TailsBufferWriter};
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let (rev_reg_def, _, rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = TailsBufferReader::new(tails_writer.into_inner());
+
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def_v1) =
+            &rev_reg_def;
+        assert_eq!(
+            rev_reg_def_v1.value.tails_location,
+            rev_reg_def_v1.value.tails_hash
+        );
+        revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+    }
+
+    #[test]
+    fn test_create_revocation_registry_progress() {
+        use crate::tails::TailsBufferWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let mut reports = Vec::new();
+        let mut tails_writer = TailsBufferWriter::new(None);
+        create_revocation_registry_with_progress(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+            &mut |generated, total| {
+                reports.push((generated, total));
+                true
+            },
+        )
+        .unwrap();
+        assert_eq!(reports, vec![(11, 11)]);
+
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let err = create_revocation_registry_with_progress(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+            &mut |_, _| false,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidState);
+    }
+
+    #[test]
+    fn test_create_credential_with_allocation() {
+        use crate::prover::create_credential_request;
+        use crate::tails::{TailsBufferReader, TailsBufferWriter};
+
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, cred_def_priv, key_proof) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(true),
+        )
+        .unwrap();
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let (rev_reg_def, rev_reg_def_priv, mut rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            3,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails = tails_writer.into_inner();
+        let master_secret = MasterSecret::new().unwrap();
+        let mut usage = RevocationRegistryUsage::new(&rev_reg_def);
+
+        for allocation in [
+            RevocationIndexAllocation::Specified(2),
+            RevocationIndexAllocation::Sequential,
+        ]
+        .iter()
+        {
+            let cred_offer =
+                create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+            let (cred_req, _) = create_credential_request(
+                &origin_did,
+                &cred_def,
+                &master_secret,
+                "default",
+                &cred_offer,
+            )
+            .unwrap();
+            let mut values = MakeCredentialValues::default();
+            values.add_raw("name", "Alex").unwrap();
+            let (credential, _, updated_reg, _) = create_credential_with_allocation(
+                &cred_def,
+                &cred_def_priv,
+                &cred_offer,
+                &cred_req,
+                values.into(),
+                RevocationAllocationConfig {
+                    reg_def: &rev_reg_def,
+                    reg_def_private: &rev_reg_def_priv,
+                    registry: &rev_reg,
+                    allocation: *allocation,
+                    tails_reader: TailsBufferReader::new(tails.clone()),
+                },
+                &mut usage,
+            )
+            .unwrap();
+            assert_eq!(credential.rev_reg_id.as_ref(), Some(rev_reg_def.id()));
+            rev_reg = updated_reg;
+        }
+        assert_eq!(usage.used, HashSet::from_iter(vec![1, 2]));
+        assert_eq!(usage.remaining(), 1);
+    }
+
+    #[test]
+    fn test_create_credential_registry_full() {
+        use crate::error::ErrorKind;
+        use crate::prover::create_credential_request;
+        use crate::tails::{TailsBufferReader, TailsBufferWriter};
+
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, cred_def_priv, key_proof) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(true),
+        )
+        .unwrap();
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let (rev_reg_def, rev_reg_def_priv, rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            2,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails = tails_writer.into_inner();
+        let master_secret = MasterSecret::new().unwrap();
+        let cred_offer = create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+        let (cred_req, _) = create_credential_request(
+            &origin_did,
+            &cred_def,
+            &master_secret,
+            "default",
+            &cred_offer,
+        )
+        .unwrap();
+
+        let mut usage = RevocationRegistryUsage::new(&rev_reg_def);
+        assert_eq!(
+            registry_remaining_capacity(&rev_reg_def, &usage).unwrap(),
+            2
+        );
+        usage.mark_used(1);
+        usage.mark_used(2);
+        assert_eq!(
+            registry_remaining_capacity(&rev_reg_def, &usage).unwrap(),
+            0
+        );
+
+        for (registry_idx, kind) in [
+            (3, ErrorKind::RevocationRegistryFull),
+            (0, ErrorKind::InvalidUserRevocId),
+        ]
+        .iter()
+        {
+            let mut values = MakeCredentialValues::default();
+            values.add_raw("name", "Alex").unwrap();
+            let err = create_credential(
+                &cred_def,
+                &cred_def_priv,
+                &cred_offer,
+                &cred_req,
+                values.into(),
+                Some(CredentialRevocationConfig {
+                    reg_def: &rev_reg_def,
+                    reg_def_private: &rev_reg_def_priv,
+                    registry: &rev_reg,
+                    registry_idx: *registry_idx,
+                    registry_used: &usage.used,
+                    tails_reader: TailsBufferReader::new(tails.clone()),
+                }),
+            )
+            .unwrap_err();
+            assert_eq!(err.kind(), *kind);
+        }
+    }
+
+    #[test]
+    fn test_next_revocation_registry_tag() {
+        assert_eq!(next_revocation_registry_tag("1"), "2");
+        assert_eq!(next_revocation_registry_tag("tag-1"), "tag-2");
+        assert_eq!(next_revocation_registry_tag("tag-a-9"), "tag-a-10");
+        assert_eq!(next_revocation_registry_tag("tag"), "tag-2");
+        assert_eq!(next_revocation_registry_tag("tag-"), "tag--2");
+    }
+
+    #[test]
+    fn test_rotate_revocation_registry() {
+        use crate::tails::TailsBufferWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let (rev_reg_def, ..) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+
+        let mut tails_writer = TailsBufferWriter::new(None);
+        let (next_def, _, _, _, rotation) =
+            rotate_revocation_registry(&rev_reg_def, &cred_def, &mut tails_writer).unwrap();
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(prev) = &rev_reg_def;
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(next) = &next_def;
+        assert_eq!(next.tag, "tag-2");
+        assert_eq!(next.cred_def_id, prev.cred_def_id);
+        assert_eq!(next.value.max_cred_num, 5);
+        assert_eq!(next.value.issuance_type, IssuanceType::ISSUANCE_ON_DEMAND);
+        assert_ne!(next.value.tails_hash, prev.value.tails_hash);
+        assert_eq!(rotation.previous_rev_reg_id, prev.id);
+        assert_eq!(rotation.rev_reg_id, next.id);
+        assert_eq!(rotation.tag, "tag-2");
+    }
+
+    #[test]
+    fn test_revocation_status_list_conversions() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, rev_reg, init_delta) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = TailsFileReader::new(match &rev_reg_def {
+            RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                v1.value.tails_location.as_str()
+            }
+        });
+        let (rev_reg, issue_delta) = update_revocation_registry(
+            &rev_reg_def,
+            &rev_reg,
+            BTreeSet::from_iter(vec![1, 2, 3]),
+            BTreeSet::new(),
+            &tails_reader,
+        )
+        .unwrap();
+        let (rev_reg, revoke_delta) =
+            revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+
+        let status_list = RevocationStatusList::from_revocation_registry(
+            &rev_reg_def,
+            &rev_reg,
+            &[init_delta, issue_delta, revoke_delta],
+            Some(1000),
+        )
+        .unwrap();
+        assert_eq!(status_list.issuer_id, origin_did.0);
+        assert_eq!(status_list.revocation_list, vec![0, 1, 0, 1, 1]);
+
+        let converted = status_list.to_revocation_registry().unwrap();
+        assert_eq!(
+            serde_json::to_value(&converted).unwrap(),
+            serde_json::to_value(&rev_reg).unwrap()
+        );
+        let delta = status_list
+            .to_revocation_registry_delta(&rev_reg_def)
+            .unwrap();
+        let delta = serde_json::to_value(&delta).unwrap();
+        let issued: HashSet<u32> =
+            serde_json::from_value(delta["value"]["issued"].clone()).unwrap();
+        assert_eq!(issued, HashSet::from_iter(vec![1, 3]));
+        assert!(delta["value"].get("revoked").is_none());
+    }
+
+    #[test]
+    fn test_revoke_credentials() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = TailsFileReader::new(match &rev_reg_def {
+            RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                v1.value.tails_location.as_str()
+            }
+        });
+
+        let (batch_reg, batch_delta) = revoke_credentials(
+            &rev_reg_def,
+            &rev_reg,
+            BTreeSet::from_iter(vec![2, 4]),
+            &tails_reader,
+        )
+        .unwrap();
+        let (single_reg, _) = revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+        let (single_reg, _) =
+            revoke_credential(&rev_reg_def, &single_reg, 4, &tails_reader).unwrap();
+        assert_eq!(
+            serde_json::to_value(&batch_reg).unwrap(),
+            serde_json::to_value(&single_reg).unwrap()
+        );
+        let delta = serde_json::to_value(&batch_delta).unwrap();
+        let revoked: HashSet<u32> =
+            serde_json::from_value(delta["value"]["revoked"].clone()).unwrap();
+        assert_eq!(revoked, HashSet::from_iter(vec![2, 4]));
+
+        assert_kind!(
+            InvalidUserRevocId,
+            revoke_credentials(
+                &rev_reg_def,
+                &rev_reg,
+                BTreeSet::from_iter(vec![2, 6]),
+                &tails_reader
+            )
+        );
+        assert_kind!(
+            Input,
+            update_revocation_registry(
+                &rev_reg_def,
+                &rev_reg,
+                BTreeSet::from_iter(vec![1]),
+                BTreeSet::from_iter(vec![1]),
+                &tails_reader
+            )
+        );
+    }
+
+    #[test]
+    fn test_revocation_registry_diff_and_apply_delta() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = TailsFileReader::new(match &rev_reg_def {
+            RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                v1.value.tails_location.as_str()
+            }
+        });
+        let (updated_reg, delta) = revoke_credentials(
+            &rev_reg_def,
+            &rev_reg,
+            BTreeSet::from_iter(vec![2, 4]),
+            &tails_reader,
+        )
+        .unwrap();
+
+        let diff = updated_reg
+            .diff(&rev_reg, &HashSet::new(), &HashSet::from_iter(vec![2, 4]))
+            .unwrap();
+        // the revoked indices are serialized from a HashSet in no particular order
+        let take_revoked = |mut value: serde_json::Value| {
+            let revoked: HashSet<u32> =
+                serde_json::from_value(value["value"]["revoked"].take()).unwrap();
+            (value, revoked)
+        };
+        assert_eq!(
+            take_revoked(serde_json::to_value(&diff).unwrap()),
+            take_revoked(serde_json::to_value(&delta).unwrap())
+        );
+        let applied = rev_reg.apply_delta(&diff).unwrap();
+        assert_eq!(
+            serde_json::to_value(&applied).unwrap(),
+            serde_json::to_value(&updated_reg).unwrap()
+        );
+
+        // the delta no longer applies once the registry has been updated
+        updated_reg.apply_delta(&diff).unwrap_err();
+        updated_reg
+            .diff(
+                &rev_reg,
+                &HashSet::from_iter(vec![2]),
+                &HashSet::from_iter(vec![2]),
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_export_import_revocation_registry_deltas() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, rev_reg, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            10,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = TailsFileReader::new(match &rev_reg_def {
+            RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                v1.value.tails_location.as_str()
+            }
+        });
+        let (rev_reg, delta_1) =
+            revoke_credential(&rev_reg_def, &rev_reg, 1, &tails_reader).unwrap();
+        let (_, delta_2) = revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+        let rev_reg_id = rev_reg_def.id().clone();
+
+        let deltas = vec![(100, delta_1.clone()), (200, delta_2.clone())];
+        let blob = export_revocation_registry_deltas(&rev_reg_id, &deltas).unwrap();
+        let (imported_id, imported) = import_revocation_registry_deltas(&blob).unwrap();
+        assert_eq!(imported_id, rev_reg_id);
+        assert_eq!(
+            imported.iter().map(|(ts, _)| *ts).collect::<Vec<_>>(),
+            vec![100, 200]
+        );
+
+        assert_kind!(
+            Input,
+            export_revocation_registry_deltas(
+                &rev_reg_id,
+                &[(200, delta_2.clone()), (100, delta_1.clone())]
+            )
+        );
+        assert_kind!(
+            Input,
+            export_revocation_registry_deltas(&rev_reg_id, &[(100, delta_2), (200, delta_1)])
+        );
+        assert_kind!(Input, import_revocation_registry_deltas(b"invalid"));
+    }
+
+    #[test]
+    fn test_export_import_public_bundle() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, _, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            10,
+            &mut tails_writer,
+        )
+        .unwrap();
+
+        let bundle = export_public_bundle(&schema, &cred_def, Some(&rev_reg_def)).unwrap();
+        let (imported_schema, imported_cred_def, imported_rev_reg_def) =
+            import_public_bundle(&bundle).unwrap();
+        assert_eq!(imported_schema.id(), schema.id());
+        assert_eq!(imported_cred_def.id(), cred_def.id());
+        assert_eq!(imported_rev_reg_def.unwrap().id(), rev_reg_def.id());
+
+        let other_schema = create_schema(
+            &origin_did,
+            "other",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        assert_kind!(Input, export_public_bundle(&other_schema, &cred_def, None));
+        let mut bundle_json: serde_json::Value = serde_json::from_str(&bundle).unwrap();
+        bundle_json["ver"] = "2.0".into();
+        assert_kind!(Input, import_public_bundle(&bundle_json.to_string()));
+    }
+
+    #[test]
+    fn test_create_status_list_credential() {
+        use crate::tails::TailsFileWriter;
+        use std::io::Read;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, _, _) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            10,
+            &mut tails_writer,
+        )
+        .unwrap();
+
+        let revoked = BTreeSet::from_iter(vec![1, 9]);
+        let cred = create_status_list_credential(
+            &rev_reg_def,
+            &revoked,
+            "did:example:issuer",
+            "https://example.com/status/1",
+        )
+        .unwrap();
+        assert_eq!(cred["credentialSubject"]["statusPurpose"], "revocation");
+
+        let encoded = cred["credentialSubject"]["encodedList"].as_str().unwrap();
+        assert!(encoded.starts_with('u'));
+        let compressed = base64::decode_config(&encoded[1..], base64::URL_SAFE_NO_PAD).unwrap();
+        let mut bits = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut bits)
+            .unwrap();
+        assert_eq!(bits.len(), STATUS_LIST_MIN_LENGTH / 8);
+        assert_eq!(bits[0], 0b0100_0000);
+        assert_eq!(bits[1], 0b0100_0000);
+        assert!(bits[2..].iter().all(|b| *b == 0));
+
+        let out_of_range = BTreeSet::from_iter(vec![11]);
+        assert_kind!(
+            InvalidUserRevocId,
+            create_status_list_credential(&rev_reg_def, &out_of_range, "issuer", "list")
+        );
+    }
+
+    #[test]
+    fn test_create_revocation_registries() {
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let registries = create_revocation_registries(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            2,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        assert_eq!(registries.len(), 2);
+        let tags: Vec<(String, String)> = registries
+            .iter()
+            .map(|(def, ..)| match def {
+                RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                    (v1.tag.clone(), v1.value.tails_hash.clone())
+                }
+            })
+            .collect();
+        assert_eq!(tags[0].0, "tag-1");
+        assert_eq!(tags[1].0, "tag-2");
+        assert_ne!(tags[0].1, tags[1].1);
+
+        assert_kind!(
+            Input,
+            create_revocation_registries(
+                &origin_did,
+                &cred_def,
+                "tag",
+                RegistryType::CL_ACCUM,
+                IssuanceType::ISSUANCE_ON_DEMAND,
+                0,
+                5,
+                &mut tails_writer,
+            )
+        );
+    }
+
+    #[test]
+    fn test_create_schema_attribute_limit() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let attr_names = |count: usize| {
+            AttributeNames::from((0..count).map(|i| format!("attr{}", i)).collect::<Vec<_>>())
+        };
+        let _config = ConfigGuard::set(CredxConfig::default());
+
+        assert_kind!(
+            Input,
+            create_schema(&origin_did, "schema", "1.0", attr_names(0), None)
+        );
+        let err = create_schema(&origin_did, "schema", "1.0", attr_names(126), None).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::LimitExceeded);
+        assert!(err.to_string().contains("126"));
+        assert!(err.to_string().contains("125"));
+
+        set_config(CredxConfig {
+            validation: ValidationConfig {
+                max_schema_attributes: 200,
+                ..ValidationConfig::default()
+            },
+            ..CredxConfig::default()
+        })
+        .unwrap();
+        let schema = create_schema(&origin_did, "schema", "1.0", attr_names(126), None).unwrap();
+        let schema: Schema =
+            serde_json::from_value(serde_json::to_value(&schema).unwrap()).unwrap();
+        schema.validate().unwrap();
+        check_limits(&schema).unwrap();
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let (schema, attr_types) = infer_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            r#"{"name": "Alex", "age": 28, "height": 175.5, "verified": true, "nickname": null}"#,
+        )
+        .unwrap();
+        match schema {
+            Schema::SchemaV1(schema) => assert_eq!(schema.attr_names.0.len(), 5),
+        }
+        assert_eq!(attr_types["name"], AttributeType::String);
+        assert_eq!(attr_types["age"], AttributeType::Integer);
+        assert_eq!(attr_types["height"], AttributeType::Number);
+        assert_eq!(attr_types["verified"], AttributeType::Boolean);
+        assert_eq!(attr_types["nickname"], AttributeType::String);
+
+        assert_kind!(
+            Input,
+            infer_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                r#"{"First Name": "Alex", "firstname": "Alex"}"#
+            )
+        );
+        assert_kind!(
+            Input,
+            infer_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                r#"{"address": {"city": "SLC"}}"#
+            )
+        );
+        assert_kind!(Input, infer_schema(&origin_did, "schema", "1.0", "[]"));
+    }
+
+    #[test]
+    fn test_verify_key_correctness_proof() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let create = |tag| {
+            create_credential_definition(
+                &origin_did,
+                &schema,
+                tag,
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(false),
+            )
+            .unwrap()
+        };
+        let (cred_def, _, _) = create("tag1");
+        let (cred_def_2, _, key_proof_2) = create("tag2");
+
+        verify_key_correctness_proof(&cred_def_2, &key_proof_2).unwrap();
+        let err = verify_key_correctness_proof(&cred_def, &key_proof_2).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ProofRejected);
+    }
+
+    #[test]
+    fn test_schema_builder() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let mut builder = SchemaBuilder::new(&origin_did, "schema", "1.0");
+        builder
+            .add_attributes(vec!["name", "age"])
+            .unwrap()
+            .seq_no(15);
+        assert_kind!(Input, builder.add_attribute("Age"));
+        assert_kind!(Input, builder.add_attribute(" "));
+        assert_kind!(Input, builder.add_attribute("master_secret"));
+        let Schema::SchemaV1(schema) = builder.build().unwrap();
+        assert_eq!(schema.attr_names.0.len(), 2);
+        assert_eq!(schema.seq_no, Some(15));
+
+        assert_kind!(
+            Input,
+            SchemaBuilder::new(&origin_did, "schema", "1.0").build()
+        );
+        assert_kind!(
+            Input,
+            create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["First Name".to_owned(), "firstname".to_owned()]),
+                None
+            )
+        );
+        assert_kind!(
+            Input,
+            create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["name".to_owned(), "".to_owned()]),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_create_credential_offer_with_nonce() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, _, key_proof) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::default(),
+        )
+        .unwrap();
+
+        let nonce = Nonce::from_dec("1208925819614629174706175").unwrap();
+        let offer =
+            create_credential_offer(schema.id(), &cred_def, &key_proof, Some(nonce)).unwrap();
+        assert_eq!(offer.nonce.as_ref(), "1208925819614629174706175");
+
+        let offer = create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+        assert!(!offer.nonce.as_ref().is_empty());
+
+        let nonce = Nonce::from_dec("1208925819614629174706176").unwrap();
+        assert_kind!(
+            Input,
+            create_credential_offer(schema.id(), &cred_def, &key_proof, Some(nonce))
+        );
+    }
+
+    #[test]
+    fn test_credential_offer_sessions() {
+        let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = create_schema(
+            &origin_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, _, key_proof) = create_credential_definition(
+            &origin_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::default(),
+        )
+        .unwrap();
+
+        assert_kind!(
+            Input,
+            create_credential_offers(schema.id(), &cred_def, &key_proof, &["a", "a"])
+        );
+        assert_kind!(
+            Input,
+            create_credential_offers(schema.id(), &cred_def, &key_proof, &[""])
+        );
+
+        let offers =
+            create_credential_offers(schema.id(), &cred_def, &key_proof, &["a", "b"]).unwrap();
+        assert_eq!(offers[0].session_id.as_deref(), Some("a"));
+        assert_eq!(offers[1].session_id.as_deref(), Some("b"));
+        assert_ne!(offers[0].nonce.as_ref(), offers[1].nonce.as_ref());
+
+        let master_secret = crate::prover::create_master_secret().unwrap();
+        let (mut cred_request, _) = crate::prover::create_credential_request(
+            &origin_did,
+            &cred_def,
+            &master_secret,
+            "default",
+            &offers[1],
+        )
+        .unwrap();
+        let offers = offers.iter().collect::<Vec<_>>();
+        let offer = validate_credential_request_session(&offers, &cred_request, "b").unwrap();
+        assert_eq!(offer.nonce.as_ref(), offers[1].nonce.as_ref());
+        assert_kind!(
+            Input,
+            validate_credential_request_session(&offers, &cred_request, "c")
+        );
+
+        cred_request.cred_def_id =
+            CredentialDefinitionId::from_str("NcYxiDXkpYi6ov5FcYDi1e:3:CL:2:other").unwrap();
+        assert_kind!(
+            Input,
+            validate_credential_request_session(&offers, &cred_request, "b")
+        );
+    }
+
+    #[test]
+    fn test_create_or_update_revocation_witnesses() {
+        use crate::prover::{
+            create_or_update_revocation_state, create_revocation_state_from_witness,
+        };
+        use crate::tails::TailsFileWriter;
+
+        let (origin_did, cred_def) = create_test_cred_def();
+        let tails_dir = tempfile::tempdir().unwrap();
+        let mut tails_writer =
+            TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+        let (rev_reg_def, _, rev_reg, init_delta) = create_revocation_registry(
+            &origin_did,
+            &cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            5,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let tails_reader = || {
+            TailsFileReader::new(match &rev_reg_def {
+                RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                    v1.value.tails_location.as_str()
+                }
+            })
+        };
+
+        let updates = create_or_update_revocation_witnesses(
+            tails_reader(),
+            &rev_reg_def,
+            &init_delta,
+            &[1, 3],
+            1000,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].rev_reg_idx, 3);
+        let rev_state =
+            create_revocation_state_from_witness(&rev_reg_def, &init_delta, &updates[0]).unwrap();
+        let expected = create_or_update_revocation_state(
+            tails_reader(),
+            &rev_reg_def,
+            &init_delta,
+            1,
+            1000,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&rev_state).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        let (_, revoke_delta) =
+            revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader()).unwrap();
+        let prev = updates.iter().collect::<Vec<_>>();
+        let updates = create_or_update_revocation_witnesses(
+            tails_reader(),
+            &rev_reg_def,
+            &revoke_delta,
+            &[1],
+            2000,
+            &prev,
+        )
+        .unwrap();
+        let rev_state =
+            create_revocation_state_from_witness(&rev_reg_def, &revoke_delta, &updates[0]).unwrap();
+        let expected = create_or_update_revocation_state(
+            tails_reader(),
+            &rev_reg_def,
+            &revoke_delta,
+            1,
+            2000,
+            Some(&expected),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&rev_state).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        assert_kind!(
+            InvalidUserRevocId,
+            create_or_update_revocation_witnesses(
+                tails_reader(),
+                &rev_reg_def,
+                &init_delta,
+                &[6],
+                1000,
+                &[],
+            )
+        );
+    }
+
+    #[test]
+    fn test_zeroize_revocation_private_key() {
+        use crate::ursa::pair::GroupOrderElement;
+
+        let gamma = GroupOrderElement::new().unwrap();
+        let mut key: RevocationRegistryDefinitionPrivate =
+            serde_json::from_value(serde_json::json!({ "value": { "gamma": gamma } })).unwrap();
+        zeroize_inline(&mut key.value);
+        let zeroed = serde_json::to_value(&key).unwrap();
+        assert_eq!(
+            zeroed["value"]["gamma"],
+            serde_json::to_value(GroupOrderElement::from_bytes(&[0; 32]).unwrap()).unwrap()
+        );
     }
 }
@@ -1,11 +1,28 @@
 mod helpers;
 
+#[cfg(feature = "cbor")]
+pub mod cbor;
 pub mod issuer;
+#[cfg(feature = "async")]
+pub mod nonblocking;
 pub mod prover;
 pub mod tails;
+#[cfg(feature = "transcript")]
+pub mod transcript;
 pub mod types;
 pub mod verifier;
+pub mod workflow;
 
 pub mod utils {
-    pub use super::helpers::encode_credential_attribute;
+    pub use super::helpers::{
+        check_attribute_name, check_limits, check_schema_attribute_names, clear_public_key_cache,
+        current_expiry_date, current_timestamp, encode_binary_attribute,
+        encode_canonical_credential_attribute, encode_credential_attribute,
+        encode_credential_attribute_salted, encode_decimal_attribute,
+        encode_decimal_predicate_value, encode_expiry_date, encode_expiry_date_for_timestamp,
+        generate_attribute_salt, get_attribute_normalization, get_clock_skew, get_config,
+        get_issuer_metrics, get_max_schema_attributes, get_non_revoked_interval_policy,
+        get_public_key_cache_size, get_validation_config, set_clock, set_config,
+        set_issuer_metrics, BINARY_ATTRIBUTE_PREFIX, RESERVED_ATTRIBUTE_NAMES,
+    };
 }
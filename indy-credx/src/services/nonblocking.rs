@@ -0,0 +1,256 @@
+//! Asynchronous versions of the issuer, prover and verifier operations.
+//!
+//! The CL computations are run on the tokio blocking thread pool, so these
+//! functions must be called within a tokio runtime. Objects which are costly to
+//! copy are accepted behind an `Arc`. Other operations may be run using
+//! `spawn_blocking` directly.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::error::Result;
+
+/// A token used to cancel pending operations.
+///
+/// An operation which has not yet started when the token is cancelled is
+/// skipped, while the result of an operation in progress is discarded.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(err_msg!(InvalidState, "Operation cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Run an operation on the blocking thread pool, awaiting its result
+pub async fn spawn_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(err_map!(Unexpected, "Error running blocking task"))?
+}
+
+/// Run an operation on the blocking thread pool unless it is cancelled
+pub async fn spawn_blocking_cancellable<F, T>(cancel: &CancelToken, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    cancel.check()?;
+    let task_cancel = cancel.clone();
+    let result = spawn_blocking(move || {
+        task_cancel.check()?;
+        f()
+    })
+    .await?;
+    cancel.check()?;
+    Ok(result)
+}
+
+async fn run<F, T>(cancel: Option<&CancelToken>, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match cancel {
+        Some(cancel) => spawn_blocking_cancellable(cancel, f).await,
+        None => spawn_blocking(f).await,
+    }
+}
+
+pub mod issuer {
+    use std::sync::Arc;
+
+    use super::{run, CancelToken};
+    use crate::error::Result;
+    use crate::services::{issuer, types::*};
+
+    pub async fn create_credential_definition(
+        origin_did: DidValue,
+        schema: Schema,
+        tag: String,
+        signature_type: SignatureType,
+        config: CredentialDefinitionConfig,
+        cancel: Option<&CancelToken>,
+    ) -> Result<(
+        CredentialDefinition,
+        CredentialDefinitionPrivate,
+        CredentialKeyCorrectnessProof,
+    )> {
+        run(cancel, move || {
+            issuer::create_credential_definition(&origin_did, &schema, &tag, signature_type, config)
+        })
+        .await
+    }
+
+    /// Create a credential without revocation support
+    pub async fn create_credential(
+        cred_def: Arc<CredentialDefinition>,
+        cred_def_private: Arc<CredentialDefinitionPrivate>,
+        cred_offer: CredentialOffer,
+        cred_request: CredentialRequest,
+        cred_values: CredentialValues,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Credential> {
+        run(cancel, move || {
+            let (credential, _, _) = issuer::create_credential(
+                &cred_def,
+                &cred_def_private,
+                &cred_offer,
+                &cred_request,
+                cred_values,
+                None,
+            )?;
+            Ok(credential)
+        })
+        .await
+    }
+}
+
+pub mod prover {
+    use std::sync::Arc;
+
+    use super::{run, CancelToken};
+    use crate::error::Result;
+    use crate::services::{prover, types::*};
+
+    pub async fn create_credential_request(
+        prover_did: DidValue,
+        cred_def: Arc<CredentialDefinition>,
+        master_secret: Arc<MasterSecret>,
+        master_secret_id: String,
+        cred_offer: CredentialOffer,
+        cancel: Option<&CancelToken>,
+    ) -> Result<(CredentialRequest, CredentialRequestMetadata)> {
+        run(cancel, move || {
+            prover::create_credential_request(
+                &prover_did,
+                &cred_def,
+                &master_secret,
+                &master_secret_id,
+                &cred_offer,
+            )
+        })
+        .await
+    }
+
+    /// Process a received credential, returning the updated credential
+    pub async fn process_credential(
+        mut credential: Credential,
+        cred_request_metadata: CredentialRequestMetadata,
+        master_secret: Arc<MasterSecret>,
+        cred_def: Arc<CredentialDefinition>,
+        rev_reg_def: Option<Arc<RevocationRegistryDefinition>>,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Credential> {
+        run(cancel, move || {
+            prover::process_credential(
+                &mut credential,
+                &cred_request_metadata,
+                &master_secret,
+                &cred_def,
+                rev_reg_def.as_deref(),
+            )?;
+            Ok(credential)
+        })
+        .await
+    }
+}
+
+pub mod verifier {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::{run, CancelToken};
+    use crate::error::Result;
+    use crate::services::{types::*, verifier};
+
+    pub async fn verify_presentation(
+        presentation: Arc<Presentation>,
+        pres_req: Arc<PresentationRequest>,
+        schemas: HashMap<SchemaId, Arc<Schema>>,
+        cred_defs: HashMap<CredentialDefinitionId, Arc<CredentialDefinition>>,
+        rev_reg_defs: Option<HashMap<RevocationRegistryId, Arc<RevocationRegistryDefinition>>>,
+        rev_regs: Option<HashMap<RevocationRegistryId, HashMap<u64, Arc<RevocationRegistry>>>>,
+        cancel: Option<&CancelToken>,
+    ) -> Result<VerificationResult> {
+        run(cancel, move || {
+            let schemas = schemas.iter().map(|(k, v)| (k.clone(), &**v)).collect();
+            let cred_defs = cred_defs.iter().map(|(k, v)| (k.clone(), &**v)).collect();
+            let rev_reg_defs = rev_reg_defs.as_ref().map(|defs| {
+                defs.iter()
+                    .map(|(k, v)| (k.clone(), &**v))
+                    .collect::<HashMap<_, _>>()
+            });
+            let rev_regs = rev_regs.as_ref().map(|regs| {
+                regs.iter()
+                    .map(|(k, entries)| {
+                        let entries = entries.iter().map(|(t, v)| (*t, &**v)).collect();
+                        (k.clone(), entries)
+                    })
+                    .collect::<HashMap<_, _>>()
+            });
+            verifier::verify_presentation(
+                &presentation,
+                &pres_req,
+                &schemas,
+                &cred_defs,
+                rev_reg_defs.as_ref(),
+                rev_regs.as_ref(),
+            )
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn spawn_blocking_cancellable_works() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let task = |runs: &Arc<AtomicUsize>| {
+            let runs = runs.clone();
+            move || Ok(runs.fetch_add(1, Ordering::SeqCst))
+        };
+        let cancel = CancelToken::new();
+        let result = runtime().block_on(spawn_blocking_cancellable(&cancel, task(&runs)));
+        assert_eq!(result.unwrap(), 0);
+
+        cancel.cancel();
+        let result = runtime().block_on(spawn_blocking_cancellable(&cancel, task(&runs)));
+        assert_kind!(InvalidState, result);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,24 +1,28 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use super::types::*;
 use crate::error::Result;
 use crate::services::helpers::*;
 use crate::ursa::cl::{
     issuer::Issuer as CryptoIssuer, prover::Prover as CryptoProver,
-    verifier::Verifier as CryptoVerifier, CredentialPublicKey,
-    RevocationRegistry as CryptoRevocationRegistry, SubProofRequest, Witness,
+    verifier::Verifier as CryptoVerifier, RevocationRegistry as CryptoRevocationRegistry,
+    RevocationRegistryDelta as CryptoRevocationRegistryDelta, SubProofRequest, Witness,
 };
 use indy_data_types::anoncreds::{
     credential::AttributeValues,
-    pres_request::{PresentationRequestPayload, RequestedAttributeInfo, RequestedPredicateInfo},
+    nonce::Nonce,
+    pres_request::{
+        PredicateTypes, PresentationRequestPayload, RequestedAttributeInfo, RequestedPredicateInfo,
+    },
     presentation::{
         AttributeValue, Identifier, RequestedProof, RevealedAttributeGroupInfo,
         RevealedAttributeInfo, SubProofReferent,
     },
 };
-use indy_utils::{Qualifiable, Validatable};
+use indy_utils::{wql::Query, Qualifiable, Validatable};
 
 use super::tails::TailsReader;
+use super::verifier;
 
 pub fn create_master_secret() -> Result<MasterSecret> {
     MasterSecret::new().map_err(err_map!(Unexpected))
@@ -41,10 +45,8 @@ pub fn create_credential_request(
     let cred_def = match cred_def {
         CredentialDefinition::CredentialDefinitionV1(cd) => cd,
     };
-    let credential_pub_key = CredentialPublicKey::build_from_parts(
-        &cred_def.value.primary,
-        cred_def.value.revocation.as_ref(),
-    )?;
+    let credential_pub_key = credential_public_key(cred_def)
+        .map_err(err_map!("Invalid credential definition public key"))?;
     let mut credential_values_builder = CryptoIssuer::new_credential_values_builder()?;
     credential_values_builder.add_value_hidden("master_secret", &master_secret.value.value()?)?;
     let cred_values = credential_values_builder.finalize()?;
@@ -96,10 +98,8 @@ pub fn process_credential(
     let cred_def = match cred_def {
         CredentialDefinition::CredentialDefinitionV1(cd) => cd,
     };
-    let credential_pub_key = CredentialPublicKey::build_from_parts(
-        &cred_def.value.primary,
-        cred_def.value.revocation.as_ref(),
-    )?;
+    let credential_pub_key = credential_public_key(cred_def)
+        .map_err(err_map!("Invalid credential definition public key"))?;
     let credential_values =
         build_credential_values(&credential.values.0, Some(&master_secret.value))?;
     let rev_pub_key = match rev_reg_def {
@@ -126,6 +126,29 @@ pub fn process_credential(
     Ok(())
 }
 
+/// Process a received credential, resolving its credential definition and any
+/// revocation registry definition
+pub fn process_credential_resolved(
+    credential: &mut Credential,
+    cred_request_metadata: &CredentialRequestMetadata,
+    master_secret: &MasterSecret,
+    resolver: &dyn LedgerResolver,
+) -> Result<()> {
+    let cred_def = resolver.resolve_credential_definition(&credential.cred_def_id)?;
+    let rev_reg_def = credential
+        .rev_reg_id
+        .as_ref()
+        .map(|rev_reg_id| resolver.resolve_revocation_registry_definition(rev_reg_id))
+        .transpose()?;
+    process_credential(
+        credential,
+        cred_request_metadata,
+        master_secret,
+        &cred_def,
+        rev_reg_def.as_ref(),
+    )
+}
+
 pub fn create_presentation(
     pres_req: &PresentationRequest,
     credentials: PresentCredentials,
@@ -133,6 +156,30 @@ pub fn create_presentation(
     master_secret: &MasterSecret,
     schemas: &HashMap<SchemaId, &Schema>,
     cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+) -> Result<Presentation> {
+    with_transcript!("create_presentation", {
+        transcript!(Input, "pres_req", pres_req);
+        transcript!(Input, "self_attested", &self_attested);
+        transcript!(Input, "schemas", schemas);
+        transcript!(Input, "cred_defs", cred_defs);
+        create_presentation_impl(
+            pres_req,
+            credentials,
+            self_attested,
+            master_secret,
+            schemas,
+            cred_defs,
+        )
+    })
+}
+
+fn create_presentation_impl(
+    pres_req: &PresentationRequest,
+    credentials: PresentCredentials,
+    self_attested: Option<HashMap<String, String>>,
+    master_secret: &MasterSecret,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
 ) -> Result<Presentation> {
     trace!("create_proof >>> credentials: {:?}, pres_req: {:?}, credentials: {:?}, self_attested: {:?}, master_secret: {:?}, schemas: {:?}, cred_defs: {:?}",
             credentials, pres_req, credentials, &self_attested, secret!(&master_secret), schemas, cred_defs);
@@ -147,10 +194,15 @@ pub fn create_presentation(
             "No credential mapping or self-attested attributes presented"
         ));
     }
+    check_limits(pres_req)?;
     // check for duplicate referents
     credentials.validate()?;
 
     let pres_req_val = pres_req.value();
+    if let Some(self_attested) = self_attested.as_ref() {
+        check_self_attested_attributes(pres_req_val, self_attested)?;
+    }
+
     let mut proof_builder = CryptoProver::new_proof_builder()?;
     proof_builder.add_common_attribute("master_secret")?;
 
@@ -185,12 +237,10 @@ pub fn create_presentation(
             CredentialDefinition::CredentialDefinitionV1(cd) => cd,
         };
 
-        let credential_pub_key = CredentialPublicKey::build_from_parts(
-            &cred_def.value.primary,
-            cred_def.value.revocation.as_ref(),
-        )?;
+        let credential_pub_key = credential_public_key(cred_def)
+            .map_err(err_map!("Invalid credential definition public key"))?;
 
-        let credential_schema = build_credential_schema(&schema.attr_names.0)?;
+        let credential_schema = build_cred_def_credential_schema(&schema.attr_names.0, cred_def)?;
         let credential_values =
             build_credential_values(&credential.values.0, Some(&master_secret.value))?;
         let (req_attrs, req_predicates) = prepare_credential_for_proving(
@@ -198,6 +248,7 @@ pub fn create_presentation(
             present.requested_predicates,
             pres_req_val,
         )?;
+        check_predicate_values(&credential.values.0, &req_predicates)?;
         let sub_proof_request = build_sub_proof_request(&req_attrs, &req_predicates)?;
 
         proof_builder.add_sub_proof_request(
@@ -226,6 +277,7 @@ pub fn create_presentation(
             },
         };
 
+        check_attribute_groups(credential, &identifier, &req_attrs)?;
         identifiers.push(identifier);
 
         update_requested_proof(
@@ -240,6 +292,9 @@ pub fn create_presentation(
         sub_proof_index += 1;
     }
 
+    transcript!(Derived, "requested_proof", &requested_proof);
+    transcript!(Derived, "identifiers", &identifiers);
+
     let proof = proof_builder.finalize(pres_req_val.nonce.as_native())?;
 
     let full_proof = Presentation {
@@ -253,6 +308,128 @@ pub fn create_presentation(
     Ok(full_proof)
 }
 
+// The approximate serialized sizes of presentation components in bytes,
+// measured from presentations using 2048-bit credential definition keys
+const ESTIMATE_PRESENTATION_BYTES: usize = 215;
+const ESTIMATE_SUB_PROOF_BYTES: usize = 3100;
+const ESTIMATE_HIDDEN_ATTRIBUTE_BYTES: usize = 190;
+const ESTIMATE_REVEALED_ATTRIBUTE_BYTES: usize = 80;
+const ESTIMATE_PREDICATE_BYTES: usize = 13220;
+const ESTIMATE_REVOCATION_BYTES: usize = 6020;
+
+/// Estimate the size of the presentation which would be created for a set of
+/// selected credentials, along with the number of proofs to be generated
+pub fn estimate_presentation(
+    pres_req: &PresentationRequest,
+    credentials: &PresentCredentials,
+) -> Result<PresentationEstimate> {
+    credentials.validate()?;
+
+    let pres_req_val = pres_req.value();
+    let mut estimate = PresentationEstimate {
+        size: ESTIMATE_PRESENTATION_BYTES,
+        ..PresentationEstimate::default()
+    };
+
+    for present in credentials.0.iter() {
+        if present.is_empty() {
+            continue;
+        }
+        let (req_attrs, req_predicates) = prepare_credential_for_proving(
+            present.requested_attributes.clone(),
+            present.requested_predicates.clone(),
+            pres_req_val,
+        )?;
+
+        let mut revealed = HashSet::new();
+        for attr in req_attrs.iter() {
+            let names = match (&attr.attr_info.name, &attr.attr_info.names) {
+                (Some(name), _) => vec![name.as_str()],
+                (None, Some(names)) => names.iter().map(String::as_str).collect(),
+                (None, None) => vec![],
+            };
+            for name in names {
+                let value = get_credential_values_for_attribute(&present.cred.values.0, name)
+                    .ok_or_else(|| {
+                        err_msg!("Credential value not found for attribute {:?}", name)
+                    })?;
+                if revealed.insert(attr_common_view(name)) {
+                    estimate.size +=
+                        ESTIMATE_REVEALED_ATTRIBUTE_BYTES + value.raw.len() + value.encoded.len();
+                }
+            }
+        }
+
+        // attributes not revealed are proven along with the master secret
+        let hidden = (present.cred.values.0.len() + 1).saturating_sub(revealed.len());
+
+        estimate.sub_proofs += 1;
+        estimate.revealed_attributes += revealed.len();
+        estimate.hidden_attributes += hidden;
+        estimate.predicate_proofs += req_predicates.len();
+        estimate.size += ESTIMATE_SUB_PROOF_BYTES
+            + hidden * ESTIMATE_HIDDEN_ATTRIBUTE_BYTES
+            + req_predicates.len() * ESTIMATE_PREDICATE_BYTES;
+        if present.rev_state.is_some() {
+            estimate.revocation_proofs += 1;
+            estimate.size += ESTIMATE_REVOCATION_BYTES;
+        }
+    }
+
+    Ok(estimate)
+}
+
+pub fn create_master_secret_proof(
+    credential: &Credential,
+    master_secret: &MasterSecret,
+    schema: &Schema,
+    cred_def: &CredentialDefinition,
+    nonce: &Nonce,
+) -> Result<MasterSecretProof> {
+    trace!("create_master_secret_proof >>> credential: {:?}, master_secret: {:?}, schema: {:?}, cred_def: {:?}, nonce: {:?}",
+            secret!(&credential), secret!(&master_secret), schema, cred_def, nonce);
+
+    let schema = match schema {
+        Schema::SchemaV1(schema) => schema,
+    };
+    let cred_def = match cred_def {
+        CredentialDefinition::CredentialDefinitionV1(cd) => cd,
+    };
+    let credential_pub_key = credential_public_key(cred_def)
+        .map_err(err_map!("Invalid credential definition public key"))?;
+    let credential_schema = build_cred_def_credential_schema(&schema.attr_names.0, cred_def)?;
+    let non_credential_schema = build_non_credential_schema()?;
+    let credential_values =
+        build_credential_values(&credential.values.0, Some(&master_secret.value))?;
+    // no attributes are revealed and no predicates are proven, so the proof only
+    // demonstrates knowledge of the signed credential values including the master secret
+    let sub_proof_request = build_sub_proof_request(&[], &[])?;
+
+    let mut proof_builder = CryptoProver::new_proof_builder()?;
+    proof_builder.add_common_attribute("master_secret")?;
+    proof_builder.add_sub_proof_request(
+        &sub_proof_request,
+        &credential_schema,
+        &non_credential_schema,
+        &credential.signature,
+        &credential_values,
+        &credential_pub_key,
+        None,
+        None,
+    )?;
+    let proof = proof_builder.finalize(nonce.as_native())?;
+
+    let ms_proof = MasterSecretProof {
+        proof,
+        schema_id: credential.schema_id.clone(),
+        cred_def_id: credential.cred_def_id.clone(),
+    };
+
+    trace!("create_master_secret_proof <<< proof: {:?}", ms_proof);
+
+    Ok(ms_proof)
+}
+
 pub fn create_or_update_revocation_state(
     tails_reader: TailsReader,
     revoc_reg_def: &RevocationRegistryDefinition,
@@ -306,6 +483,536 @@ rev_reg_delta: {:?}, rev_reg_idx: {}, timestamp: {:?}, rev_state: {:?}",
     })
 }
 
+/// Create revocation states for a credential at several timestamps.
+///
+/// Each delta must describe the registry at its timestamp, as accepted by
+/// `create_or_update_revocation_state` without a previous state. The witness is
+/// computed for the earliest timestamp and then updated with the changes between
+/// consecutive timestamps, so that each tail is only read when the issuance
+/// status of the corresponding credential changes.
+pub fn create_revocation_states_batch(
+    tails_reader: TailsReader,
+    revoc_reg_def: &RevocationRegistryDefinition,
+    deltas_by_timestamp: &HashMap<u64, &RevocationRegistryDelta>,
+    rev_reg_idx: u32,
+) -> Result<HashMap<u64, CredentialRevocationState>> {
+    trace!(
+        "create_revocation_states_batch >>> tails_reader: {:?}, revoc_reg_def: {:?}, \
+timestamps: {:?}, rev_reg_idx: {}",
+        tails_reader,
+        revoc_reg_def,
+        deltas_by_timestamp.keys(),
+        rev_reg_idx
+    );
+
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(revoc_reg_def) = revoc_reg_def;
+    let max_cred_num = revoc_reg_def.value.max_cred_num;
+    let issuance_by_default = revoc_reg_def.value.issuance_type.to_bool();
+
+    let mut timestamps = deltas_by_timestamp.keys().copied().collect::<Vec<_>>();
+    timestamps.sort_unstable();
+
+    let mut states = HashMap::with_capacity(timestamps.len());
+    let mut prev: Option<(Witness, CryptoRevocationRegistry, BTreeSet<u32>)> = None;
+    for timestamp in timestamps {
+        let RevocationRegistryDelta::RevocationRegistryDeltaV1(delta) =
+            deltas_by_timestamp[&timestamp];
+        let rev_reg = CryptoRevocationRegistry::from(delta.value.clone());
+        // the credential indices in the delta which differ from the issuance default
+        let changed = delta_indices(
+            &delta.value,
+            if issuance_by_default {
+                "revoked"
+            } else {
+                "issued"
+            },
+        )?;
+
+        let witness = match prev.take() {
+            None => Witness::new(
+                rev_reg_idx,
+                max_cred_num,
+                issuance_by_default,
+                &delta.value,
+                &tails_reader,
+            )?,
+            Some((mut witness, prev_reg, prev_changed)) => {
+                let update = witness_update_delta(
+                    (&prev_reg, &prev_changed),
+                    (&rev_reg, &changed),
+                    issuance_by_default,
+                );
+                witness.update(rev_reg_idx, max_cred_num, &update, &tails_reader)?;
+                witness
+            }
+        };
+
+        states.insert(
+            timestamp,
+            CredentialRevocationState {
+                witness: witness.clone(),
+                rev_reg: rev_reg.clone(),
+                timestamp,
+            },
+        );
+        prev.replace((witness, rev_reg, changed));
+    }
+
+    trace!(
+        "create_revocation_states_batch <<< states: {:?}",
+        states.len()
+    );
+
+    Ok(states)
+}
+
+/// Get the revocation state for a credential at `timestamp`, using the cache to
+/// avoid recomputing witnesses.
+///
+/// `rev_reg_delta` must describe the registry at `timestamp`, as accepted by
+/// `create_or_update_revocation_state` without a previous state. A state cached
+/// for the same timestamp is returned as-is. Otherwise the witness cached for the
+/// nearest earlier timestamp is updated with the changes since that timestamp, or
+/// computed from the tails file when there is none, and the new state is cached.
+pub fn get_or_create_revocation_state(
+    cache: &mut RevocationStateCache,
+    tails_reader: TailsReader,
+    revoc_reg_def: &RevocationRegistryDefinition,
+    rev_reg_delta: &RevocationRegistryDelta,
+    rev_reg_idx: u32,
+    timestamp: u64,
+) -> Result<CredentialRevocationState> {
+    trace!(
+        "get_or_create_revocation_state >>> cache: {:?}, tails_reader: {:?}, revoc_reg_def: {:?}, \
+rev_reg_delta: {:?}, rev_reg_idx: {}, timestamp: {:?}",
+        cache.len(),
+        tails_reader,
+        revoc_reg_def,
+        rev_reg_delta,
+        rev_reg_idx,
+        timestamp
+    );
+
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(revoc_reg_def) = revoc_reg_def;
+    let RevocationRegistryDelta::RevocationRegistryDeltaV1(rev_reg_delta) = rev_reg_delta;
+    if let Some(state) = cache.get(&revoc_reg_def.id, rev_reg_idx, timestamp) {
+        trace!("get_or_create_revocation_state <<< cached state");
+        return Ok(state.clone());
+    }
+
+    let max_cred_num = revoc_reg_def.value.max_cred_num;
+    let issuance_by_default = revoc_reg_def.value.issuance_type.to_bool();
+    let rev_reg = CryptoRevocationRegistry::from(rev_reg_delta.value.clone());
+    let changed = delta_indices(
+        &rev_reg_delta.value,
+        if issuance_by_default {
+            "revoked"
+        } else {
+            "issued"
+        },
+    )?;
+
+    let witness = match cache.latest_entry_at(&revoc_reg_def.id, rev_reg_idx, timestamp) {
+        None => Witness::new(
+            rev_reg_idx,
+            max_cred_num,
+            issuance_by_default,
+            &rev_reg_delta.value,
+            &tails_reader,
+        )?,
+        Some(prev) => {
+            let update = witness_update_delta(
+                (&prev.state.rev_reg, &prev.changed),
+                (&rev_reg, &changed),
+                issuance_by_default,
+            );
+            let mut witness = prev.state.witness.clone();
+            witness.update(rev_reg_idx, max_cred_num, &update, &tails_reader)?;
+            witness
+        }
+    };
+
+    let state = CredentialRevocationState {
+        witness,
+        rev_reg,
+        timestamp,
+    };
+    cache.insert(
+        &revoc_reg_def.id,
+        rev_reg_idx,
+        CachedRevocationState {
+            state: state.clone(),
+            changed,
+        },
+    );
+
+    trace!(
+        "get_or_create_revocation_state <<< cache: {:?}",
+        cache.len()
+    );
+
+    Ok(state)
+}
+
+/// Build the registry delta between two states of a registry, given the credential
+/// indices which differ from the issuance default in each
+fn witness_update_delta(
+    (prev_reg, prev_changed): (&CryptoRevocationRegistry, &BTreeSet<u32>),
+    (rev_reg, changed): (&CryptoRevocationRegistry, &BTreeSet<u32>),
+    issuance_by_default: bool,
+) -> CryptoRevocationRegistryDelta {
+    let added = changed.difference(prev_changed).copied().collect();
+    let removed = prev_changed.difference(changed).copied().collect();
+    let (issued, revoked) = if issuance_by_default {
+        (removed, added)
+    } else {
+        (added, removed)
+    };
+    CryptoRevocationRegistryDelta::from_parts(Some(prev_reg), rev_reg, &issued, &revoked)
+}
+
+/// Determine whether a credential is revoked as of `timestamp`, without requiring
+/// access to the tails file. The delta with the latest timestamp at or before
+/// `timestamp` is used, and must describe the registry at its timestamp as accepted
+/// by `create_or_update_revocation_state` without a previous state.
+///
+/// A credential which has not been issued in a registry using `ISSUANCE_ON_DEMAND`
+/// is also reported as revoked, as it could not be presented.
+pub fn check_credential_revocation_status(
+    credential: &Credential,
+    rev_reg_def: &RevocationRegistryDefinition,
+    deltas_by_timestamp: &HashMap<u64, &RevocationRegistryDelta>,
+    timestamp: u64,
+) -> Result<bool> {
+    trace!(
+        "check_credential_revocation_status >>> credential: {:?}, rev_reg_def: {:?}, \
+timestamps: {:?}, timestamp: {}",
+        secret!(credential),
+        rev_reg_def,
+        deltas_by_timestamp.keys(),
+        timestamp
+    );
+
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def_v1) = rev_reg_def;
+    if credential.rev_reg_id.as_ref() != Some(&rev_reg_def_v1.id) {
+        return Err(err_msg!(
+            "Credential does not belong to the revocation registry {:?}",
+            rev_reg_def_v1.id
+        ));
+    }
+    let rev_reg_idx = credential
+        .signature
+        .extract_index()
+        .ok_or_else(|| err_msg!("Credential has no revocation registry index"))?;
+
+    let revoked =
+        revocation_index_status(rev_reg_def, deltas_by_timestamp, rev_reg_idx, timestamp)?;
+
+    trace!(
+        "check_credential_revocation_status <<< revoked: {:?}",
+        revoked
+    );
+
+    Ok(revoked)
+}
+
+fn revocation_index_status(
+    rev_reg_def: &RevocationRegistryDefinition,
+    deltas_by_timestamp: &HashMap<u64, &RevocationRegistryDelta>,
+    rev_reg_idx: u32,
+    timestamp: u64,
+) -> Result<bool> {
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+    if rev_reg_idx == 0 || rev_reg_idx > rev_reg_def.value.max_cred_num {
+        return Err(err_msg!(
+            "Invalid credential revocation index: {}",
+            rev_reg_idx
+        ));
+    }
+
+    let delta_ts = deltas_by_timestamp
+        .keys()
+        .copied()
+        .filter(|ts| *ts <= timestamp)
+        .max()
+        .ok_or_else(|| {
+            err_msg!(
+                "No revocation registry delta found at or before timestamp {}",
+                timestamp
+            )
+        })?;
+    let RevocationRegistryDelta::RevocationRegistryDeltaV1(delta) = deltas_by_timestamp[&delta_ts];
+
+    // interpret the delta in the same manner as when creating a witness
+    if rev_reg_def.value.issuance_type.to_bool() {
+        Ok(delta_indices(&delta.value, "revoked")?.contains(&rev_reg_idx))
+    } else {
+        Ok(!delta_indices(&delta.value, "issued")?.contains(&rev_reg_idx))
+    }
+}
+
+/// Read the set of credential indices listed under `field` in a revocation registry delta
+fn delta_indices(delta: &CryptoRevocationRegistryDelta, field: &str) -> Result<BTreeSet<u32>> {
+    let mut value = serde_json::to_value(delta)?;
+    match value.get_mut(field).map(serde_json::Value::take) {
+        Some(indices) => serde_json::from_value(indices)
+            .map_err(err_map!(Unexpected, "Invalid revocation registry delta")),
+        None => Ok(BTreeSet::new()),
+    }
+}
+
+/// Build a revocation state from a witness computed by the issuer, without access
+/// to the tails file. `rev_reg_delta` must describe the registry at the timestamp
+/// of the witness update.
+pub fn create_revocation_state_from_witness(
+    revoc_reg_def: &RevocationRegistryDefinition,
+    rev_reg_delta: &RevocationRegistryDelta,
+    witness_update: &RevocationWitnessUpdate,
+) -> Result<CredentialRevocationState> {
+    trace!(
+        "create_revocation_state_from_witness >>> revoc_reg_def: {:?}, rev_reg_delta: {:?}, \
+witness_update: {:?}",
+        revoc_reg_def,
+        rev_reg_delta,
+        witness_update
+    );
+
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(revoc_reg_def) = revoc_reg_def;
+    let RevocationRegistryDelta::RevocationRegistryDeltaV1(rev_reg_delta) = rev_reg_delta;
+    if witness_update.rev_reg_id != revoc_reg_def.id {
+        return Err(err_msg!(
+            "Witness update does not match the revocation registry definition"
+        ));
+    }
+    if witness_update.rev_reg_idx == 0
+        || witness_update.rev_reg_idx > revoc_reg_def.value.max_cred_num
+    {
+        return Err(err_msg!(
+            InvalidUserRevocId,
+            "Invalid credential revocation index: {}",
+            witness_update.rev_reg_idx
+        ));
+    }
+
+    Ok(CredentialRevocationState {
+        witness: witness_update.witness.clone(),
+        rev_reg: CryptoRevocationRegistry::from(rev_reg_delta.value.clone()),
+        timestamp: witness_update.timestamp,
+    })
+}
+
+/// Select the revocation registry timestamp to present for a requested referent.
+///
+/// Of the available timestamps accepted by the configured non-revocation interval
+/// policy, the latest at or before the end of the requested interval is chosen, as
+/// RFC 0441 expects of a holder, so that a verifier applying the same policy will
+/// accept it. An accepted timestamp after the end of the interval is only chosen
+/// when there is no earlier one. Returns `None` when no non-revocation interval
+/// applies to the referent.
+pub fn select_revocation_timestamp(
+    pres_req: &PresentationRequest,
+    referent: &str,
+    timestamps: &[u64],
+) -> Result<Option<u64>> {
+    trace!(
+        "select_revocation_timestamp >>> pres_req: {:?}, referent: {:?}, timestamps: {:?}",
+        pres_req,
+        referent,
+        timestamps
+    );
+
+    let pres_req = pres_req.value();
+    let local_interval = if let Some(info) = pres_req.requested_attributes.get(referent) {
+        &info.non_revoked
+    } else if let Some(info) = pres_req.requested_predicates.get(referent) {
+        &info.non_revoked
+    } else {
+        return Err(err_msg!(
+            "Referent \"{}\" not found in presentation request",
+            referent
+        ));
+    };
+    let interval = match get_non_revoc_interval(&pres_req.non_revoked, local_interval) {
+        Some(interval) => effective_interval(&interval),
+        None => return Ok(None),
+    };
+
+    let end = interval.to.unwrap_or(u64::MAX);
+    let accepted = timestamps.iter().copied().filter(|timestamp| {
+        is_timestamp_current(*timestamp) && is_timestamp_in_interval(*timestamp, &interval)
+    });
+    let timestamp = accepted
+        .clone()
+        .filter(|timestamp| *timestamp <= end)
+        .max()
+        .or_else(|| accepted.min())
+        .ok_or_else(|| {
+            err_msg!(
+                "No available timestamp satisfies the non-revocation interval {:?}",
+                interval
+            )
+        })?;
+
+    trace!("select_revocation_timestamp <<< timestamp: {:?}", timestamp);
+
+    Ok(Some(timestamp))
+}
+
+/// Find groups of duplicate credentials, having the same credential definition and
+/// attribute values, recommending which credential of each group to keep.
+///
+/// A credential known not to be revoked according to the provided status lists is
+/// preferred, then a revocable credential, then the earliest in the collection.
+pub fn find_duplicate_credentials(
+    credentials: &[&Credential],
+    rev_status_lists: &[&RevocationStatusList],
+) -> Vec<DuplicateCredentials> {
+    let revoked = |cred: &Credential| -> Option<bool> {
+        let rev_reg_id = cred.rev_reg_id.as_ref()?;
+        let rev_reg_idx = cred.signature.extract_index()?;
+        rev_status_lists
+            .iter()
+            .find(|list| &list.rev_reg_def_id == rev_reg_id)?
+            .is_revoked(rev_reg_idx)
+    };
+
+    let mut groups: Vec<(_, Vec<usize>)> = Vec::new();
+    for (idx, cred) in credentials.iter().enumerate() {
+        let mut values = cred
+            .values
+            .0
+            .iter()
+            .map(|(name, value)| (attr_common_view(name), value.raw.as_str()))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        let key = (&cred.cred_def_id, values);
+        match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+            Some((_, members)) => members.push(idx),
+            None => groups.push((key, vec![idx])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(_, members)| {
+            let rank = |idx: &usize| {
+                let cred = credentials[*idx];
+                let status = match revoked(cred) {
+                    Some(false) => 0,
+                    None => 1,
+                    Some(true) => 2,
+                };
+                (status, cred.rev_reg_id.is_none(), *idx)
+            };
+            let keep = *members.iter().min_by_key(|idx| rank(idx)).unwrap();
+            DuplicateCredentials {
+                keep,
+                remove: members.into_iter().filter(|idx| *idx != keep).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Find the credentials which can be used to satisfy each referent of a presentation
+/// request, identified by their position in the provided collection.
+///
+/// Restrictions are evaluated as the verifier would evaluate them with the requested
+/// attributes revealed, and predicates are checked against the encoded credential values.
+pub fn get_credentials_for_presentation_request<'c>(
+    pres_req: &PresentationRequest,
+    credentials: impl IntoIterator<Item = &'c Credential>,
+) -> Result<CredentialsForPresentationRequest> {
+    trace!(
+        "get_credentials_for_presentation_request >>> pres_req: {:?}",
+        pres_req
+    );
+
+    pres_req.validate()?;
+    let pres_req_val = pres_req.value();
+
+    let mut found = CredentialsForPresentationRequest {
+        attrs: pres_req_val
+            .requested_attributes
+            .keys()
+            .map(|referent| (referent.clone(), Vec::new()))
+            .collect(),
+        predicates: pres_req_val
+            .requested_predicates
+            .keys()
+            .map(|referent| (referent.clone(), Vec::new()))
+            .collect(),
+    };
+
+    for (idx, cred) in credentials.into_iter().enumerate() {
+        let cred_attrs = &cred.values.0;
+
+        for (referent, info) in pres_req_val.requested_attributes.iter() {
+            let names = match (&info.name, &info.names) {
+                (Some(name), _) => vec![name.as_str()],
+                (None, Some(names)) => names.iter().map(String::as_str).collect(),
+                (None, None) => vec![],
+            };
+            // every requested attribute must be present in the credential
+            let attr_value_map = match names
+                .into_iter()
+                .map(|name| {
+                    get_attr_normalized(cred_attrs, name)
+                        .map(|value| (name.to_string(), Some(value.raw.as_str())))
+                })
+                .collect::<Option<HashMap<_, _>>>()
+            {
+                Some(map) if !map.is_empty() => map,
+                _ => continue,
+            };
+            if credential_satisfies_restrictions(cred, &attr_value_map, &info.restrictions) {
+                found.attrs.get_mut(referent).unwrap().push(idx);
+            }
+        }
+
+        for (referent, info) in pres_req_val.requested_predicates.iter() {
+            let satisfied = get_attr_normalized(cred_attrs, &info.name)
+                .and_then(|value| value.encoded.parse::<i32>().ok())
+                .map(|value| match info.p_type {
+                    PredicateTypes::GE => value >= info.p_value,
+                    PredicateTypes::GT => value > info.p_value,
+                    PredicateTypes::LE => value <= info.p_value,
+                    PredicateTypes::LT => value < info.p_value,
+                })
+                .unwrap_or(false);
+            if !satisfied {
+                continue;
+            }
+            let mut attr_value_map = HashMap::new();
+            attr_value_map.insert(info.name.clone(), None);
+            if credential_satisfies_restrictions(cred, &attr_value_map, &info.restrictions) {
+                found.predicates.get_mut(referent).unwrap().push(idx);
+            }
+        }
+    }
+
+    trace!(
+        "get_credentials_for_presentation_request <<< found: {:?}",
+        found
+    );
+
+    Ok(found)
+}
+
+fn credential_satisfies_restrictions(
+    cred: &Credential,
+    attr_value_map: &HashMap<String, Option<&str>>,
+    restrictions: &Option<Query>,
+) -> bool {
+    match restrictions {
+        Some(query) => {
+            verifier::check_restrictions(attr_value_map, query, &cred.schema_id, &cred.cred_def_id)
+                .is_ok()
+        }
+        None => true,
+    }
+}
+
 fn prepare_credential_for_proving(
     requested_attributes: HashSet<(String, bool)>,
     requested_predicates: HashSet<String>,
@@ -375,10 +1082,7 @@ fn get_credential_values_for_attribute(
         requested_attr
     );
 
-    let res = credential_attrs
-        .iter()
-        .find(|&(ref key, _)| attr_common_view(key) == attr_common_view(&requested_attr))
-        .map(|(_, values)| values.clone());
+    let res = get_attr_normalized(credential_attrs, requested_attr).cloned();
 
     trace!(
         "get_credential_values_for_attribute <<< res: {:?}",
@@ -388,6 +1092,126 @@ fn get_credential_values_for_attribute(
     res
 }
 
+fn check_predicate_values(
+    credential_attrs: &HashMap<String, AttributeValues>,
+    req_predicates_for_credential: &[RequestedPredicateInfo],
+) -> Result<()> {
+    for predicate in req_predicates_for_credential {
+        let name = &predicate.predicate_info.name;
+        let attr_values = get_attr_normalized(credential_attrs, name)
+            .ok_or_else(|| err_msg!("Credential value not found for attribute {:?}", name))?;
+        // predicates can only be proven over encoded values within 32-bit range
+        let value = attr_values.encoded.parse::<i32>().map_err(|_| {
+            err_msg!(
+                "Credential value for attribute {:?} is outside the range supported by predicates",
+                name
+            )
+        })?;
+        check_predicate_supported(&predicate.predicate_info)?;
+        let delta = predicate_delta(
+            &predicate.predicate_info.p_type,
+            predicate.predicate_info.p_value,
+            value,
+        );
+        if delta < 0 {
+            return Err(err_msg!(
+                "Credential value for attribute {:?} does not satisfy the predicate {:?}",
+                name,
+                predicate.predicate_referent
+            ));
+        }
+        // the difference is also proven as a signed 32-bit value
+        if delta > i32::MAX as i64 {
+            return Err(err_msg!(
+                "Difference between the credential value for attribute {:?} and the predicate \
+                value is outside the range supported by predicates",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that each self-attested value answers a requested attribute which
+/// permits self-attestation
+fn check_self_attested_attributes(
+    pres_req: &PresentationRequestPayload,
+    self_attested: &HashMap<String, String>,
+) -> Result<()> {
+    let mut referents = self_attested.keys().collect::<Vec<_>>();
+    referents.sort_unstable();
+    for referent in referents {
+        let info = pres_req.requested_attributes.get(referent).ok_or_else(|| {
+            err_msg!(
+                "Self-attested attribute \"{}\" not found in presentation request",
+                referent
+            )
+        })?;
+        if info.names.is_some() {
+            return Err(err_msg!(
+                "Attribute group \"{}\" cannot be self-attested",
+                referent
+            ));
+        }
+        if !info.allows_self_attestation() {
+            return Err(err_msg!(
+                "Attribute \"{}\" has restrictions and cannot be self-attested",
+                referent
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that a credential provides every attribute of the requested attribute
+/// groups it is presented for, and that each group's restrictions hold for all
+/// of its values
+fn check_attribute_groups(
+    credential: &Credential,
+    identifier: &Identifier,
+    req_attrs_for_credential: &[RequestedAttributeInfo],
+) -> Result<()> {
+    for attr in req_attrs_for_credential {
+        let names = match attr.attr_info.names.as_ref() {
+            Some(names) => names,
+            None => continue,
+        };
+        let mut attr_value_map = HashMap::new();
+        let mut missing = Vec::new();
+        for name in names {
+            match get_attr_normalized(&credential.values.0, name) {
+                Some(value) => {
+                    attr_value_map.insert(
+                        name.clone(),
+                        Some(value.raw.as_str()).filter(|_| attr.revealed),
+                    );
+                }
+                None => missing.push(name),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(err_msg!(
+                "Credential does not satisfy attribute group \"{}\", missing values for: {:?}",
+                attr.attr_referent,
+                missing
+            ));
+        }
+        if let Some(query) = attr.attr_info.restrictions.as_ref() {
+            verifier::check_restrictions(
+                &attr_value_map,
+                query,
+                &identifier.schema_id,
+                &identifier.cred_def_id,
+            )
+            .map_err(err_map!(
+                "Credential does not satisfy the restrictions of attribute group \"{}\"",
+                attr.attr_referent
+            ))?;
+        }
+    }
+    Ok(())
+}
+
 fn update_requested_proof(
     req_attrs_for_credential: Vec<RequestedAttributeInfo>,
     req_predicates_for_credential: Vec<RequestedPredicateInfo>,
@@ -648,6 +1472,8 @@ mod tests {
             AttributeValues {
                 raw: "Alex".to_string(),
                 encoded: "123".to_string(),
+                salt: None,
+                media_type: None,
             }
         }
 
@@ -700,4 +1526,618 @@ mod tests {
             assert_eq!(_attr_values(), res);
         }
     }
+
+    mod check_predicate_values {
+        use indy_data_types::anoncreds::pres_request::PredicateInfo;
+
+        use super::*;
+
+        fn _req_predicates() -> Vec<RequestedPredicateInfo> {
+            _req_predicate(PredicateTypes::GE, 100)
+        }
+
+        fn _req_predicate(p_type: PredicateTypes, p_value: i32) -> Vec<RequestedPredicateInfo> {
+            vec![RequestedPredicateInfo {
+                predicate_referent: "predicate_referent".to_string(),
+                predicate_info: PredicateInfo {
+                    name: "balance".to_string(),
+                    p_type,
+                    p_value,
+                    restrictions: None,
+                    non_revoked: None,
+                },
+            }]
+        }
+
+        fn _cred_value(raw: &str) -> HashMap<String, AttributeValues> {
+            let mut values = MakeCredentialValues::default();
+            values.add_raw("Balance", raw).unwrap();
+            values.0 .0.clone()
+        }
+
+        fn _cred_values(raw: &str) -> HashMap<String, AttributeValues> {
+            let mut values = MakeCredentialValues::default();
+            values.add_decimal("Balance", raw, 2).unwrap();
+            values.0 .0.clone()
+        }
+
+        #[test]
+        fn check_predicate_values_works() {
+            check_predicate_values(&_cred_values("1.25"), &_req_predicates()).unwrap();
+        }
+
+        #[test]
+        fn check_predicate_values_works_for_out_of_range_value() {
+            let res = check_predicate_values(&_cred_values("100000000"), &_req_predicates());
+            assert_kind!(Input, res);
+        }
+
+        #[test]
+        fn check_predicate_values_works_for_negative_values() {
+            let values = _cred_value("-5");
+            for (p_type, p_value) in vec![
+                (PredicateTypes::GE, -10),
+                (PredicateTypes::GT, -6),
+                (PredicateTypes::LE, 0),
+                (PredicateTypes::LT, -4),
+            ] {
+                check_predicate_values(&values, &_req_predicate(p_type, p_value)).unwrap();
+            }
+            for (p_type, p_value) in vec![
+                (PredicateTypes::GE, -4),
+                (PredicateTypes::GT, -5),
+                (PredicateTypes::LE, -6),
+                (PredicateTypes::LT, -5),
+            ] {
+                let res = check_predicate_values(&values, &_req_predicate(p_type, p_value));
+                assert_kind!(Input, res);
+            }
+        }
+
+        #[test]
+        fn check_predicate_values_works_for_out_of_range_delta() {
+            let res = check_predicate_values(
+                &_cred_value("-2147483648"),
+                &_req_predicate(PredicateTypes::LE, i32::MAX),
+            );
+            assert_kind!(Input, res);
+            let res = check_predicate_values(
+                &_cred_value("2147483647"),
+                &_req_predicate(PredicateTypes::GT, i32::MAX),
+            );
+            assert_kind!(Input, res);
+        }
+
+        #[test]
+        fn check_predicate_values_works_for_missing_value() {
+            let res = check_predicate_values(&HashMap::new(), &_req_predicates());
+            assert_kind!(Input, res);
+        }
+    }
+
+    mod select_revocation_timestamp {
+        use indy_data_types::anoncreds::pres_request::{AttributeInfo, NonRevocedInterval};
+
+        use super::*;
+
+        const REFERENT: &str = "attribute_referent";
+
+        fn _proof_req(non_revoked: Option<NonRevocedInterval>) -> PresentationRequest {
+            PresentationRequest::PresentationRequestV1(PresentationRequestPayload {
+                nonce: new_nonce().unwrap(),
+                name: "Job-Application".to_string(),
+                version: "0.1".to_string(),
+                requested_attributes: hashmap!(
+                    REFERENT.to_string() => AttributeInfo {
+                        name: Some("name".to_string()),
+                        names: None,
+                        restrictions: None,
+                        non_revoked: None,
+                    }
+                ),
+                requested_predicates: HashMap::new(),
+                non_revoked,
+            })
+        }
+
+        #[test]
+        fn select_revocation_timestamp_works() {
+            let _config = ConfigGuard::set(CredxConfig::default());
+            let proof_req = _proof_req(Some(NonRevocedInterval {
+                from: Some(100),
+                to: Some(200),
+            }));
+
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[50, 150, 210, 300]);
+            assert_eq!(Some(150), res.unwrap());
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[50, 210]);
+            assert_eq!(Some(50), res.unwrap());
+        }
+
+        #[test]
+        fn select_revocation_timestamp_works_with_tolerance() {
+            let _config = ConfigGuard::set(CredxConfig {
+                non_revoked_interval_policy: NonRevokedIntervalPolicy::Rfc0441,
+                non_revoked_tolerance: 20,
+                ..CredxConfig::default()
+            });
+            let proof_req = _proof_req(Some(NonRevocedInterval {
+                from: None,
+                to: Some(200),
+            }));
+
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[150, 210]);
+            assert_eq!(Some(150), res.unwrap());
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[210, 250]);
+            assert_eq!(Some(210), res.unwrap());
+        }
+
+        #[test]
+        fn select_revocation_timestamp_works_for_strict_policy() {
+            let _config = ConfigGuard::set(CredxConfig {
+                non_revoked_interval_policy: NonRevokedIntervalPolicy::Strict,
+                ..CredxConfig::default()
+            });
+            let proof_req = _proof_req(Some(NonRevocedInterval {
+                from: Some(100),
+                to: Some(200),
+            }));
+
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[50, 120, 150, 210]);
+            assert_eq!(Some(150), res.unwrap());
+            select_revocation_timestamp(&proof_req, REFERENT, &[50, 210]).unwrap_err();
+        }
+
+        #[test]
+        fn select_revocation_timestamp_works_without_interval() {
+            let proof_req = _proof_req(None);
+
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[50]);
+            assert_eq!(None, res.unwrap());
+        }
+
+        #[test]
+        fn select_revocation_timestamp_works_for_latest_before_interval_end() {
+            let _config = ConfigGuard::set(CredxConfig::default());
+            let proof_req = _proof_req(Some(NonRevocedInterval {
+                from: None,
+                to: Some(200),
+            }));
+
+            let res = select_revocation_timestamp(&proof_req, REFERENT, &[50, 150]);
+            assert_eq!(Some(150), res.unwrap());
+        }
+
+        #[test]
+        fn select_revocation_timestamp_fails_for_no_accepted_timestamp() {
+            let _config = ConfigGuard::set(CredxConfig {
+                non_revoked_interval_policy: NonRevokedIntervalPolicy::Rfc0441,
+                ..CredxConfig::default()
+            });
+            let proof_req = _proof_req(Some(NonRevocedInterval {
+                from: None,
+                to: Some(200),
+            }));
+
+            select_revocation_timestamp(&proof_req, REFERENT, &[250, 300]).unwrap_err();
+            select_revocation_timestamp(&proof_req, "other_referent", &[200]).unwrap_err();
+        }
+    }
+
+    mod create_revocation_states_batch {
+        use super::*;
+        use crate::services::issuer::*;
+        use crate::tails::{TailsFileReader, TailsFileWriter};
+        use crate::ursa::pair::PointG2;
+        use std::iter::FromIterator;
+
+        fn omega(witness: &Witness) -> PointG2 {
+            let witness = serde_json::to_value(witness).unwrap();
+            PointG2::from_string(witness["omega"].as_str().unwrap()).unwrap()
+        }
+
+        #[test]
+        fn create_revocation_states_batch_works() {
+            let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+            let schema = create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["name".to_owned()]),
+                None,
+            )
+            .unwrap();
+            let (cred_def, _, _) = create_credential_definition(
+                &origin_did,
+                &schema,
+                "tag",
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(true),
+            )
+            .unwrap();
+            let tails_dir = tempfile::tempdir().unwrap();
+            let mut tails_writer =
+                TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+            let (rev_reg_def, _, rev_reg, init_delta) = create_revocation_registry(
+                &origin_did,
+                &cred_def,
+                "tag",
+                RegistryType::CL_ACCUM,
+                IssuanceType::ISSUANCE_BY_DEFAULT,
+                5,
+                &mut tails_writer,
+            )
+            .unwrap();
+            let tails_path = match &rev_reg_def {
+                RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                    v1.value.tails_location.clone()
+                }
+            };
+            let tails_reader = TailsFileReader::new(&tails_path);
+
+            let (rev_reg, delta) =
+                revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+            let delta_200 = merge_revocation_registry_deltas(&init_delta, &delta).unwrap();
+            let (_, delta) = update_revocation_registry(
+                &rev_reg_def,
+                &rev_reg,
+                BTreeSet::from_iter(vec![2]),
+                BTreeSet::from_iter(vec![4, 5]),
+                &tails_reader,
+            )
+            .unwrap();
+            let delta_300 = merge_revocation_registry_deltas(&delta_200, &delta).unwrap();
+
+            let deltas = hashmap!(100 => &init_delta, 200 => &delta_200, 300 => &delta_300);
+            let states =
+                create_revocation_states_batch(tails_reader, &rev_reg_def, &deltas, 1).unwrap();
+            assert_eq!(states.len(), 3);
+            for (timestamp, delta) in deltas {
+                let expected = create_or_update_revocation_state(
+                    TailsFileReader::new(&tails_path),
+                    &rev_reg_def,
+                    delta,
+                    1,
+                    timestamp,
+                    None,
+                )
+                .unwrap();
+                let state = &states[&timestamp];
+                assert_eq!(state.timestamp, timestamp);
+                assert_eq!(omega(&state.witness), omega(&expected.witness));
+                assert_eq!(
+                    serde_json::to_value(&state.rev_reg).unwrap(),
+                    serde_json::to_value(&expected.rev_reg).unwrap()
+                );
+            }
+        }
+
+        #[test]
+        fn get_or_create_revocation_state_works() {
+            let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+            let schema = create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["name".to_owned()]),
+                None,
+            )
+            .unwrap();
+            let (cred_def, _, _) = create_credential_definition(
+                &origin_did,
+                &schema,
+                "tag",
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(true),
+            )
+            .unwrap();
+            let tails_dir = tempfile::tempdir().unwrap();
+            let mut tails_writer =
+                TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+            let (rev_reg_def, _, rev_reg, init_delta) = create_revocation_registry(
+                &origin_did,
+                &cred_def,
+                "tag",
+                RegistryType::CL_ACCUM,
+                IssuanceType::ISSUANCE_BY_DEFAULT,
+                5,
+                &mut tails_writer,
+            )
+            .unwrap();
+            let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def_v1) =
+                &rev_reg_def;
+            let tails_path = rev_reg_def_v1.value.tails_location.clone();
+            let tails_reader = TailsFileReader::new(&tails_path);
+
+            let (_, delta) = revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+            let delta_200 = merge_revocation_registry_deltas(&init_delta, &delta).unwrap();
+
+            let mut cache = RevocationStateCache::with_capacity(2);
+            for (timestamp, delta) in [(100, &init_delta), (200, &delta_200)] {
+                let state = get_or_create_revocation_state(
+                    &mut cache,
+                    TailsFileReader::new(&tails_path),
+                    &rev_reg_def,
+                    delta,
+                    1,
+                    timestamp,
+                )
+                .unwrap();
+                let expected = create_or_update_revocation_state(
+                    TailsFileReader::new(&tails_path),
+                    &rev_reg_def,
+                    delta,
+                    1,
+                    timestamp,
+                    None,
+                )
+                .unwrap();
+                assert_eq!(state.timestamp, timestamp);
+                assert_eq!(omega(&state.witness), omega(&expected.witness));
+            }
+            assert_eq!(cache.timestamps(&rev_reg_def_v1.id, 1), vec![100, 200]);
+
+            // a cached state is returned without consulting the delta
+            let state = get_or_create_revocation_state(
+                &mut cache,
+                TailsFileReader::new(&tails_path),
+                &rev_reg_def,
+                &init_delta,
+                1,
+                200,
+            )
+            .unwrap();
+            assert_eq!(
+                omega(&state.witness),
+                omega(&cache.get(&rev_reg_def_v1.id, 1, 200).unwrap().witness)
+            );
+
+            get_or_create_revocation_state(
+                &mut cache,
+                tails_reader,
+                &rev_reg_def,
+                &delta_200,
+                3,
+                300,
+            )
+            .unwrap();
+            assert_eq!(cache.len(), 2);
+            assert!(cache.get(&rev_reg_def_v1.id, 1, 100).is_none());
+
+            let restored: RevocationStateCache =
+                serde_json::from_value(serde_json::to_value(&cache).unwrap()).unwrap();
+            assert_eq!(restored.capacity(), 2);
+            assert_eq!(restored.timestamps(&rev_reg_def_v1.id, 3), vec![300]);
+
+            assert_eq!(cache.evict_before(300), 1);
+            assert_eq!(cache.len(), 1);
+            cache.remove_registry(&rev_reg_def_v1.id);
+            assert!(cache.is_empty());
+        }
+
+        #[test]
+        fn check_revocation_status_works() {
+            let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+            let schema = create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["name".to_owned()]),
+                None,
+            )
+            .unwrap();
+            let (cred_def, _, _) = create_credential_definition(
+                &origin_did,
+                &schema,
+                "tag",
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(true),
+            )
+            .unwrap();
+            let tails_dir = tempfile::tempdir().unwrap();
+            let mut tails_writer =
+                TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+            let (rev_reg_def, _, rev_reg, init_delta) = create_revocation_registry(
+                &origin_did,
+                &cred_def,
+                "tag",
+                RegistryType::CL_ACCUM,
+                IssuanceType::ISSUANCE_ON_DEMAND,
+                5,
+                &mut tails_writer,
+            )
+            .unwrap();
+            let tails_reader = TailsFileReader::new(match &rev_reg_def {
+                RevocationRegistryDefinition::RevocationRegistryDefinitionV1(v1) => {
+                    v1.value.tails_location.as_str()
+                }
+            });
+
+            let (rev_reg, delta) = update_revocation_registry(
+                &rev_reg_def,
+                &rev_reg,
+                BTreeSet::from_iter(vec![1, 2, 3]),
+                BTreeSet::new(),
+                &tails_reader,
+            )
+            .unwrap();
+            let delta_100 = merge_revocation_registry_deltas(&init_delta, &delta).unwrap();
+            let (_, delta) = revoke_credential(&rev_reg_def, &rev_reg, 2, &tails_reader).unwrap();
+            let delta_200 = merge_revocation_registry_deltas(&delta_100, &delta).unwrap();
+
+            let deltas = hashmap!(100 => &delta_100, 200 => &delta_200);
+            let status = |idx, ts| revocation_index_status(&rev_reg_def, &deltas, idx, ts);
+            assert!(!status(2, 100).unwrap());
+            assert!(status(2, 250).unwrap());
+            assert!(!status(1, 200).unwrap());
+            assert!(status(4, 200).unwrap());
+            status(1, 50).unwrap_err();
+            status(6, 200).unwrap_err();
+        }
+    }
+
+    mod check_attribute_groups {
+        use indy_data_types::anoncreds::pres_request::AttributeInfo;
+
+        use super::*;
+        use crate::services::issuer::*;
+
+        fn _credential() -> Credential {
+            let origin_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+            let schema = create_schema(
+                &origin_did,
+                "schema",
+                "1.0",
+                AttributeNames::from(vec!["name".to_owned(), "sex".to_owned()]),
+                None,
+            )
+            .unwrap();
+            let (cred_def, cred_def_priv, key_proof) = create_credential_definition(
+                &origin_did,
+                &schema,
+                "tag",
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(false),
+            )
+            .unwrap();
+            let cred_offer =
+                create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+            let (cred_req, _) = create_credential_request(
+                &origin_did,
+                &cred_def,
+                &create_master_secret().unwrap(),
+                "default",
+                &cred_offer,
+            )
+            .unwrap();
+            let mut values = MakeCredentialValues::default();
+            values.add_raw("name", "Alex").unwrap();
+            values.add_raw("sex", "male").unwrap();
+            let (credential, _, _) = create_credential(
+                &cred_def,
+                &cred_def_priv,
+                &cred_offer,
+                &cred_req,
+                values.into(),
+                None,
+            )
+            .unwrap();
+            credential
+        }
+
+        fn _group(
+            names: &[&str],
+            restrictions: Option<Query>,
+            revealed: bool,
+        ) -> RequestedAttributeInfo {
+            RequestedAttributeInfo {
+                attr_referent: "attr1_referent".to_string(),
+                attr_info: AttributeInfo {
+                    name: None,
+                    names: Some(names.iter().map(|name| name.to_string()).collect()),
+                    restrictions,
+                    non_revoked: None,
+                },
+                revealed,
+            }
+        }
+
+        #[test]
+        fn check_attribute_groups_works() {
+            let credential = _credential();
+            let identifier = Identifier {
+                schema_id: credential.schema_id.clone(),
+                cred_def_id: credential.cred_def_id.clone(),
+                rev_reg_id: None,
+                timestamp: None,
+            };
+            let check = |group| check_attribute_groups(&credential, &identifier, &[group]);
+            let value_eq = |value: &str| {
+                Some(Query::Eq(
+                    "attr::name::value".to_string(),
+                    value.to_string(),
+                ))
+            };
+
+            check(_group(&["name", "sex"], None, true)).unwrap();
+            check(_group(&["name", "sex"], value_eq("Alex"), true)).unwrap();
+            check(_group(&["name", "sex"], value_eq("Alec"), true)).unwrap_err();
+            check(_group(&["name", "email"], None, true)).unwrap_err();
+            check(_group(&["name", "email"], None, false)).unwrap_err();
+
+            // attribute values which are not revealed are not checked, as for the verifier
+            check(_group(&["name", "sex"], value_eq("Alec"), false)).unwrap();
+            let cred_def_eq = |cred_def_id: &str| {
+                Some(Query::Eq(
+                    "cred_def_id".to_string(),
+                    cred_def_id.to_string(),
+                ))
+            };
+            check(_group(
+                &["name", "sex"],
+                cred_def_eq(&credential.cred_def_id.0),
+                false,
+            ))
+            .unwrap();
+            check(_group(&["name", "sex"], cred_def_eq("other"), false)).unwrap_err();
+        }
+    }
+
+    mod check_self_attested_attributes {
+        use indy_data_types::anoncreds::pres_request::AttributeInfo;
+
+        use super::*;
+
+        fn _attr_info(name: Option<&str>, restrictions: Option<Query>) -> AttributeInfo {
+            AttributeInfo {
+                name: name.map(str::to_string),
+                names: if name.is_none() {
+                    Some(vec!["first".to_string(), "last".to_string()])
+                } else {
+                    None
+                },
+                restrictions,
+                non_revoked: None,
+            }
+        }
+
+        fn _proof_req() -> PresentationRequestPayload {
+            PresentationRequestPayload {
+                nonce: new_nonce().unwrap(),
+                name: "Job-Application".to_string(),
+                version: "0.1".to_string(),
+                requested_attributes: hashmap!(
+                    "open".to_string() => _attr_info(Some("phone"), None),
+                    "empty".to_string() => _attr_info(Some("email"), Some(Query::And(vec![]))),
+                    "restricted".to_string() => _attr_info(
+                        Some("age"),
+                        Some(Query::Eq("schema_name".to_string(), "gvt".to_string()))
+                    ),
+                    "group".to_string() => _attr_info(None, None)
+                ),
+                requested_predicates: HashMap::new(),
+                non_revoked: None,
+            }
+        }
+
+        fn _check(referent: &str) -> Result<()> {
+            check_self_attested_attributes(
+                &_proof_req(),
+                &hashmap!(referent.to_string() => "value".to_string()),
+            )
+        }
+
+        #[test]
+        fn check_self_attested_attributes_works() {
+            _check("open").unwrap();
+            _check("empty").unwrap();
+        }
+
+        #[test]
+        fn check_self_attested_attributes_rejects_disallowed() {
+            _check("restricted").unwrap_err();
+            _check("group").unwrap_err();
+            _check("unknown").unwrap_err();
+        }
+    }
 }
@@ -129,8 +129,190 @@ impl TailsReaderImpl for TailsFileReader {
     }
 }
 
+/// A pending result from an asynchronous tails reader
+#[cfg(feature = "async")]
+pub type TailsFuture<'a> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<u8>>> + Send + 'a>>;
+
+/// A tails reader performing asynchronous reads, such as from the network
+#[cfg(feature = "async")]
+pub trait AsyncTailsReaderImpl: std::fmt::Debug + Send {
+    fn hash(&mut self) -> TailsFuture<'_>;
+    fn read(&mut self, size: usize, offset: usize) -> TailsFuture<'_>;
+}
+
+/// Adapts an asynchronous tails reader for use in witness creation.
+///
+/// Reads are awaited on the runtime the reader was created in, so the resulting
+/// `TailsReader` must be used on a blocking thread, see `spawn_with_tails_reader`.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncTailsReader<R: AsyncTailsReaderImpl> {
+    inner: R,
+    handle: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncTailsReaderImpl + 'static> AsyncTailsReader<R> {
+    /// Create a new adapter bound to the current tokio runtime
+    pub fn new(inner: R) -> Result<TailsReader> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(err_map!(InvalidState, "No tokio runtime available"))?;
+        Ok(TailsReader::new(Self { inner, handle }))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncTailsReaderImpl> TailsReaderImpl for AsyncTailsReader<R> {
+    fn hash(&mut self) -> Result<Vec<u8>> {
+        self.handle.block_on(self.inner.hash())
+    }
+
+    fn read(&mut self, size: usize, offset: usize) -> Result<Vec<u8>> {
+        self.handle.block_on(self.inner.read(size, offset))
+    }
+}
+
+/// Run an operation using a tails reader on a blocking thread, awaiting its result
+#[cfg(feature = "async")]
+pub async fn spawn_with_tails_reader<R, F, T>(reader: R, f: F) -> Result<T>
+where
+    R: AsyncTailsReaderImpl + 'static,
+    F: FnOnce(TailsReader) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let reader = AsyncTailsReader::new(reader)?;
+    tokio::task::spawn_blocking(move || f(reader))
+        .await
+        .map_err(err_map!(Unexpected, "Error running blocking task"))?
+}
+
+/// An asynchronous reader for a local tails file
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncTailsFileReader {
+    path: String,
+    file: Option<tokio::fs::File>,
+    hash: Option<Vec<u8>>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncTailsFileReader {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            file: None,
+            hash: None,
+        }
+    }
+
+    async fn open(&mut self) -> Result<&mut tokio::fs::File> {
+        if self.file.is_none() {
+            let file = tokio::fs::File::open(&self.path).await?;
+            self.file.replace(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTailsReaderImpl for AsyncTailsFileReader {
+    fn hash(&mut self) -> TailsFuture<'_> {
+        Box::pin(async move {
+            use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+            if let Some(hash) = self.hash.as_ref() {
+                return Ok(hash.clone());
+            }
+            let file = self.open().await?;
+            file.seek(SeekFrom::Start(0)).await?;
+            let mut hasher = Sha256::default();
+            let mut buf = [0u8; 1024];
+            loop {
+                let sz = file.read(&mut buf).await?;
+                if sz == 0 {
+                    break;
+                }
+                hasher.input(&buf[0..sz]);
+            }
+            let hash = hasher.result().to_vec();
+            self.hash = Some(hash.clone());
+            Ok(hash)
+        })
+    }
+
+    fn read(&mut self, size: usize, offset: usize) -> TailsFuture<'_> {
+        Box::pin(async move {
+            use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+            let mut buf = vec![0u8; size];
+            let file = self.open().await?;
+            file.seek(SeekFrom::Start(offset as u64)).await?;
+            file.read_exact(buf.as_mut_slice()).await?;
+            Ok(buf)
+        })
+    }
+}
+
+/// An in-memory tails reader, used for tails data which has not been persisted
+#[derive(Debug)]
+pub struct TailsBufferReader {
+    data: Vec<u8>,
+}
+
+impl TailsBufferReader {
+    pub fn new(data: Vec<u8>) -> TailsReader {
+        TailsReader::new(Self { data })
+    }
+}
+
+impl TailsReaderImpl for TailsBufferReader {
+    fn hash(&mut self) -> Result<Vec<u8>> {
+        Ok(Sha256::digest(&self.data).to_vec())
+    }
+
+    fn read(&mut self, size: usize, offset: usize) -> Result<Vec<u8>> {
+        offset
+            .checked_add(size)
+            .and_then(|end| self.data.get(offset..end))
+            .map(|bytes| bytes.to_vec())
+            .ok_or_else(|| err_msg!(IOError, "Read past the end of the tails data"))
+    }
+}
+
+/// The number of tails generated between progress reports
+const TAILS_PROGRESS_INTERVAL: u32 = 1000;
+
+/// Receives the number of tails generated so far and the total number of tails,
+/// returning `false` to cancel tails generation
+pub type TailsProgress<'a> = &'a mut dyn FnMut(u32, u32) -> bool;
+
 pub trait TailsWriter: std::fmt::Debug {
     fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)>;
+
+    /// Write the tails, periodically reporting progress. Writers which do not
+    /// override this method report no progress and cannot be cancelled.
+    fn write_with_progress(
+        &mut self,
+        generator: &mut RevocationTailsGenerator,
+        _progress: TailsProgress<'_>,
+    ) -> Result<(String, String)> {
+        self.write(generator)
+    }
+
+    /// Whether the written tails can be read back from the returned location
+    fn is_readable(&self) -> bool {
+        true
+    }
+
+    /// Open a reader for tails data previously written to `tails_location`
+    fn reader(&self, tails_location: &str) -> Option<TailsReader> {
+        if self.is_readable() {
+            Some(TailsFileReader::new(tails_location))
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -150,19 +332,16 @@ impl TailsFileWriter {
 
 impl TailsWriter for TailsFileWriter {
     fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)> {
+        self.write_with_progress(generator, &mut |_, _| true)
+    }
+
+    fn write_with_progress(
+        &mut self,
+        generator: &mut RevocationTailsGenerator,
+        progress: TailsProgress<'_>,
+    ) -> Result<(String, String)> {
         let mut tempf = tempfile::NamedTempFile::new_in(self.root_path.clone())?;
-        let file = tempf.as_file_mut();
-        let mut hasher = Sha256::default();
-        let version = &[0u8, 2u8];
-        file.write(version)?;
-        hasher.input(version);
-        while let Some(tail) = generator.try_next()? {
-            let tail_bytes = tail.to_bytes()?;
-            file.write(tail_bytes.as_slice())?;
-            hasher.input(tail_bytes);
-        }
-        let tails_size = &file.seek(SeekFrom::Current(0))?;
-        let hash = base58::encode(hasher.result());
+        let (hash, tails_size) = write_tails(generator, tempf.as_file_mut(), progress)?;
         let path = tempf.path().with_file_name(hash.clone());
         let _outf = match tempf.persist_noclobber(&path) {
             Ok(f) => f,
@@ -178,3 +357,180 @@ impl TailsWriter for TailsFileWriter {
         Ok((path, hash))
     }
 }
+
+/// Write the tails data into an arbitrary output stream, such as a pipe, socket
+/// or in-memory buffer. The provided location is reported as the tails location.
+pub struct TailsStreamWriter<W: Write> {
+    writer: W,
+    location: String,
+}
+
+impl<W: Write> TailsStreamWriter<W> {
+    pub fn new(writer: W, location: &str) -> Self {
+        Self {
+            writer,
+            location: location.to_owned(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> std::fmt::Debug for TailsStreamWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TailsStreamWriter")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl<W: Write> TailsWriter for TailsStreamWriter<W> {
+    fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)> {
+        self.write_with_progress(generator, &mut |_, _| true)
+    }
+
+    fn write_with_progress(
+        &mut self,
+        generator: &mut RevocationTailsGenerator,
+        progress: TailsProgress<'_>,
+    ) -> Result<(String, String)> {
+        let (hash, tails_size) = write_tails(generator, &mut self.writer, progress)?;
+        self.writer.flush()?;
+        debug!(
+            "TailsStreamWriter: wrote tails data [size {}]: {}",
+            tails_size, self.location
+        );
+        Ok((self.location.clone(), hash))
+    }
+
+    fn is_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Write the tails data into an in-memory buffer, such as for uploading to
+/// remote storage. Only the most recently written tails data is retained.
+#[derive(Debug, Default)]
+pub struct TailsBufferWriter {
+    location: Option<String>,
+    data: Vec<u8>,
+}
+
+impl TailsBufferWriter {
+    /// Create a new buffer writer. When no location is provided, the tails hash
+    /// is reported as the tails location.
+    pub fn new(location: Option<String>) -> Self {
+        Self {
+            location,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl TailsWriter for TailsBufferWriter {
+    fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)> {
+        self.write_with_progress(generator, &mut |_, _| true)
+    }
+
+    fn write_with_progress(
+        &mut self,
+        generator: &mut RevocationTailsGenerator,
+        progress: TailsProgress<'_>,
+    ) -> Result<(String, String)> {
+        let mut data = Vec::new();
+        let (hash, tails_size) = write_tails(generator, &mut data, progress)?;
+        self.data = data;
+        let location = self.location.clone().unwrap_or_else(|| hash.clone());
+        debug!(
+            "TailsBufferWriter: wrote tails data [size {}]: {}",
+            tails_size, location
+        );
+        Ok((location, hash))
+    }
+
+    fn reader(&self, _tails_location: &str) -> Option<TailsReader> {
+        Some(TailsBufferReader::new(self.data.clone()))
+    }
+}
+
+fn write_tails<W: Write>(
+    generator: &mut RevocationTailsGenerator,
+    writer: &mut W,
+    progress: TailsProgress<'_>,
+) -> Result<(String, u64)> {
+    let mut hasher = Sha256::default();
+    let version = &[0u8, 2u8];
+    writer.write_all(version)?;
+    hasher.input(version);
+    let mut tails_size = version.len() as u64;
+    let total = generator.count();
+    let mut generated = 0;
+    while let Some(tail) = generator.try_next()? {
+        let tail_bytes = tail.to_bytes()?;
+        writer.write_all(tail_bytes.as_slice())?;
+        hasher.input(&tail_bytes);
+        tails_size += tail_bytes.len() as u64;
+        generated += 1;
+        if (generated % TAILS_PROGRESS_INTERVAL == 0 || generated == total)
+            && !progress(generated, total)
+        {
+            return Err(err_msg!(InvalidState, "Tails generation cancelled"));
+        }
+    }
+    Ok((base58::encode(hasher.result()), tails_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tails_buffer_reader_works() {
+        let data = (0..=255u8).collect::<Vec<_>>();
+        let reader = TailsBufferReader::new(data.clone());
+        let mut inner = reader.inner.borrow_mut();
+        assert_eq!(inner.hash().unwrap(), Sha256::digest(&data).to_vec());
+        assert_eq!(inner.read(4, 10).unwrap(), vec![10, 11, 12, 13]);
+        assert!(inner.read(4, 254).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_tails_reader_works() {
+        let mut tempf = tempfile::NamedTempFile::new().unwrap();
+        let data = (0..=255u8).collect::<Vec<_>>();
+        tempf.write_all(&data).unwrap();
+        let path = tempf.path().to_string_lossy().into_owned();
+        let expected_hash = TailsFileReader::new(&path)
+            .inner
+            .borrow_mut()
+            .hash()
+            .unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (hash, bytes) = runtime
+            .block_on(spawn_with_tails_reader(
+                AsyncTailsFileReader::new(&path),
+                |reader| {
+                    let mut inner = reader.inner.borrow_mut();
+                    Ok((inner.hash()?, inner.read(4, 10)?))
+                },
+            ))
+            .unwrap();
+        assert_eq!(hash, expected_hash);
+        assert_eq!(bytes, vec![10, 11, 12, 13]);
+    }
+}
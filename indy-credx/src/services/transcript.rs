@@ -0,0 +1,257 @@
+//! Recording of service operation transcripts for debugging.
+//!
+//! When a transcript directory is configured, selected operations record the
+//! hashes of their inputs, intermediate derived objects and their outputs, and
+//! write the transcript to a JSON file in that directory once they complete.
+//! Recorded transcripts may be loaded in tests in order to check that the same
+//! inputs are being supplied, and to compare the derived objects and outputs.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    RwLock,
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use super::helpers::current_timestamp;
+use crate::error::Result;
+use indy_utils::hash::SHA256;
+
+static TRANSCRIPT_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+static TRANSCRIPT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Transcript>> = RefCell::new(None);
+}
+
+/// Set the directory transcripts are written to, or disable recording
+pub fn set_transcript_dir(dir: Option<PathBuf>) {
+    *TRANSCRIPT_DIR
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = dir;
+}
+
+pub fn get_transcript_dir() -> Option<PathBuf> {
+    TRANSCRIPT_DIR
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptEntryKind {
+    /// An input to the operation, recorded by hash only
+    Input,
+    /// An object derived while performing the operation
+    Derived,
+    /// The result of the operation
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub kind: TranscriptEntryKind,
+    pub name: String,
+    /// The hex-encoded SHA-256 hash of the JSON encoding of the value
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+/// A transcript of a single service operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub operation: String,
+    pub timestamp: u64,
+    pub entries: Vec<TranscriptEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Transcript {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_owned(),
+            timestamp: current_timestamp(),
+            entries: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let transcript = fs::read(path)?;
+        serde_json::from_slice(&transcript).map_err(err_map!("Invalid transcript"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record<T: Serialize + ?Sized>(
+        &mut self,
+        kind: TranscriptEntryKind,
+        name: &str,
+        value: &T,
+    ) -> Result<()> {
+        let value = serde_json::to_value(value)?;
+        let hash = hash_value(&value)?;
+        self.entries.push(TranscriptEntry {
+            kind,
+            name: name.to_owned(),
+            hash,
+            value: if kind == TranscriptEntryKind::Input {
+                None
+            } else {
+                Some(value)
+            },
+        });
+        Ok(())
+    }
+
+    pub fn entry(&self, kind: TranscriptEntryKind, name: &str) -> Option<&TranscriptEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.kind == kind && entry.name == name)
+    }
+
+    /// Check that a value matches the hash of a recorded input
+    pub fn check_input<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<()> {
+        let entry = self
+            .entry(TranscriptEntryKind::Input, name)
+            .ok_or_else(|| err_msg!("Input not found in transcript: {}", name))?;
+        if entry.hash != hash_value(&serde_json::to_value(value)?)? {
+            return Err(err_msg!("Input does not match transcript: {}", name));
+        }
+        Ok(())
+    }
+
+    pub fn derived(&self, name: &str) -> Option<&serde_json::Value> {
+        self.entry(TranscriptEntryKind::Derived, name)?
+            .value
+            .as_ref()
+    }
+
+    pub fn output(&self) -> Option<&serde_json::Value> {
+        self.entry(TranscriptEntryKind::Output, "result")?
+            .value
+            .as_ref()
+    }
+}
+
+fn hash_value(value: &serde_json::Value) -> Result<String> {
+    let digest = SHA256::digest(serde_json::to_vec(value)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// An operation transcript being recorded on the current thread
+pub(crate) struct Recording {
+    dir: Option<PathBuf>,
+}
+
+impl Recording {
+    /// Begin recording an operation when a transcript directory is set. Operations
+    /// performed while another is being recorded are included in its transcript.
+    pub fn begin(operation: &str) -> Self {
+        let dir = get_transcript_dir().filter(|_| {
+            ACTIVE.with(|active| {
+                let mut active = active.borrow_mut();
+                if active.is_some() {
+                    false
+                } else {
+                    active.replace(Transcript::new(operation));
+                    true
+                }
+            })
+        });
+        Self { dir }
+    }
+
+    pub fn finish<T: Serialize>(mut self, result: &Result<T>) {
+        let dir = match self.dir.take() {
+            Some(dir) => dir,
+            None => return,
+        };
+        if let Some(mut transcript) = ACTIVE.with(|active| active.borrow_mut().take()) {
+            match result {
+                Ok(value) => {
+                    transcript
+                        .record(TranscriptEntryKind::Output, "result", value)
+                        .ok();
+                }
+                Err(err) => transcript.error = Some(err.to_string()),
+            }
+            let path = dir.join(format!(
+                "{}-{}-{}.json",
+                transcript.operation,
+                transcript.timestamp,
+                TRANSCRIPT_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            if let Err(err) = transcript.save(&path) {
+                warn!("Error writing transcript to {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if self.dir.is_some() {
+            ACTIVE.with(|active| active.borrow_mut().take());
+        }
+    }
+}
+
+/// Add an entry to the transcript being recorded on the current thread, if any
+pub(crate) fn record<T: Serialize + ?Sized>(kind: TranscriptEntryKind, name: &str, value: &T) {
+    ACTIVE.with(|active| {
+        if let Some(transcript) = active.borrow_mut().as_mut() {
+            if let Err(err) = transcript.record(kind, name, value) {
+                warn!("Error recording transcript entry {}: {}", name, err);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_recording_works() {
+        let dir = tempfile::tempdir().unwrap();
+        set_transcript_dir(Some(dir.path().to_owned()));
+
+        let recording = Recording::begin("transcript_test");
+        record(TranscriptEntryKind::Input, "input", "secret value");
+        record(TranscriptEntryKind::Derived, "derived", &[1, 2, 3]);
+        recording.finish(&Ok(true));
+        set_transcript_dir(None);
+
+        let path = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains("transcript_test-"))
+            .unwrap();
+        let transcript = Transcript::load(path).unwrap();
+        assert_eq!(transcript.operation, "transcript_test");
+        assert!(transcript.check_input("input", "secret value").is_ok());
+        assert!(transcript.check_input("input", "other value").is_err());
+        assert!(transcript
+            .entry(TranscriptEntryKind::Input, "input")
+            .unwrap()
+            .value
+            .is_none());
+        assert_eq!(
+            transcript.derived("derived"),
+            Some(&serde_json::json!([1, 2, 3]))
+        );
+        assert_eq!(transcript.output(), Some(&serde_json::json!(true)));
+    }
+}
@@ -1,4 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+use std::time::{Duration, Instant};
 
 use super::tails::TailsReader;
 pub use indy_data_types::{
@@ -10,26 +15,33 @@ pub use indy_data_types::{
         cred_offer::CredentialOffer,
         cred_request::{CredentialRequest, CredentialRequestMetadata},
         credential::{AttributeValues, Credential, CredentialValues},
+        limits::{CheckLimits, ValidationConfig},
         master_secret::MasterSecret,
-        pres_request::PresentationRequest,
-        presentation::Presentation,
+        pres_request::{NonRevocedInterval, PredicateTypes, PresentationRequest},
+        presentation::{MasterSecretProof, Presentation},
         rev_reg::{RevocationRegistry, RevocationRegistryDelta},
         rev_reg_def::{
             IssuanceType, RegistryType, RevocationRegistryDefinition,
             RevocationRegistryDefinitionPrivate,
         },
+        rev_status_list::RevocationStatusList,
         schema::{AttributeNames, Schema},
     },
     CredentialDefinitionId, RevocationRegistryId, SchemaId,
 };
 pub use indy_utils::did::DidValue;
-use indy_utils::{invalid, Validatable, ValidationError};
+use indy_utils::{invalid, wql::Query, Validatable, ValidationError};
 
 use crate::error::Error;
-use crate::services::helpers::encode_credential_attribute;
+use crate::services::helpers::{
+    attr_common_view, current_timestamp, encode_binary_attribute,
+    encode_canonical_credential_attribute, encode_credential_attribute,
+    encode_credential_attribute_salted, encode_decimal_attribute, encode_expiry_date,
+    generate_attribute_salt,
+};
 use crate::ursa::cl::{RevocationRegistry as CryptoRevocationRegistry, Witness};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CredentialDefinitionConfig {
     pub support_revocation: bool,
 }
@@ -40,15 +52,251 @@ impl CredentialDefinitionConfig {
     }
 }
 
-impl Default for CredentialDefinitionConfig {
+impl Validatable for CredentialDefinitionConfig {}
+
+/// The rules applied to attribute names before they are compared, or added to a
+/// credential schema. Issuers, provers and verifiers must agree on the policy for
+/// credentials to be presented successfully.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeNormalization {
+    /// Remove spaces and convert to lowercase
+    Default,
+    /// Convert to lowercase, preserving spaces
+    Lowercase,
+    /// Remove spaces, preserving case
+    StripSpaces,
+    /// Use attribute names exactly as given
+    Exact,
+}
+
+impl AttributeNormalization {
+    pub fn normalize(&self, attr: &str) -> String {
+        match self {
+            Self::Default => attr.replace(' ', "").to_lowercase(),
+            Self::Lowercase => attr.to_lowercase(),
+            Self::StripSpaces => attr.replace(' ', ""),
+            Self::Exact => attr.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for AttributeNormalization {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "default" => Ok(Self::Default),
+            "lowercase" => Ok(Self::Lowercase),
+            "strip_spaces" => Ok(Self::StripSpaces),
+            "exact" => Ok(Self::Exact),
+            _ => Err(err_msg!(
+                "Invalid attribute normalization policy: {}",
+                value
+            )),
+        }
+    }
+}
+
+impl Default for AttributeNormalization {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+/// The interpretation of a requested non-revocation interval applied when
+/// checking the revocation registry timestamps of a presentation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonRevokedIntervalPolicy {
+    /// Only require a timestamp to be present for the referent, as in previous
+    /// releases. The timestamp is not compared with the interval or the current time.
+    Unchecked,
+    /// Require the timestamp to fall within the requested interval
+    Strict,
+    /// Aries RFC 0441: `from` is advisory, and the timestamp must be no later
+    /// than `to` plus the configured tolerance
+    Rfc0441,
+}
+
+impl NonRevokedIntervalPolicy {
+    pub fn accepts(&self, timestamp: u64, interval: &NonRevocedInterval, tolerance: u64) -> bool {
+        match self {
+            Self::Unchecked => true,
+            Self::Strict => {
+                interval.from.map(|from| timestamp >= from).unwrap_or(true)
+                    && interval.to.map(|to| timestamp <= to).unwrap_or(true)
+            }
+            Self::Rfc0441 => interval
+                .to
+                .map(|to| timestamp <= to.saturating_add(tolerance))
+                .unwrap_or(true),
+        }
+    }
+}
+
+impl std::str::FromStr for NonRevokedIntervalPolicy {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "unchecked" => Ok(Self::Unchecked),
+            "strict" => Ok(Self::Strict),
+            "rfc0441" => Ok(Self::Rfc0441),
+            _ => Err(err_msg!(
+                "Invalid non-revocation interval policy: {}",
+                value
+            )),
+        }
+    }
+}
+
+impl Default for NonRevokedIntervalPolicy {
+    fn default() -> Self {
+        Self::Unchecked
+    }
+}
+
+/// The default tolerance in seconds for timestamps ahead of the current time
+pub const DEFAULT_CLOCK_SKEW: u64 = 300;
+
+/// A source of the current time in seconds since the Unix epoch, consulted
+/// for all comparisons against the current time
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// Encodes raw credential attribute values to the integers signed by the issuer.
+///
+/// A custom encoder may be passed to `MakeCredentialValues::add_raw_with_encoder`.
+/// Attributes supplied to the issuer with only a raw value use the `StandardEncoder`.
+pub trait AttributeEncoder: std::fmt::Debug + Send + Sync {
+    fn encode(&self, name: &str, raw: &str) -> Result<String, Error>;
+}
+
+/// The Aries standard encoding: 32-bit integers are encoded as their value and
+/// other values are encoded as the SHA-256 hash of the value
+#[derive(Debug, Default, Copy, Clone)]
+pub struct StandardEncoder;
+
+impl AttributeEncoder for StandardEncoder {
+    fn encode(&self, _name: &str, raw: &str) -> Result<String, Error> {
+        encode_credential_attribute(raw)
+    }
+}
+
+/// The standard encoding restricted to integers in canonical form, so that
+/// distinct raw values such as "7" and "007" never share an encoding
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CanonicalEncoder;
+
+impl AttributeEncoder for CanonicalEncoder {
+    fn encode(&self, _name: &str, raw: &str) -> Result<String, Error> {
+        encode_canonical_credential_attribute(raw)
+    }
+}
+
+/// The default clock, reading the system time
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock which is only advanced manually, for use in tests
+#[derive(Debug, Default)]
+pub struct TestClock(AtomicU64);
+
+impl TestClock {
+    pub fn new(now: u64) -> Self {
+        Self(AtomicU64::new(now))
+    }
+
+    pub fn set(&self, now: u64) {
+        self.0.store(now, Ordering::Release)
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.0.fetch_add(seconds, Ordering::AcqRel);
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Global configuration consulted by the issuer, prover and verifier services.
+///
+/// Any fields omitted when deserializing take their default values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CredxConfig {
+    /// Validate objects when they are loaded from JSON
+    pub strict_parsing: bool,
+    /// The policy used to compare attribute names
+    pub attribute_normalization: AttributeNormalization,
+    /// The interpretation of requested non-revocation intervals
+    pub non_revoked_interval_policy: NonRevokedIntervalPolicy,
+    /// The tolerance in seconds applied to RFC 0441 interval checks
+    pub non_revoked_tolerance: u64,
+    /// The tolerance in seconds for timestamps ahead of the current time. Presentation
+    /// timestamps are only compared with the current time under the `strict` and
+    /// `rfc0441` interval policies.
+    pub clock_skew: u64,
+    /// The number of parsed credential definition public keys to cache, or zero
+    /// to parse the keys on each use
+    pub public_key_cache_size: usize,
+    /// The size and complexity limits applied to schemas, credentials, presentations
+    /// and presentation requests
+    pub validation: ValidationConfig,
+}
+
+impl Default for CredxConfig {
     fn default() -> Self {
         Self {
-            support_revocation: false,
+            strict_parsing: false,
+            attribute_normalization: AttributeNormalization::default(),
+            non_revoked_interval_policy: NonRevokedIntervalPolicy::default(),
+            non_revoked_tolerance: 0,
+            clock_skew: DEFAULT_CLOCK_SKEW,
+            public_key_cache_size: 0,
+            validation: ValidationConfig::default(),
         }
     }
 }
 
-impl Validatable for CredentialDefinitionConfig {}
+impl Validatable for CredxConfig {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let validation = &self.validation;
+        if validation.max_schema_attributes == 0
+            || validation.max_credential_attributes == 0
+            || validation.max_presentation_credentials == 0
+            || validation.max_restriction_depth == 0
+        {
+            return Err(invalid!("Validation limits must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// The attribute value type proposed when inferring a schema from sample data
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeType {
+    String,
+    /// An integer within 32-bit range, which is encoded as-is and usable in predicates
+    Integer,
+    Number,
+    Boolean,
+}
 
 #[derive(Debug, Default)]
 pub struct MakeCredentialValues(pub(crate) CredentialValues);
@@ -65,20 +313,138 @@ impl MakeCredentialValues {
             AttributeValues {
                 raw: raw.into(),
                 encoded,
+                salt: None,
+                media_type: None,
             },
         );
     }
 
+    /// Add an attribute encoded with the Aries standard encoding
     pub fn add_raw(
         &mut self,
         name: impl Into<String>,
         raw: impl Into<String>,
     ) -> Result<(), Error> {
+        self.add_raw_with_encoder(name, raw, &StandardEncoder)
+    }
+
+    /// Add an attribute encoded by the given attribute encoder
+    pub fn add_raw_with_encoder(
+        &mut self,
+        name: impl Into<String>,
+        raw: impl Into<String>,
+        encoder: &dyn AttributeEncoder,
+    ) -> Result<(), Error> {
+        let name = name.into();
         let raw = raw.into();
-        let encoded = encode_credential_attribute(&raw)?;
-        self.0
+        let encoded = encoder.encode(&name, &raw)?;
+        self.0 .0.insert(
+            name,
+            AttributeValues {
+                raw,
+                encoded,
+                salt: None,
+                media_type: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add a fixed-point decimal or large integer attribute, encoded as the
+    /// integer `raw * 10^scale`
+    pub fn add_decimal(
+        &mut self,
+        name: impl Into<String>,
+        raw: impl Into<String>,
+        scale: u8,
+    ) -> Result<(), Error> {
+        let raw = raw.into();
+        let encoded = encode_decimal_attribute(&raw, scale)?;
+        self.0 .0.insert(
+            name.into(),
+            AttributeValues {
+                raw,
+                encoded,
+                salt: None,
+                media_type: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add an attribute whose encoded value is salted, so that an unrevealed value
+    /// can't be recovered by encoding each of a small set of candidate values.
+    ///
+    /// The salt is kept with the credential values. Salted attributes are always
+    /// hashed and so can't be used in predicates.
+    pub fn add_raw_salted(
+        &mut self,
+        name: impl Into<String>,
+        raw: impl Into<String>,
+    ) -> Result<(), Error> {
+        let raw = raw.into();
+        let salt = generate_attribute_salt();
+        let encoded = encode_credential_attribute_salted(&raw, &salt)?;
+        self.0 .0.insert(
+            name.into(),
+            AttributeValues {
+                raw,
+                encoded,
+                salt: Some(salt),
+                media_type: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add a binary attribute such as an image or document. The raw value is the
+    /// SHA-256 hash of the content, which is not itself included in the credential.
+    pub fn add_binary(
+        &mut self,
+        name: impl Into<String>,
+        content: &[u8],
+        media_type: impl Into<String>,
+    ) -> Result<(), Error> {
+        let (raw, encoded) = encode_binary_attribute(content)?;
+        self.0 .0.insert(
+            name.into(),
+            AttributeValues {
+                raw,
+                encoded,
+                salt: None,
+                media_type: Some(media_type.into()),
+            },
+        );
+        Ok(())
+    }
+
+    /// Declare the MIME type of a previously added attribute, such as a binary
+    /// attribute whose raw and encoded values were computed separately
+    pub fn set_media_type(
+        &mut self,
+        name: &str,
+        media_type: impl Into<String>,
+    ) -> Result<(), Error> {
+        let attr = self
+            .0
              .0
-            .insert(name.into(), AttributeValues { raw, encoded });
+            .get_mut(name)
+            .ok_or_else(|| err_msg!("Unknown credential attribute: {}", name))?;
+        attr.media_type = Some(media_type.into());
+        Ok(())
+    }
+
+    /// Add an expiry date attribute, encoded as a `YYYYMMDD` integer so that
+    /// presentations can prove the credential has not expired with a predicate
+    pub fn add_expiry_date(
+        &mut self,
+        name: impl Into<String>,
+        year: u32,
+        month: u32,
+        day: u32,
+    ) -> Result<(), Error> {
+        let encoded = encode_expiry_date(year, month, day)?.to_string();
+        self.add_encoded(name, encoded.clone(), encoded);
         Ok(())
     }
 }
@@ -186,6 +552,17 @@ impl<'a, 'p> AddCredential<'a, 'p> {
     pub fn add_requested_predicate(&mut self, referent: impl Into<String>) {
         self.present.requested_predicates.insert(referent.into());
     }
+
+    /// Add every predicate in the presentation request which checks that the
+    /// named expiry date attribute is not before a given date
+    pub fn add_expiry_predicates(&mut self, pres_req: &PresentationRequest, attr_name: &str) {
+        let attr_name = attr_common_view(attr_name);
+        for (referent, info) in pres_req.value().requested_predicates.iter() {
+            if info.p_type == PredicateTypes::GE && attr_common_view(&info.name) == attr_name {
+                self.add_requested_predicate(referent.as_str());
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -209,6 +586,606 @@ pub(crate) struct ProvingCredentialKey {
     pub timestamp: Option<u64>,
 }
 
+/// A mapping between schema ledger sequence numbers and schema identifiers,
+/// used to resolve the schema references in legacy credential definition IDs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaSeqNoMap {
+    schema_ids: HashMap<u32, SchemaId>,
+    seq_nos: HashMap<SchemaId, u32>,
+}
+
+impl SchemaSeqNoMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the sequence numbers of schemas retrieved from the ledger
+    pub fn from_schemas<'s>(schemas: impl IntoIterator<Item = &'s Schema>) -> Self {
+        let mut map = Self::new();
+        for schema in schemas {
+            let Schema::SchemaV1(schema) = schema;
+            if let Some(seq_no) = schema.seq_no {
+                map.insert(seq_no, schema.id.clone());
+            }
+        }
+        map
+    }
+
+    pub fn insert(&mut self, seq_no: u32, schema_id: SchemaId) {
+        self.seq_nos.insert(schema_id.clone(), seq_no);
+        self.schema_ids.insert(seq_no, schema_id);
+    }
+
+    pub fn schema_id(&self, seq_no: u32) -> Option<&SchemaId> {
+        self.schema_ids.get(&seq_no)
+    }
+
+    pub fn seq_no(&self, schema_id: &SchemaId) -> Option<u32> {
+        schema_id
+            .seq_no()
+            .or_else(|| self.seq_nos.get(schema_id).copied())
+    }
+
+    /// Replace a schema reference by sequence number with the full schema
+    /// identifier, when it is known
+    pub fn resolve(&self, schema_id: &SchemaId) -> SchemaId {
+        schema_id
+            .seq_no()
+            .and_then(|seq_no| self.schema_id(seq_no))
+            .unwrap_or(schema_id)
+            .clone()
+    }
+
+    /// Get the full identifier of the schema referenced by a credential definition ID
+    pub fn cred_def_schema_id(&self, cred_def_id: &CredentialDefinitionId) -> Option<SchemaId> {
+        let (_, _, _, schema_id, _) = cred_def_id.parts()?;
+        Some(self.resolve(&schema_id))
+    }
+}
+
+/// The ledger objects required in order to verify a presentation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequiredArtifacts {
+    pub schema_ids: Vec<SchemaId>,
+    pub cred_def_ids: Vec<CredentialDefinitionId>,
+    pub rev_reg_def_ids: Vec<RevocationRegistryId>,
+    /// The revocation registry entries, by registry ID and timestamp
+    pub rev_regs: Vec<(RevocationRegistryId, u64)>,
+}
+
+/// How a referent of a presentation request was satisfied by a presentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disclosure {
+    Revealed,
+    Unrevealed,
+    SelfAttested,
+    Predicate,
+}
+
+/// The disclosure made by a presentation for a single referent. Attribute values
+/// are not included, so the summary may be logged without retaining the
+/// disclosed data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferentDisclosure {
+    pub disclosure: Disclosure,
+    /// The attribute names covered by the referent
+    pub names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_type: Option<PredicateTypes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<i32>,
+    /// The schema of the credential used, absent for self-attested attributes
+    pub schema_id: Option<SchemaId>,
+    /// The credential definition of the credential used, absent for self-attested
+    /// attributes
+    pub cred_def_id: Option<CredentialDefinitionId>,
+}
+
+/// The data disclosed by a presentation, by referent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationSummary {
+    pub referents: BTreeMap<String, ReferentDisclosure>,
+}
+
+impl PresentationSummary {
+    /// The referents satisfied with the given kind of disclosure
+    pub fn referents(&self, disclosure: Disclosure) -> impl Iterator<Item = &str> {
+        self.referents
+            .iter()
+            .filter(move |(_, entry)| entry.disclosure == disclosure)
+            .map(|(referent, _)| referent.as_str())
+    }
+}
+
+/// The expected size and proving cost of a presentation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationEstimate {
+    /// The approximate size of the serialized presentation in bytes
+    pub size: usize,
+    /// The number of credentials proven
+    pub sub_proofs: usize,
+    /// The number of predicate proofs, each costing several times a sub-proof
+    pub predicate_proofs: usize,
+    /// The number of non-revocation proofs
+    pub revocation_proofs: usize,
+    pub revealed_attributes: usize,
+    pub hidden_attributes: usize,
+}
+
+/// The link between a revocation registry and the successor registry created
+/// to replace it once all of its credential indices have been allocated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationRegistryRotation {
+    pub cred_def_id: CredentialDefinitionId,
+    pub previous_rev_reg_id: RevocationRegistryId,
+    pub rev_reg_id: RevocationRegistryId,
+    pub tag: String,
+}
+
+/// The credentials satisfying each referent of a presentation request, identified
+/// by their index in a collection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialsForPresentationRequest {
+    pub attrs: HashMap<String, Vec<usize>>,
+    pub predicates: HashMap<String, Vec<usize>>,
+}
+
+/// The identifiers and attribute values of a credential, against which the
+/// restrictions of a presentation request are evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialIdentifier<'a> {
+    pub schema_id: &'a SchemaId,
+    pub cred_def_id: &'a CredentialDefinitionId,
+    /// Raw attribute values by name. A value of `None` marks an attribute which is
+    /// present but not revealed, so `attr::<name>::value` clauses are not checked.
+    pub attr_values: HashMap<String, Option<&'a str>>,
+}
+
+impl<'a> CredentialIdentifier<'a> {
+    pub fn new(schema_id: &'a SchemaId, cred_def_id: &'a CredentialDefinitionId) -> Self {
+        Self {
+            schema_id,
+            cred_def_id,
+            attr_values: HashMap::new(),
+        }
+    }
+
+    pub fn add_attribute(&mut self, name: impl Into<String>, value: Option<&'a str>) {
+        self.attr_values.insert(name.into(), value);
+    }
+}
+
+impl<'a> From<&'a Credential> for CredentialIdentifier<'a> {
+    fn from(cred: &'a Credential) -> Self {
+        let mut ident = Self::new(&cred.schema_id, &cred.cred_def_id);
+        for (name, value) in cred.values.0.iter() {
+            ident.add_attribute(name.as_str(), Some(value.raw.as_str()));
+        }
+        ident
+    }
+}
+
+/// The outcome of evaluating a restriction clause, along with its nested clauses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RestrictionEvaluation {
+    pub clause: Query,
+    pub matched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clauses: Vec<RestrictionEvaluation>,
+}
+
+/// A group of duplicate credentials, identified by their index in a collection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateCredentials {
+    /// The credential recommended to keep
+    pub keep: usize,
+    /// The redundant credentials
+    pub remove: Vec<usize>,
+}
+
+/// A compatibility shim applied when verifying a presentation produced by anoncreds-rs
+/// or by an older libindy or indy-sdk prover
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityShim {
+    /// Presentation identifiers used camelCase or `rev_reg_def_id` field names
+    IdentifierFieldNames,
+    /// An empty revocation registry ID was treated as absent
+    EmptyRevocationRegistryId,
+    /// A ledger object without a `ver` property was assumed to be version 1.0
+    VersionWrapper,
+    /// A ledger object without an `id` property was assigned the ID it was supplied under
+    ObjectId,
+    /// A revocation registry definition without an issuance type was assumed to
+    /// issue by default
+    IssuanceType,
+    /// A missing or null section of the requested proof was treated as empty
+    RequestedProofSection,
+    /// A timestamp given for a credential without a revocation registry was ignored
+    TimestampWithoutRegistry,
+    /// A non-revocation interval was not applied to a credential without a
+    /// revocation registry
+    NonRevokedWithoutRegistry,
+    /// The presentation request and ledger object IDs were converted to the
+    /// unqualified identifiers used by the presentation
+    UnqualifiedIdentifiers,
+    /// A requested attribute name was matched to the revealed attribute using the
+    /// libindy normalization rules
+    LegacyAttributeName,
+}
+
+/// A compatibility shim along with the object it was applied to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedCompatibilityShim {
+    pub shim: CompatibilityShim,
+    pub target: String,
+}
+
+/// The compatibility shims applied while verifying a presentation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompatibilityReport {
+    pub applied: Vec<AppliedCompatibilityShim>,
+}
+
+impl CompatibilityReport {
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty()
+    }
+
+    pub fn contains(&self, shim: CompatibilityShim) -> bool {
+        self.applied.iter().any(|applied| applied.shim == shim)
+    }
+
+    pub(crate) fn add(&mut self, shim: CompatibilityShim, target: impl Into<String>) {
+        self.applied.push(AppliedCompatibilityShim {
+            shim,
+            target: target.into(),
+        });
+    }
+}
+
+/// The check of a presentation which did not pass verification
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationCheck {
+    /// The aggregated proof over the sub-proofs
+    SubProof,
+    Attribute,
+    Predicate,
+    NonRevocationInterval,
+}
+
+/// A failed verification check, with the referent of the attribute or predicate
+/// concerned and the reason for the failure
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationFailure {
+    pub check: VerificationCheck,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referent: Option<String>,
+    pub reason: String,
+}
+
+/// Options applied to the verification of a single presentation
+#[derive(Debug, Default, Clone)]
+pub struct VerificationOptions {
+    /// Check the encoded values of revealed attributes against their raw values,
+    /// rejecting the presentation on any mismatch. Binary attributes must match
+    /// their revealed hash, and other attributes the configured encoder.
+    pub check_attribute_encoding: bool,
+    /// The encoder used by the issuer for raw values. The `StandardEncoder` is
+    /// used when not set.
+    pub encoder: Option<Arc<dyn AttributeEncoder>>,
+    /// Attributes issued with a decimal encoding, which are not checked
+    pub decimal_attributes: HashSet<String>,
+    /// Attributes issued with a salted encoding, which are not checked
+    pub salted_attributes: HashSet<String>,
+}
+
+impl VerificationOptions {
+    /// Whether the attribute is declared as decimal or salted, such that its
+    /// encoding cannot be recomputed from the raw value
+    pub(crate) fn skip_attribute_encoding(&self, attr_name: &str) -> bool {
+        let attr_name = attr_common_view(attr_name);
+        self.decimal_attributes
+            .iter()
+            .chain(self.salted_attributes.iter())
+            .any(|name| attr_common_view(name) == attr_name)
+    }
+}
+
+/// The outcome of verifying a presentation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationResult {
+    pub failures: Vec<VerificationFailure>,
+}
+
+impl VerificationResult {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub(crate) fn add(
+        &mut self,
+        check: VerificationCheck,
+        referent: Option<&str>,
+        reason: impl ToString,
+    ) {
+        self.failures.push(VerificationFailure {
+            check,
+            referent: referent.map(str::to_string),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// A cache of presentation verification results, keyed by a hash of the
+/// canonical presentation and presentation request (including its nonce),
+/// the supplied revocation registry states and the global configuration.
+///
+/// Cached results assume the same schemas and credential and revocation registry
+/// definitions are supplied under the same IDs when the presentation is received
+/// again. Entries expire after the configured TTL.
+#[derive(Debug)]
+pub struct VerificationCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<Vec<u8>, (VerificationResult, Instant)>>,
+}
+
+impl VerificationCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    pub(crate) fn get(&self, key: &[u8]) -> Option<VerificationResult> {
+        let mut entries = self.lock();
+        match entries.get(key) {
+            Some((result, added)) if added.elapsed() < self.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: Vec<u8>, result: VerificationResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, (_, added)| added.elapsed() < ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, added))| *added)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (result, Instant::now()));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Vec<u8>, (VerificationResult, Instant)>> {
+        // the cached entries remain consistent if another thread panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Activity counters recorded by `IssuerMetrics` for a credential definition.
+///
+/// Timestamps are in seconds since the Unix epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialDefinitionMetrics {
+    pub credentials_issued: u64,
+    pub credentials_revoked: u64,
+    pub registries_created: u64,
+    pub last_issued: Option<u64>,
+    pub last_revoked: Option<u64>,
+    pub last_registry_created: Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum IssuerEvent {
+    CredentialIssued,
+    CredentialsRevoked(u64),
+    RegistryCreated,
+}
+
+/// Per-credential definition counters of issuer activity, updated by the
+/// issuer service once installed with `set_issuer_metrics`
+#[derive(Debug, Default)]
+pub struct IssuerMetrics {
+    entries: Mutex<HashMap<CredentialDefinitionId, CredentialDefinitionMetrics>>,
+}
+
+impl IssuerMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy the current counters for all credential definitions
+    pub fn snapshot(&self) -> HashMap<CredentialDefinitionId, CredentialDefinitionMetrics> {
+        self.lock().clone()
+    }
+
+    pub fn get(&self, cred_def_id: &CredentialDefinitionId) -> Option<CredentialDefinitionMetrics> {
+        self.lock().get(cred_def_id).cloned()
+    }
+
+    pub fn reset(&self) {
+        self.lock().clear()
+    }
+
+    pub(crate) fn record(&self, cred_def_id: &CredentialDefinitionId, event: IssuerEvent) {
+        let now = Some(current_timestamp());
+        let mut entries = self.lock();
+        let metrics = entries.entry(cred_def_id.clone()).or_default();
+        match event {
+            IssuerEvent::CredentialIssued => {
+                metrics.credentials_issued += 1;
+                metrics.last_issued = now;
+            }
+            IssuerEvent::CredentialsRevoked(count) => {
+                metrics.credentials_revoked += count;
+                metrics.last_revoked = now;
+            }
+            IssuerEvent::RegistryCreated => {
+                metrics.registries_created += 1;
+                metrics.last_registry_created = now;
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<CredentialDefinitionId, CredentialDefinitionMetrics>> {
+        // the counters remain consistent if another thread panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TombstoneKind {
+    CredentialDefinition,
+    RevocationRegistry,
+}
+
+/// A record of the destruction of an issuer's private key material, kept for
+/// auditing once a credential definition or revocation registry is retired
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuerTombstone {
+    pub kind: TombstoneKind,
+    /// The ID of the credential definition or revocation registry
+    pub id: String,
+    pub cred_def_id: CredentialDefinitionId,
+    /// The hex-encoded SHA-256 hash of the public object
+    pub public_digest: String,
+    /// The revocation indexes revoked during teardown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revoked: Vec<u32>,
+    pub timestamp: u64,
+}
+
+/// Revocation registry deltas indexed by registry and timestamp.
+///
+/// Each stored delta covers the changes to the registry from its creation up to
+/// the associated timestamp. Inserting a delta which continues from the entry
+/// preceding its timestamp merges it onto that entry, while deltas without a
+/// previous accumulator are stored as provided.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RevocationDeltaStore {
+    registries: HashMap<RevocationRegistryId, BTreeMap<u64, RevocationRegistryDelta>>,
+}
+
+impl RevocationDeltaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(
+        &mut self,
+        rev_reg_id: &RevocationRegistryId,
+        timestamp: u64,
+        delta: RevocationRegistryDelta,
+    ) -> Result<(), Error> {
+        let RevocationRegistryDelta::RevocationRegistryDeltaV1(update) = &delta;
+        let continues = serde_json::to_value(&update.value)?
+            .get("prevAccum")
+            .is_some();
+        let entries = self.registries.entry(rev_reg_id.clone()).or_default();
+        let delta = if continues {
+            let (prev_timestamp, prev) =
+                entries.range(..=timestamp).next_back().ok_or_else(|| {
+                    err_msg!(
+                        "No revocation registry delta for {} precedes timestamp {}",
+                        rev_reg_id,
+                        timestamp
+                    )
+                })?;
+            let RevocationRegistryDelta::RevocationRegistryDeltaV1(prev) = prev;
+            let mut merged = prev.clone();
+            merged.value.merge(&update.value).map_err(|_| {
+                err_msg!(
+                    "Revocation registry delta at timestamp {} does not continue from timestamp {}",
+                    timestamp,
+                    prev_timestamp
+                )
+            })?;
+            RevocationRegistryDelta::RevocationRegistryDeltaV1(merged)
+        } else {
+            delta
+        };
+        entries.insert(timestamp, delta);
+        Ok(())
+    }
+
+    /// Get the delta recorded at exactly the given timestamp
+    pub fn get(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        timestamp: u64,
+    ) -> Option<&RevocationRegistryDelta> {
+        self.registries.get(rev_reg_id)?.get(&timestamp)
+    }
+
+    /// Get the latest delta recorded at or before the given timestamp
+    pub fn latest_at(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        timestamp: u64,
+    ) -> Option<(u64, &RevocationRegistryDelta)> {
+        self.registries
+            .get(rev_reg_id)?
+            .range(..=timestamp)
+            .next_back()
+            .map(|(timestamp, delta)| (*timestamp, delta))
+    }
+
+    /// The recorded timestamps for a registry, in increasing order
+    pub fn timestamps(&self, rev_reg_id: &RevocationRegistryId) -> Vec<u64> {
+        self.registries
+            .get(rev_reg_id)
+            .map(|entries| entries.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn remove_registry(&mut self, rev_reg_id: &RevocationRegistryId) {
+        self.registries.remove(rev_reg_id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registries.is_empty()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CredentialRevocationState {
     pub(crate) witness: Witness,
@@ -227,6 +1204,217 @@ impl Validatable for CredentialRevocationState {
     }
 }
 
+/// Credential revocation states indexed by revocation registry, credential
+/// revocation index and timestamp.
+///
+/// Each entry records the credential indices which differ from the issuance
+/// default at its timestamp, so that the state for a later timestamp can be derived
+/// by updating the nearest earlier witness. When a capacity is set, the states with
+/// the earliest timestamps are evicted first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RevocationStateCache {
+    #[serde(default)]
+    capacity: usize,
+    #[serde(default)]
+    registries: HashMap<RevocationRegistryId, BTreeMap<u32, BTreeMap<u64, CachedRevocationState>>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CachedRevocationState {
+    pub(crate) state: CredentialRevocationState,
+    pub(crate) changed: BTreeSet<u32>,
+}
+
+impl RevocationStateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache holding at most `capacity` states, or any number when zero
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the state cached for a credential at exactly the given timestamp
+    pub fn get(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        rev_reg_idx: u32,
+        timestamp: u64,
+    ) -> Option<&CredentialRevocationState> {
+        self.registries
+            .get(rev_reg_id)?
+            .get(&rev_reg_idx)?
+            .get(&timestamp)
+            .map(|entry| &entry.state)
+    }
+
+    /// The cached timestamps for a credential, in increasing order
+    pub fn timestamps(&self, rev_reg_id: &RevocationRegistryId, rev_reg_idx: u32) -> Vec<u64> {
+        self.registries
+            .get(rev_reg_id)
+            .and_then(|creds| creds.get(&rev_reg_idx))
+            .map(|states| states.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the latest entry cached for a credential at or before the given timestamp
+    pub(crate) fn latest_entry_at(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        rev_reg_idx: u32,
+        timestamp: u64,
+    ) -> Option<&CachedRevocationState> {
+        self.registries
+            .get(rev_reg_id)?
+            .get(&rev_reg_idx)?
+            .range(..=timestamp)
+            .next_back()
+            .map(|(_, entry)| entry)
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        rev_reg_id: &RevocationRegistryId,
+        rev_reg_idx: u32,
+        entry: CachedRevocationState,
+    ) {
+        self.registries
+            .entry(rev_reg_id.clone())
+            .or_default()
+            .entry(rev_reg_idx)
+            .or_default()
+            .insert(entry.state.timestamp, entry);
+        if self.capacity > 0 {
+            let excess = self.len().saturating_sub(self.capacity);
+            for _ in 0..excess {
+                self.evict_earliest();
+            }
+        }
+    }
+
+    /// Remove the states with timestamps before the given timestamp, returning the
+    /// number of states removed
+    pub fn evict_before(&mut self, timestamp: u64) -> usize {
+        let before = self.len();
+        for creds in self.registries.values_mut() {
+            for states in creds.values_mut() {
+                *states = states.split_off(&timestamp);
+            }
+            creds.retain(|_, states| !states.is_empty());
+        }
+        self.registries.retain(|_, creds| !creds.is_empty());
+        before - self.len()
+    }
+
+    pub fn remove_registry(&mut self, rev_reg_id: &RevocationRegistryId) {
+        self.registries.remove(rev_reg_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.registries
+            .values()
+            .flat_map(|creds| creds.values())
+            .map(BTreeMap::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.registries.clear()
+    }
+
+    fn evict_earliest(&mut self) {
+        let earliest = self
+            .registries
+            .iter()
+            .flat_map(|(rev_reg_id, creds)| {
+                creds.iter().filter_map(move |(rev_reg_idx, states)| {
+                    let timestamp = *states.keys().next()?;
+                    Some((timestamp, rev_reg_id, *rev_reg_idx))
+                })
+            })
+            .min_by_key(|(timestamp, _, _)| *timestamp)
+            .map(|(timestamp, rev_reg_id, rev_reg_idx)| {
+                (timestamp, rev_reg_id.clone(), rev_reg_idx)
+            });
+        if let Some((timestamp, rev_reg_id, rev_reg_idx)) = earliest {
+            if let Some(creds) = self.registries.get_mut(&rev_reg_id) {
+                if let Some(states) = creds.get_mut(&rev_reg_idx) {
+                    states.remove(&timestamp);
+                    if states.is_empty() {
+                        creds.remove(&rev_reg_idx);
+                    }
+                }
+                if creds.is_empty() {
+                    self.registries.remove(&rev_reg_id);
+                }
+            }
+        }
+    }
+}
+
+/// A witness for a credential in a revocation registry, computed by the issuer and
+/// published alongside registry deltas so that holders can build revocation states
+/// without downloading the tails file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationWitnessUpdate {
+    pub rev_reg_id: RevocationRegistryId,
+    pub rev_reg_idx: u32,
+    pub timestamp: u64,
+    pub(crate) witness: Witness,
+}
+
+impl Validatable for RevocationWitnessUpdate {
+    fn validate(&self) -> std::result::Result<(), ValidationError> {
+        self.rev_reg_id.validate()?;
+        if self.rev_reg_idx == 0 {
+            return Err(invalid!(
+                "Revocation witness update validation failed: `revRegIdx` must be greater than 0",
+            ));
+        }
+        if self.timestamp == 0 {
+            return Err(invalid!(
+                "Revocation witness update validation failed: `timestamp` must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Resolves ledger objects by identifier, for instance by querying a ledger client
+pub trait LedgerResolver {
+    fn resolve_schema(&self, schema_id: &SchemaId) -> Result<Schema, Error>;
+
+    fn resolve_credential_definition(
+        &self,
+        cred_def_id: &CredentialDefinitionId,
+    ) -> Result<CredentialDefinition, Error>;
+
+    fn resolve_revocation_registry_definition(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+    ) -> Result<RevocationRegistryDefinition, Error>;
+
+    /// Resolve the status of a revocation registry as of a timestamp
+    fn resolve_revocation_status_list(
+        &self,
+        rev_reg_id: &RevocationRegistryId,
+        timestamp: u64,
+    ) -> Result<RevocationStatusList, Error>;
+}
+
 pub struct CredentialRevocationConfig<'a> {
     pub reg_def: &'a RevocationRegistryDefinition,
     pub reg_def_private: &'a RevocationRegistryDefinitionPrivate,
@@ -249,3 +1437,286 @@ impl<'a> std::fmt::Debug for CredentialRevocationConfig<'a> {
         )
     }
 }
+
+/// The strategy used to select the revocation index of a new credential
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationIndexAllocation {
+    /// The lowest unused index
+    Sequential,
+    /// An unused index selected at random, so that the order of issuance is not
+    /// revealed by the index
+    Random,
+    /// An index chosen by the caller, which must be unused
+    Specified(u32),
+}
+
+/// The revocation indices allocated from a revocation registry, to be persisted
+/// by the issuer between credential issuances
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationRegistryUsage {
+    pub rev_reg_id: RevocationRegistryId,
+    pub max_cred_num: u32,
+    pub used: HashSet<u32>,
+}
+
+impl RevocationRegistryUsage {
+    pub fn new(rev_reg_def: &RevocationRegistryDefinition) -> Self {
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+        Self {
+            rev_reg_id: rev_reg_def.id.clone(),
+            max_cred_num: rev_reg_def.value.max_cred_num,
+            used: HashSet::new(),
+        }
+    }
+
+    pub fn is_used(&self, idx: u32) -> bool {
+        self.used.contains(&idx)
+    }
+
+    /// The number of indices which have not been allocated
+    pub fn remaining(&self) -> u32 {
+        self.max_cred_num.saturating_sub(self.used.len() as u32)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Select an unused index according to the allocation strategy, without
+    /// recording it as used
+    pub fn select(&self, allocation: RevocationIndexAllocation) -> Result<u32, Error> {
+        if self.is_full() {
+            return Err(err_msg!(
+                RevocationRegistryFull,
+                "No unused indices remain in revocation registry: {}",
+                self.rev_reg_id
+            ));
+        }
+        let mut unused = (1..=self.max_cred_num).filter(|idx| !self.is_used(*idx));
+        match allocation {
+            RevocationIndexAllocation::Sequential => unused.next(),
+            RevocationIndexAllocation::Random => {
+                use rand::Rng;
+                let skip = rand::thread_rng().gen_range(0, self.remaining());
+                unused.nth(skip as usize)
+            }
+            RevocationIndexAllocation::Specified(idx) => {
+                if idx == 0 || idx > self.max_cred_num {
+                    return Err(err_msg!(
+                        InvalidUserRevocId,
+                        "Revocation index {} is outside of the registry range",
+                        idx
+                    ));
+                }
+                if self.is_used(idx) {
+                    return Err(err_msg!(
+                        InvalidUserRevocId,
+                        "Revocation index {} is already in use",
+                        idx
+                    ));
+                }
+                Some(idx)
+            }
+        }
+        .ok_or_else(|| err_msg!(Unexpected, "Error selecting revocation index"))
+    }
+
+    /// Record an index as used, returning `false` if it was already in use
+    pub fn mark_used(&mut self, idx: u32) -> bool {
+        self.used.insert(idx)
+    }
+
+    /// Return an index to the pool of unused indices
+    pub fn release(&mut self, idx: u32) -> bool {
+        self.used.remove(&idx)
+    }
+}
+
+impl Validatable for RevocationRegistryUsage {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.rev_reg_id.validate()?;
+        if let Some(idx) = self
+            .used
+            .iter()
+            .find(|idx| **idx == 0 || **idx > self.max_cred_num)
+        {
+            return Err(invalid!(
+                "Revocation index {} is outside of the registry range",
+                idx
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The revocation registry used to issue a credential, with the index of the
+/// credential allocated from a `RevocationRegistryUsage`
+pub struct RevocationAllocationConfig<'a> {
+    pub reg_def: &'a RevocationRegistryDefinition,
+    pub reg_def_private: &'a RevocationRegistryDefinitionPrivate,
+    pub registry: &'a RevocationRegistry,
+    pub allocation: RevocationIndexAllocation,
+    pub tails_reader: TailsReader,
+}
+
+impl<'a> std::fmt::Debug for RevocationAllocationConfig<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RevocationAllocationConfig {{ reg_def: {:?}, private: {:?}, registry: {:?}, allocation: {:?}, reader: {:?} }}",
+            self.reg_def,
+            secret!(self.reg_def_private),
+            self.registry,
+            secret!(self.allocation),
+            self.tails_reader,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ursa::pair::PointG2;
+
+    fn accum() -> serde_json::Value {
+        serde_json::to_value(PointG2::new().unwrap()).unwrap()
+    }
+
+    fn delta(
+        prev_accum: Option<&serde_json::Value>,
+        accum: &serde_json::Value,
+        issued: &[u32],
+        revoked: &[u32],
+    ) -> RevocationRegistryDelta {
+        let mut value = serde_json::json!({
+            "accum": accum,
+            "issued": issued,
+            "revoked": revoked,
+        });
+        if let Some(prev_accum) = prev_accum {
+            value["prevAccum"] = prev_accum.clone();
+        }
+        serde_json::from_value(serde_json::json!({"ver": "1.0", "value": value})).unwrap()
+    }
+
+    fn delta_json(delta: &RevocationRegistryDelta) -> serde_json::Value {
+        let RevocationRegistryDelta::RevocationRegistryDeltaV1(delta) = delta;
+        serde_json::to_value(&delta.value).unwrap()
+    }
+
+    #[test]
+    fn revocation_delta_store_works() {
+        let rev_reg_id = RevocationRegistryId("rev_reg".to_string());
+        let (accum1, accum2, accum3) = (accum(), accum(), accum());
+        let mut store = RevocationDeltaStore::new();
+        assert!(store
+            .insert(&rev_reg_id, 100, delta(Some(&accum1), &accum2, &[], &[1]))
+            .is_err());
+
+        store
+            .insert(&rev_reg_id, 100, delta(None, &accum1, &[1, 2], &[]))
+            .unwrap();
+        store
+            .insert(&rev_reg_id, 200, delta(Some(&accum1), &accum2, &[], &[1]))
+            .unwrap();
+        assert!(store
+            .insert(&rev_reg_id, 300, delta(Some(&accum1), &accum3, &[], &[2]))
+            .is_err());
+        assert_eq!(store.timestamps(&rev_reg_id), vec![100, 200]);
+
+        assert!(store.latest_at(&rev_reg_id, 99).is_none());
+        let (timestamp, latest) = store.latest_at(&rev_reg_id, 250).unwrap();
+        assert_eq!(timestamp, 200);
+        let latest = delta_json(latest);
+        assert_eq!(latest["accum"], accum2);
+        assert_eq!(latest["issued"], serde_json::json!([2]));
+        assert!(latest.get("revoked").is_none());
+
+        let restored: RevocationDeltaStore =
+            serde_json::from_str(&serde_json::to_string(&store).unwrap()).unwrap();
+        assert_eq!(restored.timestamps(&rev_reg_id), vec![100, 200]);
+        let restored = delta_json(restored.get(&rev_reg_id, 200).unwrap());
+        assert_eq!(restored["accum"], accum2);
+        assert_eq!(restored["issued"], serde_json::json!([2]));
+
+        store.remove_registry(&rev_reg_id);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn credential_values_media_type() {
+        let mut values = MakeCredentialValues::default();
+        let (raw, encoded) = encode_binary_attribute(b"content").unwrap();
+        values.add_encoded("photo", raw.as_str(), encoded);
+        values.set_media_type("photo", "image/png").unwrap();
+        assert!(values.set_media_type("other", "image/png").is_err());
+        values
+            .add_binary("doc", b"content", "application/pdf")
+            .unwrap();
+
+        let values: CredentialValues = values.into();
+        assert_eq!(values.0["photo"].raw, raw);
+        assert_eq!(values.0["photo"].media_type.as_deref(), Some("image/png"));
+        assert_eq!(values.0["doc"].raw, raw);
+        assert_eq!(
+            values.0["doc"].media_type.as_deref(),
+            Some("application/pdf")
+        );
+    }
+
+    #[test]
+    fn revocation_registry_usage_allocation() {
+        let mut usage = RevocationRegistryUsage {
+            rev_reg_id: RevocationRegistryId("rev_reg".to_string()),
+            max_cred_num: 4,
+            used: HashSet::new(),
+        };
+        assert_eq!(
+            usage.select(RevocationIndexAllocation::Sequential).unwrap(),
+            1
+        );
+        assert!(usage.mark_used(1));
+        assert!(!usage.mark_used(1));
+        assert_eq!(
+            usage.select(RevocationIndexAllocation::Sequential).unwrap(),
+            2
+        );
+        assert_eq!(
+            usage
+                .select(RevocationIndexAllocation::Specified(4))
+                .unwrap(),
+            4
+        );
+        for idx in [0, 1, 5].iter() {
+            assert_eq!(
+                usage
+                    .select(RevocationIndexAllocation::Specified(*idx))
+                    .unwrap_err()
+                    .kind(),
+                crate::error::ErrorKind::InvalidUserRevocId
+            );
+        }
+        usage.mark_used(3);
+        for _ in 0..10 {
+            let idx = usage.select(RevocationIndexAllocation::Random).unwrap();
+            assert!(idx == 2 || idx == 4);
+        }
+        usage.mark_used(2);
+        usage.mark_used(4);
+        assert!(usage.is_full());
+        assert_eq!(
+            usage
+                .select(RevocationIndexAllocation::Random)
+                .unwrap_err()
+                .kind(),
+            crate::error::ErrorKind::RevocationRegistryFull
+        );
+        assert!(usage.release(2));
+        assert_eq!(usage.remaining(), 1);
+        assert_eq!(
+            usage.select(RevocationIndexAllocation::Sequential).unwrap(),
+            2
+        );
+    }
+}
@@ -1,18 +1,21 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 use super::helpers::*;
 use super::types::*;
-use crate::error::Result;
-use crate::ursa::cl::{verifier::Verifier as CryptoVerifier, CredentialPublicKey};
+use crate::error::{ErrorKind, Result};
+use crate::ursa::cl::verifier::Verifier as CryptoVerifier;
 use indy_data_types::anoncreds::{
     nonce::Nonce,
-    pres_request::{AttributeInfo, NonRevocedInterval, PredicateInfo, PresentationRequestPayload},
-    presentation::{Identifier, RequestedProof, RevealedAttributeInfo},
+    pres_request::{
+        AttributeInfo, NonRevocedInterval, PredicateInfo, PredicateTypes,
+        PresentationRequestPayload,
+    },
+    presentation::{Identifier, RequestedProof, RevealedAttributeGroupInfo, RevealedAttributeInfo},
 };
-use indy_utils::wql::Query;
+use indy_utils::{hash::SHA256, wql::Query, Qualifiable, Validatable};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Filter {
@@ -34,17 +37,119 @@ pub fn verify_presentation(
     cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
     rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
     rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
-) -> Result<bool> {
-    trace!("verify >>> presentation: {:?}, pres_req: {:?}, schemas: {:?}, cred_defs: {:?}, rev_reg_defs: {:?} rev_regs: {:?}",
-    presentation, pres_req, schemas, cred_defs, rev_reg_defs, rev_regs);
+) -> Result<VerificationResult> {
+    verify_presentation_with_overrides(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+        None,
+    )
+}
+
+/// Verify a presentation, accepting revocation registry timestamps outside of
+/// the requested non-revocation intervals.
+///
+/// `nonrevoke_interval_override` maps a revocation registry ID and the `from` or
+/// `to` timestamp of a requested non-revocation interval to a timestamp which is
+/// accepted in its place, such as the last ledger entry preceding the interval.
+/// The interval is extended to include the override timestamp, so the override
+/// applies to whichever bound the non-revocation interval policy checks.
+pub fn verify_presentation_with_overrides(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+    nonrevoke_interval_override: Option<&HashMap<RevocationRegistryId, HashMap<u64, u64>>>,
+) -> Result<VerificationResult> {
+    verify_presentation_with_options(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+        nonrevoke_interval_override,
+        VerificationOptions::default(),
+    )
+}
+
+/// Verify a presentation, applying the given options along with any interval
+/// overrides as for `verify_presentation_with_overrides`
+#[allow(clippy::too_many_arguments)]
+pub fn verify_presentation_with_options(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+    nonrevoke_interval_override: Option<&HashMap<RevocationRegistryId, HashMap<u64, u64>>>,
+    options: VerificationOptions,
+) -> Result<VerificationResult> {
+    with_transcript!("verify_presentation", {
+        transcript!(Input, "presentation", presentation);
+        transcript!(Input, "pres_req", pres_req);
+        transcript!(Input, "schemas", schemas);
+        transcript!(Input, "cred_defs", cred_defs);
+        transcript!(Input, "rev_reg_defs", &rev_reg_defs);
+        transcript!(Input, "rev_regs", &rev_regs);
+        transcript!(
+            Input,
+            "nonrevoke_interval_override",
+            &nonrevoke_interval_override
+        );
+        verify_presentation_impl(
+            presentation,
+            pres_req,
+            schemas,
+            cred_defs,
+            rev_reg_defs,
+            rev_regs,
+            nonrevoke_interval_override,
+            options,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_presentation_impl(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+    nonrevoke_interval_override: Option<&HashMap<RevocationRegistryId, HashMap<u64, u64>>>,
+    options: VerificationOptions,
+) -> Result<VerificationResult> {
+    trace!("verify >>> presentation: {:?}, pres_req: {:?}, schemas: {:?}, cred_defs: {:?}, rev_reg_defs: {:?} rev_regs: {:?}, nonrevoke_interval_override: {:?}",
+    presentation, pres_req, schemas, cred_defs, rev_reg_defs, rev_regs, nonrevoke_interval_override);
+
+    check_presentation_inputs(presentation, pres_req)?;
 
     let pres_req = pres_req.value();
+    let seq_nos = schema_seq_nos(schemas);
     let received_revealed_attrs: HashMap<String, Identifier> =
-        received_revealed_attrs(&presentation)?;
+        resolve_schema_seq_nos(received_revealed_attrs(&presentation)?, &seq_nos);
     let received_unrevealed_attrs: HashMap<String, Identifier> =
-        received_unrevealed_attrs(&presentation)?;
-    let received_predicates: HashMap<String, Identifier> = received_predicates(&presentation)?;
+        resolve_schema_seq_nos(received_unrevealed_attrs(&presentation)?, &seq_nos);
+    let received_predicates: HashMap<String, Identifier> =
+        resolve_schema_seq_nos(received_predicates(&presentation)?, &seq_nos);
     let received_self_attested_attrs: HashSet<String> = received_self_attested_attrs(&presentation);
+    transcript!(Derived, "received_revealed_attrs", &received_revealed_attrs);
+    transcript!(
+        Derived,
+        "received_unrevealed_attrs",
+        &received_unrevealed_attrs
+    );
+    transcript!(Derived, "received_predicates", &received_predicates);
+
+    let mut result = VerificationResult::default();
 
     compare_attr_from_proof_and_request(
         pres_req,
@@ -52,9 +157,23 @@ pub fn verify_presentation(
         &received_unrevealed_attrs,
         &received_self_attested_attrs,
         &received_predicates,
-    )?;
+        &mut result,
+    );
+
+    verify_attribute_groups(pres_req, &presentation.requested_proof, &mut result);
+
+    verify_self_attested_attributes(pres_req, &received_self_attested_attrs, &mut result);
+
+    verify_revealed_attribute_values(&pres_req, &presentation, &mut result);
 
-    verify_revealed_attribute_values(&pres_req, &presentation)?;
+    if options.check_attribute_encoding {
+        verify_revealed_attribute_encodings(
+            pres_req,
+            &presentation.requested_proof,
+            &options,
+            &mut result,
+        )?;
+    }
 
     verify_requested_restrictions(
         &pres_req,
@@ -63,7 +182,8 @@ pub fn verify_presentation(
         &received_unrevealed_attrs,
         &received_predicates,
         &received_self_attested_attrs,
-    )?;
+        &mut result,
+    );
 
     compare_timestamps_from_proof_and_request(
         pres_req,
@@ -71,16 +191,1325 @@ pub fn verify_presentation(
         &received_unrevealed_attrs,
         &received_self_attested_attrs,
         &received_predicates,
+        nonrevoke_interval_override,
+        &mut result,
+    );
+
+    match verify_proof(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+    ) {
+        Ok(true) => (),
+        Ok(false) => result.add(
+            VerificationCheck::SubProof,
+            None,
+            "Proof verification failed",
+        ),
+        Err(err) if err.kind() == ErrorKind::ProofRejected => {
+            result.add(VerificationCheck::SubProof, None, err)
+        }
+        Err(err) => return Err(err),
+    }
+
+    trace!("verify <<< result: {:?}", result);
+
+    Ok(result)
+}
+
+/// Enforce the configured input limits and validate the presentation before verifying it
+fn check_presentation_inputs(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+) -> Result<()> {
+    check_limits(presentation)?;
+    check_limits(pres_req)?;
+    presentation.validate()?;
+    Ok(())
+}
+
+/// Verify the selected attribute and predicate referents of a presentation.
+///
+/// The aggregated proof is always verified in full, but the consistency, restriction
+/// and timestamp checks are only applied to the selected referents, producing a result
+/// for each one. Referents which are not present in the presentation request are
+/// reported as an error.
+pub fn verify_presentation_referents(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    referents: &HashSet<String>,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+) -> Result<HashMap<String, bool>> {
+    trace!("verify_presentation_referents >>> presentation: {:?}, pres_req: {:?}, referents: {:?}, schemas: {:?}, cred_defs: {:?}, rev_reg_defs: {:?} rev_regs: {:?}",
+    presentation, pres_req, referents, schemas, cred_defs, rev_reg_defs, rev_regs);
+
+    check_presentation_inputs(presentation, pres_req)?;
+
+    let pres_req = pres_req.value();
+    for referent in referents {
+        if !pres_req.requested_attributes.contains_key(referent)
+            && !pres_req.requested_predicates.contains_key(referent)
+        {
+            return Err(err_msg!(
+                "Referent \"{}\" not found in presentation request",
+                referent
+            ));
+        }
+    }
+
+    let valid = verify_proof(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+    )?;
+
+    let seq_nos = schema_seq_nos(schemas);
+    let received_revealed_attrs: HashMap<String, Identifier> =
+        resolve_schema_seq_nos(received_revealed_attrs(presentation)?, &seq_nos);
+    let received_unrevealed_attrs: HashMap<String, Identifier> =
+        resolve_schema_seq_nos(received_unrevealed_attrs(presentation)?, &seq_nos);
+    let received_predicates: HashMap<String, Identifier> =
+        resolve_schema_seq_nos(received_predicates(presentation)?, &seq_nos);
+    let received_self_attested_attrs: HashSet<String> = received_self_attested_attrs(presentation);
+    let proof_attr_identifiers: HashMap<String, Identifier> = received_revealed_attrs
+        .iter()
+        .chain(&received_unrevealed_attrs)
+        .map(|(r, id)| (r.to_string(), id.clone()))
+        .collect();
+    let requested_attrs =
+        requested_attributes_with_restrictions(pres_req, &received_self_attested_attrs);
+
+    let results = referents
+        .iter()
+        .map(|referent| {
+            let checked = if !valid {
+                Err(err_msg!(ProofRejected, "Proof verification failed"))
+            } else if let Some(info) = pres_req.requested_attributes.get(referent) {
+                verify_attribute_referent(
+                    referent,
+                    info,
+                    pres_req,
+                    presentation,
+                    &proof_attr_identifiers,
+                    &received_self_attested_attrs,
+                )
+            } else {
+                let info = &pres_req.requested_predicates[referent];
+                validate_timestamp(
+                    &received_predicates,
+                    referent,
+                    &pres_req.non_revoked,
+                    &info.non_revoked,
+                    None,
+                )
+                .and_then(|_| {
+                    verify_predicate_restrictions(
+                        referent,
+                        info,
+                        &presentation.requested_proof,
+                        &requested_attrs,
+                        &received_predicates,
+                    )
+                })
+            };
+            if let Err(err) = checked.as_ref() {
+                debug!("Referent \"{}\" failed verification: {}", referent, err);
+            }
+            (referent.clone(), checked.is_ok())
+        })
+        .collect::<HashMap<String, bool>>();
+
+    trace!("verify_presentation_referents <<< results: {:?}", results);
+
+    Ok(results)
+}
+
+pub fn verify_master_secret_proof(
+    proof: &MasterSecretProof,
+    schema: &Schema,
+    cred_def: &CredentialDefinition,
+    nonce: &Nonce,
+) -> Result<bool> {
+    trace!(
+        "verify_master_secret_proof >>> proof: {:?}, schema: {:?}, cred_def: {:?}, nonce: {:?}",
+        proof,
+        schema,
+        cred_def,
+        nonce
+    );
+
+    let schema = match schema {
+        Schema::SchemaV1(schema) => schema,
+    };
+    let cred_def = match cred_def {
+        CredentialDefinition::CredentialDefinitionV1(cred_def) => cred_def,
+    };
+    if proof.proof.proofs.len() != 1 {
+        return Err(err_msg!(
+            ProofRejected,
+            "Master secret proof must contain a single sub-proof"
+        ));
+    }
+
+    let credential_schema = build_cred_def_credential_schema(&schema.attr_names.0, cred_def)?;
+    let non_credential_schema = build_non_credential_schema()?;
+    let credential_pub_key = credential_public_key(cred_def)
+        .map_err(err_map!("Invalid credential definition public key"))?;
+    let sub_proof_request = build_sub_proof_request(&[], &[])?;
+
+    let mut proof_verifier = CryptoVerifier::new_proof_verifier()?;
+    proof_verifier.add_common_attribute("master_secret")?;
+    proof_verifier.add_sub_proof_request(
+        &sub_proof_request,
+        &credential_schema,
+        &non_credential_schema,
+        &credential_pub_key,
+        None,
+        None,
+    )?;
+    let valid = proof_verifier.verify(&proof.proof, nonce.as_native())?;
+
+    trace!("verify_master_secret_proof <<< valid: {:?}", valid);
+
+    Ok(valid)
+}
+
+/// Determine the ledger objects which must be supplied in order to verify a
+/// presentation. The presentation is checked against the attributes and
+/// predicates of the presentation request.
+pub fn required_artifacts(
+    pres_req: &PresentationRequest,
+    presentation: &Presentation,
+) -> Result<RequiredArtifacts> {
+    trace!(
+        "required_artifacts >>> pres_req: {:?}, presentation: {:?}",
+        pres_req,
+        presentation
+    );
+
+    let mut result = VerificationResult::default();
+    compare_attr_from_proof_and_request(
+        pres_req.value(),
+        &received_revealed_attrs(presentation)?,
+        &received_unrevealed_attrs(presentation)?,
+        &received_self_attested_attrs(presentation),
+        &received_predicates(presentation)?,
+        &mut result,
+    );
+    if let Some(failure) = result.failures.first() {
+        return Err(err_msg!(
+            "{} for referent {:?}",
+            failure.reason,
+            failure.referent.as_deref().unwrap_or_default()
+        ));
+    }
+
+    fn push_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+
+    let mut artifacts = RequiredArtifacts::default();
+    for identifier in presentation.identifiers.iter() {
+        push_unique(&mut artifacts.schema_ids, identifier.schema_id.clone());
+        push_unique(&mut artifacts.cred_def_ids, identifier.cred_def_id.clone());
+        if let Some(timestamp) = identifier.timestamp {
+            let rev_reg_id = identifier.rev_reg_id.clone().ok_or_else(|| {
+                err_msg!("Timestamp provided but Revocation Registry Id not found")
+            })?;
+            push_unique(&mut artifacts.rev_reg_def_ids, rev_reg_id.clone());
+            push_unique(&mut artifacts.rev_regs, (rev_reg_id, timestamp));
+        }
+    }
+
+    trace!("required_artifacts <<< artifacts: {:?}", artifacts);
+    Ok(artifacts)
+}
+
+/// Summarize the data disclosed by a presentation: the attributes revealed,
+/// proven without being revealed, and self-attested, and the predicates proven,
+/// along with the schema and credential definition used for each referent.
+/// The presentation is not verified.
+pub fn summarize_presentation(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+) -> Result<PresentationSummary> {
+    trace!(
+        "summarize_presentation >>> presentation: {:?}, pres_req: {:?}",
+        presentation,
+        pres_req
+    );
+
+    let pres_req = pres_req.value();
+    let requested_proof = &presentation.requested_proof;
+
+    let attr_names = |referent: &str| -> Result<Vec<String>> {
+        let info = pres_req
+            .requested_attributes
+            .get(referent)
+            .ok_or_else(|| err_msg!("Referent not found in presentation request: {}", referent))?;
+        Ok(info
+            .name
+            .iter()
+            .chain(info.names.iter().flatten())
+            .cloned()
+            .collect())
+    };
+    let credential_disclosure =
+        |disclosure: Disclosure, names: Vec<String>, sub_proof_index: u32| -> Result<_> {
+            let identifier = get_proof_identifier(presentation, sub_proof_index)?;
+            Ok(ReferentDisclosure {
+                disclosure,
+                names,
+                p_type: None,
+                p_value: None,
+                schema_id: Some(identifier.schema_id),
+                cred_def_id: Some(identifier.cred_def_id),
+            })
+        };
+
+    let mut summary = PresentationSummary::default();
+    for (referent, info) in requested_proof.revealed_attrs.iter() {
+        summary.referents.insert(
+            referent.clone(),
+            credential_disclosure(
+                Disclosure::Revealed,
+                attr_names(referent)?,
+                info.sub_proof_index,
+            )?,
+        );
+    }
+    for (referent, group) in requested_proof.revealed_attr_groups.iter() {
+        let mut names = group.values.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        summary.referents.insert(
+            referent.clone(),
+            credential_disclosure(Disclosure::Revealed, names, group.sub_proof_index)?,
+        );
+    }
+    for (referent, info) in requested_proof.unrevealed_attrs.iter() {
+        summary.referents.insert(
+            referent.clone(),
+            credential_disclosure(
+                Disclosure::Unrevealed,
+                attr_names(referent)?,
+                info.sub_proof_index,
+            )?,
+        );
+    }
+    for referent in requested_proof.self_attested_attrs.keys() {
+        summary.referents.insert(
+            referent.clone(),
+            ReferentDisclosure {
+                disclosure: Disclosure::SelfAttested,
+                names: attr_names(referent)?,
+                p_type: None,
+                p_value: None,
+                schema_id: None,
+                cred_def_id: None,
+            },
+        );
+    }
+    for (referent, info) in requested_proof.predicates.iter() {
+        let predicate = pres_req
+            .requested_predicates
+            .get(referent)
+            .ok_or_else(|| err_msg!("Referent not found in presentation request: {}", referent))?;
+        let mut entry = credential_disclosure(
+            Disclosure::Predicate,
+            vec![predicate.name.clone()],
+            info.sub_proof_index,
+        )?;
+        entry.p_type = Some(predicate.p_type.clone());
+        entry.p_value = Some(predicate.p_value);
+        summary.referents.insert(referent.clone(), entry);
+    }
+
+    trace!("summarize_presentation <<< summary: {:?}", summary);
+    Ok(summary)
+}
+
+/// Verify a presentation, reusing the result of a previous verification of the
+/// same presentation and presentation request against the same schemas, credential
+/// definitions, revocation registries and configuration from the cache when
+/// available. Errors are not cached.
+pub fn verify_presentation_cached(
+    cache: &VerificationCache,
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+) -> Result<VerificationResult> {
+    let key = verification_cache_key(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+    )?;
+    if let Some(result) = cache.get(&key) {
+        trace!("verify_presentation_cached <<< cached result: {:?}", result);
+        return Ok(result);
+    }
+    let result = verify_presentation(
+        presentation,
+        pres_req,
+        schemas,
+        cred_defs,
+        rev_reg_defs,
+        rev_regs,
+    )?;
+    cache.insert(key, result.clone());
+    Ok(result)
+}
+
+fn verification_cache_key(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+) -> Result<Vec<u8>> {
+    // serde_json::Value maintains sorted object keys, producing a canonical encoding
+    let canonical = |value: serde_json::Result<serde_json::Value>| {
+        value
+            .and_then(|value| serde_json::to_vec(&value))
+            .map_err(err_map!(
+                Unexpected,
+                "Error serializing for verification cache"
+            ))
+    };
+    let presentation = canonical(serde_json::to_value(presentation))?;
+    let pres_req = canonical(serde_json::to_value(pres_req))?;
+    // the ledger objects are keyed by ID, as the same ID may resolve to different content
+    let schemas = canonical(serde_json::to_value(
+        schemas
+            .iter()
+            .map(|(id, schema)| (&id.0, schema))
+            .collect::<BTreeMap<_, _>>(),
+    ))?;
+    let cred_defs = canonical(serde_json::to_value(
+        cred_defs
+            .iter()
+            .map(|(id, cred_def)| (&id.0, cred_def))
+            .collect::<BTreeMap<_, _>>(),
+    ))?;
+    let rev_reg_defs = canonical(serde_json::to_value(rev_reg_defs.map(|rev_reg_defs| {
+        rev_reg_defs
+            .iter()
+            .map(|(id, rev_reg_def)| (&id.0, rev_reg_def))
+            .collect::<BTreeMap<_, _>>()
+    })))?;
+    // registry states are keyed by ID and timestamp, including their accumulators
+    let rev_regs = canonical(serde_json::to_value(rev_regs.map(|rev_regs| {
+        rev_regs
+            .iter()
+            .map(|(id, entries)| (&id.0, entries.iter().collect::<BTreeMap<_, _>>()))
+            .collect::<BTreeMap<_, _>>()
+    })))?;
+    let config = canonical(serde_json::to_value(get_config()))?;
+    // the presentation request includes the nonce
+    Ok(SHA256::digest(
+        [
+            presentation.as_slice(),
+            pres_req.as_slice(),
+            schemas.as_slice(),
+            cred_defs.as_slice(),
+            rev_reg_defs.as_slice(),
+            rev_regs.as_slice(),
+            config.as_slice(),
+        ]
+        .join(&0u8),
+    ))
+}
+
+/// Check supplied binary content against the hash revealed for a binary attribute.
+///
+/// The attribute is looked up by referent among the revealed attributes, or by
+/// referent and attribute name among the revealed attribute groups.
+pub fn verify_binary_attribute(
+    presentation: &Presentation,
+    referent: &str,
+    name: Option<&str>,
+    content: &[u8],
+) -> Result<bool> {
+    let requested_proof = &presentation.requested_proof;
+    let (raw, encoded) = match name {
+        None => requested_proof
+            .revealed_attrs
+            .get(referent)
+            .map(|info| (&info.raw, &info.encoded)),
+        Some(name) => requested_proof
+            .revealed_attr_groups
+            .get(referent)
+            .and_then(|group| group.values.get(name))
+            .map(|value| (&value.raw, &value.encoded)),
+    }
+    .ok_or_else(|| err_msg!("Revealed attribute not found for referent: {}", referent))?;
+    check_binary_attribute(raw, encoded, content)
+}
+
+fn check_binary_attribute(raw: &str, encoded: &str, content: &[u8]) -> Result<bool> {
+    if !raw.starts_with(BINARY_ATTRIBUTE_PREFIX) {
+        return Err(err_msg!("Revealed attribute is not a binary attribute"));
+    }
+    let (expect_raw, expect_encoded) = encode_binary_attribute(content)?;
+    Ok(raw.eq_ignore_ascii_case(&expect_raw) && encoded == expect_encoded)
+}
+
+pub fn generate_nonce() -> Result<Nonce> {
+    new_nonce()
+}
+
+/// Check that a presentation request carries the nonce previously issued by
+/// the verifier. The presentation proof is bound to the request nonce, so this
+/// confirms that a presentation verified against the request was created in
+/// response to the expected challenge rather than replayed.
+pub fn check_presentation_nonce(
+    pres_req: &PresentationRequest,
+    expected_nonce: &Nonce,
+) -> Result<()> {
+    let nonce = &pres_req.value().nonce;
+    if nonce != expected_nonce {
+        return Err(err_msg!(
+            NonceMismatch,
+            "Presentation request nonce does not match the expected nonce: {} != {}",
+            nonce,
+            expected_nonce
+        ));
+    }
+    Ok(())
+}
+
+/// Collects the attributes and predicates to request from a prover, assigning
+/// each a stable referent. Equivalent requests are merged, and attributes added
+/// under the same credential hint are requested together as a group, so that
+/// they must be satisfied by a single credential.
+#[derive(Debug)]
+pub struct PresentationRequestBuilder {
+    name: String,
+    version: String,
+    nonce: Option<Nonce>,
+    non_revoked: Option<NonRevocedInterval>,
+    attributes: Vec<(String, AttributeInfo)>,
+    groups: HashMap<String, usize>,
+    predicates: Vec<(String, PredicateInfo)>,
+    referents: HashSet<String>,
+}
+
+impl PresentationRequestBuilder {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            nonce: None,
+            non_revoked: None,
+            attributes: Vec::new(),
+            groups: HashMap::new(),
+            predicates: Vec::new(),
+            referents: HashSet::new(),
+        }
+    }
+
+    /// Use a nonce issued by the verifier. A new nonce is generated otherwise.
+    pub fn nonce(&mut self, nonce: Nonce) -> &mut Self {
+        self.nonce.replace(nonce);
+        self
+    }
+
+    pub fn non_revoked(&mut self, interval: NonRevocedInterval) -> &mut Self {
+        self.non_revoked.replace(interval);
+        self
+    }
+
+    /// Request a single attribute, returning its referent. Requesting an
+    /// equivalent attribute with the same restrictions returns the existing
+    /// referent.
+    pub fn add_attribute(&mut self, name: &str, restrictions: Option<Query>) -> Result<String> {
+        check_requested_name(name)?;
+        let normalized = attr_common_view(name);
+        if let Some((referent, _)) = self.attributes.iter().find(|(_, info)| {
+            info.restrictions == restrictions
+                && info
+                    .name
+                    .as_ref()
+                    .map(|existing| attr_common_view(existing) == normalized)
+                    .unwrap_or(false)
+        }) {
+            return Ok(referent.clone());
+        }
+        let referent = self.unique_referent(format!("attr_{}", referent_part(name)));
+        self.attributes.push((
+            referent.clone(),
+            AttributeInfo {
+                name: Some(name.to_string()),
+                names: None,
+                restrictions,
+                non_revoked: None,
+            },
+        ));
+        Ok(referent)
+    }
+
+    /// Request an attribute as part of the group for a credential hint,
+    /// returning the referent of the group. Every attribute of the group must
+    /// be added with the same restrictions.
+    pub fn add_grouped_attribute(
+        &mut self,
+        hint: &str,
+        name: &str,
+        restrictions: Option<Query>,
+    ) -> Result<String> {
+        check_requested_name(name)?;
+        if hint.trim().is_empty() {
+            return Err(err_msg!("Credential hint must not be empty"));
+        }
+        let idx = match self.groups.get(hint) {
+            Some(idx) => *idx,
+            None => {
+                let referent = self.unique_referent(format!("group_{}", referent_part(hint)));
+                let idx = self.attributes.len();
+                self.attributes.push((
+                    referent,
+                    AttributeInfo {
+                        name: None,
+                        names: Some(Vec::new()),
+                        restrictions: restrictions.clone(),
+                        non_revoked: None,
+                    },
+                ));
+                self.groups.insert(hint.to_string(), idx);
+                idx
+            }
+        };
+        let (referent, info) = &mut self.attributes[idx];
+        if info.restrictions != restrictions {
+            return Err(err_msg!(
+                "Conflicting restrictions for attributes of credential hint \"{}\"",
+                hint
+            ));
+        }
+        let names = info.names.get_or_insert_with(Vec::new);
+        let normalized = attr_common_view(name);
+        if !names
+            .iter()
+            .any(|existing| attr_common_view(existing) == normalized)
+        {
+            names.push(name.to_string());
+        }
+        Ok(referent.clone())
+    }
+
+    /// Request a predicate, returning its referent. Requesting an equivalent
+    /// predicate with the same restrictions returns the existing referent.
+    pub fn add_predicate(
+        &mut self,
+        name: &str,
+        p_type: PredicateTypes,
+        p_value: i32,
+        restrictions: Option<Query>,
+    ) -> Result<String> {
+        check_requested_name(name)?;
+        let normalized = attr_common_view(name);
+        if let Some((referent, _)) = self.predicates.iter().find(|(_, info)| {
+            info.p_type == p_type
+                && info.p_value == p_value
+                && info.restrictions == restrictions
+                && attr_common_view(&info.name) == normalized
+        }) {
+            return Ok(referent.clone());
+        }
+        let p_type_name = match p_type {
+            PredicateTypes::GE => "ge",
+            PredicateTypes::GT => "gt",
+            PredicateTypes::LE => "le",
+            PredicateTypes::LT => "lt",
+        };
+        let referent = self.unique_referent(format!(
+            "pred_{}_{}_{}",
+            referent_part(name),
+            p_type_name,
+            p_value
+        ));
+        self.predicates.push((
+            referent.clone(),
+            PredicateInfo {
+                name: name.to_string(),
+                p_type,
+                p_value,
+                restrictions,
+                non_revoked: None,
+            },
+        ));
+        Ok(referent)
+    }
+
+    pub fn build(&self) -> Result<PresentationRequestPayload> {
+        let nonce = match self.nonce.as_ref() {
+            Some(nonce) => nonce.try_clone().map_err(err_map!(Unexpected))?,
+            None => new_nonce()?,
+        };
+        Ok(PresentationRequestPayload {
+            nonce,
+            name: self.name.clone(),
+            version: self.version.clone(),
+            requested_attributes: self.attributes.iter().cloned().collect(),
+            requested_predicates: self.predicates.iter().cloned().collect(),
+            non_revoked: self.non_revoked.clone(),
+        })
+    }
+
+    /// Claim a referent, adding a numeric suffix when the name is taken
+    fn unique_referent(&mut self, base: String) -> String {
+        let mut referent = base.clone();
+        let mut idx = 1;
+        while self.referents.contains(&referent) {
+            idx += 1;
+            referent = format!("{}_{}", base, idx);
+        }
+        self.referents.insert(referent.clone());
+        referent
+    }
+}
+
+fn check_requested_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(err_msg!("Requested attribute name must not be empty"));
+    }
+    Ok(())
+}
+
+/// Reduce a name to lowercase alphanumeric characters and underscores for use
+/// within a referent
+fn referent_part(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Verify a presentation, resolving the ledger objects it references
+pub fn verify_presentation_resolved(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    resolver: &dyn LedgerResolver,
+) -> Result<VerificationResult> {
+    trace!(
+        "verify_presentation_resolved >>> presentation: {:?}, pres_req: {:?}",
+        presentation,
+        pres_req
+    );
+
+    let mut schemas = HashMap::new();
+    let mut cred_defs = HashMap::new();
+    let mut rev_reg_defs = HashMap::new();
+    let mut rev_regs: HashMap<RevocationRegistryId, HashMap<u64, RevocationRegistry>> =
+        HashMap::new();
+    for identifier in presentation.identifiers.iter() {
+        if !schemas.contains_key(&identifier.schema_id) {
+            let schema = resolver.resolve_schema(&identifier.schema_id)?;
+            schemas.insert(identifier.schema_id.clone(), schema);
+        }
+        if !cred_defs.contains_key(&identifier.cred_def_id) {
+            let cred_def = resolver.resolve_credential_definition(&identifier.cred_def_id)?;
+            cred_defs.insert(identifier.cred_def_id.clone(), cred_def);
+        }
+        if let (Some(rev_reg_id), Some(timestamp)) = (&identifier.rev_reg_id, identifier.timestamp)
+        {
+            if !rev_reg_defs.contains_key(rev_reg_id) {
+                let rev_reg_def = resolver.resolve_revocation_registry_definition(rev_reg_id)?;
+                rev_reg_defs.insert(rev_reg_id.clone(), rev_reg_def);
+            }
+            if let Entry::Vacant(entry) = rev_regs
+                .entry(rev_reg_id.clone())
+                .or_default()
+                .entry(timestamp)
+            {
+                let status_list = resolver.resolve_revocation_status_list(rev_reg_id, timestamp)?;
+                entry.insert(
+                    status_list
+                        .to_revocation_registry()
+                        .map_err(err_map!("Invalid revocation status list"))?,
+                );
+            }
+        }
+    }
+
+    let schemas = schemas.iter().map(|(k, v)| (k.clone(), v)).collect();
+    let cred_defs = cred_defs.iter().map(|(k, v)| (k.clone(), v)).collect();
+    let rev_reg_defs = rev_reg_defs
+        .iter()
+        .map(|(k, v)| (k.clone(), v))
+        .collect::<HashMap<_, _>>();
+    let rev_regs = rev_regs
+        .iter()
+        .map(|(k, entries)| (k.clone(), entries.iter().map(|(t, v)| (*t, v)).collect()))
+        .collect::<HashMap<_, _>>();
+    let result = verify_presentation(
+        presentation,
+        pres_req,
+        &schemas,
+        &cred_defs,
+        Some(&rev_reg_defs).filter(|defs| !defs.is_empty()),
+        Some(&rev_regs).filter(|regs| !regs.is_empty()),
     )?;
 
+    trace!("verify_presentation_resolved <<< result: {:?}", result);
+    Ok(result)
+}
+
+/// Verify a presentation which may have been produced by anoncreds-rs.
+///
+/// The presentation and ledger objects are supplied as JSON and adjusted for the
+/// differences between the two libraries before regular verification. The returned
+/// report lists the compatibility shims which were applied.
+pub fn verify_presentation_compat(
+    presentation: &serde_json::Value,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &serde_json::Value>,
+    cred_defs: &HashMap<CredentialDefinitionId, &serde_json::Value>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &serde_json::Value>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &serde_json::Value>>>,
+) -> Result<(bool, CompatibilityReport)> {
+    trace!("verify_presentation_compat >>> presentation: {:?}, pres_req: {:?}, schemas: {:?}, cred_defs: {:?}, rev_reg_defs: {:?} rev_regs: {:?}",
+    presentation, pres_req, schemas, cred_defs, rev_reg_defs, rev_regs);
+
+    let mut report = CompatibilityReport::default();
+    let presentation = compat_presentation(presentation, &mut report)?;
+    let schemas = schemas
+        .iter()
+        .map(|(id, schema)| {
+            let schema = compat_ledger_object(id.0.as_str(), schema, &mut report, |_| None)?;
+            Ok((id.clone(), schema))
+        })
+        .collect::<Result<HashMap<SchemaId, Schema>>>()?;
+    let cred_defs = cred_defs
+        .iter()
+        .map(|(id, cred_def)| {
+            let cred_def = compat_ledger_object(id.0.as_str(), cred_def, &mut report, |_| None)?;
+            Ok((id.clone(), cred_def))
+        })
+        .collect::<Result<HashMap<CredentialDefinitionId, CredentialDefinition>>>()?;
+    let rev_reg_defs = rev_reg_defs
+        .map(|rev_reg_defs| {
+            rev_reg_defs
+                .iter()
+                .map(|(id, rev_reg_def)| {
+                    let rev_reg_def =
+                        compat_ledger_object(id.0.as_str(), rev_reg_def, &mut report, |obj| {
+                            let value = obj.get_mut("value").and_then(|v| v.as_object_mut());
+                            match value {
+                                Some(value) if !value.contains_key("issuanceType") => {
+                                    value.insert(
+                                        "issuanceType".to_owned(),
+                                        serde_json::to_value(IssuanceType::ISSUANCE_BY_DEFAULT)
+                                            .unwrap_or_default(),
+                                    );
+                                    Some(CompatibilityShim::IssuanceType)
+                                }
+                                _ => None,
+                            }
+                        })?;
+                    Ok((id.clone(), rev_reg_def))
+                })
+                .collect::<Result<HashMap<RevocationRegistryId, RevocationRegistryDefinition>>>()
+        })
+        .transpose()?;
+    let rev_regs = rev_regs
+        .map(|rev_regs| {
+            rev_regs
+                .iter()
+                .map(|(id, entries)| {
+                    let entries = entries
+                        .iter()
+                        .map(|(timestamp, rev_reg)| {
+                            let target = format!("{}@{}", id.0, timestamp);
+                            let rev_reg = compat_versioned(&target, rev_reg, &mut report)?;
+                            Ok((*timestamp, rev_reg))
+                        })
+                        .collect::<Result<HashMap<u64, RevocationRegistry>>>()?;
+                    Ok((id.clone(), entries))
+                })
+                .collect::<Result<HashMap<RevocationRegistryId, HashMap<u64, RevocationRegistry>>>>(
+                )
+        })
+        .transpose()?;
+
+    let schemas = schemas.iter().map(|(k, v)| (k.clone(), v)).collect();
+    let cred_defs = cred_defs.iter().map(|(k, v)| (k.clone(), v)).collect();
+    let rev_reg_defs = rev_reg_defs.as_ref().map(|rev_reg_defs| {
+        rev_reg_defs
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect::<HashMap<_, _>>()
+    });
+    let rev_regs = rev_regs.as_ref().map(|rev_regs| {
+        rev_regs
+            .iter()
+            .map(|(k, entries)| (k.clone(), entries.iter().map(|(t, v)| (*t, v)).collect()))
+            .collect::<HashMap<_, _>>()
+    });
+    let valid = verify_presentation(
+        &presentation,
+        pres_req,
+        &schemas,
+        &cred_defs,
+        rev_reg_defs.as_ref(),
+        rev_regs.as_ref(),
+    )?
+    .is_valid();
+
+    trace!(
+        "verify_presentation_compat <<< valid: {:?}, report: {:?}",
+        valid,
+        report
+    );
+    Ok((valid, report))
+}
+
+fn compat_presentation(
+    presentation: &serde_json::Value,
+    report: &mut CompatibilityReport,
+) -> Result<Presentation> {
+    let mut presentation = presentation.clone();
+    if let Some(identifiers) = presentation
+        .get_mut("identifiers")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for (idx, identifier) in identifiers.iter_mut().enumerate() {
+            let identifier = match identifier.as_object_mut() {
+                Some(identifier) => identifier,
+                None => continue,
+            };
+            let target = format!("identifiers[{}]", idx);
+            let mut renamed = false;
+            for (from, to) in &[
+                ("schemaId", "schema_id"),
+                ("credDefId", "cred_def_id"),
+                ("revRegId", "rev_reg_id"),
+                ("revRegDefId", "rev_reg_id"),
+                ("rev_reg_def_id", "rev_reg_id"),
+            ] {
+                if !identifier.contains_key(*to) {
+                    if let Some(value) = identifier.remove(*from) {
+                        identifier.insert((*to).to_owned(), value);
+                        renamed = true;
+                    }
+                }
+            }
+            if renamed {
+                report.add(CompatibilityShim::IdentifierFieldNames, target.as_str());
+            }
+            if identifier
+                .get("rev_reg_id")
+                .and_then(serde_json::Value::as_str)
+                == Some("")
+            {
+                identifier.insert("rev_reg_id".to_owned(), serde_json::Value::Null);
+                report.add(CompatibilityShim::EmptyRevocationRegistryId, target);
+            }
+        }
+    }
+    serde_json::from_value(presentation).map_err(err_map!("Invalid presentation"))
+}
+
+fn compat_ledger_object<T: serde::de::DeserializeOwned>(
+    id: &str,
+    value: &serde_json::Value,
+    report: &mut CompatibilityReport,
+    shim: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>) -> Option<CompatibilityShim>,
+) -> Result<T> {
+    let mut value = value.clone();
+    if let Some(obj) = value.as_object_mut() {
+        if !obj.contains_key("id") {
+            obj.insert("id".to_owned(), id.into());
+            report.add(CompatibilityShim::ObjectId, id);
+        }
+        if let Some(applied) = shim(obj) {
+            report.add(applied, id);
+        }
+    }
+    compat_versioned(id, &value, report)
+}
+
+fn compat_versioned<T: serde::de::DeserializeOwned>(
+    target: &str,
+    value: &serde_json::Value,
+    report: &mut CompatibilityReport,
+) -> Result<T> {
+    let mut value = value.clone();
+    if let Some(obj) = value.as_object_mut() {
+        if !obj.contains_key("ver") {
+            obj.insert("ver".to_owned(), "1.0".into());
+            report.add(CompatibilityShim::VersionWrapper, target);
+        }
+    }
+    serde_json::from_value(value).map_err(err_map!("Invalid ledger object: {}", target))
+}
+
+/// Verify a presentation which may have been produced by an older libindy or
+/// indy-sdk prover.
+///
+/// Known quirks of these provers are accommodated before regular verification:
+/// missing requested proof sections, unqualified identifiers used in response to a
+/// qualified request, timestamps and non-revocation intervals for credentials
+/// without revocation support, and attribute names matched using the libindy
+/// normalization rules. The returned report lists the rules which were applied.
+pub fn verify_presentation_libindy(
+    presentation: &serde_json::Value,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+) -> Result<(bool, CompatibilityReport)> {
+    trace!("verify_presentation_libindy >>> presentation: {:?}, pres_req: {:?}, schemas: {:?}, cred_defs: {:?}, rev_reg_defs: {:?} rev_regs: {:?}",
+    presentation, pres_req, schemas, cred_defs, rev_reg_defs, rev_regs);
+
+    let mut report = CompatibilityReport::default();
+    let presentation = libindy_presentation(presentation, &mut report)?;
+    let mut pres_req: PresentationRequest =
+        serde_json::from_value(serde_json::to_value(pres_req)?)?;
+
+    let unqualified = !presentation.identifiers.is_empty()
+        && presentation.identifiers.iter().all(|identifier| {
+            !identifier.schema_id.is_fully_qualified()
+                && !identifier.cred_def_id.is_fully_qualified()
+        });
+    let mut schemas = schemas.clone();
+    let mut cred_defs = cred_defs.clone();
+    let mut rev_reg_defs = rev_reg_defs.cloned();
+    let mut rev_regs = rev_regs.cloned();
+    if unqualified {
+        let converted: PresentationRequest =
+            serde_json::from_value(serde_json::to_value(&pres_req)?)?;
+        let converted = converted.to_unqualified();
+        if converted != pres_req {
+            pres_req = converted;
+            report.add(CompatibilityShim::UnqualifiedIdentifiers, "pres_req");
+        }
+        schemas = unqualified_keys(schemas, &mut report);
+        cred_defs = unqualified_keys(cred_defs, &mut report);
+        rev_reg_defs = rev_reg_defs.map(|defs| unqualified_keys(defs, &mut report));
+        rev_regs = rev_regs.map(|regs| unqualified_keys(regs, &mut report));
+    }
+
+    libindy_attribute_names(&mut pres_req, &presentation, &mut report)?;
+    libindy_non_revoked(&mut pres_req, &presentation, &mut report)?;
+
+    let valid = verify_presentation(
+        &presentation,
+        &pres_req,
+        &schemas,
+        &cred_defs,
+        rev_reg_defs.as_ref(),
+        rev_regs.as_ref(),
+    )?
+    .is_valid();
+
+    trace!(
+        "verify_presentation_libindy <<< valid: {:?}, report: {:?}",
+        valid,
+        report
+    );
+    Ok((valid, report))
+}
+
+fn libindy_presentation(
+    presentation: &serde_json::Value,
+    report: &mut CompatibilityReport,
+) -> Result<Presentation> {
+    let mut presentation = presentation.clone();
+    if let Some(requested_proof) = presentation
+        .get_mut("requested_proof")
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        for section in &[
+            "revealed_attrs",
+            "revealed_attr_groups",
+            "self_attested_attrs",
+            "unrevealed_attrs",
+            "predicates",
+        ] {
+            let missing = match requested_proof.get(*section) {
+                Some(serde_json::Value::Null) => true,
+                None => *section == "revealed_attrs",
+                _ => false,
+            };
+            if !missing {
+                continue;
+            }
+            requested_proof.insert((*section).to_owned(), serde_json::json!({}));
+            report.add(
+                CompatibilityShim::RequestedProofSection,
+                format!("requested_proof.{}", section),
+            );
+        }
+    }
+    if let Some(identifiers) = presentation
+        .get_mut("identifiers")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for (idx, identifier) in identifiers.iter_mut().enumerate() {
+            let identifier = match identifier.as_object_mut() {
+                Some(identifier) => identifier,
+                None => continue,
+            };
+            let target = format!("identifiers[{}]", idx);
+            if identifier
+                .get("rev_reg_id")
+                .and_then(serde_json::Value::as_str)
+                == Some("")
+            {
+                identifier.insert("rev_reg_id".to_owned(), serde_json::Value::Null);
+                report.add(
+                    CompatibilityShim::EmptyRevocationRegistryId,
+                    target.as_str(),
+                );
+            }
+            let has_registry = identifier
+                .get("rev_reg_id")
+                .map(|id| !id.is_null())
+                .unwrap_or(false);
+            let has_timestamp = identifier
+                .get("timestamp")
+                .map(|ts| !ts.is_null())
+                .unwrap_or(false);
+            if has_timestamp && !has_registry {
+                identifier.insert("timestamp".to_owned(), serde_json::Value::Null);
+                report.add(CompatibilityShim::TimestampWithoutRegistry, target);
+            }
+        }
+    }
+    serde_json::from_value(presentation).map_err(err_map!("Invalid presentation"))
+}
+
+fn unqualified_keys<K, V>(map: HashMap<K, V>, report: &mut CompatibilityReport) -> HashMap<K, V>
+where
+    K: Qualifiable + Eq + std::hash::Hash,
+{
+    map.into_iter()
+        .map(|(id, value)| {
+            if id.is_fully_qualified() {
+                report.add(CompatibilityShim::UnqualifiedIdentifiers, id.to_string());
+                (id.to_unqualified(), value)
+            } else {
+                (id, value)
+            }
+        })
+        .collect()
+}
+
+fn presentation_request_mut(pres_req: &mut PresentationRequest) -> &mut PresentationRequestPayload {
+    match pres_req {
+        PresentationRequest::PresentationRequestV1(req) => req,
+        PresentationRequest::PresentationRequestV2(req) => req,
+    }
+}
+
+fn libindy_attribute_names(
+    pres_req: &mut PresentationRequest,
+    presentation: &Presentation,
+    report: &mut CompatibilityReport,
+) -> Result<()> {
+    let requested_proof = &presentation.requested_proof;
+    let revealed = requested_proof
+        .revealed_attrs
+        .iter()
+        .map(|(referent, info)| (referent, info.sub_proof_index))
+        .chain(
+            requested_proof
+                .revealed_attr_groups
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .collect::<Vec<_>>();
+    let pres_req = presentation_request_mut(pres_req);
+    for (referent, sub_proof_index) in revealed {
+        let proof_attrs = match presentation.proof.proofs.get(sub_proof_index as usize) {
+            Some(sub_proof) => sub_proof.revealed_attrs()?,
+            None => continue,
+        };
+        let attr_info = match pres_req.requested_attributes.get_mut(referent) {
+            Some(attr_info) => attr_info,
+            None => continue,
+        };
+        let names = attr_info
+            .name
+            .iter_mut()
+            .chain(attr_info.names.iter_mut().flatten());
+        for name in names {
+            let matched = proof_attrs
+                .keys()
+                .any(|attr| attr_common_view(attr) == attr_common_view(name));
+            if matched {
+                continue;
+            }
+            let legacy = AttributeNormalization::Default.normalize(name);
+            if let Some(attr) = proof_attrs
+                .keys()
+                .find(|attr| AttributeNormalization::Default.normalize(attr) == legacy)
+            {
+                *name = attr.clone();
+                report.add(CompatibilityShim::LegacyAttributeName, referent.as_str());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn libindy_non_revoked(
+    pres_req: &mut PresentationRequest,
+    presentation: &Presentation,
+    report: &mut CompatibilityReport,
+) -> Result<()> {
+    let requested_proof = &presentation.requested_proof;
+    let sub_proof_indexes = requested_proof
+        .revealed_attrs
+        .iter()
+        .map(|(referent, info)| (referent, info.sub_proof_index))
+        .chain(
+            requested_proof
+                .revealed_attr_groups
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .chain(
+            requested_proof
+                .unrevealed_attrs
+                .iter()
+                .chain(requested_proof.predicates.iter())
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .collect::<HashMap<_, _>>();
+    let has_registry = |referent: &String| {
+        sub_proof_indexes
+            .get(referent)
+            .and_then(|idx| presentation.identifiers.get(*idx as usize))
+            .map(|identifier| identifier.rev_reg_id.is_some())
+    };
+
+    let pres_req = presentation_request_mut(pres_req);
+    let global = pres_req.non_revoked.take();
+    let split_global = global.is_some()
+        && pres_req
+            .requested_attributes
+            .iter()
+            .map(|(referent, info)| (referent, &info.non_revoked))
+            .chain(
+                pres_req
+                    .requested_predicates
+                    .iter()
+                    .map(|(referent, info)| (referent, &info.non_revoked)),
+            )
+            .any(|(referent, non_revoked)| {
+                non_revoked.is_none() && has_registry(referent) == Some(false)
+            });
+    let referents = pres_req
+        .requested_attributes
+        .iter_mut()
+        .map(|(referent, info)| (referent, &mut info.non_revoked))
+        .chain(
+            pres_req
+                .requested_predicates
+                .iter_mut()
+                .map(|(referent, info)| (referent, &mut info.non_revoked)),
+        );
+    for (referent, non_revoked) in referents {
+        if has_registry(referent) == Some(false) {
+            if non_revoked.take().is_some() || split_global {
+                report.add(
+                    CompatibilityShim::NonRevokedWithoutRegistry,
+                    referent.as_str(),
+                );
+            }
+        } else if split_global && non_revoked.is_none() {
+            *non_revoked = global.clone();
+        }
+    }
+    if !split_global {
+        pres_req.non_revoked = global;
+    }
+    Ok(())
+}
+
+/// Add a predicate to a presentation request requiring that the named expiry
+/// date attribute is not before the given `YYYYMMDD` date, or the current date
+/// when none is given
+pub fn add_expiry_predicate(
+    pres_req: &mut PresentationRequest,
+    referent: &str,
+    attr_name: &str,
+    date: Option<i32>,
+    restrictions: Option<Query>,
+) -> Result<()> {
+    let date = match date {
+        Some(date) => date,
+        None => current_expiry_date()?,
+    };
+    let payload = match pres_req {
+        PresentationRequest::PresentationRequestV1(payload) => payload,
+        PresentationRequest::PresentationRequestV2(payload) => payload,
+    };
+    if payload.requested_predicates.contains_key(referent) {
+        return Err(err_msg!(
+            "Duplicate requested predicate referent: {}",
+            referent
+        ));
+    }
+    payload.requested_predicates.insert(
+        referent.to_owned(),
+        PredicateInfo {
+            name: attr_name.to_owned(),
+            p_type: PredicateTypes::GE,
+            p_value: date,
+            restrictions,
+            non_revoked: None,
+        },
+    );
+    Ok(())
+}
+
+/// Verify the aggregated proof of a presentation.
+///
+/// The sub-proofs are bound together by a single Fiat-Shamir challenge computed
+/// over all of them, which the CL verifier only exposes as one check, so they are
+/// verified together rather than concurrently.
+fn verify_proof(
+    presentation: &Presentation,
+    pres_req: &PresentationRequestPayload,
+    schemas: &HashMap<SchemaId, &Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, &CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryId, &RevocationRegistryDefinition>>,
+    rev_regs: Option<&HashMap<RevocationRegistryId, HashMap<u64, &RevocationRegistry>>>,
+) -> Result<bool> {
     let mut proof_verifier = CryptoVerifier::new_proof_verifier()?;
     let non_credential_schema = build_non_credential_schema()?;
 
     for sub_proof_index in 0..presentation.identifiers.len() {
         let identifier = presentation.identifiers[sub_proof_index].clone();
 
-        let schema = match schemas
-            .get(&identifier.schema_id)
+        let schema = match find_schema(schemas, &identifier.schema_id)
             .ok_or_else(|| err_msg!("Schema not provided for ID: {:?}", identifier.schema_id))?
         {
             Schema::SchemaV1(schema) => schema,
@@ -150,14 +1579,12 @@ pub fn verify_presentation(
             pres_req,
         )?;
 
-        let credential_schema = build_credential_schema(&schema.attr_names.0)?;
+        let credential_schema = build_cred_def_credential_schema(&schema.attr_names.0, cred_def)?;
         let sub_pres_request =
             build_sub_proof_request(&attrs_for_credential, &predicates_for_credential)?;
 
-        let credential_pub_key = CredentialPublicKey::build_from_parts(
-            &cred_def.value.primary,
-            cred_def.value.revocation.as_ref(),
-        )?;
+        let credential_pub_key = credential_public_key(cred_def)
+            .map_err(err_map!("Invalid credential definition public key"))?;
 
         let rev_key_pub = rev_reg_def.as_ref().map(|r_reg_def| match r_reg_def {
             RevocationRegistryDefinition::RevocationRegistryDefinitionV1(reg_def) => {
@@ -178,15 +1605,96 @@ pub fn verify_presentation(
         )?;
     }
 
-    let valid = proof_verifier.verify(&presentation.proof, pres_req.nonce.as_native())?;
-
-    trace!("verify <<< valid: {:?}", valid);
+    let valid = proof_verifier.verify(&presentation.proof, pres_req.nonce.as_native())?;
+
+    Ok(valid)
+}
+
+fn verify_attribute_referent(
+    referent: &str,
+    info: &AttributeInfo,
+    pres_req: &PresentationRequestPayload,
+    proof: &Presentation,
+    proof_attr_identifiers: &HashMap<String, Identifier>,
+    self_attested_attrs: &HashSet<String>,
+) -> Result<()> {
+    let requested_proof = &proof.requested_proof;
+    if let Some(attr_info) = requested_proof.revealed_attrs.get(referent) {
+        let attr_name = info.name.as_ref().ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                referent
+            )
+        })?;
+        verify_revealed_attribute_value(attr_name, proof, attr_info)?;
+    } else if let Some(attr_infos) = requested_proof.revealed_attr_groups.get(referent) {
+        let attr_names = info.names.as_ref().ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                referent
+            )
+        })?;
+        if attr_infos.values.len() != attr_names.len() {
+            return Err(err_msg!(
+                "Proof Revealed Attr Group does not match Proof Request Attribute Group",
+            ));
+        }
+        for attr_name in attr_names {
+            let attr_info =
+                get_attr_normalized(&attr_infos.values, attr_name).ok_or_else(|| {
+                    err_msg!(
+                        "Proof Revealed Attr Group does not match Proof Request Attribute Group",
+                    )
+                })?;
+            verify_revealed_attribute_value(
+                attr_name,
+                proof,
+                &RevealedAttributeInfo {
+                    sub_proof_index: attr_infos.sub_proof_index,
+                    raw: attr_info.raw.clone(),
+                    encoded: attr_info.encoded.clone(),
+                },
+            )?;
+        }
+    } else if !requested_proof.unrevealed_attrs.contains_key(referent)
+        && !requested_proof.self_attested_attrs.contains_key(referent)
+    {
+        return Err(err_msg!(
+            ProofRejected,
+            "Referent \"{}\" not found in presentation",
+            referent
+        ));
+    }
+
+    if self_attested_attrs.contains(referent) {
+        return if is_self_attested(referent, info, self_attested_attrs) {
+            Ok(())
+        } else {
+            Err(err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" cannot be self-attested",
+                referent
+            ))
+        };
+    }
 
-    Ok(valid)
-}
+    validate_timestamp(
+        proof_attr_identifiers,
+        referent,
+        &pres_req.non_revoked,
+        &info.non_revoked,
+        None,
+    )?;
 
-pub fn generate_nonce() -> Result<Nonce> {
-    new_nonce()
+    verify_attribute_restrictions(
+        referent,
+        info,
+        pres_req,
+        requested_proof,
+        proof_attr_identifiers,
+    )
 }
 
 fn get_revealed_attributes_for_credential(
@@ -261,7 +1769,8 @@ fn compare_attr_from_proof_and_request(
     received_unrevealed_attrs: &HashMap<String, Identifier>,
     received_self_attested_attrs: &HashSet<String>,
     received_predicates: &HashMap<String, Identifier>,
-) -> Result<()> {
+    result: &mut VerificationResult,
+) {
     let requested_attrs: HashSet<String> = pres_req.requested_attributes.keys().cloned().collect();
 
     let received_attrs: HashSet<String> = received_revealed_attrs
@@ -273,27 +1782,39 @@ fn compare_attr_from_proof_and_request(
         .cloned()
         .collect();
 
-    if requested_attrs != received_attrs {
-        return Err(err_msg!(
-            "Requested attributes {:?} do not correspond to received {:?}",
-            requested_attrs,
-            received_attrs
-        ));
+    for referent in requested_attrs.difference(&received_attrs) {
+        result.add(
+            VerificationCheck::Attribute,
+            Some(referent),
+            "Requested attribute not found in presentation",
+        );
+    }
+    for referent in received_attrs.difference(&requested_attrs) {
+        result.add(
+            VerificationCheck::Attribute,
+            Some(referent),
+            "Received attribute not found in presentation request",
+        );
     }
 
     let requested_predicates: HashSet<&String> = pres_req.requested_predicates.keys().collect();
 
     let received_predicates_: HashSet<&String> = received_predicates.keys().collect();
 
-    if requested_predicates != received_predicates_ {
-        return Err(err_msg!(
-            "Requested predicates {:?} do not correspond to received {:?}",
-            requested_predicates,
-            received_predicates
-        ));
+    for referent in requested_predicates.difference(&received_predicates_) {
+        result.add(
+            VerificationCheck::Predicate,
+            Some(referent),
+            "Requested predicate not found in presentation",
+        );
+    }
+    for referent in received_predicates_.difference(&requested_predicates) {
+        result.add(
+            VerificationCheck::Predicate,
+            Some(referent),
+            "Received predicate not found in presentation request",
+        );
     }
-
-    Ok(())
 }
 
 fn compare_timestamps_from_proof_and_request(
@@ -302,48 +1823,62 @@ fn compare_timestamps_from_proof_and_request(
     received_unrevealed_attrs: &HashMap<String, Identifier>,
     received_self_attested_attrs: &HashSet<String>,
     received_predicates: &HashMap<String, Identifier>,
-) -> Result<()> {
-    pres_req
-        .requested_attributes
-        .iter()
-        .map(|(referent, info)| {
-            validate_timestamp(
-                &received_revealed_attrs,
-                referent,
-                &pres_req.non_revoked,
-                &info.non_revoked,
-            )
-            .or_else(|_| {
-                validate_timestamp(
-                    &received_unrevealed_attrs,
-                    referent,
-                    &pres_req.non_revoked,
-                    &info.non_revoked,
-                )
-            })
-            .or_else(|_| {
-                received_self_attested_attrs
-                    .get(referent)
-                    .map(|_| ())
-                    .ok_or_else(|| err_msg!("Missing referent"))
-            })
-        })
-        .collect::<Result<Vec<()>>>()?;
-
-    pres_req
-        .requested_predicates
-        .iter()
-        .map(|(referent, info)| {
+    overrides: Option<&HashMap<RevocationRegistryId, HashMap<u64, u64>>>,
+    result: &mut VerificationResult,
+) {
+    for (referent, info) in pres_req.requested_attributes.iter() {
+        if has_failed(result, referent) {
+            continue;
+        }
+        let checked = validate_timestamp(
+            &received_revealed_attrs,
+            referent,
+            &pres_req.non_revoked,
+            &info.non_revoked,
+            overrides,
+        )
+        .or_else(|_| {
             validate_timestamp(
-                received_predicates,
+                &received_unrevealed_attrs,
                 referent,
                 &pres_req.non_revoked,
                 &info.non_revoked,
+                overrides,
             )
         })
-        .collect::<Result<Vec<()>>>()?;
+        .or_else(|err| {
+            received_self_attested_attrs
+                .get(referent)
+                .map(|_| ())
+                .ok_or(err)
+        });
+        if let Err(err) = checked {
+            result.add(
+                VerificationCheck::NonRevocationInterval,
+                Some(referent),
+                err,
+            );
+        }
+    }
 
-    Ok(())
+    for (referent, info) in pres_req.requested_predicates.iter() {
+        if has_failed(result, referent) {
+            continue;
+        }
+        if let Err(err) = validate_timestamp(
+            received_predicates,
+            referent,
+            &pres_req.non_revoked,
+            &info.non_revoked,
+            overrides,
+        ) {
+            result.add(
+                VerificationCheck::NonRevocationInterval,
+                Some(referent),
+                err,
+            );
+        }
+    }
 }
 
 fn validate_timestamp(
@@ -351,22 +1886,108 @@ fn validate_timestamp(
     referent: &str,
     global_interval: &Option<NonRevocedInterval>,
     local_interval: &Option<NonRevocedInterval>,
+    overrides: Option<&HashMap<RevocationRegistryId, HashMap<u64, u64>>>,
 ) -> Result<()> {
-    if get_non_revoc_interval(global_interval, local_interval).is_none() {
+    let mut interval = match get_non_revoc_interval(global_interval, local_interval) {
+        Some(interval) => interval,
+        None => return Ok(()),
+    };
+
+    let identifier = received_
+        .get(referent)
+        .ok_or_else(|| err_msg!("Missing timestamp"))?;
+    let timestamp = identifier
+        .timestamp
+        .ok_or_else(|| err_msg!("Missing timestamp"))?;
+
+    if get_non_revoked_interval_policy().0 == NonRevokedIntervalPolicy::Unchecked {
         return Ok(());
     }
 
-    if !received_
-        .get(referent)
-        .map(|attr| attr.timestamp.is_some())
-        .unwrap_or(false)
-    {
-        return Err(err_msg!("Missing timestamp"));
+    if let (Some(rev_reg_id), Some(overrides)) = (identifier.rev_reg_id.as_ref(), overrides) {
+        apply_interval_override(&mut interval, rev_reg_id, overrides);
+    }
+
+    if !is_timestamp_current(timestamp) {
+        return Err(err_msg!(
+            ProofRejected,
+            "Timestamp {} for referent \"{}\" is in the future",
+            timestamp,
+            referent
+        ));
+    }
+    if !is_timestamp_in_interval(timestamp, &interval) {
+        return Err(err_msg!(
+            ProofRejected,
+            "Timestamp {} for referent \"{}\" does not satisfy the non-revocation interval {:?}",
+            timestamp,
+            referent,
+            interval
+        ));
     }
 
     Ok(())
 }
 
+/// Replace the `from` or `to` timestamp of a requested non-revocation interval with
+/// the override timestamp registered against it for the revocation registry. The
+/// override is applied as given, so it may widen or narrow the requested interval.
+fn apply_interval_override(
+    interval: &mut NonRevocedInterval,
+    rev_reg_id: &RevocationRegistryId,
+    overrides: &HashMap<RevocationRegistryId, HashMap<u64, u64>>,
+) {
+    let overrides = match overrides.get(rev_reg_id) {
+        Some(overrides) => overrides,
+        None => return,
+    };
+    let override_for =
+        |requested: Option<u64>| requested.and_then(|requested| overrides.get(&requested).copied());
+    if let Some(override_ts) = override_for(interval.from) {
+        interval.from = Some(override_ts);
+    }
+    if let Some(override_ts) = override_for(interval.to) {
+        interval.to = Some(override_ts);
+    }
+}
+
+/// Map the schema sequence numbers of the provided schemas, including any
+/// schemas keyed by sequence number, to their full identifiers
+fn schema_seq_nos(schemas: &HashMap<SchemaId, &Schema>) -> SchemaSeqNoMap {
+    let mut seq_nos = SchemaSeqNoMap::from_schemas(schemas.values().copied());
+    for (schema_id, schema) in schemas {
+        if let Some(seq_no) = schema_id.seq_no() {
+            seq_nos.insert(seq_no, schema.id().clone());
+        }
+    }
+    seq_nos
+}
+
+/// Replace legacy schema references by sequence number in proof identifiers
+fn resolve_schema_seq_nos(
+    mut identifiers: HashMap<String, Identifier>,
+    seq_nos: &SchemaSeqNoMap,
+) -> HashMap<String, Identifier> {
+    for identifier in identifiers.values_mut() {
+        identifier.schema_id = seq_nos.resolve(&identifier.schema_id);
+    }
+    identifiers
+}
+
+/// Find a provided schema by its identifier or by its ledger sequence number
+fn find_schema<'s>(
+    schemas: &HashMap<SchemaId, &'s Schema>,
+    schema_id: &SchemaId,
+) -> Option<&'s Schema> {
+    schemas.get(schema_id).copied().or_else(|| {
+        schemas.values().copied().find(|schema| {
+            let Schema::SchemaV1(schema) = schema;
+            &schema.id == schema_id
+                || (schema_id.seq_no().is_some() && schema.seq_no == schema_id.seq_no())
+        })
+    })
+}
+
 fn received_revealed_attrs(proof: &Presentation) -> Result<HashMap<String, Identifier>> {
     let mut revealed_identifiers: HashMap<String, Identifier> = HashMap::new();
     for (referent, info) in proof.requested_proof.revealed_attrs.iter() {
@@ -423,75 +2044,272 @@ fn get_proof_identifier(proof: &Presentation, index: u32) -> Result<Identifier>
         .ok_or_else(|| err_msg!("Identifier not found for index: {}", index))
 }
 
+/// Check that each requested attribute is presented in the form matching the
+/// request: a `names` group must be revealed as a group covering every
+/// requested name (or left unrevealed), while a single `name` must not be
+/// presented as a group.
+/// Record a failure for each self-attested value provided for a requested
+/// attribute with restrictions. Attribute groups are handled separately.
+fn verify_self_attested_attributes(
+    pres_req: &PresentationRequestPayload,
+    self_attested_attrs: &HashSet<String>,
+    result: &mut VerificationResult,
+) {
+    for referent in self_attested_attrs.iter() {
+        if let Some(info) = pres_req.requested_attributes.get(referent) {
+            if info.names.is_none() && !info.allows_self_attestation() {
+                result.add(
+                    VerificationCheck::Attribute,
+                    Some(referent),
+                    "Attribute with restrictions cannot be self-attested",
+                );
+            }
+        }
+    }
+}
+
+fn verify_attribute_groups(
+    pres_req: &PresentationRequestPayload,
+    requested_proof: &RequestedProof,
+    result: &mut VerificationResult,
+) {
+    for (referent, info) in pres_req.requested_attributes.iter() {
+        let names = match info.names.as_ref() {
+            Some(names) => names,
+            None => {
+                if requested_proof.revealed_attr_groups.contains_key(referent) {
+                    result.add(
+                        VerificationCheck::Attribute,
+                        Some(referent),
+                        "Requested attribute must not be presented as an attribute group",
+                    );
+                }
+                continue;
+            }
+        };
+        if requested_proof.revealed_attrs.contains_key(referent) {
+            result.add(
+                VerificationCheck::Attribute,
+                Some(referent),
+                "Attribute group must be presented as a revealed attribute group",
+            );
+        }
+        if requested_proof.self_attested_attrs.contains_key(referent) {
+            result.add(
+                VerificationCheck::Attribute,
+                Some(referent),
+                "Attribute group cannot be self-attested",
+            );
+        }
+        if let Some(group) = requested_proof.revealed_attr_groups.get(referent) {
+            let missing = names
+                .iter()
+                .filter(|name| get_attr_normalized(&group.values, name).is_none())
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                result.add(
+                    VerificationCheck::Attribute,
+                    Some(referent),
+                    format!(
+                        "Attribute group is partially satisfied, missing values for: {:?}",
+                        missing
+                    ),
+                );
+            }
+            let unexpected = group
+                .values
+                .keys()
+                .filter(|name| {
+                    !names
+                        .iter()
+                        .any(|req| attr_common_view(req) == attr_common_view(name))
+                })
+                .collect::<Vec<_>>();
+            if !unexpected.is_empty() {
+                result.add(
+                    VerificationCheck::Attribute,
+                    Some(referent),
+                    format!(
+                        "Attribute group contains values which were not requested: {:?}",
+                        unexpected
+                    ),
+                );
+            }
+        }
+    }
+}
+
 fn verify_revealed_attribute_values(
     pres_req: &PresentationRequestPayload,
     proof: &Presentation,
-) -> Result<()> {
+    result: &mut VerificationResult,
+) {
     for (attr_referent, attr_info) in proof.requested_proof.revealed_attrs.iter() {
-        let attr_name = pres_req
-            .requested_attributes
-            .get(attr_referent)
-            .as_ref()
-            .ok_or_else(|| {
-                err_msg!(
-                    ProofRejected,
-                    "Attribute with referent \"{}\" not found in ProofRequests",
-                    attr_referent
-                )
-            })?
-            .name
-            .as_ref()
-            .ok_or_else(|| {
-                err_msg!(
-                    ProofRejected,
-                    "Attribute with referent \"{}\" not found in ProofRequests",
-                    attr_referent,
-                )
-            })?;
-        verify_revealed_attribute_value(attr_name.as_str(), proof, &attr_info)?;
+        if let Err(err) = verify_revealed_attribute(pres_req, proof, attr_referent, attr_info) {
+            result.add(VerificationCheck::Attribute, Some(attr_referent), err);
+        }
     }
 
     for (attr_referent, attr_infos) in proof.requested_proof.revealed_attr_groups.iter() {
-        let attr_names = pres_req
+        if has_failed(result, attr_referent) {
+            continue;
+        }
+        if let Err(err) =
+            verify_revealed_attribute_group(pres_req, proof, attr_referent, attr_infos)
+        {
+            result.add(VerificationCheck::Attribute, Some(attr_referent), err);
+        }
+    }
+}
+
+/// Check each revealed raw value against its encoded value, failing any attribute
+/// which does not match. Attributes declared as decimal or salted are skipped.
+fn verify_revealed_attribute_encodings(
+    pres_req: &PresentationRequestPayload,
+    requested_proof: &RequestedProof,
+    options: &VerificationOptions,
+    result: &mut VerificationResult,
+) -> Result<()> {
+    let encoder = options
+        .encoder
+        .as_deref()
+        .unwrap_or(&StandardEncoder as &dyn AttributeEncoder);
+    let mut mismatches: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (attr_referent, attr_info) in requested_proof.revealed_attrs.iter() {
+        let attr_name = pres_req
             .requested_attributes
             .get(attr_referent)
-            .as_ref()
-            .ok_or_else(|| {
-                err_msg!(
-                    ProofRejected,
-                    "Attribute with referent \"{}\" not found in ProofRequests",
-                    attr_referent,
-                )
-            })?
-            .names
-            .as_ref()
-            .ok_or_else(|| {
-                err_msg!(
-                    ProofRejected,
-                    "Attribute with referent \"{}\" not found in ProofRequests",
-                    attr_referent,
-                )
-            })?;
-        if attr_infos.values.len() != attr_names.len() {
-            error!("Proof Revealed Attr Group does not match Proof Request Attribute Group, proof request attrs: {:?}, referent: {:?}, attr_infos: {:?}", pres_req.requested_attributes, attr_referent, attr_infos);
-            return Err(err_msg!(
-                "Proof Revealed Attr Group does not match Proof Request Attribute Group",
-            ));
+            .and_then(|info| info.name.as_deref())
+            .unwrap_or(attr_referent);
+        if !options.skip_attribute_encoding(attr_name)
+            && !check_attribute_encoding(encoder, attr_name, &attr_info.raw, &attr_info.encoded)?
+        {
+            mismatches.push((attr_referent, vec![attr_name]));
         }
-        for attr_name in attr_names {
-            let attr_info = &attr_infos.values.get(attr_name).ok_or_else(|| {
-                err_msg!("Proof Revealed Attr Group does not match Proof Request Attribute Group",)
-            })?;
-            verify_revealed_attribute_value(
-                attr_name,
-                proof,
-                &RevealedAttributeInfo {
-                    sub_proof_index: attr_infos.sub_proof_index,
-                    raw: attr_info.raw.clone(),
-                    encoded: attr_info.encoded.clone(),
-                },
-            )?;
+    }
+    for (attr_referent, attr_infos) in requested_proof.revealed_attr_groups.iter() {
+        let mut invalid = Vec::new();
+        for (attr_name, attr_info) in attr_infos.values.iter() {
+            if !options.skip_attribute_encoding(attr_name)
+                && !check_attribute_encoding(
+                    encoder,
+                    attr_name,
+                    &attr_info.raw,
+                    &attr_info.encoded,
+                )?
+            {
+                invalid.push(attr_name.as_str());
+            }
         }
+        if !invalid.is_empty() {
+            invalid.sort_unstable();
+            mismatches.push((attr_referent, invalid));
+        }
+    }
+    mismatches.sort_unstable();
+    for (attr_referent, invalid) in mismatches {
+        if has_failed(result, attr_referent) {
+            continue;
+        }
+        result.add(
+            VerificationCheck::Attribute,
+            Some(attr_referent),
+            format!(
+                "Encoded value does not match raw value for attributes: {}",
+                invalid.join(", ")
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn check_attribute_encoding(
+    encoder: &dyn AttributeEncoder,
+    attr_name: &str,
+    raw: &str,
+    encoded: &str,
+) -> Result<bool> {
+    let expected = match binary_attribute_encoding(raw)? {
+        Some(expected) => expected,
+        None => encoder.encode(attr_name, raw)?,
+    };
+    Ok(encoded.trim_start_matches('0') == expected.trim_start_matches('0'))
+}
+
+fn verify_revealed_attribute(
+    pres_req: &PresentationRequestPayload,
+    proof: &Presentation,
+    attr_referent: &str,
+    attr_info: &RevealedAttributeInfo,
+) -> Result<()> {
+    let attr_name = pres_req
+        .requested_attributes
+        .get(attr_referent)
+        .as_ref()
+        .ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                attr_referent
+            )
+        })?
+        .name
+        .as_ref()
+        .ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                attr_referent,
+            )
+        })?;
+    verify_revealed_attribute_value(attr_name.as_str(), proof, &attr_info)
+}
+
+fn verify_revealed_attribute_group(
+    pres_req: &PresentationRequestPayload,
+    proof: &Presentation,
+    attr_referent: &str,
+    attr_infos: &RevealedAttributeGroupInfo,
+) -> Result<()> {
+    let attr_names = pres_req
+        .requested_attributes
+        .get(attr_referent)
+        .as_ref()
+        .ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                attr_referent,
+            )
+        })?
+        .names
+        .as_ref()
+        .ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Attribute with referent \"{}\" not found in ProofRequests",
+                attr_referent,
+            )
+        })?;
+    if attr_infos.values.len() != attr_names.len() {
+        error!("Proof Revealed Attr Group does not match Proof Request Attribute Group, proof request attrs: {:?}, referent: {:?}, attr_infos: {:?}", pres_req.requested_attributes, attr_referent, attr_infos);
+        return Err(err_msg!(
+            "Proof Revealed Attr Group does not match Proof Request Attribute Group",
+        ));
+    }
+    for attr_name in attr_names {
+        let attr_info = &get_attr_normalized(&attr_infos.values, attr_name).ok_or_else(|| {
+            err_msg!("Proof Revealed Attr Group does not match Proof Request Attribute Group",)
+        })?;
+        verify_revealed_attribute_value(
+            attr_name,
+            proof,
+            &RevealedAttributeInfo {
+                sub_proof_index: attr_infos.sub_proof_index,
+                raw: attr_info.raw.clone(),
+                encoded: attr_info.encoded.clone(),
+            },
+        )?;
     }
     Ok(())
 }
@@ -546,107 +2364,172 @@ fn verify_requested_restrictions(
     received_unrevealed_attrs: &HashMap<String, Identifier>,
     received_predicates: &HashMap<String, Identifier>,
     self_attested_attrs: &HashSet<String>,
-) -> Result<()> {
+    result: &mut VerificationResult,
+) {
     let proof_attr_identifiers: HashMap<String, Identifier> = received_revealed_attrs
         .iter()
-        .chain(received_unrevealed_attrs)
-        .map(|(r, id)| (r.to_string(), id.clone()))
-        .collect();
+        .chain(received_unrevealed_attrs)
+        .map(|(r, id)| (r.to_string(), id.clone()))
+        .collect();
+
+    let requested_attrs = requested_attributes_with_restrictions(pres_req, self_attested_attrs);
+
+    for (referent, info) in requested_attrs.iter() {
+        if has_failed(result, referent) {
+            continue;
+        }
+        if let Err(err) = verify_attribute_restrictions(
+            referent,
+            info,
+            pres_req,
+            requested_proof,
+            &proof_attr_identifiers,
+        ) {
+            result.add(VerificationCheck::Attribute, Some(referent), err);
+        }
+    }
+
+    for (referent, info) in pres_req.requested_predicates.iter() {
+        if has_failed(result, referent) {
+            continue;
+        }
+        if let Err(err) = verify_predicate_restrictions(
+            referent,
+            info,
+            requested_proof,
+            &requested_attrs,
+            received_predicates,
+        ) {
+            result.add(VerificationCheck::Predicate, Some(referent), err);
+        }
+    }
+}
+
+/// Check whether a failure has already been recorded for a referent, in which case
+/// its remaining checks are skipped
+fn has_failed(result: &VerificationResult, referent: &str) -> bool {
+    result
+        .failures
+        .iter()
+        .any(|failure| failure.referent.as_deref() == Some(referent))
+}
 
-    let requested_attrs: HashMap<String, AttributeInfo> = pres_req
+fn requested_attributes_with_restrictions(
+    pres_req: &PresentationRequestPayload,
+    self_attested_attrs: &HashSet<String>,
+) -> HashMap<String, AttributeInfo> {
+    pres_req
         .requested_attributes
         .iter()
-        .filter(|&(referent, info)| !is_self_attested(&referent, &info, self_attested_attrs))
+        .filter(|&(referent, info)| !is_self_attested(referent, info, self_attested_attrs))
         .map(|(referent, info)| (referent.to_string(), info.clone()))
-        .collect();
-
-    for (referent, info) in requested_attrs.iter() {
-        if let Some(ref query) = info.restrictions {
-            let filter = gather_filter_info(&referent, &proof_attr_identifiers)?;
+        .collect()
+}
 
-            let attr_value_map: HashMap<String, Option<&str>> = if let Some(name) =
-                info.name.as_ref()
-            {
-                let mut map = HashMap::new();
-                map.insert(
-                    name.clone(),
-                    requested_proof
-                        .revealed_attrs
-                        .get(referent)
-                        .map(|attr| attr.raw.as_str()),
-                );
-                map
-            } else if let Some(names) = info.names.as_ref() {
-                let mut map = HashMap::new();
-                let attrs = requested_proof
-                    .revealed_attr_groups
+fn verify_attribute_restrictions(
+    referent: &str,
+    info: &AttributeInfo,
+    pres_req: &PresentationRequestPayload,
+    requested_proof: &RequestedProof,
+    proof_attr_identifiers: &HashMap<String, Identifier>,
+) -> Result<()> {
+    if let Some(ref query) = info.restrictions {
+        let filter = gather_filter_info(referent, proof_attr_identifiers)?;
+
+        let attr_value_map: HashMap<String, Option<&str>> = if let Some(name) = info.name.as_ref() {
+            let mut map = HashMap::new();
+            map.insert(
+                name.clone(),
+                requested_proof
+                    .revealed_attrs
                     .get(referent)
-                    .ok_or_else(|| err_msg!("Proof does not have referent from proof request"))?;
-                for name in names {
-                    let val = attrs.values.get(name).map(|attr| attr.raw.as_str());
-                    map.insert(name.clone(), val);
-                }
-                map
-            } else {
-                error!(
-                    r#"Proof Request attribute restriction should contain "name" or "names" param. Current proof request: {:?}"#,
-                    pres_req
-                );
-                return Err(err_msg!(
-                    r#"Proof Request attribute restriction should contain "name" or "names" param"#,
-                ));
-            };
+                    .map(|attr| attr.raw.as_str()),
+            );
+            map
+        } else if let Some(names) = info.names.as_ref() {
+            // the restriction applies to every value in the group, which are all
+            // unknown when the group is not revealed
+            let attrs = requested_proof.revealed_attr_groups.get(referent);
+            if attrs.is_none() && !requested_proof.unrevealed_attrs.contains_key(referent) {
+                return Err(err_msg!("Proof does not have referent from proof request"));
+            }
+            let mut map = HashMap::new();
+            for name in names {
+                let val = attrs
+                    .and_then(|attrs| get_attr_normalized(&attrs.values, name))
+                    .map(|attr| attr.raw.as_str());
+                map.insert(name.clone(), val);
+            }
+            map
+        } else {
+            error!(
+                r#"Proof Request attribute restriction should contain "name" or "names" param. Current proof request: {:?}"#,
+                pres_req
+            );
+            return Err(err_msg!(
+                r#"Proof Request attribute restriction should contain "name" or "names" param"#,
+            ));
+        };
 
-            process_operator(&attr_value_map, &query, &filter).map_err(err_map!(
-                "Requested restriction validation failed for \"{:?}\" attributes",
-                &attr_value_map
-            ))?;
-        }
+        process_operator(&attr_value_map, query, &filter).map_err(err_map!(
+            "Requested restriction validation failed for \"{:?}\" attributes",
+            &attr_value_map
+        ))?;
     }
 
-    for (referent, info) in pres_req.requested_predicates.iter() {
-        if let Some(ref query) = info.restrictions {
-            let filter = gather_filter_info(&referent, received_predicates)?;
+    Ok(())
+}
 
-            // start with the predicate requested attribute, which is un-revealed
-            let mut attr_value_map = HashMap::new();
-            attr_value_map.insert(info.name.to_string(), None);
+fn verify_predicate_restrictions(
+    referent: &str,
+    info: &PredicateInfo,
+    requested_proof: &RequestedProof,
+    requested_attrs: &HashMap<String, AttributeInfo>,
+    received_predicates: &HashMap<String, Identifier>,
+) -> Result<()> {
+    if let Some(ref query) = info.restrictions {
+        let filter = gather_filter_info(referent, received_predicates)?;
 
-            // include any revealed attributes for the same credential (based on sub_proof_index)
-            let pred_sub_proof_index = requested_proof
-                .predicates
-                .get(referent)
-                .unwrap()
-                .sub_proof_index;
-            for attr_referent in requested_proof.revealed_attrs.keys() {
-                let attr_info = requested_proof.revealed_attrs.get(attr_referent).unwrap();
-                let attr_sub_proof_index = attr_info.sub_proof_index;
-                if pred_sub_proof_index == attr_sub_proof_index {
-                    let attr_name = requested_attrs.get(attr_referent).unwrap().name.clone();
-                    if let Some(name) = attr_name {
-                        attr_value_map.insert(name, Some(attr_info.raw.as_str()));
-                    }
+        // start with the predicate requested attribute, which is un-revealed
+        let mut attr_value_map = HashMap::new();
+        attr_value_map.insert(info.name.to_string(), None);
+
+        // include any revealed attributes for the same credential (based on sub_proof_index)
+        let pred_sub_proof_index = requested_proof
+            .predicates
+            .get(referent)
+            .ok_or_else(|| err_msg!("Proof does not have referent from proof request"))?
+            .sub_proof_index;
+        for attr_referent in requested_proof.revealed_attrs.keys() {
+            let attr_info = requested_proof.revealed_attrs.get(attr_referent).unwrap();
+            let attr_sub_proof_index = attr_info.sub_proof_index;
+            if pred_sub_proof_index == attr_sub_proof_index {
+                let attr_name = requested_attrs
+                    .get(attr_referent)
+                    .and_then(|attr| attr.name.clone());
+                if let Some(name) = attr_name {
+                    attr_value_map.insert(name, Some(attr_info.raw.as_str()));
                 }
             }
-            for attr_referent in requested_proof.revealed_attr_groups.keys() {
-                let attr_info = requested_proof
-                    .revealed_attr_groups
-                    .get(attr_referent)
-                    .unwrap();
-                let attr_sub_proof_index = attr_info.sub_proof_index;
-                if pred_sub_proof_index == attr_sub_proof_index {
-                    for name in attr_info.values.keys() {
-                        let raw_val = attr_info.values.get(name).unwrap().raw.as_str();
-                        attr_value_map.insert(name.clone(), Some(raw_val.clone()));
-                    }
+        }
+        for attr_referent in requested_proof.revealed_attr_groups.keys() {
+            let attr_info = requested_proof
+                .revealed_attr_groups
+                .get(attr_referent)
+                .unwrap();
+            let attr_sub_proof_index = attr_info.sub_proof_index;
+            if pred_sub_proof_index == attr_sub_proof_index {
+                for name in attr_info.values.keys() {
+                    let raw_val = attr_info.values.get(name).unwrap().raw.as_str();
+                    attr_value_map.insert(name.clone(), Some(raw_val.clone()));
                 }
             }
-
-            process_operator(&attr_value_map, &query, &filter).map_err(err_map!(
-                "Requested restriction validation failed for \"{}\" predicate",
-                &info.name
-            ))?;
         }
+
+        process_operator(&attr_value_map, query, &filter).map_err(err_map!(
+            "Requested restriction validation failed for \"{}\" predicate",
+            &info.name
+        ))?;
     }
 
     Ok(())
@@ -657,13 +2540,7 @@ fn is_self_attested(
     info: &AttributeInfo,
     self_attested_attrs: &HashSet<String>,
 ) -> bool {
-    match info.restrictions.as_ref() {
-        Some(&Query::And(ref array)) | Some(&Query::Or(ref array)) if array.is_empty() => {
-            self_attested_attrs.contains(referent)
-        }
-        None => self_attested_attrs.contains(referent),
-        Some(_) => false,
-    }
+    info.allows_self_attestation() && self_attested_attrs.contains(referent)
 }
 
 fn gather_filter_info(referent: &str, identifiers: &HashMap<String, Identifier>) -> Result<Filter> {
@@ -675,31 +2552,106 @@ fn gather_filter_info(referent: &str, identifiers: &HashMap<String, Identifier>)
         )
     })?;
 
-    let (_, schema_issuer_did, schema_name, schema_version) =
-        identifier.schema_id.parts().ok_or_else(|| {
-            err_msg!(
-                "Invalid Schema ID `{}`: wrong number of parts",
-                identifier.schema_id.0
-            )
-        })?;
+    build_filter(&identifier.schema_id, &identifier.cred_def_id)
+}
 
-    let issuer_did = identifier.cred_def_id.issuer_did().ok_or_else(|| {
-        err_msg!(
-            "Invalid Credential Definition ID `{}`: wrong number of parts",
-            identifier.cred_def_id.0
-        )
-    })?;
+fn build_filter(schema_id: &SchemaId, cred_def_id: &CredentialDefinitionId) -> Result<Filter> {
+    // URI identifiers which are not DID URLs in the `did:indy` object format don't
+    // carry the issuer, schema name or version, so restrictions on them can't match
+    let (schema_issuer_did, (schema_name, schema_version)) =
+        match (schema_id.issuer_did(), schema_id.name_version()) {
+            (Some(did), Some(name_version)) => (did.0, name_version),
+            (did, name_version) if schema_id.is_uri() => (
+                did.map(|did| did.0).unwrap_or_default(),
+                name_version.unwrap_or_default(),
+            ),
+            _ => {
+                return Err(err_msg!(
+                    "Invalid Schema ID `{}`: wrong number of parts",
+                    schema_id.0
+                ))
+            }
+        };
+
+    let issuer_did = match cred_def_id.issuer_did() {
+        Some(did) => did.0,
+        None if cred_def_id.is_uri() => String::new(),
+        None => {
+            return Err(err_msg!(
+                "Invalid Credential Definition ID `{}`: wrong number of parts",
+                cred_def_id.0
+            ))
+        }
+    };
 
     Ok(Filter {
-        schema_id: identifier.schema_id.0.to_string(),
+        schema_id: schema_id.0.to_string(),
         schema_name,
-        schema_issuer_did: schema_issuer_did.0,
+        schema_issuer_did,
         schema_version,
-        cred_def_id: identifier.cred_def_id.0.to_string(),
-        issuer_did: issuer_did.0,
+        cred_def_id: cred_def_id.0.to_string(),
+        issuer_did,
     })
 }
 
+/// Check a restriction query against the identifiers of a credential, as it would be
+/// checked when verifying a presentation with the given attribute values revealed
+pub(crate) fn check_restrictions(
+    attr_value_map: &HashMap<String, Option<&str>>,
+    restrictions: &Query,
+    schema_id: &SchemaId,
+    cred_def_id: &CredentialDefinitionId,
+) -> Result<()> {
+    let filter = build_filter(schema_id, cred_def_id)?;
+    process_operator(attr_value_map, restrictions, &filter)
+}
+
+/// Evaluate a restriction query against the identifiers and attribute values of a
+/// credential, using the same semantics as presentation verification. The result
+/// records whether each clause of the query matched, and why it did not.
+pub fn evaluate_restrictions(
+    credential: &CredentialIdentifier,
+    restrictions: &Query,
+) -> Result<RestrictionEvaluation> {
+    trace!(
+        "evaluate_restrictions >>> credential: {:?}, restrictions: {:?}",
+        credential,
+        restrictions
+    );
+
+    let filter = build_filter(credential.schema_id, credential.cred_def_id)?;
+    let evaluation = evaluate_clause(&credential.attr_values, restrictions, &filter);
+
+    trace!("evaluate_restrictions <<< evaluation: {:?}", evaluation);
+
+    Ok(evaluation)
+}
+
+fn evaluate_clause(
+    attr_value_map: &HashMap<String, Option<&str>>,
+    clause: &Query,
+    filter: &Filter,
+) -> RestrictionEvaluation {
+    let clauses = match clause {
+        Query::And(operators) | Query::Or(operators) => operators
+            .iter()
+            .map(|op| evaluate_clause(attr_value_map, op, filter))
+            .collect(),
+        Query::Not(operator) => vec![evaluate_clause(attr_value_map, operator, filter)],
+        _ => vec![],
+    };
+    let (matched, reason) = match process_operator(attr_value_map, clause, filter) {
+        Ok(()) => (true, None),
+        Err(err) => (false, Some(err.to_string())),
+    };
+    RestrictionEvaluation {
+        clause: clause.clone(),
+        matched,
+        reason,
+        clauses,
+    }
+}
+
 fn process_operator(
     attr_value_map: &HashMap<String, Option<&str>>,
     restriction_op: &Query,
@@ -816,7 +2768,7 @@ fn is_attr_internal_tag(key: &str, attr_value_map: &HashMap<String, Option<&str>
         .captures(key)
         .map(|caps| {
             caps.get(1)
-                .map(|s| attr_value_map.contains_key(&s.as_str().to_string()))
+                .map(|s| get_attr_normalized(attr_value_map, s.as_str()).is_some())
                 .unwrap_or(false)
         })
         .unwrap_or(false)
@@ -833,7 +2785,7 @@ fn check_internal_tag_revealed_value(
         .get(1)
         .ok_or_else(|| err_msg!(InvalidState, "No name has been parsed",))?
         .as_str();
-    if let Some(Some(revealed_value)) = attr_value_map.get(attr_name) {
+    if let Some(Some(revealed_value)) = get_attr_normalized(attr_value_map, attr_name) {
         if *revealed_value != tag_value {
             return Err(err_msg!(
                 ProofRejected,
@@ -855,6 +2807,211 @@ fn is_attr_operator(key: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn check_presentation_nonce_works() {
+        let nonce = generate_nonce().unwrap();
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": nonce.to_string(),
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {},
+            "requested_predicates": {},
+        }))
+        .unwrap();
+        check_presentation_nonce(&pres_req, &nonce).unwrap();
+
+        let other = generate_nonce().unwrap();
+        assert_kind!(NonceMismatch, check_presentation_nonce(&pres_req, &other));
+    }
+
+    #[test]
+    fn verification_cache_expires_entries() {
+        use std::time::Duration;
+
+        let valid = VerificationResult::default();
+        let mut invalid = VerificationResult::default();
+        invalid.add(VerificationCheck::SubProof, None, "Invalid proof");
+
+        let cache = VerificationCache::new(Duration::from_millis(50), 2);
+        cache.insert(vec![1], valid.clone());
+        cache.insert(vec![2], invalid.clone());
+        assert_eq!(cache.get(&[1]), Some(valid.clone()));
+        assert_eq!(cache.get(&[2]), Some(invalid));
+
+        cache.insert(vec![3], valid);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&[1]), None);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&[3]), None);
+    }
+
+    #[test]
+    fn verification_cache_key_covers_inputs_and_config() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {},
+        }))
+        .unwrap();
+        let presentation: Presentation = serde_json::from_value(serde_json::json!({
+            "proof": {"proofs": [], "aggregated_proof": {"c_hash": "1", "c_list": []}},
+            "requested_proof": {"revealed_attrs": {}},
+            "identifiers": [],
+        }))
+        .unwrap();
+        let _config = ConfigGuard::set(CredxConfig::default());
+        let no_schemas = HashMap::new();
+        let cred_defs = HashMap::new();
+        type RevRegs<'a> = HashMap<RevocationRegistryId, HashMap<u64, &'a RevocationRegistry>>;
+        let cache_key = |schemas: &HashMap<SchemaId, &Schema>, rev_regs: Option<&RevRegs>| {
+            verification_cache_key(
+                &presentation,
+                &pres_req,
+                schemas,
+                &cred_defs,
+                None,
+                rev_regs,
+            )
+            .unwrap()
+        };
+
+        let key = cache_key(&no_schemas, None);
+        assert_eq!(cache_key(&no_schemas, None), key);
+
+        let schema_id = SchemaId("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0".into());
+        let schema = |attr_names: &[&str]| -> Schema {
+            serde_json::from_value(serde_json::json!({
+                "ver": "1.0",
+                "id": schema_id,
+                "name": "gvt",
+                "version": "1.0",
+                "attrNames": attr_names,
+                "seqNo": 1,
+            }))
+            .unwrap()
+        };
+        let (schema_1, schema_2) = (schema(&["name"]), schema(&["name", "age"]));
+        let mut schemas_1 = HashMap::new();
+        schemas_1.insert(schema_id.clone(), &schema_1);
+        let mut schemas_2 = HashMap::new();
+        schemas_2.insert(schema_id.clone(), &schema_2);
+        assert_ne!(cache_key(&schemas_1, None), key);
+        assert_ne!(cache_key(&schemas_1, None), cache_key(&schemas_2, None));
+
+        let mut rev_regs = HashMap::new();
+        rev_regs.insert(
+            RevocationRegistryId("NcYxiDXkpYi6ov5FcYDi1e:4:tag".into()),
+            HashMap::new(),
+        );
+        assert_ne!(cache_key(&no_schemas, Some(&rev_regs)), key);
+
+        set_config(CredxConfig {
+            non_revoked_interval_policy: NonRevokedIntervalPolicy::Strict,
+            ..CredxConfig::default()
+        })
+        .unwrap();
+        assert_ne!(cache_key(&no_schemas, None), key);
+    }
+
+    #[test]
+    fn verify_presentation_referents_checks_limits() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {},
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+            },
+        }))
+        .unwrap();
+        let presentation: Presentation = serde_json::from_value(serde_json::json!({
+            "proof": {"proofs": [], "aggregated_proof": {"c_hash": "1", "c_list": []}},
+            "requested_proof": {"revealed_attrs": {}},
+            "identifiers": [],
+        }))
+        .unwrap();
+        let mut config = CredxConfig::default();
+        config.validation.max_predicates = 0;
+        let _config = ConfigGuard::set(config);
+
+        let referents = vec!["predicate1_referent".to_string()]
+            .into_iter()
+            .collect();
+        assert_kind!(
+            LimitExceeded,
+            verify_presentation_referents(
+                &presentation,
+                &pres_req,
+                &referents,
+                &HashMap::new(),
+                &HashMap::new(),
+                None,
+                None,
+            )
+        );
+    }
+
+    #[test]
+    fn build_filter_for_uri_identifiers() {
+        let schema_id =
+            SchemaId("did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/SCHEMA/gvt/1.0".into());
+        let cred_def_id = CredentialDefinitionId(
+            "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/CLAIM_DEF/1/tag".into(),
+        );
+        let filter = build_filter(&schema_id, &cred_def_id).unwrap();
+        assert_eq!(filter.schema_name, "gvt");
+        assert_eq!(filter.schema_version, "1.0");
+        assert_eq!(
+            filter.schema_issuer_did,
+            "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e"
+        );
+        assert_eq!(filter.issuer_did, "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e");
+
+        let restrictions = Query::Eq("cred_def_id".to_string(), cred_def_id.0.clone());
+        check_restrictions(&HashMap::new(), &restrictions, &schema_id, &cred_def_id).unwrap();
+
+        let schema_id = SchemaId("https://example.com/schemas/gvt".into());
+        let cred_def_id = CredentialDefinitionId("https://example.com/cred-defs/gvt".into());
+        let filter = build_filter(&schema_id, &cred_def_id).unwrap();
+        assert_eq!(filter.schema_name, "");
+        assert_eq!(filter.issuer_did, "");
+        let restrictions = Query::Eq("schema_name".to_string(), "gvt".to_string());
+        check_restrictions(&HashMap::new(), &restrictions, &schema_id, &cred_def_id).unwrap_err();
+    }
+
+    #[test]
+    fn evaluate_restrictions_reports_clauses() {
+        let schema_id = SchemaId("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0".into());
+        let cred_def_id = CredentialDefinitionId("NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag".into());
+        let mut credential = CredentialIdentifier::new(&schema_id, &cred_def_id);
+        credential.add_attribute("Name", Some("Alex"));
+
+        let restrictions = Query::And(vec![
+            Query::Eq("schema_name".into(), "gvt".into()),
+            Query::Or(vec![
+                Query::Eq("issuer_did".into(), "other".into()),
+                Query::Eq("attr::name::value".into(), "Alex".into()),
+            ]),
+        ]);
+        let evaluation = evaluate_restrictions(&credential, &restrictions).unwrap();
+        assert!(evaluation.matched);
+        assert_eq!(evaluation.clauses.len(), 2);
+        assert!(evaluation.clauses[0].matched);
+        let or_clause = &evaluation.clauses[1];
+        assert!(or_clause.matched);
+        assert!(!or_clause.clauses[0].matched);
+        assert!(or_clause.clauses[0].reason.is_some());
+        assert!(or_clause.clauses[1].matched);
+
+        let restrictions = Query::Not(Box::new(Query::Eq("schema_version".into(), "1.0".into())));
+        let evaluation = evaluate_restrictions(&credential, &restrictions).unwrap();
+        assert!(!evaluation.matched);
+        assert!(evaluation.clauses[0].matched);
+    }
+
     pub const SCHEMA_ID: &str = "123";
     pub const SCHEMA_NAME: &str = "Schema Name";
     pub const SCHEMA_ISSUER_DID: &str = "234";
@@ -1162,6 +3319,16 @@ mod tests {
         assert!(_process_operator("zip", &op, &filter, Some("NOT HERE")).is_err());
     }
 
+    #[test]
+    fn test_process_op_eq_revealed_value_normalized_name() {
+        let filter = filter();
+        let value = "value";
+
+        let op = Query::Eq(attr_tag_value(), value.to_string());
+        _process_operator("Zip", &op, &filter, Some(value)).unwrap();
+        assert!(_process_operator("Z ip", &op, &filter, Some("NOT HERE")).is_err());
+    }
+
     fn _received() -> HashMap<String, Identifier> {
         let mut res: HashMap<String, Identifier> = HashMap::new();
         res.insert(
@@ -1194,15 +3361,642 @@ mod tests {
 
     #[test]
     fn validate_timestamp_works() {
-        validate_timestamp(&_received(), "referent_1", &None, &None).unwrap();
-        validate_timestamp(&_received(), "referent_1", &Some(_interval()), &None).unwrap();
-        validate_timestamp(&_received(), "referent_1", &None, &Some(_interval())).unwrap();
+        validate_timestamp(&_received(), "referent_1", &None, &None, None).unwrap();
+        validate_timestamp(&_received(), "referent_1", &Some(_interval()), &None, None).unwrap();
+        validate_timestamp(&_received(), "referent_1", &None, &Some(_interval()), None).unwrap();
     }
 
     #[test]
     fn validate_timestamp_not_work() {
-        validate_timestamp(&_received(), "referent_2", &Some(_interval()), &None).unwrap_err();
-        validate_timestamp(&_received(), "referent_2", &None, &Some(_interval())).unwrap_err();
-        validate_timestamp(&_received(), "referent_3", &None, &Some(_interval())).unwrap_err();
+        validate_timestamp(&_received(), "referent_2", &Some(_interval()), &None, None)
+            .unwrap_err();
+        validate_timestamp(&_received(), "referent_2", &None, &Some(_interval()), None)
+            .unwrap_err();
+        validate_timestamp(&_received(), "referent_3", &None, &Some(_interval()), None)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn validate_timestamp_only_requires_presence_by_default() {
+        let _config = ConfigGuard::set(CredxConfig::default());
+        let interval = NonRevocedInterval {
+            from: None,
+            to: Some(1000),
+        };
+        validate_timestamp(
+            &_received(),
+            "referent_1",
+            &None,
+            &Some(interval.clone()),
+            None,
+        )
+        .unwrap();
+
+        let mut received = _received();
+        received.get_mut("referent_1").unwrap().timestamp =
+            Some(current_timestamp() + get_clock_skew() + 60);
+        validate_timestamp(
+            &received,
+            "referent_1",
+            &None,
+            &Some(interval.clone()),
+            None,
+        )
+        .unwrap();
+        validate_timestamp(&received, "referent_2", &None, &Some(interval), None).unwrap_err();
+    }
+
+    #[test]
+    fn validate_timestamp_before_interval_end() {
+        let _config = ConfigGuard::set(CredxConfig {
+            non_revoked_interval_policy: NonRevokedIntervalPolicy::Rfc0441,
+            ..CredxConfig::default()
+        });
+        let interval = NonRevocedInterval {
+            from: Some(2000),
+            to: Some(3000),
+        };
+        validate_timestamp(&_received(), "referent_1", &None, &Some(interval), None).unwrap();
+
+        let interval = NonRevocedInterval {
+            from: None,
+            to: Some(1000),
+        };
+        assert_kind!(
+            ProofRejected,
+            validate_timestamp(&_received(), "referent_1", &None, &Some(interval), None)
+        );
+
+        let mut received = _received();
+        received.get_mut("referent_1").unwrap().timestamp =
+            Some(current_timestamp() + get_clock_skew() + 60);
+        let interval = NonRevocedInterval {
+            from: None,
+            to: Some(u64::MAX),
+        };
+        assert_kind!(
+            ProofRejected,
+            validate_timestamp(&received, "referent_1", &None, &Some(interval), None)
+        );
+    }
+
+    #[test]
+    fn apply_interval_override_works() {
+        let rev_reg_id = RevocationRegistryId("rev_reg".to_string());
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            rev_reg_id.clone(),
+            vec![(1000, 900), (2000, 2500)].into_iter().collect(),
+        );
+
+        let mut interval = NonRevocedInterval {
+            from: Some(1000),
+            to: Some(2000),
+        };
+        apply_interval_override(&mut interval, &rev_reg_id, &overrides);
+        assert_eq!(interval.from, Some(900));
+        assert_eq!(interval.to, Some(2500));
+        assert!(NonRevokedIntervalPolicy::Strict.accepts(950, &interval, 0));
+        assert!(NonRevokedIntervalPolicy::Rfc0441.accepts(2400, &interval, 0));
+
+        let mut other = NonRevocedInterval {
+            from: Some(1000),
+            to: Some(2000),
+        };
+        apply_interval_override(
+            &mut other,
+            &RevocationRegistryId("other".to_string()),
+            &overrides,
+        );
+        assert_eq!(other.from, Some(1000));
+        assert_eq!(other.to, Some(2000));
+
+        // an override which narrows the interval is applied as given
+        let mut later = NonRevocedInterval {
+            from: Some(2000),
+            to: Some(3000),
+        };
+        apply_interval_override(&mut later, &rev_reg_id, &overrides);
+        assert_eq!(later.from, Some(2500));
+        assert_eq!(later.to, Some(3000));
+        assert!(!NonRevokedIntervalPolicy::Strict.accepts(2200, &later, 0));
+    }
+
+    #[test]
+    fn validate_timestamp_with_override_for_rfc0441_policy() {
+        let _config = ConfigGuard::set(CredxConfig {
+            non_revoked_interval_policy: NonRevokedIntervalPolicy::Rfc0441,
+            ..CredxConfig::default()
+        });
+        let rev_reg_id = RevocationRegistryId(String::new());
+        let interval = NonRevocedInterval {
+            from: Some(1000),
+            to: Some(1200),
+        };
+        validate_timestamp(
+            &_received(),
+            "referent_1",
+            &Some(interval.clone()),
+            &None,
+            None,
+        )
+        .unwrap_err();
+
+        let mut overrides = HashMap::new();
+        overrides.insert(rev_reg_id.clone(), vec![(1200, 1234)].into_iter().collect());
+        validate_timestamp(
+            &_received(),
+            "referent_1",
+            &Some(interval.clone()),
+            &None,
+            Some(&overrides),
+        )
+        .unwrap();
+
+        // an override for the advisory start of the interval has no effect
+        let mut overrides = HashMap::new();
+        overrides.insert(rev_reg_id.clone(), vec![(1000, 1234)].into_iter().collect());
+        validate_timestamp(
+            &_received(),
+            "referent_1",
+            &Some(interval),
+            &None,
+            Some(&overrides),
+        )
+        .unwrap_err();
+
+        // an override which narrows the requested interval rejects a timestamp
+        // that the request alone would accept
+        let interval = NonRevocedInterval {
+            from: None,
+            to: Some(1300),
+        };
+        validate_timestamp(
+            &_received(),
+            "referent_1",
+            &Some(interval.clone()),
+            &None,
+            None,
+        )
+        .unwrap();
+        let mut overrides = HashMap::new();
+        overrides.insert(rev_reg_id, vec![(1300, 1200)].into_iter().collect());
+        assert_kind!(
+            ProofRejected,
+            validate_timestamp(
+                &_received(),
+                "referent_1",
+                &Some(interval),
+                &None,
+                Some(&overrides),
+            )
+        );
+    }
+
+    #[test]
+    fn non_revoked_interval_policy_works() {
+        let interval = NonRevocedInterval {
+            from: Some(1000),
+            to: Some(2000),
+        };
+        let strict = NonRevokedIntervalPolicy::Strict;
+        assert!(strict.accepts(1000, &interval, 0));
+        assert!(strict.accepts(2000, &interval, 0));
+        assert!(!strict.accepts(999, &interval, 0));
+        assert!(!strict.accepts(2001, &interval, 0));
+
+        let rfc0441 = NonRevokedIntervalPolicy::Rfc0441;
+        assert!(rfc0441.accepts(2000, &interval, 0));
+        assert!(rfc0441.accepts(1500, &interval, 0));
+        assert!(rfc0441.accepts(500, &interval, 0));
+        assert!(!rfc0441.accepts(2500, &interval, 0));
+        assert!(rfc0441.accepts(2500, &interval, 500));
+        assert!(rfc0441.accepts(
+            500,
+            &NonRevocedInterval {
+                from: Some(1000),
+                to: None
+            },
+            0
+        ));
+    }
+
+    #[test]
+    fn add_expiry_predicate_works() {
+        let mut pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {},
+            "requested_predicates": {},
+        }))
+        .unwrap();
+        add_expiry_predicate(&mut pres_req, "expiry", "expiry_date", Some(20240229), None).unwrap();
+        let info = &pres_req.value().requested_predicates["expiry"];
+        assert_eq!(info.name, "expiry_date");
+        assert_eq!(info.p_type, PredicateTypes::GE);
+        assert_eq!(info.p_value, 20240229);
+
+        assert!(add_expiry_predicate(&mut pres_req, "expiry", "expiry_date", None, None).is_err());
+        add_expiry_predicate(&mut pres_req, "expiry_now", "expiry_date", None, None).unwrap();
+        assert!(pres_req.value().requested_predicates["expiry_now"].p_value > 20240229);
+    }
+
+    #[test]
+    fn resolve_schema_seq_nos_works() {
+        use indy_data_types::anoncreds::schema::SchemaV1;
+
+        let full_id = SchemaId::new(
+            &DidValue::new(SCHEMA_ISSUER_DID, None),
+            SCHEMA_NAME,
+            SCHEMA_VERSION,
+        );
+        let schema = Schema::SchemaV1(SchemaV1 {
+            id: full_id.clone(),
+            name: SCHEMA_NAME.to_string(),
+            version: SCHEMA_VERSION.to_string(),
+            attr_names: ["name"][..].into(),
+            seq_no: Some(15),
+        });
+        let mut schemas = HashMap::new();
+        schemas.insert(full_id.clone(), &schema);
+        let seq_nos = schema_seq_nos(&schemas);
+        assert_eq!(seq_nos.seq_no(&full_id), Some(15));
+
+        let mut received = _received();
+        for identifier in received.values_mut() {
+            identifier.schema_id = SchemaId::from_seq_no(15);
+        }
+        let received = resolve_schema_seq_nos(received, &seq_nos);
+        assert!(received.values().all(|id| id.schema_id == full_id));
+
+        let unknown = resolve_schema_seq_nos(_received(), &seq_nos);
+        assert!(unknown.values().all(|id| id.schema_id.0.is_empty()));
+        assert!(find_schema(&schemas, &SchemaId::from_seq_no(15)).is_some());
+        assert!(find_schema(&schemas, &SchemaId::from_seq_no(16)).is_none());
+    }
+
+    #[test]
+    fn verify_attribute_groups_works() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {
+                "complete": {"names": ["first", "last"]},
+                "partial": {"names": ["first", "last"]},
+                "extra": {"names": ["first"]},
+                "single": {"names": ["first", "last"]},
+                "self_attested": {"names": ["first", "last"]},
+                "unrevealed": {"names": ["first", "last"]},
+                "as_group": {"name": "first"},
+            },
+        }))
+        .unwrap();
+        let requested_proof: RequestedProof = serde_json::from_value(serde_json::json!({
+            "revealed_attrs": {
+                "single": {"sub_proof_index": 0, "raw": "Alex", "encoded": "1"},
+            },
+            "revealed_attr_groups": {
+                "complete": {"sub_proof_index": 0, "values": {
+                    "First": {"raw": "Alex", "encoded": "1"},
+                    "last": {"raw": "Smith", "encoded": "2"},
+                }},
+                "partial": {"sub_proof_index": 0, "values": {
+                    "first": {"raw": "Alex", "encoded": "1"},
+                }},
+                "extra": {"sub_proof_index": 0, "values": {
+                    "first": {"raw": "Alex", "encoded": "1"},
+                    "last": {"raw": "Smith", "encoded": "2"},
+                }},
+                "as_group": {"sub_proof_index": 0, "values": {
+                    "first": {"raw": "Alex", "encoded": "1"},
+                }},
+            },
+            "self_attested_attrs": {"self_attested": "Alex Smith"},
+            "unrevealed_attrs": {"unrevealed": {"sub_proof_index": 0}},
+            "predicates": {},
+        }))
+        .unwrap();
+
+        let mut result = VerificationResult::default();
+        verify_attribute_groups(pres_req.value(), &requested_proof, &mut result);
+        let mut failed = result
+            .failures
+            .iter()
+            .map(|failure| failure.referent.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        failed.sort_unstable();
+        assert_eq!(
+            failed,
+            vec!["as_group", "extra", "partial", "self_attested", "single"]
+        );
+    }
+
+    #[test]
+    fn presentation_request_builder_works() {
+        let restrictions = Some(Query::Eq("schema_name".to_string(), "gvt".to_string()));
+        let mut builder = PresentationRequestBuilder::new("proof", "1.0");
+        let name = builder.add_attribute("First Name", None).unwrap();
+        assert_eq!(name, "attr_first_name");
+        assert_eq!(builder.add_attribute("firstname", None).unwrap(), name);
+        let restricted = builder
+            .add_attribute("first_name", restrictions.clone())
+            .unwrap();
+        assert_eq!(restricted, "attr_first_name_2");
+
+        let group = builder
+            .add_grouped_attribute("license", "number", restrictions.clone())
+            .unwrap();
+        assert_eq!(group, "group_license");
+        assert_eq!(
+            builder
+                .add_grouped_attribute("license", "Number", restrictions.clone())
+                .unwrap(),
+            group
+        );
+        builder
+            .add_grouped_attribute("license", "class", restrictions.clone())
+            .unwrap();
+        builder
+            .add_grouped_attribute("license", "expiry", None)
+            .unwrap_err();
+
+        let pred = builder
+            .add_predicate("age", PredicateTypes::GE, 18, None)
+            .unwrap();
+        assert_eq!(pred, "pred_age_ge_18");
+        assert_eq!(
+            builder
+                .add_predicate("Age", PredicateTypes::GE, 18, None)
+                .unwrap(),
+            pred
+        );
+        builder.add_attribute(" ", None).unwrap_err();
+
+        let pres_req = builder.build().unwrap();
+        assert_eq!(pres_req.requested_attributes.len(), 3);
+        assert_eq!(
+            pres_req.requested_attributes[&group].names,
+            Some(vec!["number".to_string(), "class".to_string()])
+        );
+        assert_eq!(pres_req.requested_predicates.len(), 1);
+        PresentationRequest::PresentationRequestV1(pres_req)
+            .validate()
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_self_attested_attributes_works() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {
+                "open": {"name": "phone"},
+                "empty": {"name": "email", "restrictions": []},
+                "restricted": {"name": "age", "restrictions": {"schema_name": "gvt"}},
+                "group": {"names": ["first", "last"]},
+            },
+        }))
+        .unwrap();
+        let self_attested = ["open", "empty", "restricted", "group", "unknown"]
+            .iter()
+            .map(|referent| referent.to_string())
+            .collect::<HashSet<_>>();
+
+        let mut result = VerificationResult::default();
+        verify_self_attested_attributes(pres_req.value(), &self_attested, &mut result);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].referent.as_deref(), Some("restricted"));
+        assert!(!is_self_attested(
+            "restricted",
+            &pres_req.value().requested_attributes["restricted"],
+            &self_attested
+        ));
+    }
+
+    #[test]
+    fn verify_revealed_attribute_encodings_works() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {
+                "attr1": {"name": "name"},
+                "attr2": {"name": "age"},
+                "attr3": {"names": ["first", "last", "photo"]},
+                "attr4": {"name": "document"},
+                "attr5": {"name": "balance"},
+            },
+        }))
+        .unwrap();
+        let (photo_raw, photo_encoded) = encode_binary_attribute(b"photo").unwrap();
+        let (document_raw, _) = encode_binary_attribute(b"document").unwrap();
+        let presentation: Presentation = serde_json::from_value(serde_json::json!({
+            "proof": {"proofs": [], "aggregated_proof": {"c_hash": "1", "c_list": []}},
+            "requested_proof": {
+                "revealed_attrs": {
+                    "attr1": {"sub_proof_index": 0, "raw": "Alex", "encoded": "1"},
+                    "attr2": {"sub_proof_index": 0, "raw": "007", "encoded": "7"},
+                    "attr4": {"sub_proof_index": 0, "raw": document_raw, "encoded": "3"},
+                    "attr5": {
+                        "sub_proof_index": 0,
+                        "raw": "12.50",
+                        "encoded": encode_decimal_attribute("12.50", 2).unwrap(),
+                    },
+                },
+                "revealed_attr_groups": {
+                    "attr3": {"sub_proof_index": 0, "values": {
+                        "first": {"raw": "Alex", "encoded": encode_credential_attribute("Alex").unwrap()},
+                        "last": {"raw": "Smith", "encoded": "2"},
+                        "photo": {"raw": photo_raw, "encoded": photo_encoded},
+                    }},
+                },
+            },
+            "identifiers": [],
+        }))
+        .unwrap();
+
+        let mut options = VerificationOptions::default();
+        let mut result = VerificationResult::default();
+        verify_revealed_attribute_encodings(
+            pres_req.value(),
+            &presentation.requested_proof,
+            &options,
+            &mut result,
+        )
+        .unwrap();
+        let failed = |result: &VerificationResult| {
+            result
+                .failures
+                .iter()
+                .map(|f| {
+                    (
+                        f.referent.clone().unwrap(),
+                        f.reason.rsplit(": ").next().unwrap().to_owned(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            failed(&result),
+            vec![
+                ("attr1".to_owned(), "name".to_owned()),
+                ("attr3".to_owned(), "last".to_owned()),
+                ("attr4".to_owned(), "document".to_owned()),
+                ("attr5".to_owned(), "balance".to_owned()),
+            ]
+        );
+
+        // only attributes declared as decimal or salted are skipped
+        options.decimal_attributes.insert("Balance".to_owned());
+        options.salted_attributes.insert("name".to_owned());
+        let mut result = VerificationResult::default();
+        verify_revealed_attribute_encodings(
+            pres_req.value(),
+            &presentation.requested_proof,
+            &options,
+            &mut result,
+        )
+        .unwrap();
+        assert_eq!(
+            failed(&result),
+            vec![
+                ("attr3".to_owned(), "last".to_owned()),
+                ("attr4".to_owned(), "document".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_revealed_attribute_encodings_uses_encoder() {
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {"attr1": {"name": "name"}},
+        }))
+        .unwrap();
+        let presentation: Presentation = serde_json::from_value(serde_json::json!({
+            "proof": {"proofs": [], "aggregated_proof": {"c_hash": "1", "c_list": []}},
+            "requested_proof": {
+                "revealed_attrs": {
+                    "attr1": {
+                        "sub_proof_index": 0,
+                        "raw": "007",
+                        "encoded": encode_canonical_credential_attribute("007").unwrap(),
+                    },
+                },
+            },
+            "identifiers": [],
+        }))
+        .unwrap();
+
+        let mut options = VerificationOptions::default();
+        let mut result = VerificationResult::default();
+        verify_revealed_attribute_encodings(
+            pres_req.value(),
+            &presentation.requested_proof,
+            &options,
+            &mut result,
+        )
+        .unwrap();
+        assert!(!result.is_valid());
+
+        options
+            .encoder
+            .replace(std::sync::Arc::new(CanonicalEncoder));
+        let mut result = VerificationResult::default();
+        verify_revealed_attribute_encodings(
+            pres_req.value(),
+            &presentation.requested_proof,
+            &options,
+            &mut result,
+        )
+        .unwrap();
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn summarize_presentation_works() {
+        let schema_id = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {
+                "attr1": {"name": "name"},
+                "attr2": {"names": ["first", "last"]},
+                "attr3": {"name": "ssn"},
+                "attr4": {"name": "nickname"},
+                "unused": {"name": "email"},
+            },
+            "requested_predicates": {
+                "pred1": {"name": "age", "p_type": ">=", "p_value": 18},
+            },
+        }))
+        .unwrap();
+        let presentation: Presentation = serde_json::from_value(serde_json::json!({
+            "proof": {"proofs": [], "aggregated_proof": {"c_hash": "1", "c_list": []}},
+            "requested_proof": {
+                "revealed_attrs": {
+                    "attr1": {"sub_proof_index": 0, "raw": "Alex", "encoded": "1"},
+                },
+                "revealed_attr_groups": {
+                    "attr2": {"sub_proof_index": 1, "values": {
+                        "last": {"raw": "Smith", "encoded": "2"},
+                        "first": {"raw": "Alex", "encoded": "3"},
+                    }},
+                },
+                "unrevealed_attrs": {"attr3": {"sub_proof_index": 1}},
+                "self_attested_attrs": {"attr4": "Al"},
+                "predicates": {"pred1": {"sub_proof_index": 0}},
+            },
+            "identifiers": [
+                {"schema_id": schema_id, "cred_def_id": "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag"},
+                {"schema_id": schema_id, "cred_def_id": "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:other"},
+            ],
+        }))
+        .unwrap();
+
+        let summary = summarize_presentation(&presentation, &pres_req).unwrap();
+        assert_eq!(summary.referents.len(), 5);
+        assert_eq!(
+            summary.referents(Disclosure::Revealed).collect::<Vec<_>>(),
+            vec!["attr1", "attr2"]
+        );
+        assert_eq!(summary.referents["attr2"].names, vec!["first", "last"]);
+        assert_eq!(
+            summary.referents["attr3"].cred_def_id.as_ref().unwrap().0,
+            "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:other"
+        );
+        let self_attested = &summary.referents["attr4"];
+        assert_eq!(self_attested.disclosure, Disclosure::SelfAttested);
+        assert_eq!(self_attested.cred_def_id, None);
+        let predicate = &summary.referents["pred1"];
+        assert_eq!(predicate.names, vec!["age"]);
+        assert_eq!(predicate.p_type, Some(PredicateTypes::GE));
+        assert_eq!(predicate.p_value, Some(18));
+        assert_eq!(predicate.schema_id.as_ref().unwrap().0, schema_id);
+
+        let summary = serde_json::to_value(&summary).unwrap();
+        assert_eq!(summary["referents"]["attr3"]["disclosure"], "unrevealed");
+        assert!(summary.to_string().find("Smith").is_none());
+    }
+
+    #[test]
+    fn check_binary_attribute_works() {
+        let (raw, encoded) = encode_binary_attribute(b"content").unwrap();
+        assert!(check_binary_attribute(&raw, &encoded, b"content").unwrap());
+        assert!(check_binary_attribute(
+            &raw.to_uppercase().replace("SHA256", "sha256"),
+            &encoded,
+            b"content"
+        )
+        .unwrap());
+        assert!(!check_binary_attribute(&raw, &encoded, b"other").unwrap());
+        assert!(!check_binary_attribute(&raw, "1", b"content").unwrap());
+        assert!(check_binary_attribute("content", &encoded, b"content").is_err());
     }
 }
@@ -0,0 +1,511 @@
+//! A high-level API for the prover and verifier workflows using owned types.
+//!
+//! The `Prover` and `Verifier` builders take ownership of the ledger objects
+//! they are given, instead of requiring maps of borrowed references, so they
+//! can be held across `await` points or moved between tasks.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::types::*;
+use super::{prover, verifier};
+use crate::error::Result;
+
+/// Collects the ledger objects needed to verify presentations
+#[derive(Debug, Default)]
+pub struct Verifier {
+    schemas: HashMap<SchemaId, Schema>,
+    cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition>,
+    rev_reg_defs: HashMap<RevocationRegistryId, RevocationRegistryDefinition>,
+    rev_regs: HashMap<RevocationRegistryId, HashMap<u64, RevocationRegistry>>,
+    interval_overrides: HashMap<RevocationRegistryId, HashMap<u64, u64>>,
+    options: VerificationOptions,
+}
+
+impl Verifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schemas.insert(schema.id().clone(), schema);
+        self
+    }
+
+    pub fn with_cred_def(mut self, cred_def: CredentialDefinition) -> Self {
+        self.cred_defs.insert(cred_def.id().clone(), cred_def);
+        self
+    }
+
+    pub fn with_rev_reg_def(mut self, rev_reg_def: RevocationRegistryDefinition) -> Self {
+        self.rev_reg_defs
+            .insert(rev_reg_def.id().clone(), rev_reg_def);
+        self
+    }
+
+    /// Add a revocation registry entry, as published at `timestamp`
+    pub fn with_rev_reg(
+        mut self,
+        rev_reg_id: RevocationRegistryId,
+        timestamp: u64,
+        rev_reg: RevocationRegistry,
+    ) -> Self {
+        self.rev_regs
+            .entry(rev_reg_id)
+            .or_default()
+            .insert(timestamp, rev_reg);
+        self
+    }
+
+    /// Accept the registry entry at `override_timestamp` in place of the
+    /// `from` or `to` timestamp `requested_timestamp` of a non-revocation interval
+    pub fn with_interval_override(
+        mut self,
+        rev_reg_id: RevocationRegistryId,
+        requested_timestamp: u64,
+        override_timestamp: u64,
+    ) -> Self {
+        self.interval_overrides
+            .entry(rev_reg_id)
+            .or_default()
+            .insert(requested_timestamp, override_timestamp);
+        self
+    }
+
+    /// Check the encoded values of revealed attributes against their raw values
+    pub fn with_attribute_encoding_check(mut self, enabled: bool) -> Self {
+        self.options.check_attribute_encoding = enabled;
+        self
+    }
+
+    /// Check the encoded values of revealed attributes using the issuer's encoder
+    pub fn with_attribute_encoder(mut self, encoder: Arc<dyn AttributeEncoder>) -> Self {
+        self.options.encoder.replace(encoder);
+        self
+    }
+
+    /// Skip the encoding check for an attribute issued with a decimal encoding
+    pub fn with_decimal_attribute(mut self, attr_name: impl Into<String>) -> Self {
+        self.options.decimal_attributes.insert(attr_name.into());
+        self
+    }
+
+    /// Skip the encoding check for an attribute issued with a salted encoding
+    pub fn with_salted_attribute(mut self, attr_name: impl Into<String>) -> Self {
+        self.options.salted_attributes.insert(attr_name.into());
+        self
+    }
+
+    pub fn verify(
+        &self,
+        presentation: &Presentation,
+        pres_req: &PresentationRequest,
+    ) -> Result<VerificationResult> {
+        let schemas = self.schemas.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let cred_defs = self.cred_defs.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let rev_reg_defs = self
+            .rev_reg_defs
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect();
+        let rev_regs = self
+            .rev_regs
+            .iter()
+            .map(|(k, regs)| (k.clone(), regs.iter().map(|(ts, r)| (*ts, r)).collect()))
+            .collect();
+        verifier::verify_presentation_with_options(
+            presentation,
+            pres_req,
+            &schemas,
+            &cred_defs,
+            Some(&rev_reg_defs),
+            Some(&rev_regs),
+            Some(&self.interval_overrides),
+            self.options.clone(),
+        )
+    }
+}
+
+/// Holds a master secret along with the ledger objects needed to request
+/// credentials and create presentations
+#[derive(Debug)]
+pub struct Prover {
+    master_secret: MasterSecret,
+    schemas: HashMap<SchemaId, Schema>,
+    cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition>,
+}
+
+impl Prover {
+    pub fn new(master_secret: MasterSecret) -> Self {
+        Self {
+            master_secret,
+            schemas: HashMap::new(),
+            cred_defs: HashMap::new(),
+        }
+    }
+
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schemas.insert(schema.id().clone(), schema);
+        self
+    }
+
+    pub fn with_cred_def(mut self, cred_def: CredentialDefinition) -> Self {
+        self.cred_defs.insert(cred_def.id().clone(), cred_def);
+        self
+    }
+
+    fn cred_def(&self, cred_def_id: &CredentialDefinitionId) -> Result<&CredentialDefinition> {
+        self.cred_defs
+            .get(cred_def_id)
+            .ok_or_else(|| err_msg!("Unknown credential definition: {}", cred_def_id))
+    }
+
+    /// Create a credential request in response to an offer. The credential
+    /// definition referenced by the offer must have been added to the prover.
+    pub fn create_credential_request(
+        &self,
+        prover_did: &DidValue,
+        master_secret_id: &str,
+        cred_offer: &CredentialOffer,
+    ) -> Result<(CredentialRequest, CredentialRequestMetadata)> {
+        prover::create_credential_request(
+            prover_did,
+            self.cred_def(&cred_offer.cred_def_id)?,
+            &self.master_secret,
+            master_secret_id,
+            cred_offer,
+        )
+    }
+
+    /// Process a credential received from the issuer, returning the credential
+    /// ready for storage
+    pub fn process_credential(
+        &self,
+        mut credential: Credential,
+        cred_request_metadata: &CredentialRequestMetadata,
+        rev_reg_def: Option<&RevocationRegistryDefinition>,
+    ) -> Result<Credential> {
+        let cred_def = self.cred_def(&credential.cred_def_id)?;
+        prover::process_credential(
+            &mut credential,
+            cred_request_metadata,
+            &self.master_secret,
+            cred_def,
+            rev_reg_def,
+        )?;
+        Ok(credential)
+    }
+
+    /// Start building a presentation in response to a presentation request
+    pub fn presentation<'a>(
+        &'a self,
+        pres_req: &'a PresentationRequest,
+    ) -> PresentationBuilder<'a> {
+        PresentationBuilder {
+            prover: self,
+            pres_req,
+            credentials: Vec::new(),
+            self_attested: HashMap::new(),
+        }
+    }
+}
+
+/// A credential to be included in a presentation, along with the referents of
+/// the presentation request it is used to satisfy
+#[derive(Debug)]
+pub struct PresentedCredential {
+    credential: Credential,
+    revocation: Option<(u64, CredentialRevocationState)>,
+    attributes: Vec<(String, bool)>,
+    predicates: Vec<String>,
+}
+
+impl PresentedCredential {
+    pub fn new(credential: Credential) -> Self {
+        Self {
+            credential,
+            revocation: None,
+            attributes: Vec::new(),
+            predicates: Vec::new(),
+        }
+    }
+
+    /// Prove non-revocation using a revocation state created at `timestamp`
+    pub fn with_revocation_state(
+        mut self,
+        timestamp: u64,
+        rev_state: CredentialRevocationState,
+    ) -> Self {
+        self.revocation = Some((timestamp, rev_state));
+        self
+    }
+
+    pub fn with_attribute(mut self, referent: impl Into<String>, revealed: bool) -> Self {
+        self.attributes.push((referent.into(), revealed));
+        self
+    }
+
+    pub fn with_predicate(mut self, referent: impl Into<String>) -> Self {
+        self.predicates.push(referent.into());
+        self
+    }
+}
+
+/// Collects the credentials and self-attested values for a presentation
+#[derive(Debug)]
+pub struct PresentationBuilder<'a> {
+    prover: &'a Prover,
+    pres_req: &'a PresentationRequest,
+    credentials: Vec<PresentedCredential>,
+    self_attested: HashMap<String, String>,
+}
+
+impl PresentationBuilder<'_> {
+    pub fn with_credential(mut self, credential: PresentedCredential) -> Self {
+        self.credentials.push(credential);
+        self
+    }
+
+    pub fn with_self_attested(
+        mut self,
+        referent: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.self_attested.insert(referent.into(), value.into());
+        self
+    }
+
+    pub fn create(self) -> Result<Presentation> {
+        let mut present = PresentCredentials::new();
+        for entry in self.credentials.iter() {
+            let (timestamp, rev_state) = match &entry.revocation {
+                Some((timestamp, rev_state)) => (Some(*timestamp), Some(rev_state)),
+                None => (None, None),
+            };
+            let mut add = present.add_credential(&entry.credential, timestamp, rev_state);
+            for (referent, revealed) in entry.attributes.iter() {
+                add.add_requested_attribute(referent.as_str(), *revealed);
+            }
+            for referent in entry.predicates.iter() {
+                add.add_requested_predicate(referent.as_str());
+            }
+        }
+        let schemas = self
+            .prover
+            .schemas
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect();
+        let cred_defs = self
+            .prover
+            .cred_defs
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect();
+        prover::create_presentation(
+            self.pres_req,
+            present,
+            if self.self_attested.is_empty() {
+                None
+            } else {
+                Some(self.self_attested)
+            },
+            &self.prover.master_secret,
+            &schemas,
+            &cred_defs,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::issuer;
+
+    #[test]
+    fn workflow_issue_and_verify() {
+        let issuer_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = issuer::create_schema(
+            &issuer_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec!["name".to_owned(), "age".to_owned()]),
+            None,
+        )
+        .unwrap();
+        let (cred_def, cred_def_priv, key_proof) = issuer::create_credential_definition(
+            &issuer_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(false),
+        )
+        .unwrap();
+        let cred_offer =
+            issuer::create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+
+        let prover = Prover::new(MasterSecret::new().unwrap())
+            .with_schema(schema.clone())
+            .with_cred_def(cred_def.try_clone().unwrap());
+        let (cred_req, cred_req_metadata) = prover
+            .create_credential_request(
+                &DidValue::new("VsKV7grR1BUE29mG2Fm2kX", None),
+                "default",
+                &cred_offer,
+            )
+            .unwrap();
+
+        let mut values = MakeCredentialValues::default();
+        values.add_raw("name", "Alex").unwrap();
+        values.add_raw("age", "28").unwrap();
+        let (credential, _, _) = issuer::create_credential(
+            &cred_def,
+            &cred_def_priv,
+            &cred_offer,
+            &cred_req,
+            values.into(),
+            None,
+        )
+        .unwrap();
+        let credential = prover
+            .process_credential(credential, &cred_req_metadata, None)
+            .unwrap();
+
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": verifier::generate_nonce().unwrap().to_string(),
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {"attr1_referent": {"name": "name"}},
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+            },
+        }))
+        .unwrap();
+        let presentation = prover
+            .presentation(&pres_req)
+            .with_credential(
+                PresentedCredential::new(credential)
+                    .with_attribute("attr1_referent", true)
+                    .with_predicate("predicate1_referent"),
+            )
+            .create()
+            .unwrap();
+
+        let result = Verifier::new()
+            .with_schema(schema)
+            .with_cred_def(cred_def)
+            .verify(&presentation, &pres_req)
+            .unwrap();
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn workflow_cred_def_attribute_subset() {
+        let issuer_did = DidValue::new("NcYxiDXkpYi6ov5FcYDi1e", None);
+        let schema = issuer::create_schema(
+            &issuer_did,
+            "schema",
+            "1.0",
+            AttributeNames::from(vec![
+                "name".to_owned(),
+                "age".to_owned(),
+                "address".to_owned(),
+            ]),
+            None,
+        )
+        .unwrap();
+        issuer::create_credential_definition_with_attributes(
+            &issuer_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(false),
+            &["name".to_owned(), "phone".to_owned()],
+        )
+        .unwrap_err();
+        issuer::create_credential_definition_with_attributes(
+            &issuer_did,
+            &schema,
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(false),
+            &["name".to_owned(), "Name".to_owned()],
+        )
+        .unwrap_err();
+
+        // the listed order is not significant
+        let (cred_def, cred_def_priv, key_proof) =
+            issuer::create_credential_definition_with_attributes(
+                &issuer_did,
+                &schema,
+                "tag",
+                SignatureType::CL,
+                CredentialDefinitionConfig::new(false),
+                &["name".to_owned(), "age".to_owned()],
+            )
+            .unwrap();
+        let CredentialDefinition::CredentialDefinitionV1(cred_def_v1) = &cred_def;
+        assert_eq!(
+            cred_def_v1.value.attribute_names().unwrap(),
+            vec!["age".to_owned(), "name".to_owned()]
+                .into_iter()
+                .collect()
+        );
+        let cred_offer =
+            issuer::create_credential_offer(schema.id(), &cred_def, &key_proof, None).unwrap();
+
+        let prover = Prover::new(MasterSecret::new().unwrap())
+            .with_schema(schema.clone())
+            .with_cred_def(cred_def.try_clone().unwrap());
+        let (cred_req, cred_req_metadata) = prover
+            .create_credential_request(
+                &DidValue::new("VsKV7grR1BUE29mG2Fm2kX", None),
+                "default",
+                &cred_offer,
+            )
+            .unwrap();
+
+        let mut values = MakeCredentialValues::default();
+        values.add_raw("name", "Alex").unwrap();
+        values.add_raw("age", "28").unwrap();
+        let (credential, _, _) = issuer::create_credential(
+            &cred_def,
+            &cred_def_priv,
+            &cred_offer,
+            &cred_req,
+            values.into(),
+            None,
+        )
+        .unwrap();
+        let credential = prover
+            .process_credential(credential, &cred_req_metadata, None)
+            .unwrap();
+
+        let pres_req: PresentationRequest = serde_json::from_value(serde_json::json!({
+            "nonce": verifier::generate_nonce().unwrap().to_string(),
+            "name": "proof",
+            "version": "1.0",
+            "requested_attributes": {"attr1_referent": {"name": "name"}},
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+            },
+        }))
+        .unwrap();
+        let presentation = prover
+            .presentation(&pres_req)
+            .with_credential(
+                PresentedCredential::new(credential)
+                    .with_attribute("attr1_referent", true)
+                    .with_predicate("predicate1_referent"),
+            )
+            .create()
+            .unwrap();
+
+        let result = Verifier::new()
+            .with_schema(schema)
+            .with_cred_def(cred_def)
+            .verify(&presentation, &pres_req)
+            .unwrap();
+        assert!(result.is_valid());
+    }
+}
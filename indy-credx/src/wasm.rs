@@ -0,0 +1,348 @@
+//! `wasm-bindgen` bindings for the core issuer, prover and verifier operations.
+//!
+//! Objects are passed in and returned as JSON strings, so the bindings can be used
+//! from browsers and Node without the handle-based C FFI. Operations which need
+//! access to a tails file are not exposed here.
+
+use std::collections::{BTreeMap, HashMap};
+
+use indy_utils::Qualifiable;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::Result;
+use crate::services::{
+    issuer, prover,
+    types::{
+        AttributeNames, Credential, CredentialDefinition, CredentialDefinitionConfig,
+        CredentialDefinitionId, CredentialDefinitionPrivate, CredentialKeyCorrectnessProof,
+        CredentialOffer, CredentialRequest, CredentialRequestMetadata, CredentialRevocationState,
+        DidValue, MakeCredentialValues, MasterSecret, PresentCredentials, Presentation,
+        PresentationRequest, Schema, SchemaId, SignatureType,
+    },
+    verifier,
+};
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str, name: &str) -> Result<T> {
+    serde_json::from_str(json).map_err(err_map!("Invalid {}", name))
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(err_map!(Unexpected, "Error serializing result"))
+}
+
+fn js_result<T>(result: Result<T>) -> std::result::Result<T, JsError> {
+    result.map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn refs_map<K: Clone + Eq + std::hash::Hash, V>(map: &HashMap<K, V>) -> HashMap<K, &V> {
+    map.iter().map(|(k, v)| (k.clone(), v)).collect()
+}
+
+#[wasm_bindgen(js_name = createSchema)]
+pub fn create_schema(
+    origin_did: &str,
+    schema_name: &str,
+    schema_version: &str,
+    attr_names_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let origin_did = DidValue::from_str(origin_did)?;
+        let attr_names: Vec<String> = from_json(attr_names_json, "attribute names")?;
+        let schema = issuer::create_schema(
+            &origin_did,
+            schema_name,
+            schema_version,
+            AttributeNames(attr_names.into_iter().collect()),
+            None,
+        )?;
+        to_json(&schema)
+    })())
+}
+
+#[derive(Serialize)]
+struct CredentialDefinitionResult<'a> {
+    cred_def: &'a CredentialDefinition,
+    cred_def_private: &'a CredentialDefinitionPrivate,
+    key_correctness_proof: &'a CredentialKeyCorrectnessProof,
+}
+
+/// Create a credential definition, returning a JSON object containing the
+/// `cred_def`, `cred_def_private` and `key_correctness_proof` entries
+#[wasm_bindgen(js_name = createCredentialDefinition)]
+pub fn create_credential_definition(
+    origin_did: &str,
+    schema_json: &str,
+    tag: &str,
+    signature_type: &str,
+    support_revocation: bool,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let origin_did = DidValue::from_str(origin_did)?;
+        let schema: Schema = from_json(schema_json, "schema")?;
+        let signature_type = SignatureType::from_str(signature_type).map_err(err_map!(Input))?;
+        let (cred_def, cred_def_private, key_correctness_proof) =
+            issuer::create_credential_definition(
+                &origin_did,
+                &schema,
+                tag,
+                signature_type,
+                CredentialDefinitionConfig { support_revocation },
+            )?;
+        to_json(&CredentialDefinitionResult {
+            cred_def: &cred_def,
+            cred_def_private: &cred_def_private,
+            key_correctness_proof: &key_correctness_proof,
+        })
+    })())
+}
+
+#[wasm_bindgen(js_name = createCredentialOffer)]
+pub fn create_credential_offer(
+    schema_id: &str,
+    cred_def_json: &str,
+    key_proof_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let schema_id = SchemaId::from_str(schema_id)?;
+        let cred_def: CredentialDefinition = from_json(cred_def_json, "credential definition")?;
+        let key_proof: CredentialKeyCorrectnessProof =
+            from_json(key_proof_json, "key correctness proof")?;
+        let offer = issuer::create_credential_offer(&schema_id, &cred_def, &key_proof, None)?;
+        to_json(&offer)
+    })())
+}
+
+/// Create a non-revocable credential from a JSON object of raw attribute values
+#[wasm_bindgen(js_name = createCredential)]
+pub fn create_credential(
+    cred_def_json: &str,
+    cred_def_private_json: &str,
+    cred_offer_json: &str,
+    cred_request_json: &str,
+    attr_raw_values_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let cred_def: CredentialDefinition = from_json(cred_def_json, "credential definition")?;
+        let cred_def_private: CredentialDefinitionPrivate =
+            from_json(cred_def_private_json, "private credential definition")?;
+        let cred_offer: CredentialOffer = from_json(cred_offer_json, "credential offer")?;
+        let cred_request: CredentialRequest = from_json(cred_request_json, "credential request")?;
+        let attr_raw_values: BTreeMap<String, String> =
+            from_json(attr_raw_values_json, "attribute values")?;
+        let mut cred_values = MakeCredentialValues::default();
+        for (name, raw) in attr_raw_values {
+            cred_values.add_raw(name, raw)?;
+        }
+        let (credential, _, _) = issuer::create_credential(
+            &cred_def,
+            &cred_def_private,
+            &cred_offer,
+            &cred_request,
+            cred_values.into(),
+            None,
+        )?;
+        to_json(&credential)
+    })())
+}
+
+#[wasm_bindgen(js_name = createMasterSecret)]
+pub fn create_master_secret() -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let master_secret = prover::create_master_secret()?;
+        to_json(&master_secret.export())
+    })())
+}
+
+#[derive(Serialize)]
+struct CredentialRequestResult<'a> {
+    cred_req: &'a CredentialRequest,
+    cred_req_metadata: &'a CredentialRequestMetadata,
+}
+
+/// Create a credential request, returning a JSON object containing the
+/// `cred_req` and `cred_req_metadata` entries
+#[wasm_bindgen(js_name = createCredentialRequest)]
+pub fn create_credential_request(
+    prover_did: &str,
+    cred_def_json: &str,
+    master_secret_json: &str,
+    master_secret_id: &str,
+    cred_offer_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let prover_did = DidValue::from_str(prover_did)?;
+        let cred_def: CredentialDefinition = from_json(cred_def_json, "credential definition")?;
+        let master_secret: MasterSecret = from_json(master_secret_json, "master secret")?;
+        let cred_offer: CredentialOffer = from_json(cred_offer_json, "credential offer")?;
+        let (cred_req, cred_req_metadata) = prover::create_credential_request(
+            &prover_did,
+            &cred_def,
+            &master_secret,
+            master_secret_id,
+            &cred_offer,
+        )?;
+        to_json(&CredentialRequestResult {
+            cred_req: &cred_req,
+            cred_req_metadata: &cred_req_metadata,
+        })
+    })())
+}
+
+#[wasm_bindgen(js_name = processCredential)]
+pub fn process_credential(
+    credential_json: &str,
+    cred_req_metadata_json: &str,
+    master_secret_json: &str,
+    cred_def_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let mut credential: Credential = from_json(credential_json, "credential")?;
+        let cred_req_metadata: CredentialRequestMetadata =
+            from_json(cred_req_metadata_json, "credential request metadata")?;
+        let master_secret: MasterSecret = from_json(master_secret_json, "master secret")?;
+        let cred_def: CredentialDefinition = from_json(cred_def_json, "credential definition")?;
+        prover::process_credential(
+            &mut credential,
+            &cred_req_metadata,
+            &master_secret,
+            &cred_def,
+            None,
+        )?;
+        to_json(&credential)
+    })())
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SelectedCredential {
+    credential: usize,
+    #[serde(default)]
+    timestamp: Option<u64>,
+    #[serde(default)]
+    rev_state: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SelectedAttribute {
+    #[serde(flatten)]
+    credential: SelectedCredential,
+    #[serde(default = "default_revealed")]
+    revealed: bool,
+}
+
+fn default_revealed() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct PresentationSelection {
+    #[serde(default)]
+    requested_attributes: BTreeMap<String, SelectedAttribute>,
+    #[serde(default)]
+    requested_predicates: BTreeMap<String, SelectedCredential>,
+    #[serde(default)]
+    self_attested_attributes: HashMap<String, String>,
+}
+
+/// Create a presentation from a JSON selection document. Each requested attribute
+/// and predicate referent selects a credential by its index in `credentials_json`,
+/// along with an optional timestamp and revocation state index in `rev_states_json`.
+#[wasm_bindgen(js_name = createPresentation)]
+pub fn create_presentation(
+    pres_req_json: &str,
+    credentials_json: &str,
+    rev_states_json: &str,
+    selection_json: &str,
+    master_secret_json: &str,
+    schemas_json: &str,
+    cred_defs_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let pres_req: PresentationRequest = from_json(pres_req_json, "presentation request")?;
+        let credentials: Vec<Credential> = from_json(credentials_json, "credentials")?;
+        let rev_states: Vec<CredentialRevocationState> =
+            from_json(rev_states_json, "revocation states")?;
+        let selection: PresentationSelection = from_json(selection_json, "presentation selection")?;
+        let master_secret: MasterSecret = from_json(master_secret_json, "master secret")?;
+        let schemas: HashMap<SchemaId, Schema> = from_json(schemas_json, "schemas")?;
+        let cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition> =
+            from_json(cred_defs_json, "credential definitions")?;
+
+        let mut groups: BTreeMap<SelectedCredential, Vec<(String, Option<bool>)>> = BTreeMap::new();
+        for (referent, attr) in selection.requested_attributes {
+            groups
+                .entry(attr.credential)
+                .or_default()
+                .push((referent, Some(attr.revealed)));
+        }
+        for (referent, selected) in selection.requested_predicates {
+            groups.entry(selected).or_default().push((referent, None));
+        }
+
+        let mut present_creds = PresentCredentials::default();
+        for (selected, referents) in groups {
+            let credential = credentials
+                .get(selected.credential)
+                .ok_or_else(|| err_msg!("Invalid credential index"))?;
+            let rev_state = selected
+                .rev_state
+                .map(|idx| {
+                    rev_states
+                        .get(idx)
+                        .ok_or_else(|| err_msg!("Invalid revocation state index"))
+                })
+                .transpose()?;
+            let mut add_cred =
+                present_creds.add_credential(credential, selected.timestamp, rev_state);
+            for (referent, revealed) in referents {
+                match revealed {
+                    Some(revealed) => add_cred.add_requested_attribute(referent, revealed),
+                    None => add_cred.add_requested_predicate(referent),
+                }
+            }
+        }
+
+        let self_attested = Some(selection.self_attested_attributes).filter(|s| !s.is_empty());
+        let presentation = prover::create_presentation(
+            &pres_req,
+            present_creds,
+            self_attested,
+            &master_secret,
+            &refs_map(&schemas),
+            &refs_map(&cred_defs),
+        )?;
+        to_json(&presentation)
+    })())
+}
+
+/// Verify a presentation without revocation, returning the verification result
+/// as JSON
+#[wasm_bindgen(js_name = verifyPresentation)]
+pub fn verify_presentation(
+    presentation_json: &str,
+    pres_req_json: &str,
+    schemas_json: &str,
+    cred_defs_json: &str,
+) -> std::result::Result<String, JsError> {
+    js_result((|| {
+        let presentation: Presentation = from_json(presentation_json, "presentation")?;
+        let pres_req: PresentationRequest = from_json(pres_req_json, "presentation request")?;
+        let schemas: HashMap<SchemaId, Schema> = from_json(schemas_json, "schemas")?;
+        let cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition> =
+            from_json(cred_defs_json, "credential definitions")?;
+        let result = verifier::verify_presentation(
+            &presentation,
+            &pres_req,
+            &refs_map(&schemas),
+            &refs_map(&cred_defs),
+            None,
+            None,
+        )?;
+        to_json(&result)
+    })())
+}
+
+#[wasm_bindgen(js_name = generateNonce)]
+pub fn generate_nonce() -> std::result::Result<String, JsError> {
+    js_result(verifier::generate_nonce().map(|nonce| nonce.to_string()))
+}
@@ -1,11 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+use std::sync::Arc;
+use std::time::Duration;
 
 use indy_credx::{
     issuer, prover,
-    types::{CredentialDefinitionConfig, MakeCredentialValues, PresentCredentials, SignatureType},
-    verifier,
+    tails::{TailsFileReader, TailsFileWriter},
+    types::{
+        CompatibilityShim, CredentialDefinition, CredentialDefinitionConfig,
+        CredentialDefinitionId, CredentialRevocationConfig, CredxConfig, IssuanceType,
+        IssuerMetrics, LedgerResolver, MakeCredentialValues, NonRevokedIntervalPolicy,
+        PresentCredentials, Presentation, PresentationRequest, RegistryType,
+        RevocationRegistryDefinition, RevocationRegistryId, RevocationStatusList, Schema, SchemaId,
+        SignatureType, TombstoneKind, VerificationCache, VerificationCheck, VerificationOptions,
+    },
+    verifier, Error,
 };
 
+use indy_utils::Validatable;
 use serde_json::json;
 
 use self::utils::anoncreds::{IssuerWallet, ProverWallet};
@@ -23,6 +35,10 @@ fn anoncreds_works_for_single_issuer_single_prover() {
     // Create Prover pseudo wallet and master secret
     let mut prover_wallet = ProverWallet::default();
 
+    // Issuer records activity metrics
+    let metrics = Arc::new(IssuerMetrics::new());
+    indy_credx::utils::set_issuer_metrics(Some(metrics.clone()));
+
     // Issuer creates Schema - would be published to the ledger
     let gvt_schema = issuer::create_schema(
         &issuer_wallet.did,
@@ -54,6 +70,7 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         gvt_schema.id(),
         &gvt_cred_def,
         &issuer_wallet.cred_defs[0].key_proof,
+        None,
     )
     .expect("Error creating credential offer");
 
@@ -101,6 +118,66 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         None,
     )
     .expect("Error processing credential");
+
+    // Prover detects a duplicate of a credential being issued again
+    let mut dup_values = MakeCredentialValues::default();
+    for (name, value) in recv_cred.values.0.iter() {
+        dup_values
+            .add_raw(name.as_str(), value.raw.as_str())
+            .expect("Error encoding attribute");
+    }
+    let (mut dup_cred, _, _) = issuer::create_credential(
+        &*gvt_cred_def,
+        &issuer_wallet.cred_defs[0].private,
+        &cred_offer,
+        &cred_request,
+        dup_values.into(),
+        None,
+    )
+    .expect("Error creating credential");
+    prover::process_credential(
+        &mut dup_cred,
+        &cred_request_metadata,
+        &prover_wallet.master_secret,
+        &*gvt_cred_def,
+        None,
+    )
+    .expect("Error processing credential");
+
+    // Issuer creates a batch of credentials in one call
+    let batch = issuer::create_credentials(
+        &*gvt_cred_def,
+        &issuer_wallet.cred_defs[0].private,
+        vec![
+            (&cred_offer, &cred_request, recv_cred.values.clone()),
+            (&cred_offer, &cred_request, recv_cred.values.clone()),
+        ],
+    )
+    .expect("Error creating credentials");
+    assert_eq!(batch.len(), 2);
+    for mut batch_cred in batch {
+        prover::process_credential(
+            &mut batch_cred,
+            &cred_request_metadata,
+            &prover_wallet.master_secret,
+            &*gvt_cred_def,
+            None,
+        )
+        .expect("Error processing credential");
+    }
+    let issued = metrics
+        .get(gvt_cred_def.id())
+        .expect("Missing issuer metrics");
+    assert_eq!(issued.credentials_issued, 4);
+    assert_eq!(issued.credentials_revoked, 0);
+    assert!(issued.last_issued.is_some());
+    indy_credx::utils::set_issuer_metrics(None);
+
+    let duplicates = prover::find_duplicate_credentials(&[&recv_cred, &dup_cred], &[]);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].keep, 0);
+    assert_eq!(duplicates[0].remove, vec![1]);
+
     prover_wallet.credentials.push(recv_cred);
 
     // Verifier creates a presentation request
@@ -127,7 +204,39 @@ fn anoncreds_works_for_single_issuer_single_prover() {
     }))
     .expect("Error creating proof request");
 
-    // TODO: show deriving the wallet query from the proof request (need to add helper)
+    // Prover finds the credentials matching each referent
+    let found =
+        prover::get_credentials_for_presentation_request(&pres_request, &prover_wallet.credentials)
+            .expect("Error finding credentials for presentation request");
+    assert_eq!(found.attrs["attr1_referent"], vec![0]);
+    assert_eq!(found.attrs["attr4_referent"], vec![0]);
+    assert!(found.attrs["attr3_referent"].is_empty());
+    assert_eq!(found.predicates["predicate1_referent"], vec![0]);
+    let restricted: PresentationRequest = serde_json::from_value(json!({
+        "nonce": nonce,
+        "name":"pres_req_restricted",
+        "version":"0.1",
+        "requested_attributes":{
+            "attr1_referent":{
+                "name":"name",
+                "restrictions": {"cred_def_id": gvt_cred_def.id(), "attr::name::value": "Alex"}
+            },
+            "attr2_referent":{
+                "name":"name",
+                "restrictions": {"schema_name": "other"}
+            }
+        },
+        "requested_predicates":{
+            "predicate1_referent":{"name":"age","p_type":">","p_value":28}
+        }
+    }))
+    .expect("Error creating proof request");
+    let found =
+        prover::get_credentials_for_presentation_request(&restricted, &prover_wallet.credentials)
+            .expect("Error finding credentials for presentation request");
+    assert_eq!(found.attrs["attr1_referent"], vec![0]);
+    assert!(found.attrs["attr2_referent"].is_empty());
+    assert!(found.predicates["predicate1_referent"].is_empty());
 
     // Prover creates presentation
     let mut present = PresentCredentials::default();
@@ -138,6 +247,11 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         cred1.add_requested_attribute("attr4_referent", true);
         cred1.add_requested_predicate("predicate1_referent");
     }
+    let estimate = prover::estimate_presentation(&pres_request, &present)
+        .expect("Error estimating presentation");
+    assert_eq!(estimate.sub_proofs, 1);
+    assert_eq!(estimate.predicate_proofs, 1);
+    assert_eq!(estimate.revocation_proofs, 0);
 
     let mut self_attested = HashMap::new();
     let self_attested_phone = "8-800-300";
@@ -161,6 +275,8 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         &cred_defs,
     )
     .expect("Error creating presentation");
+    let size = serde_json::to_vec(&presentation).unwrap().len();
+    assert!(estimate.size * 4 > size * 3 && estimate.size * 3 < size * 4);
 
     // Verifier verifies presentation
     assert_eq!(
@@ -199,7 +315,7 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         "175",
         revealed_attr_groups.values.get("height").unwrap().raw
     );
-    let valid = verifier::verify_presentation(
+    let result = verifier::verify_presentation(
         &presentation,
         &pres_request,
         &schemas,
@@ -208,7 +324,587 @@ fn anoncreds_works_for_single_issuer_single_prover() {
         None,
     )
     .expect("Error verifying presentation");
+    assert!(result.is_valid());
+
+    // Verifier reports why a presentation with a mismatched revealed value fails
+    let mut tampered = serde_json::to_value(&presentation).unwrap();
+    tampered["requested_proof"]["revealed_attrs"]["attr1_referent"]["encoded"] = json!("1");
+    let tampered: Presentation = serde_json::from_value(tampered).unwrap();
+    let result =
+        verifier::verify_presentation(&tampered, &pres_request, &schemas, &cred_defs, None, None)
+            .expect("Error verifying presentation");
+    assert!(!result.is_valid());
+    assert_eq!(result.failures.len(), 1);
+    assert_eq!(result.failures[0].check, VerificationCheck::Attribute);
+    assert_eq!(
+        result.failures[0].referent.as_deref(),
+        Some("attr1_referent")
+    );
+
+    // Verifier rejects a malformed presentation before checking the proof
+    let mut malformed = serde_json::to_value(&presentation).unwrap();
+    malformed["requested_proof"]["self_attested_attrs"]["attr1_referent"] = json!("Alex");
+    let malformed: Presentation = serde_json::from_value(malformed).unwrap();
+    assert!(malformed.validate().is_err());
+    assert!(verifier::verify_presentation(
+        &malformed,
+        &pres_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None
+    )
+    .is_err());
+    let mut malformed = serde_json::to_value(&presentation).unwrap();
+    malformed["requested_proof"]["predicates"]["predicate1_referent"]["sub_proof_index"] = json!(1);
+    let malformed: Presentation = serde_json::from_value(malformed).unwrap();
+    assert!(malformed.validate().is_err());
+
+    // Verifier determines the ledger objects needed for verification
+    let artifacts = verifier::required_artifacts(&pres_request, &presentation)
+        .expect("Error determining required artifacts");
+    assert_eq!(artifacts.schema_ids, vec![gvt_schema.id().clone()]);
+    assert_eq!(artifacts.cred_def_ids, vec![gvt_cred_def.id().clone()]);
+    assert!(artifacts.rev_reg_def_ids.is_empty());
+    assert!(artifacts.rev_regs.is_empty());
+
+    // Verifier caches the result for a repeated presentation
+    let cache = VerificationCache::new(Duration::from_secs(60), 10);
+    for _ in 0..2 {
+        let result = verifier::verify_presentation_cached(
+            &cache,
+            &presentation,
+            &pres_request,
+            &schemas,
+            &cred_defs,
+            None,
+            None,
+        )
+        .expect("Error verifying presentation");
+        assert!(result.is_valid());
+    }
+    assert_eq!(cache.len(), 1);
+
+    // Verifier accepts the presentation and ledger objects in the anoncreds-rs format
+    let mut compat_presentation = serde_json::to_value(&presentation).unwrap();
+    let identifier = compat_presentation["identifiers"][0]
+        .as_object_mut()
+        .unwrap();
+    let schema_id = identifier.remove("schema_id").unwrap();
+    let cred_def_id = identifier.remove("cred_def_id").unwrap();
+    identifier.insert("schemaId".to_string(), schema_id);
+    identifier.insert("credDefId".to_string(), cred_def_id);
+    identifier.insert("rev_reg_id".to_string(), json!(""));
+    let mut compat_schema = serde_json::to_value(&gvt_schema).unwrap();
+    compat_schema.as_object_mut().unwrap().remove("ver");
+    compat_schema.as_object_mut().unwrap().remove("id");
+    let mut compat_cred_def = serde_json::to_value(&*gvt_cred_def).unwrap();
+    compat_cred_def.as_object_mut().unwrap().remove("ver");
+    let compat_schemas = HashMap::from_iter(vec![(gvt_schema.id().clone(), &compat_schema)]);
+    let compat_cred_defs = HashMap::from_iter(vec![(gvt_cred_def.id().clone(), &compat_cred_def)]);
+    let (valid, report) = verifier::verify_presentation_compat(
+        &compat_presentation,
+        &pres_request,
+        &compat_schemas,
+        &compat_cred_defs,
+        None,
+        None,
+    )
+    .expect("Error verifying presentation");
     assert!(valid);
+    assert!(report.contains(CompatibilityShim::IdentifierFieldNames));
+    assert!(report.contains(CompatibilityShim::EmptyRevocationRegistryId));
+    assert!(report.contains(CompatibilityShim::ObjectId));
+    assert!(!report.contains(CompatibilityShim::IssuanceType));
+    assert_eq!(
+        report
+            .applied
+            .iter()
+            .filter(|applied| applied.shim == CompatibilityShim::VersionWrapper)
+            .count(),
+        2
+    );
+
+    // Verifier accepts a presentation with the quirks of older libindy provers
+    let mut legacy_presentation = serde_json::to_value(&presentation).unwrap();
+    legacy_presentation["identifiers"][0]["timestamp"] = json!(1234);
+    let mut legacy_request = serde_json::to_value(&pres_request).unwrap();
+    legacy_request["non_revoked"] = json!({"to": 1234});
+    let legacy_request: PresentationRequest = serde_json::from_value(legacy_request).unwrap();
+    let result = verifier::verify_presentation(
+        &presentation,
+        &legacy_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+    )
+    .expect("Error verifying presentation");
+    assert!(!result.is_valid());
+    assert!(result
+        .failures
+        .iter()
+        .all(|failure| failure.check == VerificationCheck::NonRevocationInterval));
+    let (valid, report) = verifier::verify_presentation_libindy(
+        &legacy_presentation,
+        &legacy_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+    )
+    .expect("Error verifying presentation");
+    assert!(valid);
+    assert!(report.contains(CompatibilityShim::TimestampWithoutRegistry));
+    assert!(report.contains(CompatibilityShim::NonRevokedWithoutRegistry));
+    assert!(!report.contains(CompatibilityShim::UnqualifiedIdentifiers));
+
+    // Verifier resolves the ledger objects referenced by the presentation
+    struct Resolver<'a> {
+        schema: &'a Schema,
+        cred_def: &'a CredentialDefinition,
+    }
+    impl LedgerResolver for Resolver<'_> {
+        fn resolve_schema(&self, schema_id: &SchemaId) -> Result<Schema, Error> {
+            assert_eq!(schema_id, self.schema.id());
+            Ok(self.schema.clone())
+        }
+        fn resolve_credential_definition(
+            &self,
+            cred_def_id: &CredentialDefinitionId,
+        ) -> Result<CredentialDefinition, Error> {
+            assert_eq!(cred_def_id, self.cred_def.id());
+            Ok(self.cred_def.try_clone().unwrap())
+        }
+        fn resolve_revocation_registry_definition(
+            &self,
+            _rev_reg_id: &RevocationRegistryId,
+        ) -> Result<RevocationRegistryDefinition, Error> {
+            unreachable!()
+        }
+        fn resolve_revocation_status_list(
+            &self,
+            _rev_reg_id: &RevocationRegistryId,
+            _timestamp: u64,
+        ) -> Result<RevocationStatusList, Error> {
+            unreachable!()
+        }
+    }
+    let resolver = Resolver {
+        schema: &gvt_schema,
+        cred_def: &*gvt_cred_def,
+    };
+    let result = verifier::verify_presentation_resolved(&presentation, &pres_request, &resolver)
+        .expect("Error verifying presentation");
+    assert!(result.is_valid(), "{:?}", result.failures);
+
+    // Verifier re-checks a subset of the referents
+    let mut presentation = presentation;
+    presentation
+        .requested_proof
+        .revealed_attrs
+        .get_mut("attr1_referent")
+        .unwrap()
+        .encoded = "1234".to_string();
+    let referents: HashSet<String> = ["attr1_referent", "attr3_referent", "predicate1_referent"]
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    let results = verifier::verify_presentation_referents(
+        &presentation,
+        &pres_request,
+        &referents,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+    )
+    .expect("Error verifying presentation referents");
+    assert_eq!(results.len(), 3);
+    assert!(!results["attr1_referent"]);
+    assert!(results["attr3_referent"]);
+    assert!(results["predicate1_referent"]);
+
+    // Prover proves possession of the master secret bound to the credential
+    let nonce = verifier::generate_nonce().expect("Error generating nonce");
+    let ms_proof = prover::create_master_secret_proof(
+        &prover_wallet.credentials[0],
+        &prover_wallet.master_secret,
+        &gvt_schema,
+        &*gvt_cred_def,
+        &nonce,
+    )
+    .expect("Error creating master secret proof");
+    let valid =
+        verifier::verify_master_secret_proof(&ms_proof, &gvt_schema, &*gvt_cred_def, &nonce)
+            .expect("Error verifying master secret proof");
+    assert!(valid);
+
+    let other_nonce = verifier::generate_nonce().expect("Error generating nonce");
+    let valid =
+        verifier::verify_master_secret_proof(&ms_proof, &gvt_schema, &*gvt_cred_def, &other_nonce)
+            .expect("Error verifying master secret proof");
+    assert!(!valid);
+
+    // Issuer retires the credential definition
+    let stored = issuer_wallet.cred_defs.remove(0);
+    let tombstone = issuer::destroy_credential_definition(&stored.public, stored.private)
+        .expect("Error destroying credential definition");
+    assert_eq!(tombstone.kind, TombstoneKind::CredentialDefinition);
+    assert_eq!(&tombstone.cred_def_id, stored.public.id());
+    assert_eq!(tombstone.public_digest.len(), 64);
+}
+
+#[test]
+fn anoncreds_works_for_attribute_encoding_check() {
+    let mut issuer_wallet = IssuerWallet::default();
+    let mut prover_wallet = ProverWallet::default();
+
+    let schema = issuer::create_schema(
+        &issuer_wallet.did,
+        "account",
+        "1.0",
+        vec!["name".to_owned(), "balance".to_owned(), "ssn".to_owned()].into(),
+        None,
+    )
+    .expect("Error creating schema");
+    let cred_def_parts = issuer::create_credential_definition(
+        &issuer_wallet.did,
+        &schema,
+        "tag",
+        SignatureType::CL,
+        CredentialDefinitionConfig::new(false),
+    )
+    .expect("Error creating credential definition");
+    issuer_wallet.cred_defs.push(cred_def_parts.into());
+    let cred_def = &issuer_wallet.cred_defs[0].public;
+
+    let cred_offer = issuer::create_credential_offer(
+        schema.id(),
+        cred_def,
+        &issuer_wallet.cred_defs[0].key_proof,
+        None,
+    )
+    .expect("Error creating credential offer");
+    let (cred_request, cred_request_metadata) = prover::create_credential_request(
+        &prover_wallet.did,
+        cred_def,
+        &prover_wallet.master_secret,
+        "default",
+        &cred_offer,
+    )
+    .expect("Error creating credential request");
+
+    // Issuer encodes a decimal and a salted attribute, which verifiers cannot recompute
+    // unless they are declared
+    let mut cred_values = MakeCredentialValues::default();
+    cred_values
+        .add_raw("name", "Alex")
+        .expect("Error encoding attribute");
+    cred_values
+        .add_decimal("balance", "12.50", 2)
+        .expect("Error encoding attribute");
+    cred_values
+        .add_raw_salted("ssn", "123-45-6789")
+        .expect("Error encoding attribute");
+    let (mut credential, _, _) = issuer::create_credential(
+        cred_def,
+        &issuer_wallet.cred_defs[0].private,
+        &cred_offer,
+        &cred_request,
+        cred_values.into(),
+        None,
+    )
+    .expect("Error creating credential");
+    prover::process_credential(
+        &mut credential,
+        &cred_request_metadata,
+        &prover_wallet.master_secret,
+        cred_def,
+        None,
+    )
+    .expect("Error processing credential");
+    prover_wallet.credentials.push(credential);
+
+    let pres_request: PresentationRequest = serde_json::from_value(json!({
+        "nonce": verifier::generate_nonce().expect("Error generating nonce"),
+        "name": "pres_req_encoding",
+        "version": "0.1",
+        "requested_attributes": {
+            "attr1_referent": {"names": ["name", "balance", "ssn"]},
+        },
+    }))
+    .expect("Error creating proof request");
+
+    let mut present = PresentCredentials::default();
+    present
+        .add_credential(&prover_wallet.credentials[0], None, None)
+        .add_requested_attribute("attr1_referent", true);
+    let mut schemas = HashMap::new();
+    schemas.insert(schema.id().clone(), &schema);
+    let mut cred_defs = HashMap::new();
+    cred_defs.insert(cred_def.id().clone(), cred_def);
+    let presentation = prover::create_presentation(
+        &pres_request,
+        present,
+        None,
+        &prover_wallet.master_secret,
+        &schemas,
+        &cred_defs,
+    )
+    .expect("Error creating presentation");
+
+    // Verifier checks the encodings, rejecting those which do not match
+    let mut options = VerificationOptions {
+        check_attribute_encoding: true,
+        ..Default::default()
+    };
+    let result = verifier::verify_presentation_with_options(
+        &presentation,
+        &pres_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+        None,
+        options.clone(),
+    )
+    .expect("Error verifying presentation");
+    assert!(!result.is_valid());
+    assert_eq!(result.failures.len(), 1);
+    assert_eq!(
+        result.failures[0].reason,
+        "Encoded value does not match raw value for attributes: balance, ssn"
+    );
+
+    // Verifier declares the decimal and salted attributes, which are not checked
+    options.decimal_attributes.insert("balance".to_owned());
+    let result = verifier::verify_presentation_with_options(
+        &presentation,
+        &pres_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+        None,
+        options.clone(),
+    )
+    .expect("Error verifying presentation");
+    assert!(!result.is_valid());
+    assert_eq!(
+        result.failures[0].reason,
+        "Encoded value does not match raw value for attributes: ssn"
+    );
+
+    options.salted_attributes.insert("ssn".to_owned());
+    let result = verifier::verify_presentation_with_options(
+        &presentation,
+        &pres_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+        None,
+        options,
+    )
+    .expect("Error verifying presentation");
+    assert!(result.is_valid());
+
+    let result = verifier::verify_presentation(
+        &presentation,
+        &pres_request,
+        &schemas,
+        &cred_defs,
+        None,
+        None,
+    )
+    .expect("Error verifying presentation");
+    assert!(result.is_valid());
+}
+
+#[test]
+fn anoncreds_works_for_revocation_interval() {
+    let mut issuer_wallet = IssuerWallet::default();
+    let prover_wallet = ProverWallet::default();
+
+    let gvt_schema = issuer::create_schema(
+        &issuer_wallet.did,
+        GVT_SCHEMA_NAME,
+        "1.0",
+        GVT_SCHEMA_ATTRIBUTES[..].into(),
+        None,
+    )
+    .expect("Error creating gvt schema for issuer");
+    let cred_def_parts = issuer::create_credential_definition(
+        &issuer_wallet.did,
+        &gvt_schema,
+        "tag",
+        SignatureType::CL,
+        CredentialDefinitionConfig::new(true),
+    )
+    .expect("Error creating gvt credential definition");
+    issuer_wallet.cred_defs.push(cred_def_parts.into());
+    let gvt_cred_def = &issuer_wallet.cred_defs[0].public;
+
+    // Issuer creates a revocation registry - the definition and the initial
+    // entry would be published to the ledger
+    let tails_dir = tempfile::tempdir().expect("Error creating tails directory");
+    let mut tails_writer =
+        TailsFileWriter::new(Some(tails_dir.path().to_string_lossy().into_owned()));
+    let (rev_reg_def, rev_reg_def_priv, rev_reg, rev_reg_delta) =
+        issuer::create_revocation_registry(
+            &issuer_wallet.did,
+            gvt_cred_def,
+            "tag",
+            RegistryType::CL_ACCUM,
+            IssuanceType::ISSUANCE_BY_DEFAULT,
+            10,
+            &mut tails_writer,
+        )
+        .expect("Error creating revocation registry");
+    let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def_v1) = &rev_reg_def;
+    let tails_path = rev_reg_def_v1.value.tails_location.clone();
+
+    let cred_offer = issuer::create_credential_offer(
+        gvt_schema.id(),
+        gvt_cred_def,
+        &issuer_wallet.cred_defs[0].key_proof,
+        None,
+    )
+    .expect("Error creating credential offer");
+    let (cred_request, cred_request_metadata) = prover::create_credential_request(
+        &prover_wallet.did,
+        gvt_cred_def,
+        &prover_wallet.master_secret,
+        "default",
+        &cred_offer,
+    )
+    .expect("Error creating credential request");
+
+    let mut cred_values = MakeCredentialValues::default();
+    for (name, value) in [
+        ("sex", "male"),
+        ("name", "Alex"),
+        ("height", "175"),
+        ("age", "28"),
+    ] {
+        cred_values
+            .add_raw(name, value)
+            .expect("Error encoding attribute");
+    }
+    let (mut credential, _, _) = issuer::create_credential(
+        gvt_cred_def,
+        &issuer_wallet.cred_defs[0].private,
+        &cred_offer,
+        &cred_request,
+        cred_values.into(),
+        Some(CredentialRevocationConfig {
+            reg_def: &rev_reg_def,
+            reg_def_private: &rev_reg_def_priv,
+            registry: &rev_reg,
+            registry_idx: 1,
+            registry_used: &HashSet::new(),
+            tails_reader: TailsFileReader::new(&tails_path),
+        }),
+    )
+    .expect("Error creating credential");
+    prover::process_credential(
+        &mut credential,
+        &cred_request_metadata,
+        &prover_wallet.master_secret,
+        gvt_cred_def,
+        Some(&rev_reg_def),
+    )
+    .expect("Error processing credential");
+
+    // The registry entry is published before the requested interval begins
+    let now = indy_credx::utils::current_timestamp();
+    let entry_timestamp = now - 100;
+    let rev_state = prover::create_or_update_revocation_state(
+        TailsFileReader::new(&tails_path),
+        &rev_reg_def,
+        &rev_reg_delta,
+        1,
+        entry_timestamp,
+        None,
+    )
+    .expect("Error creating revocation state");
+
+    let mut schemas = HashMap::new();
+    schemas.insert(gvt_schema.id().clone(), &gvt_schema);
+    let mut cred_defs = HashMap::new();
+    cred_defs.insert(gvt_cred_def.id().clone(), gvt_cred_def);
+    let mut rev_reg_defs = HashMap::new();
+    rev_reg_defs.insert(rev_reg_def_v1.id.clone(), &rev_reg_def);
+    let mut rev_regs = HashMap::new();
+    rev_regs.insert(
+        rev_reg_def_v1.id.clone(),
+        HashMap::from_iter(vec![(entry_timestamp, &rev_reg)]),
+    );
+
+    let present_and_verify = |interval: serde_json::Value| {
+        let pres_request: PresentationRequest = serde_json::from_value(json!({
+            "nonce": verifier::generate_nonce().expect("Error generating nonce"),
+            "name": "pres_req_1",
+            "version": "0.1",
+            "requested_attributes": {"attr1_referent": {"name": "name"}},
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}
+            },
+            "non_revoked": interval,
+        }))
+        .expect("Error creating proof request");
+        let mut present = PresentCredentials::default();
+        {
+            let mut cred =
+                present.add_credential(&credential, Some(entry_timestamp), Some(&rev_state));
+            cred.add_requested_attribute("attr1_referent", true);
+            cred.add_requested_predicate("predicate1_referent");
+        }
+        let presentation = prover::create_presentation(
+            &pres_request,
+            present,
+            None,
+            &prover_wallet.master_secret,
+            &schemas,
+            &cred_defs,
+        )
+        .expect("Error creating presentation");
+        verifier::verify_presentation(
+            &presentation,
+            &pres_request,
+            &schemas,
+            &cred_defs,
+            Some(&rev_reg_defs),
+            Some(&rev_regs),
+        )
+        .expect("Error verifying presentation")
+    };
+
+    // By default the timestamp is only required to be present
+    let result = present_and_verify(json!({"from": now - 50, "to": now}));
+    assert!(result.is_valid(), "{:?}", result.failures);
+    let result = present_and_verify(json!({"to": entry_timestamp - 10}));
+    assert!(result.is_valid(), "{:?}", result.failures);
+
+    indy_credx::utils::set_config(CredxConfig {
+        non_revoked_interval_policy: NonRevokedIntervalPolicy::Rfc0441,
+        ..CredxConfig::default()
+    })
+    .expect("Error setting configuration");
+
+    // Under RFC 0441 `from` is advisory, so the latest entry before `to` is accepted
+    let result = present_and_verify(json!({"from": now - 50, "to": now}));
+    assert!(result.is_valid(), "{:?}", result.failures);
+
+    // An entry published after the end of the requested interval is rejected
+    let result = present_and_verify(json!({"to": entry_timestamp - 10}));
+    indy_credx::utils::set_config(CredxConfig::default()).expect("Error setting configuration");
+    assert!(!result.is_valid());
+    assert!(result
+        .failures
+        .iter()
+        .all(|failure| failure.check == VerificationCheck::NonRevocationInterval));
 }
 
 /*
@@ -0,0 +1,93 @@
+//! Compatibility with objects produced by legacy libindy and by anoncreds-rs.
+//!
+//! With the `compat` feature enabled, the alternative field names used by
+//! anoncreds-rs (such as `link_secret_name` in place of `master_secret_name`)
+//! are accepted when deserializing, as are credential attribute values encoded
+//! as JSON numbers. Objects are always serialized using the libindy field names.
+
+use std::fmt;
+
+use serde::de::{Deserializer, Error, Visitor};
+
+/// Deserialize a string, accepting a JSON number in its place
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StringVisitor;
+
+    impl<'de> Visitor<'de> for StringVisitor {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("string or number")
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_f64<E: Error>(self, value: f64) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_string<E: Error>(self, value: String) -> Result<String, E> {
+            Ok(value)
+        }
+    }
+
+    deserializer.deserialize_any(StringVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::credential::{AttributeValues, CredentialValues};
+
+    #[test]
+    fn attribute_values_accept_numbers() {
+        let values: CredentialValues = serde_json::from_value(json!({
+            "age": {"raw": 28, "encoded": 28},
+            "name": {"raw": "Alex", "encoded": "1139481716457488690172217916278103335"},
+        }))
+        .unwrap();
+        assert_eq!(
+            values.0["age"],
+            AttributeValues {
+                raw: "28".to_string(),
+                encoded: "28".to_string(),
+                salt: None,
+                media_type: None,
+            }
+        );
+        let json = serde_json::to_value(&values).unwrap();
+        assert_eq!(json["age"], json!({"raw": "28", "encoded": "28"}));
+    }
+
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    #[test]
+    fn request_metadata_accepts_link_secret_names() {
+        use super::super::cred_request::CredentialRequestMetadata;
+
+        let meta: CredentialRequestMetadata = serde_json::from_value(json!({
+            "link_secret_blinding_data": {"v_prime": "1234", "vr_prime": null},
+            "nonce": "5678",
+            "link_secret_name": "default",
+        }))
+        .unwrap();
+        assert_eq!(meta.master_secret_name, "default");
+
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json["master_secret_name"], "default");
+        assert_eq!(json["master_secret_blinding_data"]["v_prime"], "1234");
+        let restored: CredentialRequestMetadata = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.master_secret_name, "default");
+    }
+}
@@ -1,3 +1,8 @@
+#[cfg(feature = "serde")]
+use std::collections::BTreeSet;
+
+use once_cell::sync::OnceCell;
+
 use crate::identifiers::cred_def::CredentialDefinitionId;
 use crate::identifiers::schema::SchemaId;
 use crate::utils::Qualifiable;
@@ -5,6 +10,10 @@ use crate::{ConversionError, Validatable, ValidationError};
 
 pub const CL_SIGNATURE_TYPE: &str = "CL";
 
+/// The name of the hidden attribute holding the prover's master secret
+#[cfg(feature = "serde")]
+const MASTER_SECRET_ATTRIBUTE: &str = "master_secret";
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SignatureType {
@@ -26,12 +35,163 @@ impl SignatureType {
     }
 }
 
+/// Key material which is kept in its JSON representation until first accessed
+struct LazyKey<T> {
+    raw: Option<serde_json::Value>,
+    value: OnceCell<T>,
+}
+
+impl<T> LazyKey<T> {
+    fn new(value: T) -> Self {
+        let cell = OnceCell::new();
+        cell.set(value).ok();
+        Self {
+            raw: None,
+            value: cell,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned> LazyKey<T> {
+    fn get(&self) -> Result<&T, ConversionError> {
+        self.value.get_or_try_init(|| match self.raw.as_ref() {
+            Some(raw) => Ok(serde_json::from_value(raw.clone())?),
+            None => Err(ConversionError::from_msg("Missing key value")),
+        })
+    }
+}
+
+impl<T> LazyKey<T> {
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    fn try_clone_with(
+        &self,
+        clone: impl FnOnce(&T) -> Result<T, ConversionError>,
+    ) -> Result<Self, ConversionError> {
+        match (self.raw.as_ref(), self.value.get()) {
+            (Some(raw), _) => Ok(Self {
+                raw: Some(raw.clone()),
+                value: OnceCell::new(),
+            }),
+            (None, Some(value)) => Ok(Self::new(clone(value)?)),
+            (None, None) => Err(ConversionError::from_msg("Missing key value")),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for LazyKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.value.get(), self.raw.as_ref()) {
+            (Some(value), _) => value.fmt(f),
+            (None, raw) => f.debug_tuple("Unparsed").field(&raw).finish(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for LazyKey<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match (self.raw.as_ref(), self.value.get()) {
+            (Some(raw), _) => raw.serialize(serializer),
+            (None, value) => value.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for LazyKey<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Self {
+            raw: Some(serde::Deserialize::deserialize(deserializer)?),
+            value: OnceCell::new(),
+        })
+    }
+}
+
+/// The public keys of a credential definition. These are only parsed when
+/// first accessed, so that operations which only inspect the identifiers of
+/// a credential definition avoid the cost of loading the key material.
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CredentialDefinitionData {
-    pub primary: ursa_cl!(CredentialPrimaryPublicKey),
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
-    pub revocation: Option<ursa_cl!(CredentialRevocationPublicKey)>,
+    primary: LazyKey<ursa_cl!(CredentialPrimaryPublicKey)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    revocation: Option<LazyKey<ursa_cl!(CredentialRevocationPublicKey)>>,
+}
+
+impl CredentialDefinitionData {
+    pub fn new(
+        primary: ursa_cl!(CredentialPrimaryPublicKey),
+        revocation: Option<ursa_cl!(CredentialRevocationPublicKey)>,
+    ) -> Self {
+        Self {
+            primary: LazyKey::new(primary),
+            revocation: revocation.map(LazyKey::new),
+        }
+    }
+
+    /// Access the primary public key, parsing it if necessary
+    #[cfg(feature = "serde")]
+    pub fn primary(&self) -> Result<&ursa_cl!(CredentialPrimaryPublicKey), ConversionError> {
+        self.primary.get()
+    }
+
+    /// Access the revocation public key, parsing it if necessary
+    #[cfg(feature = "serde")]
+    pub fn revocation(
+        &self,
+    ) -> Result<Option<&ursa_cl!(CredentialRevocationPublicKey)>, ConversionError> {
+        self.revocation.as_ref().map(LazyKey::get).transpose()
+    }
+
+    /// List the normalized names of the attributes signed by the primary
+    /// public key, excluding the master secret
+    #[cfg(feature = "serde")]
+    pub fn attribute_names(&self) -> Result<BTreeSet<String>, ConversionError> {
+        let parsed;
+        let key = match self.primary.raw.as_ref() {
+            Some(raw) => raw,
+            None => {
+                parsed = serde_json::to_value(self.primary()?)?;
+                &parsed
+            }
+        };
+        let attrs = key
+            .get("r")
+            .and_then(serde_json::Value::as_object)
+            .ok_or_else(|| ConversionError::from_msg("Invalid primary public key"))?;
+        Ok(attrs
+            .keys()
+            .filter(|name| name.as_str() != MASTER_SECRET_ATTRIBUTE)
+            .cloned()
+            .collect())
+    }
+
+    /// Check whether the credential definition includes a revocation key
+    pub fn supports_revocation(&self) -> bool {
+        self.revocation.is_some()
+    }
+
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    fn try_clone(&self) -> Result<Self, ConversionError> {
+        Ok(Self {
+            primary: self.primary.try_clone_with(|key| Ok(key.try_clone()?))?,
+            revocation: self
+                .revocation
+                .as_ref()
+                .map(|key| key.try_clone_with(|key| Ok(key.clone())))
+                .transpose()?,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -48,6 +208,20 @@ impl CredentialDefinition {
         }
     }
 
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    pub fn try_clone(&self) -> Result<Self, ConversionError> {
+        let CredentialDefinition::CredentialDefinitionV1(cred_def) = self;
+        Ok(CredentialDefinition::CredentialDefinitionV1(
+            CredentialDefinitionV1 {
+                id: cred_def.id.clone(),
+                schema_id: cred_def.schema_id.clone(),
+                signature_type: cred_def.signature_type,
+                tag: cred_def.tag.clone(),
+                value: cred_def.value.try_clone()?,
+            },
+        ))
+    }
+
     pub fn to_unqualified(self) -> CredentialDefinition {
         match self {
             CredentialDefinition::CredentialDefinitionV1(cred_def) => {
@@ -92,8 +266,8 @@ impl CredentialDefinitionV1 {
         &self,
     ) -> Result<crate::ursa::cl::CredentialPublicKey, crate::ConversionError> {
         let key = crate::ursa::cl::CredentialPublicKey::build_from_parts(
-            &self.value.primary,
-            self.value.revocation.as_ref(),
+            self.value.primary()?,
+            self.value.revocation()?,
         )?;
         Ok(key)
     }
@@ -112,12 +286,16 @@ pub struct CredentialDefinitionPrivate {
     pub value: ursa_cl!(CredentialPrivateKey),
 }
 
+impl Validatable for CredentialDefinitionPrivate {}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(transparent))]
 pub struct CredentialKeyCorrectnessProof {
     pub value: ursa_cl!(CredentialKeyCorrectnessProof),
 }
 
+impl Validatable for CredentialKeyCorrectnessProof {}
+
 impl CredentialKeyCorrectnessProof {
     pub fn try_clone(&self) -> Result<Self, ConversionError> {
         #[cfg(any(feature = "cl", feature = "cl_native"))]
@@ -134,3 +312,33 @@ impl CredentialKeyCorrectnessProof {
         }
     }
 }
+
+#[cfg(all(test, any(feature = "cl", feature = "cl_native")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cred_def_keys_parsed_on_access() {
+        let json = serde_json::json!({
+            "ver": "1.0",
+            "id": "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag",
+            "schemaId": "1",
+            "type": "CL",
+            "tag": "tag",
+            "value": {"primary": {"n": "invalid"}}
+        })
+        .to_string();
+        let cred_def: CredentialDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(cred_def.id().0, "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag");
+
+        let CredentialDefinition::CredentialDefinitionV1(cred_def) = cred_def;
+        assert!(!cred_def.value.supports_revocation());
+        assert!(cred_def.value.revocation().unwrap().is_none());
+        assert!(cred_def.value.primary().is_err());
+        let copy = cred_def.value.try_clone().unwrap();
+        assert_eq!(
+            serde_json::to_value(&copy).unwrap(),
+            serde_json::json!({"primary": {"n": "invalid"}})
+        );
+    }
+}
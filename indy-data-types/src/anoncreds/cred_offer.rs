@@ -13,6 +13,12 @@ pub struct CredentialOffer {
     pub nonce: Nonce,
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub method_name: Option<String>,
+    /// The issuer-assigned holder session this offer is bound to
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub session_id: Option<String>,
 }
 
 impl CredentialOffer {
@@ -25,6 +31,7 @@ impl CredentialOffer {
             key_correctness_proof: self.key_correctness_proof,
             nonce: self.nonce,
             method_name,
+            session_id: self.session_id,
         }
     }
 }
@@ -9,7 +9,15 @@ use indy_utils::did::DidValue;
 pub struct CredentialRequest {
     pub prover_did: DidValue,
     pub cred_def_id: CredentialDefinitionId,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(alias = "blinded_ls")
+    )]
     pub blinded_ms: ursa_cl!(BlindedCredentialSecrets),
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(alias = "blinded_ls_correctness_proof")
+    )]
     pub blinded_ms_correctness_proof: ursa_cl!(BlindedCredentialSecretsCorrectnessProof),
     pub nonce: Nonce,
 }
@@ -38,8 +46,16 @@ impl Validatable for CredentialRequest {
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct CredentialRequestMetadata {
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(alias = "link_secret_blinding_data")
+    )]
     pub master_secret_blinding_data: ursa_cl!(CredentialSecretsBlindingFactors),
     pub nonce: Nonce,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(alias = "link_secret_name")
+    )]
     pub master_secret_name: String,
 }
 
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use zeroize::Zeroize;
 
+use super::limits::{check_limit, CheckLimits, ValidationConfig};
 use crate::identifiers::cred_def::CredentialDefinitionId;
 use crate::identifiers::rev_reg::RevocationRegistryId;
 use crate::identifiers::schema::SchemaId;
@@ -46,6 +47,12 @@ impl Credential {
     ];
 }
 
+impl CheckLimits for Credential {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        self.values.check_limits(limits)
+    }
+}
+
 impl Validatable for Credential {
     fn validate(&self) -> Result<(), ValidationError> {
         self.schema_id.validate()?;
@@ -87,6 +94,16 @@ impl Drop for CredentialValues {
     }
 }
 
+impl CheckLimits for CredentialValues {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        check_limit(
+            "credential attributes",
+            self.0.len(),
+            limits.credential_attributes_limit(),
+        )
+    }
+}
+
 impl Validatable for CredentialValues {
     fn validate(&self) -> Result<(), ValidationError> {
         if self.0.is_empty() {
@@ -108,6 +125,27 @@ impl Zeroize for CredentialValues {
 #[derive(Clone, Debug, PartialEq, Eq, Zeroize)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct AttributeValues {
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub raw: String,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub encoded: String,
+    /// The salt mixed into the encoded value of a salted attribute
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub salt: Option<String>,
+    /// The declared MIME type of a binary attribute, whose raw value is the
+    /// hash of the content
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub media_type: Option<String>,
 }
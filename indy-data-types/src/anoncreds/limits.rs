@@ -0,0 +1,123 @@
+use crate::{invalid, ValidationError};
+use indy_utils::wql::Query;
+
+use super::schema::MAX_ATTRIBUTES_COUNT;
+
+/// Size and complexity limits enforced on schemas, credentials, presentations
+/// and presentation requests, protecting services from oversized payloads.
+///
+/// Any fields omitted when deserializing take their default values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct ValidationConfig {
+    /// The maximum number of attributes in a schema. Defaults to the Indy ledger limit.
+    pub max_schema_attributes: usize,
+    /// The maximum number of attributes in a credential
+    pub max_credential_attributes: usize,
+    /// The maximum number of credentials proven in a presentation
+    pub max_presentation_credentials: usize,
+    /// The maximum nesting depth of a restriction query
+    pub max_restriction_depth: usize,
+    /// The maximum number of predicates in a presentation request or presentation
+    pub max_predicates: usize,
+}
+
+impl ValidationConfig {
+    /// The maximum number of attributes in a credential. A credential may always
+    /// carry as many attributes as the schema limit allows.
+    pub fn credential_attributes_limit(&self) -> usize {
+        self.max_credential_attributes
+            .max(self.max_schema_attributes)
+    }
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_schema_attributes: MAX_ATTRIBUTES_COUNT,
+            max_credential_attributes: MAX_ATTRIBUTES_COUNT,
+            max_presentation_credentials: 64,
+            max_restriction_depth: 16,
+            max_predicates: 128,
+        }
+    }
+}
+
+/// Objects subject to size and complexity limits. The limits are chosen by the
+/// caller, so they are checked separately from validation.
+pub trait CheckLimits {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError>;
+}
+
+pub(crate) fn check_limit(what: &str, count: usize, limit: usize) -> Result<(), ValidationError> {
+    if count > limit {
+        Err(invalid!(
+            "The number of {} {} exceeds the limit of {}",
+            what,
+            count,
+            limit
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The nesting depth of a restriction query, where a single clause has a depth of one
+pub fn query_depth(query: &Query) -> usize {
+    match query {
+        Query::And(queries) | Query::Or(queries) => {
+            1 + queries.iter().map(query_depth).max().unwrap_or(0)
+        }
+        Query::Not(query) => 1 + query_depth(query),
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_depth_works() {
+        let eq = Query::Eq("schema_id".to_string(), "1".to_string());
+        assert_eq!(query_depth(&eq), 1);
+        assert_eq!(query_depth(&Query::And(vec![])), 1);
+        let nested = Query::Or(vec![
+            eq.clone(),
+            Query::Not(Box::new(Query::And(vec![eq.clone(), eq]))),
+        ]);
+        assert_eq!(query_depth(&nested), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pres_req_restriction_depth_limit() {
+        use super::super::pres_request::PresentationRequest;
+
+        let pres_req = |depth: usize| -> PresentationRequest {
+            let mut restriction = json!({"schema_name": "gvt"});
+            for _ in 1..depth {
+                restriction = json!({"$or": [restriction]});
+            }
+            serde_json::from_value(json!({
+                "nonce": "123",
+                "name": "proof",
+                "version": "1.0",
+                "requested_attributes": {
+                    "attr1": {"name": "name", "restrictions": restriction}
+                },
+            }))
+            .unwrap()
+        };
+        let limits = ValidationConfig::default();
+        let limit = limits.max_restriction_depth;
+        assert!(pres_req(limit).check_limits(&limits).is_ok());
+        assert!(pres_req(limit + 1).check_limits(&limits).is_err());
+    }
+
+    #[test]
+    fn check_limit_works() {
+        assert!(check_limit("predicates", 2, 2).is_ok());
+        assert!(check_limit("predicates", 3, 2).is_err());
+    }
+}
@@ -1,11 +1,18 @@
 use std::fmt;
 
 use crate::ursa::cl::{prover::Prover as UrsaProver, MasterSecret as UrsaMasterSecret};
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
-use crate::ConversionError;
+use crate::{ConversionError, Validatable};
 
-#[derive(Serialize, Deserialize)]
+/// The holder's master secret.
+///
+/// `Serialize` is only derived with the `serde_secrets` feature, so the raw secret
+/// can't be written out through generic serialization paths by accident. Use
+/// `MasterSecret::export` to serialize it deliberately, for instance before
+/// encrypting it for backup.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde_secrets", derive(Serialize))]
 pub struct MasterSecret {
     pub value: UrsaMasterSecret,
 }
@@ -25,6 +32,116 @@ impl MasterSecret {
             value: self.value.try_clone()?,
         })
     }
+
+    /// Get a serializable view of the master secret
+    #[inline]
+    pub fn export(&self) -> MasterSecretExport<'_> {
+        MasterSecretExport(self)
+    }
+}
+
+impl Validatable for MasterSecret {}
+
+#[cfg(feature = "protected_secrets")]
+mod protected {
+    use chacha20poly1305::aead::{Aead, NewAead, Payload};
+    use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+    use hmac::Hmac;
+    use rand::{rngs::OsRng, RngCore};
+    use sha2::Sha256;
+    use zeroize::Zeroize;
+
+    use super::MasterSecret;
+    use crate::ConversionError;
+
+    const VERSION: u8 = 1;
+    const KDF_ITERATIONS: u32 = 100_000;
+    const MAX_KDF_ITERATIONS: u32 = 1_000_000;
+    const SALT_LEN: usize = 16;
+    const NONCE_LEN: usize = 24;
+    const HEADER_LEN: usize = 1 + 4 + SALT_LEN + NONCE_LEN;
+
+    fn derive_key(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, iterations as usize, &mut key);
+        key
+    }
+
+    impl MasterSecret {
+        /// Export the master (link) secret encrypted under a password, for backup.
+        ///
+        /// The key is derived using PBKDF2-HMAC-SHA256 with a random salt, and the
+        /// secret is sealed with XChaCha20-Poly1305. The KDF parameters are stored
+        /// in the authenticated header of the output.
+        pub fn to_protected_bytes(&self, password: &[u8]) -> Result<Vec<u8>, ConversionError> {
+            let mut header = Vec::with_capacity(HEADER_LEN);
+            header.push(VERSION);
+            header.extend_from_slice(&KDF_ITERATIONS.to_be_bytes());
+            let mut salt_nonce = [0u8; SALT_LEN + NONCE_LEN];
+            OsRng.fill_bytes(&mut salt_nonce);
+            header.extend_from_slice(&salt_nonce);
+
+            let mut key = derive_key(password, &salt_nonce[..SALT_LEN], KDF_ITERATIONS);
+            let mut plaintext = serde_json::to_vec(&self.export())?;
+            let sealed = XChaCha20Poly1305::new(Key::from_slice(&key)).encrypt(
+                XNonce::from_slice(&salt_nonce[SALT_LEN..]),
+                Payload {
+                    msg: &plaintext,
+                    aad: &header,
+                },
+            );
+            key.zeroize();
+            plaintext.zeroize();
+            let sealed =
+                sealed.map_err(|_| ConversionError::from_msg("Error encrypting master secret"))?;
+
+            header.extend_from_slice(&sealed);
+            Ok(header)
+        }
+
+        /// Restore a master (link) secret exported with `to_protected_bytes`
+        pub fn from_protected_bytes(
+            protected: &[u8],
+            password: &[u8],
+        ) -> Result<Self, ConversionError> {
+            if protected.len() <= HEADER_LEN {
+                return Err(ConversionError::from_msg("Invalid protected master secret"));
+            }
+            let (header, sealed) = protected.split_at(HEADER_LEN);
+            if header[0] != VERSION {
+                return Err(ConversionError::from_msg(format!(
+                    "Unsupported protected master secret version: {}",
+                    header[0]
+                )));
+            }
+            let mut iterations = [0u8; 4];
+            iterations.copy_from_slice(&header[1..5]);
+            let iterations = u32::from_be_bytes(iterations);
+            if iterations == 0 || iterations > MAX_KDF_ITERATIONS {
+                return Err(ConversionError::from_msg("Invalid protected master secret"));
+            }
+            let salt = &header[5..5 + SALT_LEN];
+            let nonce = &header[5 + SALT_LEN..];
+
+            let mut key = derive_key(password, salt, iterations);
+            let plaintext = XChaCha20Poly1305::new(Key::from_slice(&key)).decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: sealed,
+                    aad: header,
+                },
+            );
+            key.zeroize();
+            let mut plaintext = plaintext.map_err(|_| {
+                ConversionError::from_msg(
+                    "Error decrypting master secret: invalid password or corrupted data",
+                )
+            })?;
+            let secret = serde_json::from_slice(&plaintext);
+            plaintext.zeroize();
+            Ok(secret?)
+        }
+    }
 }
 
 impl fmt::Debug for MasterSecret {
@@ -34,3 +151,54 @@ impl fmt::Debug for MasterSecret {
             .finish()
     }
 }
+
+/// A serializable view of a master secret, produced by `MasterSecret::export`
+pub struct MasterSecretExport<'a>(&'a MasterSecret);
+
+impl Serialize for MasterSecretExport<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("MasterSecret", 1)?;
+        state.serialize_field("value", &self.0.value)?;
+        state.end()
+    }
+}
+
+impl fmt::Debug for MasterSecretExport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("MasterSecretExport").field(&self.0).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn master_secret_export_round_trip() {
+        let secret = MasterSecret::new().unwrap();
+        let json = serde_json::to_string(&secret.export()).unwrap();
+        let restored: MasterSecret = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored.export()).unwrap(), json);
+    }
+
+    #[cfg(feature = "protected_secrets")]
+    #[test]
+    fn master_secret_protected_round_trip() {
+        let secret = MasterSecret::new().unwrap();
+        let protected = secret.to_protected_bytes(b"password").unwrap();
+        let restored = MasterSecret::from_protected_bytes(&protected, b"password").unwrap();
+        assert_eq!(
+            serde_json::to_string(&restored.export()).unwrap(),
+            serde_json::to_string(&secret.export()).unwrap()
+        );
+
+        MasterSecret::from_protected_bytes(&protected, b"wrong").unwrap_err();
+        let mut tampered = protected.clone();
+        tampered[10] ^= 1;
+        MasterSecret::from_protected_bytes(&tampered, b"password").unwrap_err();
+        MasterSecret::from_protected_bytes(&protected[..40], b"password").unwrap_err();
+    }
+}
@@ -1,6 +1,10 @@
 #[macro_use]
 mod macros;
 
+#[cfg(all(feature = "serde", feature = "compat"))]
+/// Compatibility with legacy libindy and anoncreds-rs objects
+pub mod compat;
+
 /// Credential definitions
 pub mod cred_def;
 
@@ -13,6 +17,9 @@ pub mod cred_request;
 /// Credentials
 pub mod credential;
 
+/// Size and complexity limits applied during validation
+pub mod limits;
+
 /// Identity master secret
 #[cfg(any(feature = "cl", feature = "cl_native"))]
 pub mod master_secret;
@@ -32,9 +39,16 @@ pub mod rev_reg;
 /// Revocation registry definitions
 pub mod rev_reg_def;
 
+/// Revocation status lists
+pub mod rev_status_list;
+
 #[cfg(any(feature = "rich_schema", test))]
 /// Rich schemas
 pub mod rich_schema;
 
 /// V1 credential schemas
 pub mod schema;
+
+#[cfg(feature = "serde")]
+/// Version envelope detection and migration
+pub mod versioned;
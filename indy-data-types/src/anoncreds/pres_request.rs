@@ -7,8 +7,10 @@ use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
 use super::credential::Credential;
+use super::limits::{check_limit, query_depth, CheckLimits, ValidationConfig};
 use super::nonce::Nonce;
 use crate::identifiers::cred_def::CredentialDefinitionId;
+use crate::identifiers::is_uri_identifier;
 use crate::identifiers::rev_reg::RevocationRegistryId;
 use crate::identifiers::schema::SchemaId;
 use crate::utils::{qualifiable, Qualifiable};
@@ -147,6 +149,21 @@ pub struct AttributeInfo {
     pub non_revoked: Option<NonRevocedInterval>,
 }
 
+impl AttributeInfo {
+    /// Whether the prover may self-attest a value for this attribute. Only
+    /// single attributes without restrictions can be self-attested.
+    pub fn allows_self_attestation(&self) -> bool {
+        if self.name.is_none() {
+            return false;
+        }
+        match self.restrictions.as_ref() {
+            None => true,
+            Some(Query::And(queries)) | Some(Query::Or(queries)) => queries.is_empty(),
+            Some(_) => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PredicateInfo {
@@ -196,6 +213,38 @@ pub struct RequestedPredicateInfo {
     pub predicate_info: PredicateInfo,
 }
 
+impl CheckLimits for PresentationRequest {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        let value = self.value();
+        check_limit(
+            "requested predicates",
+            value.requested_predicates.len(),
+            limits.max_predicates,
+        )?;
+        let restrictions = value
+            .requested_attributes
+            .values()
+            .filter_map(|info| info.restrictions.as_ref())
+            .chain(
+                value
+                    .requested_predicates
+                    .values()
+                    .filter_map(|info| info.restrictions.as_ref()),
+            );
+        for query in restrictions {
+            let depth = query_depth(query);
+            if depth > limits.max_restriction_depth {
+                return Err(invalid!(
+                    "The restriction depth {} exceeds the limit of {}",
+                    depth,
+                    limits.max_restriction_depth
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Validatable for PresentationRequest {
     fn validate(&self) -> Result<(), ValidationError> {
         let value = self.value();
@@ -244,6 +293,8 @@ impl Validatable for PresentationRequest {
             }
         }
 
+        _check_consistent_qualification(value)?;
+
         Ok(())
     }
 }
@@ -251,66 +302,133 @@ impl Validatable for PresentationRequest {
 impl PresentationRequest {
     #[allow(unused)]
     pub fn to_unqualified(self) -> PresentationRequest {
-        let convert = |request: &mut PresentationRequestPayload| {
-            for (_, requested_attribute) in request.requested_attributes.iter_mut() {
-                requested_attribute.restrictions = requested_attribute
-                    .restrictions
-                    .as_mut()
-                    .map(|ref mut restrictions| _convert_query_to_unqualified(&restrictions));
-            }
-            for (_, requested_predicate) in request.requested_predicates.iter_mut() {
-                requested_predicate.restrictions = requested_predicate
-                    .restrictions
-                    .as_mut()
-                    .map(|ref mut restrictions| _convert_query_to_unqualified(&restrictions));
-            }
-        };
+        let version = self.version();
+        let mut request = self.into_payload();
+        request
+            .map_restrictions(|tag_name, tag_value| {
+                Ok(_convert_value_to_unqualified(tag_name, tag_value))
+            })
+            .expect("Unqualified conversion is infallible");
+        Self::from_payload(version, request)
+    }
 
+    /// Convert to a version 1 presentation request, removing the qualification
+    /// from all identifiers in the attribute and predicate restrictions
+    pub fn to_v1(self) -> PresentationRequest {
+        let mut request = self.into_payload();
+        request
+            .map_restrictions(|tag_name, tag_value| {
+                Ok(_convert_value_to_unqualified(tag_name, tag_value))
+            })
+            .expect("Unqualified conversion is infallible");
+        PresentationRequest::PresentationRequestV1(request)
+    }
+
+    /// Convert to a version 2 presentation request, qualifying all identifiers
+    /// in the attribute and predicate restrictions with the DID method `method`.
+    /// Fails if an identifier is already qualified with a different method.
+    pub fn to_v2(self, method: &str) -> Result<PresentationRequest, ValidationError> {
+        let mut request = self.into_payload();
+        request.map_restrictions(|tag_name, tag_value| {
+            _convert_value_to_qualified(tag_name, tag_value, method)
+        })?;
+        Ok(PresentationRequest::PresentationRequestV2(request))
+    }
+
+    fn into_payload(self) -> PresentationRequestPayload {
         match self {
-            PresentationRequest::PresentationRequestV2(mut request) => {
-                convert(&mut request);
-                PresentationRequest::PresentationRequestV2(request)
+            PresentationRequest::PresentationRequestV1(request) => request,
+            PresentationRequest::PresentationRequestV2(request) => request,
+        }
+    }
+
+    fn from_payload(
+        version: PresentationRequestVersion,
+        request: PresentationRequestPayload,
+    ) -> PresentationRequest {
+        match version {
+            PresentationRequestVersion::V1 => PresentationRequest::PresentationRequestV1(request),
+            PresentationRequestVersion::V2 => PresentationRequest::PresentationRequestV2(request),
+        }
+    }
+}
+
+impl PresentationRequestPayload {
+    /// List the referents of the requested attributes which may be satisfied
+    /// by a self-attested value, in sorted order
+    pub fn self_attestable_referents(&self) -> Vec<&str> {
+        let mut referents = self
+            .requested_attributes
+            .iter()
+            .filter(|(_, info)| info.allows_self_attestation())
+            .map(|(referent, _)| referent.as_str())
+            .collect::<Vec<_>>();
+        referents.sort_unstable();
+        referents
+    }
+
+    fn map_restrictions<F>(&mut self, mut f: F) -> Result<(), ValidationError>
+    where
+        F: FnMut(&str, &str) -> Result<String, ValidationError>,
+    {
+        for (_, requested_attribute) in self.requested_attributes.iter_mut() {
+            if let Some(restrictions) = requested_attribute.restrictions.as_mut() {
+                *restrictions = _map_query_values(restrictions, &mut f)?;
             }
-            PresentationRequest::PresentationRequestV1(mut request) => {
-                convert(&mut request);
-                PresentationRequest::PresentationRequestV1(request)
+        }
+        for (_, requested_predicate) in self.requested_predicates.iter_mut() {
+            if let Some(restrictions) = requested_predicate.restrictions.as_mut() {
+                *restrictions = _map_query_values(restrictions, &mut f)?;
             }
         }
+        Ok(())
+    }
+
+    fn restrictions(&self) -> impl Iterator<Item = &Query> {
+        self.requested_attributes
+            .values()
+            .filter_map(|attr| attr.restrictions.as_ref())
+            .chain(
+                self.requested_predicates
+                    .values()
+                    .filter_map(|pred| pred.restrictions.as_ref()),
+            )
     }
 }
 
-fn _convert_query_to_unqualified(query: &Query) -> Query {
-    match query {
-        Query::Eq(tag_name, ref tag_value) => Query::Eq(
-            tag_name.to_string(),
-            _convert_value_to_unqualified(tag_name, tag_value),
-        ),
-        Query::Neq(ref tag_name, ref tag_value) => Query::Neq(
-            tag_name.to_string(),
-            _convert_value_to_unqualified(tag_name, tag_value),
-        ),
+fn _map_query_values<F>(query: &Query, f: &mut F) -> Result<Query, ValidationError>
+where
+    F: FnMut(&str, &str) -> Result<String, ValidationError>,
+{
+    Ok(match query {
+        Query::Eq(ref tag_name, ref tag_value) => {
+            Query::Eq(tag_name.to_string(), f(tag_name, tag_value)?)
+        }
+        Query::Neq(ref tag_name, ref tag_value) => {
+            Query::Neq(tag_name.to_string(), f(tag_name, tag_value)?)
+        }
         Query::In(ref tag_name, ref tag_values) => Query::In(
             tag_name.to_string(),
             tag_values
                 .iter()
-                .map(|tag_value| _convert_value_to_unqualified(tag_name, tag_value))
-                .collect::<Vec<String>>(),
+                .map(|tag_value| f(tag_name, tag_value))
+                .collect::<Result<Vec<String>, ValidationError>>()?,
         ),
         Query::And(ref queries) => Query::And(
             queries
                 .iter()
-                .map(|query| _convert_query_to_unqualified(query))
-                .collect::<Vec<Query>>(),
+                .map(|query| _map_query_values(query, f))
+                .collect::<Result<Vec<Query>, ValidationError>>()?,
         ),
         Query::Or(ref queries) => Query::Or(
             queries
                 .iter()
-                .map(|query| _convert_query_to_unqualified(query))
-                .collect::<Vec<Query>>(),
+                .map(|query| _map_query_values(query, f))
+                .collect::<Result<Vec<Query>, ValidationError>>()?,
         ),
-        Query::Not(ref query) => _convert_query_to_unqualified(query),
+        Query::Not(ref query) => Query::Not(Box::new(_map_query_values(query, f)?)),
         query => query.clone(),
-    }
+    })
 }
 
 fn _convert_value_to_unqualified(tag_name: &str, tag_value: &str) -> String {
@@ -331,6 +449,51 @@ fn _convert_value_to_unqualified(tag_name: &str, tag_value: &str) -> String {
     }
 }
 
+fn _convert_value_to_qualified(
+    tag_name: &str,
+    tag_value: &str,
+    method: &str,
+) -> Result<String, ValidationError> {
+    if is_uri_identifier(tag_value) {
+        return Ok(tag_value.to_string());
+    }
+    Ok(match tag_name {
+        "issuer_did" | "schema_issuer_did" => {
+            DidValue(tag_value.to_string()).to_qualified(method)?.0
+        }
+        "schema_id" => SchemaId(tag_value.to_string()).to_qualified(method)?.0,
+        "cred_def_id" => {
+            CredentialDefinitionId(tag_value.to_string())
+                .to_qualified(method)?
+                .0
+        }
+        "rev_reg_id" => {
+            RevocationRegistryId(tag_value.to_string())
+                .to_qualified(method)?
+                .0
+        }
+        _ => tag_value.to_string(),
+    })
+}
+
+fn _check_consistent_qualification(
+    request: &PresentationRequestPayload,
+) -> Result<(), ValidationError> {
+    let mut qualified = None;
+    for restrictions in request.restrictions() {
+        _map_query_values(restrictions, &mut |tag_name, tag_value| {
+            if Credential::QUALIFIABLE_TAGS.contains(&tag_name) && !is_uri_identifier(tag_value) {
+                let is_qualified = qualifiable::is_fully_qualified(tag_value);
+                if *qualified.get_or_insert(is_qualified) != is_qualified {
+                    return Err(invalid!("Presentation request validation failed: restrictions mix fully qualified and unqualified identifiers"));
+                }
+            }
+            Ok(tag_value.to_string())
+        })?;
+    }
+    Ok(())
+}
+
 fn _process_operator(
     restriction_op: &Query,
     version: &PresentationRequestVersion,
@@ -526,5 +689,128 @@ mod tests {
             );
             assert_eq!(PresentationRequestVersion::V2, request.version());
         }
+
+        fn _request_with_restrictions(
+            version: PresentationRequestVersion,
+            restrictions: Query,
+        ) -> PresentationRequest {
+            let mut requested_attributes: HashMap<String, AttributeInfo> = HashMap::new();
+            requested_attributes.insert(
+                "attr1_referent".to_string(),
+                AttributeInfo {
+                    name: Some("name".to_string()),
+                    names: None,
+                    restrictions: Some(restrictions),
+                    non_revoked: None,
+                },
+            );
+            PresentationRequest::from_payload(
+                version,
+                PresentationRequestPayload {
+                    nonce: Nonce::from_dec("112233445566").unwrap(),
+                    name: "presentation_request_versions".to_string(),
+                    version: "1.0".to_string(),
+                    requested_attributes,
+                    requested_predicates: HashMap::new(),
+                    non_revoked: None,
+                },
+            )
+        }
+
+        #[test]
+        fn presentation_request_version_conversions() {
+            let unqualified = Query::And(vec![
+                Query::Eq("issuer_did".to_string(), DID_UNQUALIFIED.to_string()),
+                Query::Not(Box::new(Query::Eq(
+                    "cred_def_id".to_string(),
+                    CRED_DEF_ID_UNQUALIFIED.to_string(),
+                ))),
+                Query::In(
+                    "schema_id".to_string(),
+                    vec![SCHEMA_ID_UNQUALIFIED.to_string()],
+                ),
+            ]);
+            let qualified = Query::And(vec![
+                Query::Eq("issuer_did".to_string(), DID_QUALIFIED.to_string()),
+                Query::Not(Box::new(Query::Eq(
+                    "cred_def_id".to_string(),
+                    CRED_DEF_ID_QUALIFIED.to_string(),
+                ))),
+                Query::In(
+                    "schema_id".to_string(),
+                    vec![SCHEMA_ID_QUALIFIED.to_string()],
+                ),
+            ]);
+
+            let request =
+                _request_with_restrictions(PresentationRequestVersion::V1, unqualified.clone());
+            let request = request.to_v2("sov").unwrap();
+            assert_eq!(PresentationRequestVersion::V2, request.version());
+            assert_eq!(
+                request.value().requested_attributes["attr1_referent"].restrictions,
+                Some(qualified)
+            );
+            request.validate().unwrap();
+
+            let request = request.to_v1();
+            assert_eq!(PresentationRequestVersion::V1, request.version());
+            assert_eq!(
+                request.value().requested_attributes["attr1_referent"].restrictions,
+                Some(unqualified)
+            );
+            request.validate().unwrap();
+        }
+
+        #[test]
+        fn presentation_request_to_v2_conflicting_method() {
+            let request = _request_with_restrictions(
+                PresentationRequestVersion::V2,
+                Query::Eq("issuer_did".to_string(), DID_QUALIFIED.to_string()),
+            );
+            request.to_v2("indy").unwrap_err();
+        }
+
+        #[test]
+        fn presentation_request_mixed_qualification_invalid() {
+            let request = _request_with_restrictions(
+                PresentationRequestVersion::V2,
+                Query::Or(vec![
+                    Query::Eq("issuer_did".to_string(), DID_QUALIFIED.to_string()),
+                    Query::Eq("schema_id".to_string(), SCHEMA_ID_UNQUALIFIED.to_string()),
+                ]),
+            );
+            request.validate().unwrap_err();
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod self_attestation {
+        use super::*;
+
+        #[test]
+        fn self_attestable_referents_works() {
+            let req: PresentationRequest = serde_json::from_value(json!({
+                "nonce": "123456",
+                "name": "name",
+                "version": "1.0",
+                "requested_attributes": {
+                    "open": {"name": "phone"},
+                    "empty_and": {"name": "email", "restrictions": {"$and": []}},
+                    "empty_or": {"name": "address", "restrictions": []},
+                    "restricted": {"name": "age", "restrictions": {"schema_name": "gvt"}},
+                    "group": {"names": ["name", "sex"]},
+                },
+                "requested_predicates": {},
+            }))
+            .unwrap();
+
+            assert_eq!(
+                req.value().self_attestable_referents(),
+                vec!["empty_and", "empty_or", "open"]
+            );
+            let attrs = &req.value().requested_attributes;
+            assert!(!attrs["restricted"].allows_self_attestation());
+            assert!(!attrs["group"].allows_self_attestation());
+        }
     }
 }
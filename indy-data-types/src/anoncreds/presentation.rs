@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
+use super::limits::{check_limit, CheckLimits, ValidationConfig};
 use crate::identifiers::cred_def::CredentialDefinitionId;
 use crate::identifiers::rev_reg::RevocationRegistryId;
 use crate::identifiers::schema::SchemaId;
-use crate::Validatable;
+use crate::{invalid, Validatable, ValidationError};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -50,7 +51,15 @@ pub struct SubProofReferent {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct RevealedAttributeInfo {
     pub sub_proof_index: u32,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub raw: String,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub encoded: String,
 }
 
@@ -64,7 +73,15 @@ pub struct RevealedAttributeGroupInfo {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct AttributeValue {
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub raw: String,
+    #[cfg_attr(
+        all(feature = "serde", feature = "compat"),
+        serde(deserialize_with = "super::compat::string_or_number")
+    )]
     pub encoded: String,
 }
 
@@ -77,7 +94,118 @@ pub struct Identifier {
     pub timestamp: Option<u64>,
 }
 
-impl Validatable for Presentation {}
+impl Presentation {
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    fn sub_proof_count(&self) -> Option<usize> {
+        Some(self.proof.proofs.len())
+    }
+
+    #[cfg(not(any(feature = "cl", feature = "cl_native")))]
+    fn sub_proof_count(&self) -> Option<usize> {
+        self.proof
+            .get("proofs")
+            .and_then(serde_json::Value::as_array)
+            .map(Vec::len)
+    }
+}
+
+impl CheckLimits for Presentation {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        check_limit(
+            "presentation credentials",
+            self.identifiers.len(),
+            limits.max_presentation_credentials,
+        )?;
+        check_limit(
+            "predicates",
+            self.requested_proof.predicates.len(),
+            limits.max_predicates,
+        )
+    }
+}
+
+impl Validatable for Presentation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        for identifier in self.identifiers.iter() {
+            identifier.schema_id.validate()?;
+            identifier.cred_def_id.validate()?;
+            if let Some(rev_reg_id) = identifier.rev_reg_id.as_ref() {
+                rev_reg_id.validate()?;
+            }
+        }
+
+        let count = self.identifiers.len();
+        if let Some(sub_proofs) = self.sub_proof_count() {
+            if sub_proofs != count {
+                return Err(invalid!(
+                    "Presentation has {} identifiers but {} sub-proofs",
+                    count,
+                    sub_proofs
+                ));
+            }
+        }
+
+        let proof = &self.requested_proof;
+        let indexes = proof
+            .revealed_attrs
+            .iter()
+            .map(|(referent, info)| (referent, info.sub_proof_index))
+            .chain(
+                proof
+                    .revealed_attr_groups
+                    .iter()
+                    .map(|(referent, info)| (referent, info.sub_proof_index)),
+            )
+            .chain(
+                proof
+                    .unrevealed_attrs
+                    .iter()
+                    .chain(proof.predicates.iter())
+                    .map(|(referent, info)| (referent, info.sub_proof_index)),
+            );
+        for (referent, index) in indexes {
+            if index as usize >= count {
+                return Err(invalid!(
+                    "Sub-proof index {} out of range for referent: {}",
+                    index,
+                    referent
+                ));
+            }
+        }
+
+        for referent in proof.self_attested_attrs.keys() {
+            if proof.revealed_attrs.contains_key(referent)
+                || proof.revealed_attr_groups.contains_key(referent)
+                || proof.unrevealed_attrs.contains_key(referent)
+            {
+                return Err(invalid!(
+                    "Referent is both self-attested and proven: {}",
+                    referent
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A standalone zero-knowledge proof that the holder knows the master (link)
+/// secret bound to a credential, without disclosing any of its attributes
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct MasterSecretProof {
+    pub proof: ursa_cl!(Proof),
+    pub schema_id: SchemaId,
+    pub cred_def_id: CredentialDefinitionId,
+}
+
+impl Validatable for MasterSecretProof {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.schema_id.validate()?;
+        self.cred_def_id.validate()?;
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -2,6 +2,8 @@
 use std::collections::HashSet;
 
 use crate::Validatable;
+#[cfg(any(feature = "cl", feature = "cl_native"))]
+use crate::{invalid, ValidationError};
 
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -11,6 +13,8 @@ pub enum RevocationRegistry {
     RevocationRegistryV1(RevocationRegistryV1),
 }
 
+impl Validatable for RevocationRegistry {}
+
 impl RevocationRegistry {
     #[cfg(any(feature = "cl", feature = "cl_native"))]
     pub fn initial_delta(&self) -> RevocationRegistryDelta {
@@ -27,6 +31,62 @@ impl RevocationRegistry {
             }
         }
     }
+
+    /// Describe the changes from an earlier state of this registry as a delta,
+    /// given the indices issued and revoked in the meantime
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    pub fn diff(
+        &self,
+        earlier: &RevocationRegistry,
+        issued: &HashSet<u32>,
+        revoked: &HashSet<u32>,
+    ) -> Result<RevocationRegistryDelta, ValidationError> {
+        if let Some(idx) = issued.intersection(revoked).next() {
+            return Err(invalid!(
+                "Revocation index {} cannot be both issued and revoked",
+                idx
+            ));
+        }
+        match (self, earlier) {
+            (Self::RevocationRegistryV1(v1), Self::RevocationRegistryV1(earlier)) => Ok(
+                RevocationRegistryDelta::RevocationRegistryDeltaV1(RevocationRegistryDeltaV1 {
+                    value: crate::ursa::cl::RevocationRegistryDelta::from_parts(
+                        Some(&earlier.value),
+                        &v1.value,
+                        issued,
+                        revoked,
+                    ),
+                }),
+            ),
+        }
+    }
+
+    /// Apply a delta to this registry, producing the updated registry. The
+    /// delta must have been produced from the current state of the registry.
+    #[cfg(any(feature = "cl", feature = "cl_native"))]
+    pub fn apply_delta(
+        &self,
+        delta: &RevocationRegistryDelta,
+    ) -> Result<RevocationRegistry, ValidationError> {
+        match (self, delta) {
+            (
+                Self::RevocationRegistryV1(v1),
+                RevocationRegistryDelta::RevocationRegistryDeltaV1(delta),
+            ) => {
+                let empty = HashSet::new();
+                let mut current = crate::ursa::cl::RevocationRegistryDelta::from_parts(
+                    None, &v1.value, &empty, &empty,
+                );
+                // merging checks that the delta starts from the current accumulator
+                current.merge(&delta.value).map_err(|_| {
+                    invalid!("Revocation registry delta does not apply to the registry state")
+                })?;
+                Ok(Self::RevocationRegistryV1(RevocationRegistryV1 {
+                    value: current.into(),
+                }))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -146,6 +146,8 @@ pub struct RevocationRegistryDefinitionPrivate {
     pub value: ursa_cl!(RevocationKeyPrivate),
 }
 
+impl Validatable for RevocationRegistryDefinitionPrivate {}
+
 #[derive(Deserialize, Debug, Serialize)]
 pub struct RevocationRegistryConfig {
     pub issuance_type: Option<IssuanceType>,
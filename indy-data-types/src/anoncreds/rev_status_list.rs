@@ -0,0 +1,221 @@
+use crate::identifiers::rev_reg::RevocationRegistryId;
+use crate::{invalid, Validatable, ValidationError};
+
+#[cfg(feature = "serde")]
+use super::{
+    rev_reg::{
+        RevocationRegistry, RevocationRegistryDelta, RevocationRegistryDeltaV1,
+        RevocationRegistryV1,
+    },
+    rev_reg_def::{IssuanceType, RevocationRegistryDefinition},
+};
+#[cfg(feature = "serde")]
+use crate::ConversionError;
+
+/// The AnonCreds 1.0 revocation status list for a revocation registry.
+///
+/// Entry `n` of the revocation list holds the status of the credential with
+/// revocation registry index `n + 1`, where `1` marks a revoked credential.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RevocationStatusList {
+    pub issuer_id: String,
+    pub rev_reg_def_id: RevocationRegistryId,
+    pub revocation_list: Vec<u8>,
+    pub current_accumulator: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub timestamp: Option<u64>,
+}
+
+impl RevocationStatusList {
+    pub fn is_revoked(&self, rev_reg_idx: u32) -> Option<bool> {
+        if rev_reg_idx == 0 {
+            return None;
+        }
+        self.revocation_list
+            .get(rev_reg_idx as usize - 1)
+            .map(|status| *status == 1)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl RevocationStatusList {
+    /// Build the status list for a revocation registry from its current state
+    /// and the full, ordered history of deltas applied to it.
+    pub fn from_revocation_registry(
+        rev_reg_def: &RevocationRegistryDefinition,
+        rev_reg: &RevocationRegistry,
+        deltas: &[RevocationRegistryDelta],
+        timestamp: Option<u64>,
+    ) -> Result<Self, ConversionError> {
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+        let issuer_id = rev_reg_def
+            .id
+            .issuer_did()
+            .map(|did| did.0)
+            .ok_or_else(|| ConversionError::from_msg("Invalid revocation registry ID"))?;
+        let initial = match rev_reg_def.value.issuance_type {
+            IssuanceType::ISSUANCE_BY_DEFAULT => 0,
+            IssuanceType::ISSUANCE_ON_DEMAND => 1,
+        };
+        let mut revocation_list = vec![initial; rev_reg_def.value.max_cred_num as usize];
+        for delta in deltas {
+            let delta = match delta {
+                RevocationRegistryDelta::RevocationRegistryDeltaV1(v1) => to_json(&v1.value)?,
+            };
+            for (field, status) in &[("issued", 0), ("revoked", 1)] {
+                for idx in delta_indices(&delta, field)? {
+                    let entry = idx
+                        .checked_sub(1)
+                        .and_then(|pos| revocation_list.get_mut(pos as usize))
+                        .ok_or_else(|| {
+                            ConversionError::from_msg(format!(
+                                "Revocation registry index out of range: {}",
+                                idx
+                            ))
+                        })?;
+                    *entry = *status;
+                }
+            }
+        }
+        let current_accumulator = match rev_reg {
+            RevocationRegistry::RevocationRegistryV1(v1) => to_json(&v1.value)?,
+        }
+        .get("accum")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_owned)
+        .ok_or_else(|| ConversionError::from_msg("Missing revocation registry accumulator"))?;
+
+        Ok(Self {
+            issuer_id,
+            rev_reg_def_id: rev_reg_def.id.clone(),
+            revocation_list,
+            current_accumulator,
+            timestamp,
+        })
+    }
+
+    /// Convert the current accumulator to a revocation registry entry
+    pub fn to_revocation_registry(&self) -> Result<RevocationRegistry, ConversionError> {
+        let value = from_json(serde_json::json!({ "accum": self.current_accumulator }))?;
+        Ok(RevocationRegistry::RevocationRegistryV1(
+            RevocationRegistryV1 { value },
+        ))
+    }
+
+    /// Convert the status list to a single delta from the initial state of the
+    /// revocation registry
+    pub fn to_revocation_registry_delta(
+        &self,
+        rev_reg_def: &RevocationRegistryDefinition,
+    ) -> Result<RevocationRegistryDelta, ConversionError> {
+        let RevocationRegistryDefinition::RevocationRegistryDefinitionV1(rev_reg_def) = rev_reg_def;
+        if rev_reg_def.id != self.rev_reg_def_id {
+            return Err(ConversionError::from_msg(
+                "Revocation registry definition does not match the status list",
+            ));
+        }
+        if rev_reg_def.value.max_cred_num as usize != self.revocation_list.len() {
+            return Err(ConversionError::from_msg(
+                "Revocation list length does not match the revocation registry size",
+            ));
+        }
+        let indices = |status: u8| {
+            self.revocation_list
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| **s == status)
+                .map(|(pos, _)| pos as u32 + 1)
+                .collect::<Vec<_>>()
+        };
+        let (issued, revoked) = match rev_reg_def.value.issuance_type {
+            IssuanceType::ISSUANCE_BY_DEFAULT => (vec![], indices(1)),
+            IssuanceType::ISSUANCE_ON_DEMAND => (indices(0), vec![]),
+        };
+        let value = from_json(serde_json::json!({
+            "accum": self.current_accumulator,
+            "issued": issued,
+            "revoked": revoked,
+        }))?;
+        Ok(RevocationRegistryDelta::RevocationRegistryDeltaV1(
+            RevocationRegistryDeltaV1 { value },
+        ))
+    }
+}
+
+impl Validatable for RevocationStatusList {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.rev_reg_def_id.validate()?;
+        if self.revocation_list.iter().any(|status| *status > 1) {
+            return Err(invalid!("Revocation list entries must be 0 or 1"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn to_json<T: serde::Serialize>(value: &T) -> Result<serde_json::Value, ConversionError> {
+    serde_json::to_value(value)
+        .map_err(|err| ConversionError::from_msg_err("Error serializing revocation data", err))
+}
+
+#[cfg(feature = "serde")]
+fn from_json<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+) -> Result<T, ConversionError> {
+    serde_json::from_value(value)
+        .map_err(|err| ConversionError::from_msg_err("Invalid revocation data", err))
+}
+
+#[cfg(feature = "serde")]
+fn delta_indices(delta: &serde_json::Value, field: &str) -> Result<Vec<u32>, ConversionError> {
+    match delta.get(field) {
+        None => Ok(vec![]),
+        Some(indices) => from_json(indices.clone()),
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    const REV_REG_ID: &str =
+        "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag:CL_ACCUM:default";
+
+    #[test]
+    fn status_list_round_trip() {
+        let list: RevocationStatusList = serde_json::from_value(json!({
+            "issuerId": "NcYxiDXkpYi6ov5FcYDi1e",
+            "revRegDefId": REV_REG_ID,
+            "revocationList": [0, 1, 1, 0],
+            "currentAccumulator": "21 124C594B6B20E41B681E92B2C43FD165EA9E68BC3C9D63A82C8893124983CAE94",
+            "timestamp": 1669640864,
+        }))
+        .unwrap();
+        list.validate().unwrap();
+        assert_eq!(list.is_revoked(1), Some(false));
+        assert_eq!(list.is_revoked(2), Some(true));
+        assert_eq!(list.is_revoked(0), None);
+        assert_eq!(list.is_revoked(5), None);
+
+        let json = serde_json::to_value(&list).unwrap();
+        assert_eq!(json["revocationList"], json!([0, 1, 1, 0]));
+        assert_eq!(json["timestamp"], 1669640864);
+    }
+
+    #[test]
+    fn status_list_invalid_entry() {
+        let list: RevocationStatusList = serde_json::from_value(json!({
+            "issuerId": "NcYxiDXkpYi6ov5FcYDi1e",
+            "revRegDefId": REV_REG_ID,
+            "revocationList": [0, 2],
+            "currentAccumulator": "21 124C594B6B20E41B681E92B2C43FD165EA9E68BC3C9D63A82C8893124983CAE94",
+        }))
+        .unwrap();
+        assert!(list.validate().is_err());
+    }
+}
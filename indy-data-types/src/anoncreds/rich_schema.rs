@@ -1,5 +1,9 @@
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+
 use crate::identifiers::rich_schema::RichSchemaId;
-use crate::{Validatable, ValidationError};
+use crate::{invalid, Validatable, ValidationError};
 
 #[macro_export]
 macro_rules! build_rs_operation {
@@ -17,10 +21,30 @@ macro_rules! build_rs_operation {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RSContent(pub String);
 
+impl RSContent {
+    /// Parse the JSON-LD document, which must be a JSON object
+    pub fn to_value(&self) -> Result<Map<String, Value>, ValidationError> {
+        match serde_json::from_str(&self.0) {
+            Ok(Value::Object(map)) => Ok(map),
+            Ok(_) => Err(invalid!("Rich schema content must be a JSON object")),
+            Err(err) => Err(invalid!("Rich schema content is not valid JSON: {}", err)),
+        }
+    }
+
+    /// Parse the JSON-LD document as the content of a specific rich schema object type
+    pub fn parse<T: serde::de::DeserializeOwned + Validatable>(
+        &self,
+    ) -> Result<T, ValidationError> {
+        let content: T = serde_json::from_str(&self.0)
+            .map_err(|err| invalid!("Invalid rich schema content: {}", err))?;
+        content.validate()?;
+        Ok(content)
+    }
+}
+
 impl Validatable for RSContent {
     fn validate(&self) -> Result<(), ValidationError> {
-        // ToDo: Add JSON-LD validation if needed
-        return Ok(());
+        self.to_value().map(|_| ())
     }
 }
 
@@ -54,16 +78,219 @@ impl RichSchema {
     }
 }
 
+impl RichSchema {
+    /// Create a rich schema object, deriving its identifier from the content. The
+    /// `@id` of the content, if any, is replaced by the derived identifier.
+    #[cfg(feature = "hash")]
+    pub fn from_content(
+        content: Map<String, Value>,
+        rs_name: String,
+        rs_version: String,
+        rs_type: RSType,
+        ver: String,
+    ) -> Result<Self, ValidationError> {
+        let id = RichSchemaId::from_content(&content)?;
+        let mut content = content;
+        if rs_type != RSType::Ctx {
+            content.insert(JSON_LD_ID.to_owned(), Value::String(id.0.clone()));
+        }
+        let content = serde_json::to_string(&content).map_err(|err| invalid!("{}", err))?;
+        Ok(Self::new(
+            id,
+            RSContent(content),
+            rs_name,
+            rs_version,
+            rs_type.as_str().to_owned(),
+            ver,
+        ))
+    }
+
+    pub fn rs_type(&self) -> Result<RSType, ValidationError> {
+        serde_json::from_value(Value::String(self.rs_type.clone()))
+            .map_err(|err| invalid!("{}", err))
+    }
+}
+
 impl Validatable for RichSchema {
     fn validate(&self) -> Result<(), ValidationError> {
-        let _rs_type: RSType =
-            serde_json::from_value(serde_json::value::Value::String(self.rs_type.clone()))
-                .map_err(|_err| _err.to_string())?;
-        return self.id.validate();
+        let rs_type = self.rs_type()?;
+        self.id.validate()?;
+        let content = self.content.to_value()?;
+        if rs_type != RSType::Ctx {
+            match content.get(JSON_LD_ID) {
+                Some(Value::String(id)) if id == &self.id.0 => (),
+                _ => {
+                    return Err(invalid!(
+                        "Rich schema content `{}` must match the identifier `{}`",
+                        JSON_LD_ID,
+                        self.id.0
+                    ))
+                }
+            }
+        }
+        match rs_type {
+            RSType::Ctx => self.content.parse::<RSContext>().map(|_| ()),
+            RSType::Sch => self.content.parse::<RSSchema>().map(|_| ()),
+            RSType::Map => self.content.parse::<RSMapping>().map(|_| ()),
+            RSType::Pdf => self.content.parse::<RSPresentationDefinition>().map(|_| ()),
+            RSType::Enc | RSType::Cdf => Ok(()),
+        }
+    }
+}
+
+const JSON_LD_ID: &str = "@id";
+
+/// The content of a rich schema context object
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RSContext {
+    #[serde(rename = "@context")]
+    pub context: Value,
+}
+
+impl Validatable for RSContext {
+    fn validate(&self) -> Result<(), ValidationError> {
+        match self.context {
+            Value::Object(_) | Value::Array(_) | Value::String(_) => Ok(()),
+            _ => Err(invalid!("Invalid `@context` for rich schema context")),
+        }
+    }
+}
+
+/// The content of a rich schema, describing the attributes of a class of objects
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RSSchema {
+    #[serde(rename = "@id")]
+    pub id: RichSchemaId,
+    #[serde(rename = "@type")]
+    pub type_: String,
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl Validatable for RSSchema {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.id.validate()?;
+        check_json_ld_type(&self.type_)
     }
 }
 
-#[derive(Serialize, Debug, Deserialize, Clone)]
+/// An encoding applied to a mapped attribute, and its position in the credential
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RSMappingEncoding {
+    pub enc: RichSchemaId,
+    pub rank: u32,
+}
+
+/// The content of a rich schema mapping, assigning encodings to the attributes of
+/// a rich schema
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RSMapping {
+    #[serde(rename = "@id")]
+    pub id: RichSchemaId,
+    #[serde(rename = "@type")]
+    pub type_: String,
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    pub schema: RichSchemaId,
+    pub attributes: Map<String, Value>,
+}
+
+impl RSMapping {
+    /// Collect the encodings of each mapped attribute, keyed by the path of the attribute
+    pub fn encodings(&self) -> Result<Vec<(String, RSMappingEncoding)>, ValidationError> {
+        fn collect(
+            prefix: &str,
+            attributes: &Map<String, Value>,
+            found: &mut Vec<(String, RSMappingEncoding)>,
+        ) -> Result<(), ValidationError> {
+            for (name, value) in attributes {
+                let path = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                match value {
+                    Value::Object(nested) => collect(&path, nested, found)?,
+                    Value::Array(_) => {
+                        let encodings: Vec<RSMappingEncoding> =
+                            serde_json::from_value(value.clone()).map_err(|err| {
+                                invalid!(
+                                    "Invalid encodings for mapped attribute `{}`: {}",
+                                    path,
+                                    err
+                                )
+                            })?;
+                        found.extend(encodings.into_iter().map(|enc| (path.clone(), enc)));
+                    }
+                    _ => return Err(invalid!("Invalid mapped attribute `{}`", path)),
+                }
+            }
+            Ok(())
+        }
+
+        let mut found = Vec::new();
+        collect("", &self.attributes, &mut found)?;
+        Ok(found)
+    }
+}
+
+impl Validatable for RSMapping {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.id.validate()?;
+        self.schema.validate()?;
+        check_json_ld_type(&self.type_)?;
+        let encodings = self.encodings()?;
+        if encodings.is_empty() {
+            return Err(invalid!(
+                "Rich schema mapping must map at least one attribute"
+            ));
+        }
+        let mut ranks = HashSet::new();
+        for (path, enc) in encodings.iter() {
+            enc.enc.validate()?;
+            if !ranks.insert(enc.rank) {
+                return Err(invalid!(
+                    "Duplicate rank {} for mapped attribute `{}`",
+                    enc.rank,
+                    path
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The content of a rich schema presentation definition
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RSPresentationDefinition {
+    #[serde(rename = "@id")]
+    pub id: RichSchemaId,
+    #[serde(rename = "@type")]
+    pub type_: String,
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl Validatable for RSPresentationDefinition {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.id.validate()?;
+        check_json_ld_type(&self.type_)
+    }
+}
+
+fn check_json_ld_type(type_: &str) -> Result<(), ValidationError> {
+    if type_.is_empty() {
+        Err(invalid!("Rich schema content `@type` must not be empty"))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum RSType {
     #[serde(rename = "sch")]
     Sch,
@@ -111,6 +338,73 @@ mod tests {
         )
     }
 
+    fn _rs_mapping(rank: u32) -> RichSchema {
+        let id = _rich_schema_id();
+        let content = json!({
+            "@id": id,
+            "@type": "rdfs:Class",
+            "schema": "did:sov:some_schema_hash",
+            "attributes": {
+                "name": [{"enc": "did:sov:some_enc_hash", "rank": 1}],
+                "address": {
+                    "city": [{"enc": "did:sov:some_enc_hash", "rank": rank}]
+                }
+            }
+        });
+        RichSchema::new(
+            id,
+            RSContent(content.to_string()),
+            "test_mapping".to_string(),
+            "1.0".to_string(),
+            RSType::Map.as_str().to_owned(),
+            "1".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_validate_mapping() {
+        let mapping = _rs_mapping(2);
+        mapping.validate().unwrap();
+        let content = mapping.content.parse::<RSMapping>().unwrap();
+        let mut encodings = content.encodings().unwrap();
+        encodings.sort_by_key(|(_, enc)| enc.rank);
+        assert_eq!(encodings[0].0, "name");
+        assert_eq!(encodings[1].0, "address.city");
+    }
+
+    #[test]
+    fn test_fail_on_duplicate_mapping_rank() {
+        let err = _rs_mapping(1).validate().unwrap_err();
+        assert!(err.to_string().contains("Duplicate rank"));
+    }
+
+    #[test]
+    fn test_fail_on_mismatched_content_id() {
+        let mut rs_schema = _rs_schema();
+        rs_schema.content = RSContent(r#"{"@id": "did:sov:other", "@type": "sch"}"#.to_string());
+        assert!(rs_schema.validate().is_err());
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_rich_schema_from_content() {
+        let content = json!({"@type": "rdfs:Class", "name": "string"});
+        let rs_schema = RichSchema::from_content(
+            content.as_object().unwrap().clone(),
+            "test_rich_schema".to_string(),
+            "1.0".to_string(),
+            RSType::Sch,
+            "1".to_string(),
+        )
+        .unwrap();
+        rs_schema.validate().unwrap();
+        assert!(rs_schema.id.0.starts_with("did:sov:"));
+        assert_eq!(
+            RichSchemaId::from_content(&rs_schema.content.to_value().unwrap()).unwrap(),
+            rs_schema.id
+        );
+    }
+
     #[test]
     fn test_fail_on_wrong_rs_type() {
         let mut rs_schema = _rs_schema();
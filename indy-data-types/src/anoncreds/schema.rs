@@ -1,3 +1,4 @@
+use super::limits::{check_limit, CheckLimits, ValidationConfig};
 use crate::identifiers::schema::SchemaId;
 use crate::utils::Qualifiable;
 use crate::{Validatable, ValidationError};
@@ -43,6 +44,14 @@ impl Validatable for Schema {
     }
 }
 
+impl CheckLimits for Schema {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        match self {
+            Schema::SchemaV1(schema) => schema.attr_names.check_limits(limits),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
@@ -122,19 +131,20 @@ impl Validatable for AttributeNames {
         if self.0.is_empty() {
             return Err("Empty list of Schema attributes has been passed".into());
         }
-
-        if self.0.len() > MAX_ATTRIBUTES_COUNT {
-            return Err(format!(
-                "The number of Schema attributes {} cannot be greater than {}",
-                self.0.len(),
-                MAX_ATTRIBUTES_COUNT
-            )
-            .into());
-        }
         Ok(())
     }
 }
 
+impl CheckLimits for AttributeNames {
+    fn check_limits(&self, limits: &ValidationConfig) -> Result<(), ValidationError> {
+        check_limit(
+            "schema attributes",
+            self.0.len(),
+            limits.max_schema_attributes,
+        )
+    }
+}
+
 #[cfg(test)]
 mod test_schema_validation {
     use super::*;
@@ -161,6 +171,22 @@ mod test_schema_validation {
         assert_eq!(schema.version, "1.0");
     }
 
+    #[test]
+    fn test_schema_attributes_limit() {
+        let attr_names =
+            AttributeNames::from((0..126).map(|i| format!("attr{}", i)).collect::<Vec<_>>());
+        attr_names.validate().unwrap();
+        attr_names
+            .check_limits(&ValidationConfig::default())
+            .unwrap_err();
+        attr_names
+            .check_limits(&ValidationConfig {
+                max_schema_attributes: 200,
+                ..ValidationConfig::default()
+            })
+            .unwrap();
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_invalid_name_schema() {
@@ -0,0 +1,145 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::cred_def::CredentialDefinition;
+use super::pres_request::PresentationRequest;
+use super::rev_reg::{RevocationRegistry, RevocationRegistryDelta};
+use super::rev_reg_def::RevocationRegistryDefinition;
+use super::schema::Schema;
+use crate::ConversionError;
+
+/// The name of the version tag attached to enveloped objects
+pub const VERSION_TAG: &str = "ver";
+
+/// Fetch the version tag of a JSON object, if any
+pub fn detect_version(value: &Value) -> Option<&str> {
+    value.get(VERSION_TAG).and_then(Value::as_str)
+}
+
+/// Object types which are wrapped in a `ver`-tagged envelope
+pub trait Versioned: DeserializeOwned {
+    /// The envelope versions which may be loaded
+    const SUPPORTED_VERSIONS: &'static [&'static str];
+
+    /// The version assumed for objects without a version tag
+    const DEFAULT_VERSION: &'static str;
+
+    /// Upgrade an object loaded from an older envelope version
+    fn upgrade(self) -> Self {
+        self
+    }
+
+    /// Load an object from a JSON value of any supported envelope version,
+    /// converting it to the latest representation
+    fn migrate_to_latest(mut value: Value) -> Result<Self, ConversionError> {
+        let obj = value
+            .as_object_mut()
+            .ok_or_else(|| ConversionError::from_msg("Expected a JSON object"))?;
+        match obj.get(VERSION_TAG) {
+            None => {
+                obj.insert(VERSION_TAG.to_owned(), Value::from(Self::DEFAULT_VERSION));
+            }
+            Some(Value::String(ver)) if Self::SUPPORTED_VERSIONS.contains(&ver.as_str()) => (),
+            Some(ver) => {
+                return Err(ConversionError::from_msg(format!(
+                    "Unsupported object version: {}, expected one of {:?}",
+                    ver,
+                    Self::SUPPORTED_VERSIONS
+                )));
+            }
+        }
+        let result = serde_json::from_value::<Self>(value)
+            .map_err(|err| ConversionError::from_msg_err("Error loading object", err))?;
+        Ok(result.upgrade())
+    }
+}
+
+macro_rules! impl_versioned_v1 {
+    ($type:ty) => {
+        impl Versioned for $type {
+            const SUPPORTED_VERSIONS: &'static [&'static str] = &["1.0"];
+            const DEFAULT_VERSION: &'static str = "1.0";
+        }
+    };
+}
+
+impl_versioned_v1!(Schema);
+impl_versioned_v1!(CredentialDefinition);
+impl_versioned_v1!(RevocationRegistryDefinition);
+impl_versioned_v1!(RevocationRegistry);
+impl_versioned_v1!(RevocationRegistryDelta);
+
+/// Version 1.0 requests are not upgraded, as the version determines whether
+/// the resulting presentation uses qualified identifiers
+impl Versioned for PresentationRequest {
+    const SUPPORTED_VERSIONS: &'static [&'static str] = &["1.0", "2.0"];
+    const DEFAULT_VERSION: &'static str = "1.0";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+
+    fn _schema_json() -> Value {
+        json!({
+            "id": SCHEMA_ID,
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age"],
+            "seqNo": 14
+        })
+    }
+
+    #[test]
+    fn detect_version_works() {
+        assert_eq!(detect_version(&_schema_json()), None);
+        let mut schema = _schema_json();
+        schema["ver"] = json!("1.0");
+        assert_eq!(detect_version(&schema), Some("1.0"));
+    }
+
+    #[test]
+    fn migrate_to_latest_works_for_untagged() {
+        let schema = Schema::migrate_to_latest(_schema_json()).unwrap();
+        assert_eq!(schema.id().0, SCHEMA_ID);
+    }
+
+    #[test]
+    fn migrate_to_latest_works_for_tagged() {
+        let mut schema = _schema_json();
+        schema["ver"] = json!("1.0");
+        let schema = Schema::migrate_to_latest(schema).unwrap();
+        assert_eq!(schema.id().0, SCHEMA_ID);
+    }
+
+    #[test]
+    fn migrate_to_latest_fails_for_unknown_version() {
+        let mut schema = _schema_json();
+        schema["ver"] = json!("3.0");
+        assert!(Schema::migrate_to_latest(schema).is_err());
+        assert!(Schema::migrate_to_latest(json!([])).is_err());
+    }
+
+    #[test]
+    fn migrate_to_latest_works_for_presentation_request() {
+        let mut req = json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {},
+        });
+        let pres_req = PresentationRequest::migrate_to_latest(req.clone()).unwrap();
+        assert!(matches!(
+            pres_req,
+            PresentationRequest::PresentationRequestV1(_)
+        ));
+        req["ver"] = json!("2.0");
+        let pres_req = PresentationRequest::migrate_to_latest(req).unwrap();
+        assert!(matches!(
+            pres_req,
+            PresentationRequest::PresentationRequestV2(_)
+        ));
+    }
+}
@@ -1,10 +1,15 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
 use super::schema::SchemaId;
 use crate::utils::{qualifiable, Qualifiable};
 use crate::{Validatable, ValidationError};
 use indy_utils::did::DidValue;
 use indy_utils::qualifiable_type;
 
-use super::DELIMITER;
+use super::{is_uri_identifier, uri_issuer_id, DELIMITER};
 
 qualifiable_type!(CredentialDefinitionId, "A credential definition identifier");
 
@@ -41,7 +46,16 @@ impl CredentialDefinitionId {
         ))
     }
 
+    /// Check whether this is a ledger-agnostic AnonCreds URI identifier
+    pub fn is_uri(&self) -> bool {
+        is_uri_identifier(&self.0)
+    }
+
     pub fn parts(&self) -> Option<(Option<&str>, DidValue, String, SchemaId, String)> {
+        if self.is_uri() {
+            return None;
+        }
+
         let parts = self.0.split_terminator(DELIMITER).collect::<Vec<&str>>();
 
         if parts.len() == 4 {
@@ -139,8 +153,13 @@ impl CredentialDefinitionId {
         None
     }
 
+    /// Get the DID of the issuer, for legacy identifiers and DID URLs
     pub fn issuer_did(&self) -> Option<DidValue> {
-        self.parts().map(|(_, did, _, _, _)| did)
+        if self.is_uri() {
+            uri_issuer_id(&self.0).map(|did| DidValue(did.to_string()))
+        } else {
+            self.parts().map(|(_, did, _, _, _)| did)
+        }
     }
 }
 
@@ -177,6 +196,9 @@ impl Qualifiable for CredentialDefinitionId {
 
 impl Validatable for CredentialDefinitionId {
     fn validate(&self) -> Result<(), ValidationError> {
+        if self.is_uri() {
+            return Ok(());
+        }
         self.parts().ok_or(format!(
             "Credential Definition Id validation failed: {:?}, doesn't match pattern",
             self.0
@@ -400,6 +422,23 @@ mod tests {
             _cred_def_id_qualified().validate().unwrap();
         }
 
+        #[test]
+        fn test_validate_cred_def_id_as_uri() {
+            let id = CredentialDefinitionId(
+                "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/CLAIM_DEF/1/tag".to_string(),
+            );
+            id.validate().unwrap();
+            assert!(id.is_uri());
+            assert!(id.parts().is_none());
+            assert_eq!(
+                id.issuer_did(),
+                Some(DidValue(
+                    "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e".to_string()
+                ))
+            );
+            assert_eq!(id.to_unqualified(), id);
+        }
+
         #[test]
         fn test_validate_cred_def_id_as_fully_qualified_with_schema_as_seq_no() {
             _cred_def_id_qualified_with_schema_as_seq_no()
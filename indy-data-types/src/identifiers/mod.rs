@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 /// Credential definition identifiers
 pub mod cred_def;
 /// Revocation registry identifiers
@@ -11,3 +13,108 @@ pub mod rich_schema;
 
 /// The standard delimiter used in identifier strings
 pub const DELIMITER: &'static str = ":";
+
+fn has_whitespace(val: &str) -> bool {
+    val.contains(char::is_whitespace)
+}
+
+/// Match a URL with a scheme, authority and path, as in
+/// `https://example.com/schemas/gvt`
+fn is_url(id: &str) -> bool {
+    let scheme_end = match id.find(':') {
+        Some(pos) => pos,
+        None => return false,
+    };
+    let mut scheme = id[..scheme_end].chars();
+    let valid_scheme = matches!(scheme.next(), Some(c) if c.is_ascii_alphabetic())
+        && scheme.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-');
+    let rest = &id[scheme_end..];
+    valid_scheme
+        && rest.starts_with("://")
+        && !has_whitespace(rest)
+        && matches!(rest[3..].find('/'), Some(pos) if pos > 0)
+}
+
+/// Match a DID URL with a non-empty path, as in `did:method:id/path`,
+/// returning the DID
+fn did_url_did(id: &str) -> Option<&str> {
+    let path_start = id.find('/')?;
+    let (did, path) = (&id[..path_start], &id[path_start + 1..]);
+    let mut parts = did.splitn(3, ':');
+    let valid = parts.next() == Some("did")
+        && matches!(parts.next(), Some(method) if !method.is_empty()
+            && method.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()))
+        && matches!(parts.next(), Some(did_id) if !did_id.is_empty())
+        && !path.is_empty()
+        && !has_whitespace(id);
+    if valid {
+        Some(did)
+    } else {
+        None
+    }
+}
+
+/// Check whether an identifier uses the ledger-agnostic URI form defined by the
+/// AnonCreds specification, either a DID URL such as
+/// `did:indy:sovrin:F72i3Y3Q4i466efjYJYCHM/anoncreds/v0/SCHEMA/gvt/1.0`
+/// or a URL such as `https://example.com/schemas/gvt`
+pub fn is_uri_identifier(id: &str) -> bool {
+    did_url_did(id).is_some() || is_url(id)
+}
+
+/// Get the issuer of an object from a URI identifier, when the identifier is a
+/// DID URL
+pub fn uri_issuer_id(id: &str) -> Option<&str> {
+    did_url_did(id)
+}
+
+/// Get the object type and trailing path segments from a `did:indy` style
+/// AnonCreds object identifier
+pub(crate) fn uri_object_path(id: &str) -> Option<(&str, Vec<&str>)> {
+    let did = uri_issuer_id(id)?;
+    let mut segments = id[did.len() + 1..].split('/');
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some("anoncreds"), Some(_version), Some(object_type)) => {
+            Some((object_type, segments.collect()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_identifiers() {
+        let schema_id = "did:indy:sovrin:F72i3Y3Q4i466efjYJYCHM/anoncreds/v0/SCHEMA/gvt/1.0";
+        assert!(is_uri_identifier(schema_id));
+        assert_eq!(
+            uri_issuer_id(schema_id),
+            Some("did:indy:sovrin:F72i3Y3Q4i466efjYJYCHM")
+        );
+        assert_eq!(
+            uri_object_path(schema_id),
+            Some(("SCHEMA", vec!["gvt", "1.0"]))
+        );
+
+        assert!(is_uri_identifier("https://example.com/schemas/gvt"));
+        assert_eq!(uri_issuer_id("https://example.com/schemas/gvt"), None);
+
+        for legacy in &[
+            "1",
+            "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0",
+            "schema:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0",
+            "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag",
+            "did:sov:NcYxiDXkpYi6ov5FcYDi1e",
+            "https://example.com",
+            "https:///schemas",
+            "1https://example.com/schemas",
+            "did:indy:sovrin:F72i3Y3Q4i466efjYJYCHM/",
+            "did:Indy:sovrin/anoncreds",
+            "did:indy:sovrin:F72i3Y3Q4i466efjYJYCHM/anoncreds v0",
+        ] {
+            assert!(!is_uri_identifier(legacy), "{}", legacy);
+        }
+    }
+}
@@ -1,17 +1,32 @@
-use once_cell::sync::Lazy;
-
-use regex::Regex;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
 
 use super::cred_def::CredentialDefinitionId;
-use super::DELIMITER;
+use super::{is_uri_identifier, uri_issuer_id, DELIMITER};
 use crate::utils::{qualifiable, Qualifiable};
 use crate::{Validatable, ValidationError};
 use indy_utils::did::DidValue;
 use indy_utils::qualifiable_type;
 
-static QUALIFIED_REV_REG_ID: Lazy<Regex> = Lazy::new(|| {
-    Regex::new("(^revreg:(?P<method>[a-z0-9]+):)?(?P<did>.+):4:(?P<cred_def_id>.+):(?P<rev_reg_type>.+):(?P<tag>.+)$").unwrap()
-});
+/// Split the unqualified form of a revocation registry identifier,
+/// `did:4:cred_def_id:rev_reg_type:tag`, into its components. Where the marker
+/// occurs more than once, the last one leaving a valid remainder is used.
+fn parse_rev_reg_id(id: &str) -> Option<(&str, &str, &str, &str)> {
+    if id.contains('\n') {
+        return None;
+    }
+    (1..id.len()).rev().find_map(|pos| {
+        if !id.as_bytes()[pos..].starts_with(b":4:") {
+            return None;
+        }
+        let mut rest = id[pos + 3..].rsplitn(3, DELIMITER);
+        let tag = rest.next().filter(|tag| !tag.is_empty())?;
+        let rev_reg_type = rest.next().filter(|rtype| !rtype.is_empty())?;
+        let cred_def_id = rest.next().filter(|cdef| !cdef.is_empty())?;
+        Some((&id[..pos], cred_def_id, rev_reg_type, tag))
+    })
+}
 
 qualifiable_type!(RevocationRegistryId, "A revocation registry identifier");
 
@@ -44,16 +59,38 @@ impl RevocationRegistryId {
         ))
     }
 
+    /// Check whether this is a ledger-agnostic AnonCreds URI identifier
+    pub fn is_uri(&self) -> bool {
+        is_uri_identifier(&self.0)
+    }
+
+    /// Get the DID of the issuer, for legacy identifiers and DID URLs
+    pub fn issuer_did(&self) -> Option<DidValue> {
+        if self.is_uri() {
+            uri_issuer_id(&self.0).map(|did| DidValue(did.to_string()))
+        } else {
+            self.parts().map(|(did, _, _, _)| did)
+        }
+    }
+
     pub fn parts(&self) -> Option<(DidValue, CredentialDefinitionId, String, String)> {
-        match QUALIFIED_REV_REG_ID.captures(&self.0) {
-            Some(caps) => Some((
-                DidValue(caps["did"].to_string()),
-                CredentialDefinitionId(caps["cred_def_id"].to_string()),
-                caps["rev_reg_type"].to_string(),
-                caps["tag"].to_string(),
-            )),
-            None => None,
+        if self.is_uri() {
+            return None;
         }
+        let parsed = match qualifiable::split(Self::PREFIX, &self.0) {
+            (Some(_method), rest) => parse_rev_reg_id(rest),
+            (None, _) => None,
+        };
+        parsed
+            .or_else(|| parse_rev_reg_id(&self.0))
+            .map(|(did, cred_def_id, rev_reg_type, tag)| {
+                (
+                    DidValue(did.to_string()),
+                    CredentialDefinitionId(cred_def_id.to_string()),
+                    rev_reg_type.to_string(),
+                    tag.to_string(),
+                )
+            })
     }
 }
 
@@ -90,6 +127,9 @@ impl Qualifiable for RevocationRegistryId {
 
 impl Validatable for RevocationRegistryId {
     fn validate(&self) -> Result<(), ValidationError> {
+        if self.is_uri() {
+            return Ok(());
+        }
         self.parts().ok_or(format!(
             "Revocation Registry Id validation failed: {:?}, doesn't match pattern",
             self.0
@@ -176,6 +216,24 @@ mod tests {
             assert_eq!(_rev_reg_type(), rev_reg_type);
             assert_eq!(_tag(), tag);
         }
+
+        #[test]
+        fn test_rev_reg_id_parts_for_repeated_marker() {
+            let id = RevocationRegistryId("did:4:4:CL_ACCUM:tag".to_string());
+            let (did, cred_def_id, rev_reg_type, tag) = id.parts().unwrap();
+            assert_eq!(did, DidValue("did".to_string()));
+            assert_eq!(cred_def_id, CredentialDefinitionId("4".to_string()));
+            assert_eq!(rev_reg_type, "CL_ACCUM");
+            assert_eq!(tag, "tag");
+
+            for invalid in &[
+                "did:4:cdef:CL_ACCUM",
+                ":4:cdef:CL_ACCUM:tag",
+                "did:4:cdef::tag",
+            ] {
+                assert!(RevocationRegistryId(invalid.to_string()).parts().is_none());
+            }
+        }
     }
 
     mod validate {
@@ -190,6 +248,23 @@ mod tests {
         fn test_validate_rev_reg_id_as_fully_qualified() {
             _rev_reg_id_qualified().validate().unwrap();
         }
+
+        #[test]
+        fn test_validate_rev_reg_id_as_uri() {
+            let id = RevocationRegistryId(
+                "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/REV_REG_DEF/1/tag/TAG_1"
+                    .to_string(),
+            );
+            id.validate().unwrap();
+            assert!(id.parts().is_none());
+            assert_eq!(
+                id.issuer_did(),
+                Some(DidValue(
+                    "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e".to_string()
+                ))
+            );
+            assert_eq!(_rev_reg_id_unqualified().issuer_did(), Some(_did()));
+        }
     }
 
     mod to_qualified {
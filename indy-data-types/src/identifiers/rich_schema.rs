@@ -1,5 +1,5 @@
 use crate::utils::Qualifiable;
-use crate::{Validatable, ValidationError};
+use crate::{invalid, Validatable, ValidationError};
 use indy_utils::qualifiable_type;
 
 qualifiable_type!(RichSchemaId, "A rich schema identifier");
@@ -7,15 +7,38 @@ qualifiable_type!(RichSchemaId, "A rich schema identifier");
 impl RichSchemaId {
     pub const PREFIX: &'static str = "rich_schema";
     pub fn new(did_string: String) -> RichSchemaId {
-        // ToDo: add RichSchema specific id forming if needed
         return RichSchemaId(did_string);
     }
+
+    /// Derive the identifier of a rich schema object from the SHA-256 hash of its
+    /// JSON-LD content, excluding any `@id` property
+    #[cfg(feature = "hash")]
+    pub fn from_content(
+        content: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<RichSchemaId, ValidationError> {
+        use crate::utils::{base58, hash::SHA256};
+
+        let mut content = content.clone();
+        content.remove("@id");
+        // object keys are serialized in sorted order
+        let bytes = serde_json::to_vec(&content).map_err(|err| invalid!("{}", err))?;
+        Ok(RichSchemaId(format!(
+            "did:sov:{}",
+            base58::encode(SHA256::digest(bytes))
+        )))
+    }
 }
 
 impl Validatable for RichSchemaId {
     fn validate(&self) -> Result<(), ValidationError> {
-        // ToDO: add RichSchema ID specific validation
-        return Ok(());
+        let parts: Vec<&str> = self.0.splitn(3, ':').collect();
+        if parts.len() != 3 || parts[0] != "did" || parts[1].is_empty() || parts[2].is_empty() {
+            return Err(invalid!(
+                "Rich schema ID `{}` must be a fully-qualified DID",
+                self.0
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -42,8 +65,8 @@ mod tests {
         assert_eq!(_rs_id_qualified().validate().unwrap(), ())
     }
 
-    // #[test]
-    // fn _validate_unqualified_rs_id() {
-    //     assert_eq!(_rs_id_unqualified().validate().unwrap(), false)
-    // }
+    #[test]
+    fn _validate_unqualified_rs_id() {
+        assert!(_rs_id_unqualified().validate().is_err())
+    }
 }
@@ -1,9 +1,14 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
 use crate::utils::{qualifiable, Qualifiable};
 use crate::{Validatable, ValidationError};
 use indy_utils::did::DidValue;
 use indy_utils::qualifiable_type;
 
-use super::DELIMITER;
+use super::{is_uri_identifier, uri_issuer_id, uri_object_path, DELIMITER};
 
 qualifiable_type!(SchemaId, "A V1 schema identifier");
 
@@ -29,7 +34,51 @@ impl SchemaId {
         ))
     }
 
+    /// Create an identifier referencing a schema by its ledger sequence number,
+    /// as embedded in legacy credential definition identifiers
+    pub fn from_seq_no(seq_no: u32) -> SchemaId {
+        Self(seq_no.to_string())
+    }
+
+    /// Get the ledger sequence number when the schema is referenced by one
+    pub fn seq_no(&self) -> Option<u32> {
+        self.0.parse().ok()
+    }
+
+    /// Check whether this is a ledger-agnostic AnonCreds URI identifier
+    pub fn is_uri(&self) -> bool {
+        is_uri_identifier(&self.0)
+    }
+
+    /// Get the DID of the schema issuer, for legacy identifiers and DID URLs
+    pub fn issuer_did(&self) -> Option<DidValue> {
+        if self.is_uri() {
+            uri_issuer_id(&self.0).map(|did| DidValue(did.to_string()))
+        } else {
+            self.parts().map(|(_, did, _, _)| did)
+        }
+    }
+
+    /// Get the schema name and version, for legacy identifiers and `did:indy`
+    /// style URI identifiers
+    pub fn name_version(&self) -> Option<(String, String)> {
+        if self.is_uri() {
+            match uri_object_path(&self.0) {
+                Some(("SCHEMA", path)) if path.len() == 2 => {
+                    Some((path[0].to_string(), path[1].to_string()))
+                }
+                _ => None,
+            }
+        } else {
+            self.parts().map(|(_, _, name, version)| (name, version))
+        }
+    }
+
     pub fn parts(&self) -> Option<(Option<&str>, DidValue, String, String)> {
+        if self.is_uri() {
+            return None;
+        }
+
         let parts = self.0.split_terminator(DELIMITER).collect::<Vec<&str>>();
 
         if parts.len() == 1 {
@@ -90,7 +139,7 @@ impl Qualifiable for SchemaId {
 
 impl Validatable for SchemaId {
     fn validate(&self) -> Result<(), ValidationError> {
-        if self.0.parse::<i32>().is_ok() {
+        if self.0.parse::<i32>().is_ok() || self.is_uri() {
             return Ok(());
         }
 
@@ -127,6 +176,10 @@ mod tests {
         SchemaId("schema:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0".to_string())
     }
 
+    fn _schema_id_uri() -> SchemaId {
+        SchemaId("did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/SCHEMA/gvt/1.0".to_string())
+    }
+
     fn _schema_id_invalid() -> SchemaId {
         SchemaId("NcYxiDXkpYi6ov5FcYDi1e:2".to_string())
     }
@@ -159,6 +212,47 @@ mod tests {
         fn test_schema_id_parts_for_invalid_unqualified() {
             assert_eq!(_schema_id_invalid(), _schema_id_invalid().to_unqualified());
         }
+
+        #[test]
+        fn test_schema_id_unqualify_for_id_as_uri() {
+            assert_eq!(_schema_id_uri(), _schema_id_uri().to_unqualified());
+        }
+    }
+
+    mod uri {
+        use super::*;
+
+        #[test]
+        fn test_schema_id_uri() {
+            let id = _schema_id_uri();
+            assert!(id.is_uri());
+            assert!(id.parts().is_none());
+            assert_eq!(
+                id.issuer_did(),
+                Some(DidValue(
+                    "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e".to_string()
+                ))
+            );
+            assert_eq!(
+                id.name_version(),
+                Some(("gvt".to_string(), "1.0".to_string()))
+            );
+            assert_eq!(id.to_qualified("sov").unwrap(), id);
+            assert!(!_schema_id_unqualified().is_uri());
+            assert_eq!(_schema_id_unqualified().issuer_did(), Some(_did()));
+        }
+    }
+
+    mod seq_no {
+        use super::*;
+
+        #[test]
+        fn test_schema_id_seq_no() {
+            assert_eq!(_schema_id_seq_no().seq_no(), Some(1));
+            assert_eq!(SchemaId::from_seq_no(1), _schema_id_seq_no());
+            assert_eq!(_schema_id_unqualified().seq_no(), None);
+            assert_eq!(_schema_id_qualified().seq_no(), None);
+        }
     }
 
     mod parts {
@@ -205,6 +299,14 @@ mod tests {
             _schema_id_qualified().validate().unwrap();
         }
 
+        #[test]
+        fn test_validate_schema_id_as_uri() {
+            _schema_id_uri().validate().unwrap();
+            SchemaId("https://example.com/schemas/gvt".to_string())
+                .validate()
+                .unwrap();
+        }
+
         #[test]
         fn test_validate_schema_id_for_invalid_unqualified() {
             _schema_id_invalid().validate().unwrap_err();
@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 #[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde;
@@ -17,6 +21,7 @@ mod utils {
 }
 
 pub use indy_utils::did;
+#[cfg(feature = "std")]
 pub use indy_utils::keys;
 pub use indy_utils::{invalid, ConversionError, Validatable, ValidationError};
 
@@ -24,6 +29,7 @@ pub use indy_utils::{invalid, ConversionError, Validatable, ValidationError};
 pub use indy_utils::ursa;
 
 /// Type definitions related Indy credential issuance and verification
+#[cfg(feature = "std")]
 pub mod anoncreds;
 
 #[cfg(feature = "merkle_tree")]
@@ -1,13 +1,24 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use bs58;
 
 use super::error::ConversionError;
 
+#[cfg(feature = "std")]
 pub fn decode<T: AsRef<[u8]>>(val: T) -> Result<Vec<u8>, ConversionError> {
     Ok(bs58::decode(val)
         .into_vec()
         .map_err(|err| ("Error decoding base58 data", err))?)
 }
 
+#[cfg(not(feature = "std"))]
+pub fn decode<T: AsRef<[u8]>>(val: T) -> Result<Vec<u8>, ConversionError> {
+    bs58::decode(val)
+        .into_vec()
+        .map_err(|_| ConversionError::from("Error decoding base58 data"))
+}
+
 pub fn encode<T: AsRef<[u8]>>(val: T) -> String {
     bs58::encode(val).into_string()
 }
@@ -1,13 +1,16 @@
-use once_cell::sync::Lazy;
+use alloc::string::String;
 
-use regex::Regex;
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
 
 use crate::base58;
 #[cfg(feature = "ed25519")]
 use crate::keys::{KeyType, PrivateKey, VerKey};
+use crate::qualifiable::is_method_name;
 use crate::{Qualifiable, Validatable, ValidationError};
 
 /// The default identifier DID used when submitting ledger read requests
+#[cfg(feature = "std")]
 pub static DEFAULT_LIBINDY_DID: Lazy<DidValue> =
     Lazy::new(|| DidValue::new("LibindyDid111111111111", None));
 
@@ -32,9 +35,7 @@ pub struct DidMethod(pub String);
 
 impl Validatable for DidMethod {
     fn validate(&self) -> Result<(), ValidationError> {
-        static REGEX_METHOD_NAME: Lazy<Regex> = Lazy::new(|| Regex::new("^[a-z0-9]+$").unwrap());
-
-        if !REGEX_METHOD_NAME.is_match(&self.0) {
+        if !is_method_name(&self.0) {
             return Err(invalid!(
                 "Invalid default name: {}. It does not match the DID method name format.",
                 self.0
@@ -98,7 +99,7 @@ impl From<String> for ShortDidValue {
     }
 }
 
-impl std::ops::Deref for ShortDidValue {
+impl core::ops::Deref for ShortDidValue {
     type Target = str;
     fn deref(&self) -> &str {
         &self.0
@@ -1,11 +1,22 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+#[cfg(feature = "std")]
 use std::error::Error as StdError;
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 type DynError = Box<dyn StdError + Send + Sync + 'static>;
+#[cfg(not(feature = "std"))]
+type DynError = Box<dyn fmt::Debug + Send + Sync + 'static>;
 
 macro_rules! define_error {
     ($name:tt, $short:expr, $doc:tt) => {
-        #[derive(Debug, Error)]
+        #[derive(Debug)]
+        #[cfg_attr(feature = "std", derive(Error))]
         #[doc=$doc]
         pub struct $name {
             pub context: Option<String>,
@@ -17,6 +28,7 @@ macro_rules! define_error {
                 Self::from(msg.into())
             }
 
+            #[cfg(feature = "std")]
             pub fn from_err<E>(err: E) -> Self
             where E: StdError + Send + Sync + 'static {
                 Self {
@@ -25,6 +37,7 @@ macro_rules! define_error {
                 }
             }
 
+            #[cfg(feature = "std")]
             pub fn from_msg_err<M, E>(msg: M, err: E) -> Self
             where M: Into<String>, E: StdError + Send + Sync + 'static
              {
@@ -53,6 +66,7 @@ macro_rules! define_error {
             }
         }
 
+        #[cfg(feature = "std")]
         impl<M, E> From<(M, E)> for $name
         where M: Into<String>,
         E: StdError + Send + Sync + 'static {
@@ -68,8 +82,8 @@ macro_rules! define_error {
             }
         }
 
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(f, $short)?;
                 match self.context {
                     Some(ref context) =>
@@ -112,14 +126,14 @@ impl From<serde_json::error::Error> for ConversionError {
     }
 }
 
-impl From<std::str::Utf8Error> for ConversionError {
-    fn from(_err: std::str::Utf8Error) -> Self {
+impl From<core::str::Utf8Error> for ConversionError {
+    fn from(_err: core::str::Utf8Error) -> Self {
         Self::from("UTF-8 decoding error")
     }
 }
 
-impl From<std::string::FromUtf8Error> for ConversionError {
-    fn from(_err: std::string::FromUtf8Error) -> Self {
+impl From<alloc::string::FromUtf8Error> for ConversionError {
+    fn from(_err: alloc::string::FromUtf8Error) -> Self {
         Self::from("UTF-8 decoding error")
     }
 }
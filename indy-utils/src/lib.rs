@@ -1,12 +1,19 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[doc(hidden)]
+pub extern crate alloc;
+
+#[cfg(feature = "std")]
 pub extern crate aead;
 
+#[cfg(feature = "std")]
 pub extern crate hex;
 
 #[cfg(any(feature = "serde_support", test))]
 #[macro_use]
 pub extern crate serde;
 
-#[macro_use]
+#[cfg_attr(feature = "std", macro_use)]
 pub extern crate zeroize;
 
 /// Common macros
@@ -33,9 +40,11 @@ pub mod base58;
 pub mod did;
 
 /// Indy signing keys and verification keys
+#[cfg(feature = "std")]
 pub mod keys;
 
 /// Random number generation
+#[cfg(feature = "std")]
 pub mod random;
 
 /// Base64 encoding and decoding
@@ -69,4 +78,5 @@ pub mod wql;
 ))]
 pub extern crate ursa;
 
+#[cfg(feature = "std")]
 pub extern crate once_cell;
@@ -1,3 +1,4 @@
+#[cfg(feature = "std")]
 macro_rules! unwrap_opt_or_return {
     ($opt:expr, $err:expr) => {
         match $opt {
@@ -171,23 +171,79 @@ pub async fn unpack_jwe<'f>(
     jwe_struct: &JWE,
     lookup: impl KeyLookup<'f>,
 ) -> Result<(Vec<u8>, EncodedVerKey, Option<EncodedVerKey>), ConversionError> {
-    // decode protected data
-    let protected_decoded = base64::decode_urlsafe(&jwe_struct.protected)?;
-    let protected: Protected = serde_json::from_slice(&protected_decoded)?;
+    let protected = decode_protected(jwe_struct)?;
 
     // extract recipient that matches a key in the wallet
     let (recipient, recip_pk, recip_sk) = unwrap_opt_or_return!(
         find_unpack_recipient(protected, lookup).await?,
         Err("No matching recipient found".into())
     );
+
+    let (message, sender_verkey_option) = unpack_payload(jwe_struct, &recipient, &recip_sk)?;
+    Ok((message, recip_pk, sender_verkey_option))
+}
+
+/// Unpack a message using a list of candidate recipient keys, without requiring
+/// an async executor
+pub fn unpack_message_with_keys(
+    message: impl AsRef<[u8]>,
+    recip_keys: &[PrivateKey],
+) -> Result<(Vec<u8>, EncodedVerKey, Option<EncodedVerKey>), ConversionError> {
+    let jwe_struct: JWE = serde_json::from_slice(message.as_ref())?;
+    let protected = decode_protected(&jwe_struct)?;
+
+    let mut recip_vks = Vec::with_capacity(recip_keys.len());
+    for recip_sk in recip_keys {
+        recip_vks.push(recip_sk.public_key()?.as_base58()?);
+    }
+    for recipient in protected.recipients {
+        let vk = EncodedVerKey::from_str(&recipient.header.kid)?;
+        if let Some(idx) = recip_vks.iter().position(|recip_vk| recip_vk == &vk) {
+            let (message, sender_verkey_option) =
+                unpack_payload(&jwe_struct, &recipient, &recip_keys[idx])?;
+            return Ok((message, vk, sender_verkey_option));
+        }
+    }
+    Err("No matching recipient found".into())
+}
+
+fn decode_protected(jwe_struct: &JWE) -> Result<Protected, ConversionError> {
+    let protected_decoded = base64::decode_urlsafe(&jwe_struct.protected)?;
+    let protected: Protected = serde_json::from_slice(&protected_decoded)?;
+    if protected.enc != PROTECTED_HEADER_ENC {
+        return Err(format!("Unsupported message encryption: {}", protected.enc).into());
+    }
+    let is_authcrypt = match protected.alg.as_str() {
+        PROTECTED_HEADER_ALG_AUTH => true,
+        PROTECTED_HEADER_ALG_ANON => false,
+        alg => return Err(format!("Unsupported message algorithm: {}", alg).into()),
+    };
+    for recipient in &protected.recipients {
+        let has_sender = recipient.header.sender.is_some() && recipient.header.iv.is_some();
+        if has_sender != is_authcrypt {
+            return Err(format!(
+                "Recipient header is inconsistent with message algorithm: {}",
+                protected.alg
+            )
+            .into());
+        }
+    }
+    Ok(protected)
+}
+
+fn unpack_payload(
+    jwe_struct: &JWE,
+    recipient: &Recipient,
+    recip_sk: &PrivateKey,
+) -> Result<(Vec<u8>, Option<EncodedVerKey>), ConversionError> {
     let is_auth_recipient = recipient.header.sender.is_some() && recipient.header.iv.is_some();
 
     // get cek and sender data
     let (sender_verkey_option, cek) = if is_auth_recipient {
-        let (send, cek) = unpack_cek_authcrypt(&recipient, &recip_sk)?;
+        let (send, cek) = unpack_cek_authcrypt(recipient, recip_sk)?;
         (Some(send), cek)
     } else {
-        let cek = unpack_cek_anoncrypt(&recipient, &recip_sk)?;
+        let cek = unpack_cek_anoncrypt(recipient, recip_sk)?;
         (None, cek)
     };
 
@@ -208,7 +264,7 @@ pub async fn unpack_jwe<'f>(
         .decrypt(nonce.as_slice().into(), payload)
         .map_err(|_| "Error decrypting message payload")?;
 
-    Ok((message, recip_pk, sender_verkey_option))
+    Ok((message, sender_verkey_option))
 }
 
 fn unpack_cek_authcrypt(
@@ -358,4 +414,42 @@ mod tests {
         assert_eq!(p_recip, pk2);
         assert_eq!(p_send, Some(pk1));
     }
+
+    #[test]
+    fn test_pack_round_trip_with_keys() {
+        let sk1 = PrivateKey::from_seed(b"000000000000000000000000000Test3").unwrap();
+        let pk1 = sk1.public_key().unwrap().as_base58().unwrap();
+        let sk2 = PrivateKey::from_seed(b"000000000000000000000000000Test4").unwrap();
+        let pk2 = sk2.public_key().unwrap().as_base58().unwrap();
+        let other = PrivateKey::from_seed(b"000000000000000000000000000Test5").unwrap();
+
+        let input_msg = b"hello there";
+        let packed = pack_message(&input_msg, vec![pk2.clone()], Some(sk1.clone())).unwrap();
+        let (msg, p_recip, p_send) =
+            unpack_message_with_keys(&packed, &[other.clone(), sk2.clone()]).unwrap();
+        assert_eq!(msg, input_msg);
+        assert_eq!(p_recip, pk2);
+        assert_eq!(p_send, Some(pk1));
+
+        let packed = pack_message(&input_msg, vec![pk2.clone()], None).unwrap();
+        let (msg, _, p_send) = unpack_message_with_keys(&packed, &[sk2]).unwrap();
+        assert_eq!(msg, input_msg);
+        assert_eq!(p_send, None);
+        assert!(unpack_message_with_keys(&packed, &[other]).is_err());
+    }
+
+    #[test]
+    fn test_unpack_rejects_mismatched_alg() {
+        let sk1 = PrivateKey::from_seed(b"000000000000000000000000000Test3").unwrap();
+        let pk1 = sk1.public_key().unwrap().as_base58().unwrap();
+
+        let packed = pack_message(b"hello there", vec![pk1], None).unwrap();
+        let mut jwe: JWE = serde_json::from_slice(&packed).unwrap();
+        let mut protected: Protected =
+            serde_json::from_slice(&base64::decode_urlsafe(&jwe.protected).unwrap()).unwrap();
+        protected.alg = PROTECTED_HEADER_ALG_AUTH.to_string();
+        jwe.protected = base64::encode_urlsafe(serde_json::to_vec(&protected).unwrap());
+        let packed = serde_json::to_vec(&jwe).unwrap();
+        assert!(unpack_message_with_keys(&packed, &[sk1]).is_err());
+    }
 }
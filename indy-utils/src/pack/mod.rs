@@ -2,5 +2,5 @@ mod alg;
 mod nacl_box;
 mod types;
 
-pub use alg::{pack_message, unpack_message};
+pub use alg::{pack_message, unpack_message, unpack_message_with_keys};
 pub use types::{key_lookup_fn, KeyLookup};
@@ -1,11 +1,30 @@
-use once_cell::sync::Lazy;
-
-use regex::Regex;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
 
 use super::{invalid, Validatable, ValidationError};
 
-pub(crate) static REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new("^([a-z0-9]+):([a-z0-9]+):(.*)$").unwrap());
+/// Check for a valid prefix or method name, matching `[a-z0-9]+`
+pub(crate) fn is_method_name(val: &str) -> bool {
+    !val.is_empty()
+        && val
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+}
+
+/// Parse a qualified identifier of the form `prefix:method:value` into its
+/// components, where the value may not contain a line break
+fn parse_qualified(val: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = val.splitn(3, ':');
+    let prefix = parts.next()?;
+    let method = parts.next()?;
+    let rest = parts.next()?;
+    if is_method_name(prefix) && is_method_name(method) && !rest.contains('\n') {
+        Some((prefix, method, rest))
+    } else {
+        None
+    }
+}
 
 /// Combine a prefix, method, and value into a qualified identifier
 pub fn combine(prefix: &str, method: Option<&str>, entity: &str) -> String {
@@ -17,28 +36,19 @@ pub fn combine(prefix: &str, method: Option<&str>, entity: &str) -> String {
 
 /// Split a qualifiable identifier into its method and value components
 pub fn split<'a>(prefix: &str, val: &'a str) -> (Option<&'a str>, &'a str) {
-    match REGEX.captures(&val) {
-        None => (None, val),
-        Some(caps) => {
-            if caps.get(1).map(|m| m.as_str()) == Some(prefix) {
-                (
-                    Some(caps.get(2).unwrap().as_str()),
-                    caps.get(3).unwrap().as_str(),
-                )
-            } else {
-                (None, val)
-            }
-        }
+    match parse_qualified(val) {
+        Some((val_prefix, method, rest)) if val_prefix == prefix => (Some(method), rest),
+        _ => (None, val),
     }
 }
 
 /// Check if an identifier is qualified by a prefix and method
 pub fn is_fully_qualified(entity: &str) -> bool {
-    REGEX.captures(entity).is_some()
+    parse_qualified(entity).is_some()
 }
 
 /// An identifier which can be qualified with a prefix and method
-pub trait Qualifiable: From<String> + std::ops::Deref<Target = str> + Validatable {
+pub trait Qualifiable: From<String> + core::ops::Deref<Target = str> + Validatable {
     fn prefix() -> &'static str;
 
     fn combine(method: Option<&str>, entity: &str) -> Self {
@@ -111,24 +121,24 @@ macro_rules! qualifiable_type {
         $crate::serde_derive_impl! {
             #[doc=$doc]
             #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-            pub struct $newtype(pub String);
+            pub struct $newtype(pub $crate::alloc::string::String);
         }
 
-        impl From<String> for $newtype {
-            fn from(val: String) -> Self {
+        impl From<$crate::alloc::string::String> for $newtype {
+            fn from(val: $crate::alloc::string::String) -> Self {
                 Self(val)
             }
         }
 
-        impl std::ops::Deref for $newtype {
+        impl ::core::ops::Deref for $newtype {
             type Target = str;
             fn deref(&self) -> &str {
                 &self.0
             }
         }
 
-        impl std::fmt::Display for $newtype {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl ::core::fmt::Display for $newtype {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 f.write_str(self.0.as_str())
             }
         }
@@ -137,3 +147,31 @@ macro_rules! qualifiable_type {
         qualifiable_type!($newtype, "");
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_qualified() {
+        assert_eq!(
+            split("did", "did:sov:NcYxiDXkpYi6ov5FcYDi1e"),
+            (Some("sov"), "NcYxiDXkpYi6ov5FcYDi1e")
+        );
+        assert_eq!(split("did", "did:sov:"), (Some("sov"), ""));
+        assert_eq!(
+            split("schema", "did:sov:NcYxiDXkpYi6ov5FcYDi1e"),
+            (None, "did:sov:NcYxiDXkpYi6ov5FcYDi1e")
+        );
+        for unqualified in &[
+            "NcYxiDXkpYi6ov5FcYDi1e",
+            "did:Sov:abc",
+            "did::abc",
+            "did:sov",
+            "did:sov:a\nb",
+        ] {
+            assert_eq!(split("did", unqualified), (None, *unqualified));
+            assert!(!is_fully_qualified(unqualified));
+        }
+    }
+}
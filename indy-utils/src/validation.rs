@@ -5,7 +5,7 @@ use crate::error::ValidationError;
 macro_rules! invalid {
     () => { $crate::ValidationError::from(None) };
     ($($arg:tt)+) => {
-        $crate::ValidationError::from(format!($($arg)+))
+        $crate::ValidationError::from($crate::alloc::format!($($arg)+))
     };
 }
 
@@ -1,7 +1,12 @@
 """Indy-Credx Python wrapper library"""
 
-from .bindings import encode_credential_attributes, generate_nonce, library_version
-from .error import CredxError, CredxErrorCode
+from .bindings import (
+    encode_binary_attribute,
+    encode_credential_attributes,
+    generate_nonce,
+    library_version,
+)
+from .error import CredxError, CredxErrorCategory, CredxErrorCode
 from .types import (
     Credential,
     CredentialDefinition,
@@ -21,13 +26,16 @@ from .types import (
     RevocationRegistryDefinition,
     RevocationRegistryDefinitionPrivate,
     RevocationRegistryDelta,
+    RevocationRegistryUsage,
 )
 
 __all__ = (
+    "encode_binary_attribute",
     "encode_credential_attributes",
     "generate_nonce",
     "library_version",
     "CredxError",
+    "CredxErrorCategory",
     "CredxErrorCode",
     "Credential",
     "CredentialDefinition",
@@ -46,5 +54,6 @@ __all__ = (
     "RevocationRegistryDefinition",
     "RevocationRegistryDefinitionPrivate",
     "RevocationRegistryDelta",
+    "RevocationRegistryUsage",
     "Schema",
 )
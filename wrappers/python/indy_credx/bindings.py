@@ -7,6 +7,7 @@ import sys
 from ctypes import (
     Array,
     CDLL,
+    CFUNCTYPE,
     POINTER,
     Structure,
     byref,
@@ -20,12 +21,13 @@ from ctypes import (
 )
 from ctypes.util import find_library
 from io import BytesIO
-from typing import Optional, Mapping, Sequence, Union
+from typing import Callable, Optional, Mapping, Sequence, Union
 
-from .error import CredxError, CredxErrorCode
+from .error import CredxError, CredxErrorCategory, CredxErrorCode
 
 
 CALLBACKS = {}
+TAILS_PROGRESS_CB = CFUNCTYPE(c_size_t, c_int64, c_int64, c_int64)
 LIB: CDLL = None
 LOGGER = logging.getLogger(__name__)
 
@@ -77,6 +79,22 @@ class IndyObject:
     def to_json_buffer(self) -> memoryview:
         return memoryview(object_get_json(self.handle).raw)
 
+    def to_cbor(self) -> bytes:
+        """Encode the object as CBOR (requires the `cbor` library feature)."""
+        return bytes(object_get_cbor(self.handle))
+
+    def validate(self) -> dict:
+        return json.loads(str(object_validate(self.handle)))
+
+    @property
+    def tag(self) -> Optional[str]:
+        tag = object_get_tag(self.handle)
+        return None if tag is None else str(tag)
+
+    @tag.setter
+    def tag(self, value: Optional[str]):
+        object_set_tag(self.handle, value)
+
 
 class ByteBuffer(Structure):
     """A byte buffer allocated by the library."""
@@ -285,6 +303,36 @@ class RevocationConfig(Structure):
         )
 
 
+class RevocationAllocationConfig(Structure):
+    _fields_ = [
+        ("rev_reg_def", ObjectHandle),
+        ("rev_reg_def_private", ObjectHandle),
+        ("rev_reg", ObjectHandle),
+        ("allocation", c_char_p),
+        ("rev_reg_index", c_int64),
+        ("tails_path", c_char_p),
+    ]
+
+    @classmethod
+    def create(
+        cls,
+        rev_reg_def: ObjectHandle,
+        rev_reg_def_private: ObjectHandle,
+        rev_reg: ObjectHandle,
+        allocation: str,
+        rev_reg_index: Optional[int],
+        tails_path: str,
+    ) -> "RevocationAllocationConfig":
+        return RevocationAllocationConfig(
+            rev_reg_def=rev_reg_def,
+            rev_reg_def_private=rev_reg_def_private,
+            rev_reg=rev_reg,
+            allocation=encode_str(allocation),
+            rev_reg_index=rev_reg_index or 0,
+            tails_path=encode_str(tails_path),
+        )
+
+
 class RevocationEntry(Structure):
     _fields_ = [
         ("def_entry_idx", c_int64),
@@ -313,6 +361,34 @@ class RevocationEntryList(Structure):
     ]
 
 
+class NonRevokedIntervalOverride(Structure):
+    _fields_ = [
+        ("rev_reg_def_idx", c_int64),
+        ("requested_from_ts", c_int64),
+        ("override_ts", c_int64),
+    ]
+
+    @classmethod
+    def create(
+        cls,
+        rev_reg_def_idx: int,
+        requested_from_ts: int,
+        override_ts: int,
+    ) -> "NonRevokedIntervalOverride":
+        return NonRevokedIntervalOverride(
+            rev_reg_def_idx=rev_reg_def_idx,
+            requested_from_ts=requested_from_ts,
+            override_ts=override_ts,
+        )
+
+
+class NonRevokedIntervalOverrideList(Structure):
+    _fields_ = [
+        ("count", c_int64),
+        ("data", POINTER(NonRevokedIntervalOverride)),
+    ]
+
+
 def get_library() -> CDLL:
     """Return the CDLL instance, loading it if necessary."""
     global LIB
@@ -385,14 +461,26 @@ def get_current_error(expect: bool = False) -> Optional[CredxError]:
             LOGGER.warning("JSON decode error for credx_get_current_error")
             msg = None
         if msg and "message" in msg and "code" in msg:
+            category = msg.get("category")
             return CredxError(
-                CredxErrorCode(msg["code"]), msg["message"], msg.get("extra")
+                CredxErrorCode(msg["code"]),
+                msg["message"],
+                msg.get("extra"),
+                category=CredxErrorCategory(category) if category else None,
+                causes=msg.get("causes"),
             )
         if not expect:
             return None
     return CredxError(CredxErrorCode.WRAPPER, "Unknown error")
 
 
+def get_error_codes() -> Sequence[dict]:
+    """List the library error codes along with their names and categories."""
+    result = StrBuffer()
+    do_call("credx_get_error_codes", byref(result))
+    return json.loads(str(result))
+
+
 def decode_str(value: c_char_p) -> str:
     return value.decode("utf-8")
 
@@ -438,22 +526,152 @@ def encode_bytes(arg: Optional[Union[str, bytes]]) -> FfiByteBuffer:
     return buf
 
 
+def _encode_json(value: Union[dict, str, bytes]) -> FfiByteBuffer:
+    if isinstance(value, dict):
+        value = json.dumps(value)
+    return encode_bytes(value)
+
+
+class CredentialJsonEntry(Structure):
+    _fields_ = [
+        ("credential", FfiByteBuffer),
+        ("timestamp", c_int64),
+        ("rev_state", FfiByteBuffer),
+    ]
+
+    @classmethod
+    def create(
+        cls,
+        credential: Union[dict, str, bytes],
+        timestamp: int = None,
+        rev_state: Union[dict, str, bytes] = None,
+    ) -> "CredentialJsonEntry":
+        return CredentialJsonEntry(
+            credential=_encode_json(credential),
+            timestamp=-1 if timestamp is None else timestamp,
+            rev_state=_encode_json(rev_state),
+        )
+
+
+class CredentialJsonEntryList(Structure):
+    _fields_ = [
+        ("count", c_int64),
+        ("data", POINTER(CredentialJsonEntry)),
+    ]
+
+
+class RevocationJsonEntry(Structure):
+    _fields_ = [
+        ("def_entry_idx", c_int64),
+        ("entry", FfiByteBuffer),
+        ("timestamp", c_int64),
+    ]
+
+    @classmethod
+    def create(
+        cls,
+        def_entry_idx: int,
+        entry: Union[dict, str, bytes],
+        timestamp: int,
+    ) -> "RevocationJsonEntry":
+        return RevocationJsonEntry(
+            def_entry_idx=def_entry_idx,
+            entry=_encode_json(entry),
+            timestamp=timestamp,
+        )
+
+
+class RevocationJsonEntryList(Structure):
+    _fields_ = [
+        ("count", c_int64),
+        ("data", POINTER(RevocationJsonEntry)),
+    ]
+
+
+class FfiByteBufferList(Structure):
+    _fields_ = [
+        ("count", c_size_t),
+        ("data", POINTER(FfiByteBuffer)),
+    ]
+
+    @classmethod
+    def create(
+        cls, values: Optional[Sequence[Union[dict, str, bytes]]]
+    ) -> "FfiByteBufferList":
+        inst = FfiByteBufferList()
+        if values is not None:
+            values = [_encode_json(v) for v in values]
+            inst.count = len(values)
+            inst.data = (FfiByteBuffer * inst.count)(*values)
+        return inst
+
+
 def object_free(handle: ObjectHandle):
     get_library().credx_object_free(handle)
 
 
+def object_free_list(handles: Sequence[ObjectHandle]):
+    get_library().credx_object_free_list(FfiObjectHandleList.create(handles))
+
+
+def clear_object_store():
+    do_call("credx_clear_object_store")
+
+
+def object_count() -> int:
+    result = c_int64()
+    do_call("credx_object_count", byref(result))
+    return result.value
+
+
+def object_list_handles() -> Sequence[int]:
+    result = StrBuffer()
+    do_call("credx_object_list_handles", byref(result))
+    return json.loads(str(result))
+
+
+def object_dump() -> Sequence[dict]:
+    result = StrBuffer()
+    do_call("credx_object_dump", byref(result))
+    return json.loads(str(result))
+
+
 def object_get_json(handle: ObjectHandle) -> ByteBuffer:
     result = ByteBuffer()
     do_call("credx_object_get_json", handle, byref(result))
     return result
 
 
+def object_get_cbor(handle: ObjectHandle) -> ByteBuffer:
+    result = ByteBuffer()
+    do_call("credx_object_get_cbor", handle, byref(result))
+    return result
+
+
 def object_get_type_name(handle: ObjectHandle) -> StrBuffer:
     result = StrBuffer()
     do_call("credx_object_get_type_name", handle, byref(result))
     return result
 
 
+def object_validate(handle: ObjectHandle) -> StrBuffer:
+    result = StrBuffer()
+    do_call("credx_object_validate", handle, byref(result))
+    return result
+
+
+def object_get_tag(handle: ObjectHandle) -> Optional[StrBuffer]:
+    result = StrBuffer()
+    do_call("credx_object_get_tag", handle, byref(result))
+    if result.is_none():
+        result = None
+    return result
+
+
+def object_set_tag(handle: ObjectHandle, tag: Optional[str]):
+    do_call("credx_object_set_tag", handle, encode_str(tag))
+
+
 def _object_from_json(method: str, value: Union[dict, str, bytes]) -> ObjectHandle:
     if isinstance(value, dict):
         value = json.dumps(value)
@@ -462,6 +680,12 @@ def _object_from_json(method: str, value: Union[dict, str, bytes]) -> ObjectHand
     return result
 
 
+def _object_from_cbor(method: str, value: bytes) -> ObjectHandle:
+    result = ObjectHandle()
+    do_call(method, encode_bytes(value), byref(result))
+    return result
+
+
 def _object_get_attribute(
     method: str, handle: ObjectHandle, name: str
 ) -> Optional[StrBuffer]:
@@ -478,6 +702,14 @@ def generate_nonce() -> str:
     return str(result)
 
 
+def presentation_request_check_nonce(pres_req: ObjectHandle, nonce: str):
+    do_call(
+        "credx_presentation_request_check_nonce",
+        pres_req,
+        encode_str(nonce),
+    )
+
+
 def create_schema(
     origin_did: str,
     name: str,
@@ -505,15 +737,17 @@ def create_credential_definition(
     tag: str,
     signature_type: str,
     support_revocation: bool,
+    attr_names: Sequence[str] = None,
 ) -> (ObjectHandle, ObjectHandle, ObjectHandle):
     cred_def, cred_def_pvt, key_proof = ObjectHandle(), ObjectHandle(), ObjectHandle()
     do_call(
-        "credx_create_credential_definition",
+        "credx_create_credential_definition_with_attributes",
         encode_str(origin_did),
         schema,
         encode_str(tag),
         encode_str(signature_type),
         c_int8(support_revocation),
+        FfiStrList.create(attr_names),
         byref(cred_def),
         byref(cred_def_pvt),
         byref(key_proof),
@@ -521,18 +755,15 @@ def create_credential_definition(
     return (cred_def, cred_def_pvt, key_proof)
 
 
-def create_credential(
-    cred_def: ObjectHandle,
-    cred_def_private: ObjectHandle,
-    cred_offer: ObjectHandle,
-    cred_request: ObjectHandle,
+def verify_key_correctness_proof(cred_def: ObjectHandle, key_proof: ObjectHandle):
+    do_call("credx_verify_key_correctness_proof", cred_def, key_proof)
+
+
+def _credential_attribute_lists(
     attr_raw_values: Mapping[str, str],
     attr_enc_values: Optional[Mapping[str, str]],
-    revocation_config: Optional[RevocationConfig],
-) -> (ObjectHandle, ObjectHandle, ObjectHandle):
-    cred = ObjectHandle()
-    rev_reg = ObjectHandle()
-    rev_delta = ObjectHandle()
+    attr_media_types: Optional[Mapping[str, str]],
+) -> (FfiStrList, FfiStrList, FfiStrList, FfiStrList):
     attr_keys = list(attr_raw_values.keys())
     names_list = FfiStrList.create(attr_keys)
     raw_values_list = FfiStrList.create(str(attr_raw_values[k]) for k in attr_keys)
@@ -543,15 +774,34 @@ def create_credential(
     else:
         enc_values_list = None
     enc_values_list = FfiStrList().create(enc_values_list)
+    media_types_list = FfiStrList().create(
+        [attr_media_types.get(name) for name in attr_keys] if attr_media_types else None
+    )
+    return names_list, raw_values_list, enc_values_list, media_types_list
+
+
+def create_credential(
+    cred_def: ObjectHandle,
+    cred_def_private: ObjectHandle,
+    cred_offer: ObjectHandle,
+    cred_request: ObjectHandle,
+    attr_raw_values: Mapping[str, str],
+    attr_enc_values: Optional[Mapping[str, str]],
+    revocation_config: Optional[RevocationConfig],
+    attr_media_types: Optional[Mapping[str, str]] = None,
+) -> (ObjectHandle, ObjectHandle, ObjectHandle):
+    cred = ObjectHandle()
+    rev_reg = ObjectHandle()
+    rev_delta = ObjectHandle()
     do_call(
-        "credx_create_credential",
+        "credx_create_credential_with_media_types",
         cred_def,
         cred_def_private,
         cred_offer,
         cred_request,
-        names_list,
-        raw_values_list,
-        enc_values_list,
+        *_credential_attribute_lists(
+            attr_raw_values, attr_enc_values, attr_media_types
+        ),
         pointer(revocation_config)
         if revocation_config
         else POINTER(RevocationConfig)(),
@@ -562,6 +812,61 @@ def create_credential(
     return cred, rev_reg, rev_delta
 
 
+def create_credential_with_allocation(
+    cred_def: ObjectHandle,
+    cred_def_private: ObjectHandle,
+    cred_offer: ObjectHandle,
+    cred_request: ObjectHandle,
+    attr_raw_values: Mapping[str, str],
+    attr_enc_values: Optional[Mapping[str, str]],
+    attr_media_types: Optional[Mapping[str, str]],
+    revocation_config: RevocationAllocationConfig,
+    usage: ObjectHandle,
+) -> (ObjectHandle, ObjectHandle, ObjectHandle, ObjectHandle, int):
+    cred = ObjectHandle()
+    rev_reg = ObjectHandle()
+    rev_delta = ObjectHandle()
+    upd_usage = ObjectHandle()
+    rev_reg_index = c_int64()
+    do_call(
+        "credx_create_credential_with_allocation",
+        cred_def,
+        cred_def_private,
+        cred_offer,
+        cred_request,
+        *_credential_attribute_lists(
+            attr_raw_values, attr_enc_values, attr_media_types
+        ),
+        pointer(revocation_config),
+        usage,
+        byref(cred),
+        byref(rev_reg),
+        byref(rev_delta),
+        byref(upd_usage),
+        byref(rev_reg_index),
+    )
+    return cred, rev_reg, rev_delta, upd_usage, rev_reg_index.value
+
+
+def create_revocation_registry_usage(rev_reg_def: ObjectHandle) -> ObjectHandle:
+    usage = ObjectHandle()
+    do_call("credx_create_revocation_registry_usage", rev_reg_def, byref(usage))
+    return usage
+
+
+def revocation_registry_remaining_capacity(
+    rev_reg_def: ObjectHandle, usage: ObjectHandle
+) -> int:
+    result = c_int64()
+    do_call(
+        "credx_revocation_registry_remaining_capacity",
+        rev_reg_def,
+        usage,
+        byref(result),
+    )
+    return result.value
+
+
 def encode_credential_attributes(
     attr_raw_values: Mapping[str, str]
 ) -> Mapping[str, str]:
@@ -572,6 +877,17 @@ def encode_credential_attributes(
     return dict(zip(attr_keys, str(result).split(",")))
 
 
+def encode_binary_attribute(content: bytes) -> (str, str):
+    raw, encoded = StrBuffer(), StrBuffer()
+    do_call(
+        "credx_encode_binary_attribute",
+        encode_bytes(content),
+        byref(raw),
+        byref(encoded),
+    )
+    return str(raw), str(encoded)
+
+
 def process_credential(
     cred: ObjectHandle,
     cred_req_metadata: ObjectHandle,
@@ -612,8 +928,31 @@ def revoke_credential(
     return upd_rev_reg, rev_delta
 
 
+def revoke_credentials(
+    rev_reg_def: ObjectHandle,
+    rev_reg: ObjectHandle,
+    cred_rev_idxs: Sequence[int],
+    tails_path: str,
+) -> (ObjectHandle, ObjectHandle):
+    upd_rev_reg = ObjectHandle()
+    rev_delta = ObjectHandle()
+    do_call(
+        "credx_revoke_credentials",
+        rev_reg_def,
+        rev_reg,
+        FfiIntList.create(cred_rev_idxs),
+        encode_str(tails_path),
+        byref(upd_rev_reg),
+        byref(rev_delta),
+    )
+    return upd_rev_reg, rev_delta
+
+
 def create_credential_offer(
-    schema_id: str, cred_def: ObjectHandle, key_proof: ObjectHandle
+    schema_id: str,
+    cred_def: ObjectHandle,
+    key_proof: ObjectHandle,
+    nonce: Optional[str] = None,
 ) -> ObjectHandle:
     cred_offer = ObjectHandle()
     do_call(
@@ -621,11 +960,30 @@ def create_credential_offer(
         encode_str(schema_id),
         cred_def,
         key_proof,
+        encode_str(nonce),
         byref(cred_offer),
     )
     return cred_offer
 
 
+def create_credential_offer_json(
+    schema_id: str,
+    cred_def: Union[dict, str, bytes],
+    key_proof: Union[dict, str, bytes],
+    nonce: Optional[str] = None,
+) -> ByteBuffer:
+    result = ByteBuffer()
+    do_call(
+        "credx_create_credential_offer_json",
+        encode_str(schema_id),
+        _encode_json(cred_def),
+        _encode_json(key_proof),
+        encode_str(nonce),
+        byref(result),
+    )
+    return result
+
+
 def create_credential_request(
     prover_did: str,
     cred_def: ObjectHandle,
@@ -656,6 +1014,32 @@ def create_master_secret() -> ObjectHandle:
     return secret
 
 
+def link_secret_export(
+    master_secret: ObjectHandle, password: Union[str, bytes]
+) -> ByteBuffer:
+    result = ByteBuffer()
+    do_call(
+        "credx_link_secret_export",
+        master_secret,
+        encode_bytes(password),
+        byref(result),
+    )
+    return result
+
+
+def link_secret_import(
+    protected: Union[bytes, memoryview], password: Union[str, bytes]
+) -> ObjectHandle:
+    secret = ObjectHandle()
+    do_call(
+        "credx_link_secret_import",
+        encode_bytes(protected),
+        encode_bytes(password),
+        byref(secret),
+    )
+    return secret
+
+
 def create_presentation(
     pres_req: ObjectHandle,
     credentials: Sequence[CredentialEntry],
@@ -687,6 +1071,59 @@ def create_presentation(
     return present
 
 
+def create_presentation_from_json(
+    pres_req: Union[dict, str, bytes],
+    credentials: Sequence[CredentialJsonEntry],
+    credentials_prove: Sequence[CredentialProve],
+    self_attest: Mapping[str, str],
+    master_secret: ObjectHandle,
+    schemas: Sequence[Union[dict, str, bytes]],
+    cred_defs: Sequence[Union[dict, str, bytes]],
+) -> ByteBuffer:
+    entry_list = CredentialJsonEntryList()
+    entry_list.count = len(credentials)
+    entry_list.data = (CredentialJsonEntry * entry_list.count)(*credentials)
+    prove_list = CredentialProveList()
+    prove_list.count = len(credentials_prove)
+    prove_list.data = (CredentialProve * prove_list.count)(*credentials_prove)
+    result = ByteBuffer()
+    do_call(
+        "credx_create_presentation_from_json",
+        _encode_json(pres_req),
+        entry_list,
+        prove_list,
+        FfiStrList.create(self_attest.keys()),
+        FfiStrList.create(self_attest.values()),
+        master_secret,
+        FfiByteBufferList.create(schemas),
+        FfiByteBufferList.create(cred_defs),
+        byref(result),
+    )
+    return result
+
+
+def create_presentation_from_selection(
+    pres_req: ObjectHandle,
+    selection: Union[dict, str],
+    master_secret: ObjectHandle,
+    schemas: Sequence[ObjectHandle],
+    cred_defs: Sequence[ObjectHandle],
+) -> ObjectHandle:
+    if isinstance(selection, dict):
+        selection = json.dumps(selection)
+    present = ObjectHandle()
+    do_call(
+        "credx_create_presentation_from_selection",
+        pres_req,
+        encode_str(selection),
+        master_secret,
+        FfiObjectHandleList.create(schemas),
+        FfiObjectHandleList.create(cred_defs),
+        byref(present),
+    )
+    return present
+
+
 def verify_presentation(
     presentation: ObjectHandle,
     pres_req: ObjectHandle,
@@ -694,23 +1131,60 @@ def verify_presentation(
     cred_defs: Sequence[ObjectHandle],
     rev_reg_defs: Sequence[ObjectHandle],
     rev_regs: Sequence[RevocationEntry],
-) -> bool:
+    nonrevoke_interval_override: Sequence[NonRevokedIntervalOverride] = None,
+) -> (bool, list):
     verify = c_int8()
+    failures = StrBuffer()
     entry_list = RevocationEntryList()
     if rev_regs:
         entry_list.count = len(rev_regs)
         entry_list.data = (RevocationEntry * entry_list.count)(*rev_regs)
+    override_list = NonRevokedIntervalOverrideList()
+    if nonrevoke_interval_override:
+        override_list.count = len(nonrevoke_interval_override)
+        override_list.data = (NonRevokedIntervalOverride * override_list.count)(
+            *nonrevoke_interval_override
+        )
     do_call(
-        "credx_verify_presentation",
+        "credx_verify_presentation_with_overrides",
         presentation,
         pres_req,
         FfiObjectHandleList.create(schemas),
         FfiObjectHandleList.create(cred_defs),
         FfiObjectHandleList.create(rev_reg_defs),
         entry_list,
+        override_list,
         byref(verify),
+        byref(failures),
     )
-    return bool(verify)
+    return bool(verify), json.loads(failures.opt_str() or "[]")
+
+
+def verify_presentation_json(
+    presentation: Union[dict, str, bytes],
+    pres_req: Union[dict, str, bytes],
+    schemas: Sequence[Union[dict, str, bytes]],
+    cred_defs: Sequence[Union[dict, str, bytes]],
+    rev_reg_defs: Sequence[Union[dict, str, bytes]] = None,
+    rev_regs: Sequence[RevocationJsonEntry] = None,
+) -> (bool, list):
+    entry_list = RevocationJsonEntryList()
+    if rev_regs:
+        entry_list.count = len(rev_regs)
+        entry_list.data = (RevocationJsonEntry * entry_list.count)(*rev_regs)
+    result = ByteBuffer()
+    do_call(
+        "credx_verify_presentation_json",
+        _encode_json(presentation),
+        _encode_json(pres_req),
+        FfiByteBufferList.create(schemas),
+        FfiByteBufferList.create(cred_defs),
+        FfiByteBufferList.create(rev_reg_defs),
+        entry_list,
+        byref(result),
+    )
+    result = json.loads(bytes(result))
+    return result["verified"], result["failures"]
 
 
 def create_revocation_registry(
@@ -721,13 +1195,23 @@ def create_revocation_registry(
     issuance_type: Optional[str],
     max_cred_num: int,
     tails_dir_path: Optional[str],
+    progress: Callable[[int, int], bool] = None,
 ) -> (ObjectHandle, ObjectHandle, ObjectHandle, ObjectHandle):
     reg_def = ObjectHandle()
     reg_def_private = ObjectHandle()
     reg_entry = ObjectHandle()
     reg_init_delta = ObjectHandle()
+    progress_cb = None
+    if progress:
+
+        def _progress(_context, generated, total):
+            if progress(generated, total) is False:
+                return CredxErrorCode.INVALID_STATE
+            return CredxErrorCode.SUCCESS
+
+        progress_cb = TAILS_PROGRESS_CB(_progress)
     do_call(
-        "credx_create_revocation_registry",
+        "credx_create_revocation_registry_with_progress",
         encode_str(origin_did),
         cred_def,
         encode_str(tag),
@@ -735,6 +1219,8 @@ def create_revocation_registry(
         encode_str(issuance_type),
         c_int64(max_cred_num),
         encode_str(tails_dir_path),
+        progress_cb,
+        c_int64(0),
         byref(reg_def),
         byref(reg_def_private),
         byref(reg_entry),
@@ -779,6 +1265,38 @@ def merge_revocation_registry_deltas(
     return rev_delta
 
 
+def revocation_registry_diff(
+    rev_reg: ObjectHandle,
+    earlier_rev_reg: ObjectHandle,
+    issued: Sequence[int],
+    revoked: Sequence[int],
+) -> ObjectHandle:
+    rev_delta = ObjectHandle()
+    do_call(
+        "credx_revocation_registry_diff",
+        rev_reg,
+        earlier_rev_reg,
+        FfiIntList.create(issued),
+        FfiIntList.create(revoked),
+        byref(rev_delta),
+    )
+    return rev_delta
+
+
+def revocation_registry_apply_delta(
+    rev_reg: ObjectHandle,
+    rev_reg_delta: ObjectHandle,
+) -> ObjectHandle:
+    upd_rev_reg = ObjectHandle()
+    do_call(
+        "credx_revocation_registry_apply_delta",
+        rev_reg,
+        rev_reg_delta,
+        byref(upd_rev_reg),
+    )
+    return upd_rev_reg
+
+
 def create_or_update_revocation_state(
     rev_reg_def: ObjectHandle,
     rev_reg_delta: ObjectHandle,
@@ -799,3 +1317,27 @@ def create_or_update_revocation_state(
         byref(rev_state),
     )
     return rev_state
+
+
+def presentation_summarize(presentation: ObjectHandle, pres_req: ObjectHandle) -> dict:
+    result = StrBuffer()
+    do_call("credx_presentation_summarize", presentation, pres_req, byref(result))
+    return json.loads(str(result))
+
+
+def presentation_verify_binary_attribute(
+    presentation: ObjectHandle,
+    referent: str,
+    name: Optional[str],
+    content: bytes,
+) -> bool:
+    verify = c_int8()
+    do_call(
+        "credx_presentation_verify_binary_attribute",
+        presentation,
+        encode_str(referent),
+        encode_str(name),
+        encode_bytes(content),
+        byref(verify),
+    )
+    return bool(verify)
@@ -1,6 +1,7 @@
 """Error classes."""
 
-from enum import IntEnum
+from enum import Enum, IntEnum
+from typing import Sequence
 
 
 class CredxErrorCode(IntEnum):
@@ -13,11 +14,31 @@ class CredxErrorCode(IntEnum):
     INVALID_USER_REVOC_ID = 6
     PROOF_REJECTED = 7
     REVOCATION_REGISTRY_FULL = 8
+    LIMIT_EXCEEDED = 9
+    NONCE_MISMATCH = 10
     WRAPPER = 99
 
 
+class CredxErrorCategory(str, Enum):
+    INPUT = "input"
+    IO = "io"
+    REVOCATION = "revocation"
+    PROOF = "proof"
+    UNEXPECTED = "unexpected"
+
+
 class CredxError(Exception):
-    def __init__(self, code: CredxErrorCode, message: str, extra: str = None):
+    def __init__(
+        self,
+        code: CredxErrorCode,
+        message: str,
+        extra: str = None,
+        *,
+        category: CredxErrorCategory = None,
+        causes: Sequence[str] = None,
+    ):
         super().__init__(message)
         self.code = code
         self.extra = extra
+        self.category = category
+        self.causes = list(causes or [])
@@ -1,4 +1,4 @@
-from typing import Mapping, Optional, Sequence, Union
+from typing import Callable, Mapping, Optional, Sequence, Tuple, Union
 
 from . import bindings
 
@@ -15,11 +15,17 @@ class CredentialDefinition(bindings.IndyObject):
         tag: str,
         *,
         support_revocation: bool = False,
+        attr_names: Sequence[str] = None,
     ) -> ("CredentialDefinition", "CredentialDefinitionPrivate", "KeyCorrectnessProof"):
         if not isinstance(schema, bindings.IndyObject):
             schema = Schema.load(schema)
         cred_def, cred_def_pvt, key_proof = bindings.create_credential_definition(
-            origin_did, schema.handle, tag, signature_type, support_revocation
+            origin_did,
+            schema.handle,
+            tag,
+            signature_type,
+            support_revocation,
+            attr_names,
         )
         return (
             CredentialDefinition(cred_def),
@@ -53,6 +59,13 @@ class CredentialDefinition(bindings.IndyObject):
             )
         )
 
+    def verify_key_correctness_proof(
+        self, key_proof: Union[str, "KeyCorrectnessProof"]
+    ):
+        if not isinstance(key_proof, bindings.IndyObject):
+            key_proof = KeyCorrectnessProof.load(key_proof)
+        bindings.verify_key_correctness_proof(self.handle, key_proof.handle)
+
 
 class CredentialDefinitionPrivate(bindings.IndyObject):
     @classmethod
@@ -81,6 +94,7 @@ class CredentialOffer(bindings.IndyObject):
         schema_id: str,
         cred_def: [str, CredentialDefinition],
         key_proof: [str, KeyCorrectnessProof],
+        nonce: str = None,
     ) -> "CredentialOffer":
         if not isinstance(cred_def, bindings.IndyObject):
             cred_def = CredentialDefinition.load(cred_def)
@@ -88,7 +102,7 @@ class CredentialOffer(bindings.IndyObject):
             key_proof = KeyCorrectnessProof.load(key_proof)
         return CredentialOffer(
             bindings.create_credential_offer(
-                schema_id, cred_def.handle, key_proof.handle
+                schema_id, cred_def.handle, key_proof.handle, nonce
             )
         )
 
@@ -154,6 +168,15 @@ class MasterSecret(bindings.IndyObject):
             bindings._object_from_json("credx_master_secret_from_json", value)
         )
 
+    def to_protected_bytes(self, password: Union[str, bytes]) -> bytes:
+        return bytes(bindings.link_secret_export(self.handle, password))
+
+    @classmethod
+    def from_protected_bytes(
+        cls, protected: Union[bytes, memoryview], password: Union[str, bytes]
+    ) -> "MasterSecret":
+        return MasterSecret(bindings.link_secret_import(protected, password))
+
 
 class Schema(bindings.IndyObject):
     GET_ATTR = "credx_schema_get_attribute"
@@ -200,6 +223,7 @@ class Credential(bindings.IndyObject):
         attr_raw_values: Mapping[str, str],
         attr_enc_values: Mapping[str, str] = None,
         revocation_config: "CredentialRevocationConfig" = None,
+        attr_media_types: Mapping[str, str] = None,
     ) -> (
         "Credential",
         Optional["RevocationRegistry"],
@@ -221,6 +245,7 @@ class Credential(bindings.IndyObject):
             attr_raw_values,
             attr_enc_values,
             revocation_config._native if revocation_config else None,
+            attr_media_types,
         )
         return (
             Credential(cred),
@@ -228,6 +253,62 @@ class Credential(bindings.IndyObject):
             RevocationRegistryDelta(rev_delta) if rev_delta else None,
         )
 
+    @classmethod
+    def create_with_allocation(
+        cls,
+        cred_def: [str, CredentialDefinition],
+        cred_def_private: [str, CredentialDefinitionPrivate],
+        cred_offer: [str, CredentialOffer],
+        cred_request: [str, CredentialRequest],
+        attr_raw_values: Mapping[str, str],
+        revocation_config: "CredentialRevocationConfig",
+        usage: [str, "RevocationRegistryUsage"],
+        *,
+        allocation: str = "sequential",
+        attr_enc_values: Mapping[str, str] = None,
+        attr_media_types: Mapping[str, str] = None,
+    ) -> (
+        "Credential",
+        int,
+        "RevocationRegistry",
+        Optional["RevocationRegistryDelta"],
+        "RevocationRegistryUsage",
+    ):
+        if not isinstance(cred_def, bindings.IndyObject):
+            cred_def = CredentialDefinition.load(cred_def)
+        if not isinstance(cred_def_private, bindings.IndyObject):
+            cred_def_private = CredentialDefinitionPrivate.load(cred_def_private)
+        if not isinstance(cred_offer, bindings.IndyObject):
+            cred_offer = CredentialOffer.load(cred_offer)
+        if not isinstance(cred_request, bindings.IndyObject):
+            cred_request = CredentialRequest.load(cred_request)
+        if not isinstance(usage, bindings.IndyObject):
+            usage = RevocationRegistryUsage.load(usage)
+        (
+            cred,
+            rev_reg,
+            rev_delta,
+            usage,
+            rev_reg_index,
+        ) = bindings.create_credential_with_allocation(
+            cred_def.handle,
+            cred_def_private.handle,
+            cred_offer.handle,
+            cred_request.handle,
+            attr_raw_values,
+            attr_enc_values,
+            attr_media_types,
+            revocation_config._allocation_native(allocation),
+            usage.handle,
+        )
+        return (
+            Credential(cred),
+            rev_reg_index,
+            RevocationRegistry(rev_reg),
+            RevocationRegistryDelta(rev_delta) if rev_delta else None,
+            RevocationRegistryUsage(usage),
+        )
+
     def process(
         self,
         cred_req_metadata: [str, CredentialRequestMetadata],
@@ -259,6 +340,12 @@ class Credential(bindings.IndyObject):
             bindings._object_from_json("credx_credential_from_json", value)
         )
 
+    @classmethod
+    def load_cbor(cls, value: bytes) -> "Credential":
+        return Credential(
+            bindings._object_from_cbor("credx_credential_from_cbor", value)
+        )
+
     @property
     def schema_id(self) -> str:
         return str(
@@ -298,6 +385,22 @@ class Credential(bindings.IndyObject):
         )
         return int(str(sval)) if sval is not None else None
 
+    def raw_value(self, name: str) -> Optional[str]:
+        sval = bindings._object_get_attribute(
+            self.GET_ATTR,
+            self.handle,
+            f"values.{name}",
+        )
+        return str(sval) if sval is not None else None
+
+    def media_type(self, name: str) -> Optional[str]:
+        sval = bindings._object_get_attribute(
+            self.GET_ATTR,
+            self.handle,
+            f"media_types.{name}",
+        )
+        return str(sval) if sval is not None else None
+
 
 class PresentationRequest(bindings.IndyObject):
     @classmethod
@@ -306,6 +409,15 @@ class PresentationRequest(bindings.IndyObject):
             bindings._object_from_json("credx_presentation_request_from_json", value)
         )
 
+    @classmethod
+    def load_cbor(cls, value: bytes) -> "PresentationRequest":
+        return PresentationRequest(
+            bindings._object_from_cbor("credx_presentation_request_from_cbor", value)
+        )
+
+    def check_nonce(self, nonce: str):
+        bindings.presentation_request_check_nonce(self.handle, nonce)
+
 
 class PresentCredentials:
     def __init__(self):
@@ -423,6 +535,12 @@ class Presentation(bindings.IndyObject):
             bindings._object_from_json("credx_presentation_from_json", value)
         )
 
+    @classmethod
+    def load_cbor(cls, value: bytes) -> "Presentation":
+        return Presentation(
+            bindings._object_from_cbor("credx_presentation_from_cbor", value)
+        )
+
     def verify(
         self,
         pres_req: [str, PresentationRequest],
@@ -432,7 +550,10 @@ class Presentation(bindings.IndyObject):
         rev_reg_entries: Mapping[
             str, Mapping[int, Union[str, "RevocationRegistry"]]
         ] = None,
-    ) -> bool:
+        nonrevoke_interval_override: Mapping[str, Mapping[int, int]] = None,
+        *,
+        with_failures: bool = False,
+    ) -> Union[bool, Tuple[bool, list]]:
         if not isinstance(pres_req, bindings.IndyObject):
             pres_req = PresentationRequest.load(pres_req)
         schemas = [
@@ -449,6 +570,7 @@ class Presentation(bindings.IndyObject):
         ]
         reg_defs = []
         reg_entries = []
+        overrides = []
         for reg_def in rev_reg_defs:
             if not isinstance(reg_def, bindings.IndyObject):
                 reg_def = RevocationRegistryDefinition.load(reg_def)
@@ -462,15 +584,38 @@ class Presentation(bindings.IndyObject):
                             len(reg_defs), entry.handle, timestamp
                         )
                     )
+            if nonrevoke_interval_override and reg_def_id in nonrevoke_interval_override:
+                for requested_ts, override_ts in nonrevoke_interval_override[
+                    reg_def_id
+                ].items():
+                    overrides.append(
+                        bindings.NonRevokedIntervalOverride.create(
+                            len(reg_defs), requested_ts, override_ts
+                        )
+                    )
             reg_defs.append(reg_def.handle)
 
-        return bindings.verify_presentation(
+        valid, failures = bindings.verify_presentation(
             self.handle,
             pres_req.handle,
             schemas,
             cred_defs,
             reg_defs,
             reg_entries or None,
+            overrides or None,
+        )
+        return (valid, failures) if with_failures else valid
+
+    def summarize(self, pres_req: Union[str, PresentationRequest]) -> dict:
+        if not isinstance(pres_req, bindings.IndyObject):
+            pres_req = PresentationRequest.load(pres_req)
+        return bindings.presentation_summarize(self.handle, pres_req.handle)
+
+    def verify_binary_attribute(
+        self, referent: str, content: bytes, name: str = None
+    ) -> bool:
+        return bindings.presentation_verify_binary_attribute(
+            self.handle, referent, name, content
         )
 
 
@@ -488,6 +633,7 @@ class RevocationRegistryDefinition(bindings.IndyObject):
         *,
         issuance_type: str = None,
         tails_dir_path: str = None,
+        progress: Callable[[int, int], bool] = None,
     ) -> (
         "RevocationRegistryDefinition",
         "RevocationRegistryDefinitionPrivate",
@@ -509,6 +655,7 @@ class RevocationRegistryDefinition(bindings.IndyObject):
             issuance_type,
             max_cred_num,
             tails_dir_path,
+            progress,
         )
         return (
             RevocationRegistryDefinition(reg_def),
@@ -602,6 +749,19 @@ class RevocationRegistry(bindings.IndyObject):
         )
         return RevocationRegistryDelta(rev_delta)
 
+    def revoke_credentials(
+        self,
+        rev_reg_def: [str, RevocationRegistryDefinition],
+        cred_rev_idxs: Sequence[int],
+        tails_path: str,
+    ) -> "RevocationRegistryDelta":
+        if not isinstance(rev_reg_def, bindings.IndyObject):
+            rev_reg_def = RevocationRegistryDefinition.load(rev_reg_def)
+        self.handle, rev_delta = bindings.revoke_credentials(
+            rev_reg_def.handle, self.handle, cred_rev_idxs, tails_path
+        )
+        return RevocationRegistryDelta(rev_delta)
+
     def update(
         self,
         rev_reg_def: [str, RevocationRegistryDefinition],
@@ -616,6 +776,27 @@ class RevocationRegistry(bindings.IndyObject):
         )
         return RevocationRegistryDelta(rev_delta)
 
+    def diff(
+        self,
+        earlier: [str, "RevocationRegistry"],
+        issued: Sequence[int],
+        revoked: Sequence[int],
+    ) -> "RevocationRegistryDelta":
+        if not isinstance(earlier, bindings.IndyObject):
+            earlier = RevocationRegistry.load(earlier)
+        return RevocationRegistryDelta(
+            bindings.revocation_registry_diff(
+                self.handle, earlier.handle, issued, revoked
+            )
+        )
+
+    def apply_delta(self, rev_reg_delta: [str, "RevocationRegistryDelta"]):
+        if not isinstance(rev_reg_delta, bindings.IndyObject):
+            rev_reg_delta = RevocationRegistryDelta.load(rev_reg_delta)
+        self.handle = bindings.revocation_registry_apply_delta(
+            self.handle, rev_reg_delta.handle
+        )
+
 
 class RevocationRegistryDelta(bindings.IndyObject):
     @classmethod
@@ -674,6 +855,47 @@ class CredentialRevocationConfig:
             self.tails_path,
         )
 
+    def _allocation_native(self, allocation: str) -> bindings.RevocationAllocationConfig:
+        return bindings.RevocationAllocationConfig.create(
+            self.rev_reg_def.handle,
+            self.rev_reg_def_private.handle,
+            self.rev_reg.handle,
+            allocation,
+            self.rev_reg_index,
+            self.tails_path,
+        )
+
+
+class RevocationRegistryUsage(bindings.IndyObject):
+    @classmethod
+    def create(
+        cls, rev_reg_def: [str, RevocationRegistryDefinition]
+    ) -> "RevocationRegistryUsage":
+        if not isinstance(rev_reg_def, bindings.IndyObject):
+            rev_reg_def = RevocationRegistryDefinition.load(rev_reg_def)
+        return RevocationRegistryUsage(
+            bindings.create_revocation_registry_usage(rev_reg_def.handle)
+        )
+
+    @classmethod
+    def load(
+        cls, value: Union[dict, str, bytes, memoryview]
+    ) -> "RevocationRegistryUsage":
+        return RevocationRegistryUsage(
+            bindings._object_from_json(
+                "credx_revocation_registry_usage_from_json", value
+            )
+        )
+
+    def remaining_capacity(
+        self, rev_reg_def: [str, RevocationRegistryDefinition]
+    ) -> int:
+        if not isinstance(rev_reg_def, bindings.IndyObject):
+            rev_reg_def = RevocationRegistryDefinition.load(rev_reg_def)
+        return bindings.revocation_registry_remaining_capacity(
+            rev_reg_def.handle, self.handle
+        )
+
 
 class CredentialRevocationState(bindings.IndyObject):
     @classmethod